@@ -1,4 +1,4 @@
-use crate::{prelude::*, Interpolation};
+use crate::{prelude::*, Interpolation, Wrapping};
 
 /// A  set of key frames and transformations associated with a specific animation for a specific [Primitive](crate::Primitive).
 #[derive(Debug, Clone, Default)]
@@ -20,6 +20,17 @@ impl KeyFrameAnimation {
         }
         transformation
     }
+
+    ///
+    /// Calculates the morph weights for this animation at the given time, if the morph weights are animated.
+    /// The weights can be applied to a mesh using [TriMesh::apply_morph](crate::TriMesh::apply_morph).
+    ///
+    pub fn weights(&self, time: f32) -> Option<Vec<f32>> {
+        self.key_frames
+            .iter()
+            .rev()
+            .find_map(|(_, animation)| animation.weights(time))
+    }
 }
 
 ///
@@ -30,6 +41,7 @@ pub struct KeyFrames {
     /// Optional time where the animation repeats itself.
     pub loop_time: Option<f32>,
     /// The type of interpolation used in between the key frames.
+    /// In case of [Interpolation::CubicSpline], there are three values for each key frame, the in-tangent, the value and the out-tangent, in that order.
     pub interpolation: Interpolation,
     /// The time value for each key frame.
     pub times: Vec<f32>,
@@ -87,37 +99,21 @@ impl KeyFrames {
     }
 
     fn interpolate_rotation(&self, time: f32, values: &[Quat]) -> Quat {
-        let time = self.loop_time.map(|t| time % t).unwrap_or(time);
-        if time < self.times[0] {
-            values[0]
-        } else {
-            for i in 0..self.times.len() - 1 {
-                if self.times[i] <= time && time < self.times[i + 1] {
-                    let t = (time - self.times[i]) / (self.times[i + 1] - self.times[i]);
-                    return values[i].slerp(values[i + 1], t);
-                }
-            }
-            *values.last().unwrap()
+        match self.blend_weights(time)[..] {
+            [(i0, _), (i1, t)] => values[i0].slerp(values[i1], t),
+            ref weights => weights
+                .iter()
+                .map(|(i, w)| values[*i] * *w)
+                .fold(Quat::zero(), |a, b| a + b)
+                .normalize(),
         }
     }
 
     fn interpolate_array(&self, time: f32, values: &[Vec<f32>]) -> Vec<f32> {
-        let time = self.loop_time.map(|t| time % t).unwrap_or(time);
-        if time < self.times[0] {
-            values[0].clone()
-        } else {
-            for i in 0..self.times.len() - 1 {
-                if self.times[i] <= time && time < self.times[i + 1] {
-                    let t = (time - self.times[i]) / (self.times[i + 1] - self.times[i]);
-                    let mut result = Vec::new();
-                    for j in 0..values[i].len() {
-                        result.push(values[i][j] * (1.0 - t) + values[i + 1][j] * t);
-                    }
-                    return result;
-                }
-            }
-            values.last().unwrap().clone()
-        }
+        let weights = self.blend_weights(time);
+        (0..values[0].len())
+            .map(|j| weights.iter().map(|(i, w)| values[*i][j] * w).sum())
+            .collect()
     }
 
     fn interpolate<T: Copy + std::ops::Mul<f32, Output = T> + std::ops::Add<T, Output = T>>(
@@ -125,17 +121,196 @@ impl KeyFrames {
         time: f32,
         values: &[T],
     ) -> T {
+        let weights = self.blend_weights(time);
+        weights[1..]
+            .iter()
+            .fold(values[weights[0].0] * weights[0].1, |a, (i, w)| {
+                a + values[*i] * *w
+            })
+    }
+
+    ///
+    /// Returns the indices into the key frame values together with the weights they should be blended with at the given time.
+    /// For [Interpolation::Linear], there are exactly two weights in between two key frames.
+    ///
+    fn blend_weights(&self, time: f32) -> Vec<(usize, f32)> {
         let time = self.loop_time.map(|t| time % t).unwrap_or(time);
-        if time < self.times[0] {
-            values[0]
+        // Cubic spline values are stored as an in-tangent, a value and an out-tangent for each key frame.
+        let (stride, offset) = if self.interpolation == Interpolation::CubicSpline {
+            (3, 1)
         } else {
-            for i in 0..self.times.len() - 1 {
-                if self.times[i] <= time && time < self.times[i + 1] {
-                    let t = (time - self.times[i]) / (self.times[i + 1] - self.times[i]);
-                    return values[i] * (1.0 - t) + values[i + 1] * t;
-                }
+            (1, 0)
+        };
+        let last = self.times.len() - 1;
+        if time < self.times[0] {
+            return vec![(offset, 1.0)];
+        }
+        for i in 0..last {
+            if self.times[i] <= time && time < self.times[i + 1] {
+                let dt = self.times[i + 1] - self.times[i];
+                let t = (time - self.times[i]) / dt;
+                return match self.interpolation {
+                    Interpolation::Nearest => vec![(i, 1.0)],
+                    Interpolation::Linear => vec![(i, 1.0 - t), (i + 1, t)],
+                    Interpolation::CubicSpline => {
+                        let t2 = t * t;
+                        let t3 = t2 * t;
+                        vec![
+                            (3 * i + 1, 2.0 * t3 - 3.0 * t2 + 1.0),
+                            (3 * i + 2, (t3 - 2.0 * t2 + t) * dt),
+                            (3 * i + 4, -2.0 * t3 + 3.0 * t2),
+                            (3 * i + 3, (t3 - t2) * dt),
+                        ]
+                    }
+                };
+            }
+        }
+        vec![(stride * last + offset, 1.0)]
+    }
+}
+
+///
+/// A joint in a [Skin].
+///
+#[derive(Debug, Clone)]
+pub struct Joint {
+    /// The name. Might not be anything meaningful.
+    pub name: String,
+    /// Optional index into [Skin::joints] of the parent joint. Is `None` for the root joints.
+    pub parent: Option<usize>,
+    /// A transformation applied before the local transformation of the joint.
+    /// For a root joint, it is the transformation from the space of its parent node to world space,
+    /// otherwise it is the transformation of any nodes in between the joint and its parent joint, which is most often the identity.
+    pub base_transformation: Mat4,
+    /// The translation of the joint in the rest pose.
+    pub translation: Vec3,
+    /// The rotation of the joint in the rest pose.
+    pub rotation: Quat,
+    /// The non uniform scale of the joint in the rest pose.
+    pub scale: Vec3,
+    /// The matrix which transforms from world space to the local space of the joint in the bind pose.
+    pub inverse_bind_matrix: Mat4,
+}
+
+impl Joint {
+    fn local_transformation(&self, translation: Vec3, rotation: Quat, scale: Vec3) -> Mat4 {
+        self.base_transformation
+            * Mat4::from_translation(translation)
+            * Mat4::from(rotation)
+            * Mat4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+    }
+}
+
+///
+/// A skeleton used to deform a [TriMesh](crate::TriMesh) with [TriMesh::joint_indices](crate::TriMesh::joint_indices) and [TriMesh::joint_weights](crate::TriMesh::joint_weights).
+///
+#[derive(Debug, Clone, Default)]
+pub struct Skin {
+    /// The name. Might not be anything meaningful.
+    pub name: String,
+    /// The joints of the skeleton, the vertex joint indices refer to this list.
+    pub joints: Vec<Joint>,
+    /// The animations of the joints of this skin.
+    pub animations: Vec<SkeletalAnimation>,
+}
+
+impl Skin {
+    ///
+    /// Returns the joint matrices in the rest pose, one for each joint in [Skin::joints].
+    /// A joint matrix transforms a vertex from the bind pose to world space.
+    ///
+    pub fn joint_matrices(&self) -> Vec<Mat4> {
+        self.compute_joint_matrices(
+            self.joints
+                .iter()
+                .map(|joint| {
+                    joint.local_transformation(joint.translation, joint.rotation, joint.scale)
+                })
+                .collect(),
+        )
+    }
+
+    fn compute_joint_matrices(&self, local_transformations: Vec<Mat4>) -> Vec<Mat4> {
+        fn global(
+            joints: &[Joint],
+            local_transformations: &[Mat4],
+            globals: &mut Vec<Option<Mat4>>,
+            i: usize,
+        ) -> Mat4 {
+            if let Some(transformation) = globals[i] {
+                return transformation;
             }
-            *values.last().unwrap()
+            let transformation = if let Some(parent) = joints[i].parent {
+                global(joints, local_transformations, globals, parent) * local_transformations[i]
+            } else {
+                local_transformations[i]
+            };
+            globals[i] = Some(transformation);
+            transformation
         }
+        let mut globals = vec![None; self.joints.len()];
+        (0..self.joints.len())
+            .map(|i| {
+                global(&self.joints, &local_transformations, &mut globals, i)
+                    * self.joints[i].inverse_bind_matrix
+            })
+            .collect()
+    }
+}
+
+///
+/// An animation of the joints of a [Skin].
+///
+#[derive(Debug, Clone, Default)]
+pub struct SkeletalAnimation {
+    /// Optional name of the animation.
+    pub name: Option<String>,
+    /// The duration of the animation.
+    pub duration: f32,
+    /// A list of [KeyFrames] together with the index into [Skin::joints] of the joint they animate.
+    /// The translation, rotation or scale of the rest pose of the joint is replaced by the animated value, if any.
+    pub channels: Vec<(usize, KeyFrames)>,
+}
+
+impl SkeletalAnimation {
+    ///
+    /// Returns the joint matrices of the given skin at the given time, one for each joint in [Skin::joints], see [Skin::joint_matrices].
+    /// The wrapping determines the behaviour outside the range `[0..duration]`,
+    /// ie. [Wrapping::Repeat] loops the animation, [Wrapping::MirroredRepeat] plays it back and forth and [Wrapping::ClampToEdge] holds the first or last pose.
+    ///
+    pub fn sample(&self, skin: &Skin, time: f32, wrapping: Wrapping) -> Vec<Mat4> {
+        let time = if self.duration > 0.0 {
+            match wrapping {
+                Wrapping::Repeat => time.rem_euclid(self.duration),
+                Wrapping::MirroredRepeat => {
+                    let time = time.rem_euclid(2.0 * self.duration);
+                    if time > self.duration {
+                        2.0 * self.duration - time
+                    } else {
+                        time
+                    }
+                }
+                Wrapping::ClampToEdge => time.clamp(0.0, self.duration),
+            }
+        } else {
+            0.0
+        };
+        skin.compute_joint_matrices(
+            skin.joints
+                .iter()
+                .enumerate()
+                .map(|(i, joint)| {
+                    let mut translation = joint.translation;
+                    let mut rotation = joint.rotation;
+                    let mut scale = joint.scale;
+                    for (_, key_frames) in self.channels.iter().filter(|(j, _)| *j == i) {
+                        translation = key_frames.translation(time).unwrap_or(translation);
+                        rotation = key_frames.rotation(time).unwrap_or(rotation);
+                        scale = key_frames.scale(time).unwrap_or(scale);
+                    }
+                    joint.local_transformation(translation, rotation, scale)
+                })
+                .collect(),
+        )
     }
 }
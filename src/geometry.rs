@@ -32,12 +32,13 @@ impl Geometry {
     }
 
     ///
-    /// Computes tangents if it is relevant for the geometry.
+    /// Computes tangents if it is relevant for the geometry, see [TriMesh::compute_tangents].
     ///
-    pub fn compute_tangents(&mut self) {
+    pub fn compute_tangents(&mut self) -> crate::Result<()> {
         if let Self::Triangles(mesh) = self {
-            mesh.compute_tangents()
+            mesh.compute_tangents()?;
         }
+        Ok(())
     }
 
     ///
@@ -54,9 +55,10 @@ impl Geometry {
 ///
 /// An array of indices. Supports different data types.
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum Indices {
     /// Do not use indices, ie. the faces are all unconnected.
+    #[default]
     None,
     /// Uses unsigned 8 bit integer for each index.
     U8(Vec<u8>),
@@ -91,6 +93,104 @@ impl Indices {
         }
     }
 
+    ///
+    /// Converts all the indices to `u16` data type.
+    /// Returns an error if an index is larger than [u16::MAX] instead of truncating it.
+    ///
+    pub fn to_u16(&self) -> crate::Result<Option<Vec<u16>>> {
+        Ok(match self {
+            Self::None => None,
+            Self::U8(values) => Some(values.iter().map(|i| *i as u16).collect::<Vec<_>>()),
+            Self::U16(values) => Some(values.clone()),
+            Self::U32(values) => Some(
+                values
+                    .iter()
+                    .map(|i| {
+                        u16::try_from(*i)
+                            .map_err(|_| crate::Error::IndexOverflow(*i, "u16".to_owned()))
+                    })
+                    .collect::<crate::Result<Vec<_>>>()?,
+            ),
+        })
+    }
+
+    ///
+    /// Converts all the indices to `u8` data type.
+    /// Returns an error if an index is larger than [u8::MAX] instead of truncating it.
+    ///
+    pub fn to_u8(&self) -> crate::Result<Option<Vec<u8>>> {
+        let convert =
+            |i: u32| u8::try_from(i).map_err(|_| crate::Error::IndexOverflow(i, "u8".to_owned()));
+        Ok(match self {
+            Self::None => None,
+            Self::U8(values) => Some(values.clone()),
+            Self::U16(values) => Some(
+                values
+                    .iter()
+                    .map(|i| convert(*i as u32))
+                    .collect::<crate::Result<Vec<_>>>()?,
+            ),
+            Self::U32(values) => Some(
+                values
+                    .iter()
+                    .map(|i| convert(*i))
+                    .collect::<crate::Result<Vec<_>>>()?,
+            ),
+        })
+    }
+
+    ///
+    /// Returns the largest index or `None` if there are no indices.
+    ///
+    pub fn max_index(&self) -> Option<u32> {
+        match self {
+            Self::None => None,
+            Self::U8(values) => values.iter().max().map(|i| *i as u32),
+            Self::U16(values) => values.iter().max().map(|i| *i as u32),
+            Self::U32(values) => values.iter().max().copied(),
+        }
+    }
+
+    ///
+    /// Converts the indices to the smallest data type which can represent the largest index.
+    /// Returns an error if an index is not less than the given number of vertices.
+    /// [Indices::None] is left unchanged, see [Indices::expand].
+    ///
+    pub fn optimize(&mut self, vertex_count: usize) -> crate::Result<()> {
+        if let Some(max_index) = self.max_index() {
+            if max_index as usize >= vertex_count {
+                Err(crate::Error::InvalidIndices(
+                    max_index as usize,
+                    vertex_count,
+                ))?;
+            }
+            *self = if max_index <= u8::MAX as u32 {
+                Self::U8(self.to_u8()?.unwrap())
+            } else if max_index <= u16::MAX as u32 {
+                Self::U16(self.to_u16()?.unwrap())
+            } else {
+                Self::U32(self.to_u32().unwrap())
+            };
+        }
+        Ok(())
+    }
+
+    ///
+    /// Converts [Indices::None] into explicit indices `0, 1, ..., vertex_count - 1` using the smallest data type which can represent them.
+    /// Other indices are left unchanged.
+    ///
+    pub fn expand(&mut self, vertex_count: usize) {
+        if let Self::None = self {
+            *self = if vertex_count <= u8::MAX as usize + 1 {
+                Self::U8((0..vertex_count).map(|i| i as u8).collect())
+            } else if vertex_count <= u16::MAX as usize + 1 {
+                Self::U16((0..vertex_count).map(|i| i as u16).collect())
+            } else {
+                Self::U32((0..vertex_count).map(|i| i as u32).collect())
+            };
+        }
+    }
+
     ///
     /// Returns the number of indices.
     ///
@@ -111,12 +211,6 @@ impl Indices {
     }
 }
 
-impl std::default::Default for Indices {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 ///
 /// An array of positions. Supports f32 and f64 data types.
 ///
@@ -131,6 +225,7 @@ pub enum Positions {
 impl Positions {
     ///
     /// Converts and returns all the positions as `f32` data type.
+    /// The conversion is lossy if the positions are `f64`, for example for large coordinates.
     ///
     pub fn into_f32(self) -> Vec<Vec3> {
         match self {
@@ -144,6 +239,7 @@ impl Positions {
 
     ///
     /// Clones and converts all the positions as `f32` data type.
+    /// The conversion is lossy if the positions are `f64`, for example for large coordinates.
     ///
     pub fn to_f32(&self) -> Vec<Vec3> {
         match self {
@@ -156,6 +252,7 @@ impl Positions {
     }
     ///
     /// Converts and returns all the positions as `f64` data type.
+    /// The conversion is lossless.
     ///
     pub fn into_f64(self) -> Vec<Vector3<f64>> {
         match self {
@@ -169,6 +266,7 @@ impl Positions {
 
     ///
     /// Clones and converts all the positions as `f64` data type.
+    /// The conversion is lossless.
     ///
     pub fn to_f64(&self) -> Vec<Vector3<f64>> {
         match self {
@@ -180,6 +278,30 @@ impl Positions {
         }
     }
 
+    ///
+    /// Returns the precision of the positions.
+    ///
+    pub fn precision(&self) -> PositionPrecision {
+        match self {
+            Self::F32(_) => PositionPrecision::F32,
+            Self::F64(_) => PositionPrecision::F64,
+        }
+    }
+
+    ///
+    /// Converts the positions in place to the given precision.
+    /// Converting from `f32` to `f64` is lossless while converting from `f64` to `f32` is lossy, see [Positions::into_f32].
+    ///
+    pub fn convert(&mut self, precision: PositionPrecision) {
+        if self.precision() != precision {
+            let positions = std::mem::take(self);
+            *self = match precision {
+                PositionPrecision::F32 => Self::F32(positions.into_f32()),
+                PositionPrecision::F64 => Self::F64(positions.into_f64()),
+            };
+        }
+    }
+
     ///
     /// Returns the number of positions.
     ///
@@ -214,6 +336,17 @@ impl Positions {
     }
 }
 
+///
+/// The precision of the data type used for [Positions].
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PositionPrecision {
+    /// 32 bit float.
+    F32,
+    /// 64 bit float.
+    F64,
+}
+
 impl std::default::Default for Positions {
     fn default() -> Self {
         Self::F32(Vec::new())
@@ -230,3 +363,135 @@ impl std::fmt::Debug for Positions {
         d.finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn convert_positions() {
+        let original = vec![vec3(1.5, -2.25, 3.1), vec3(1e-7, 16777216.0, -0.0)];
+        let mut positions = Positions::F32(original.clone());
+        assert_eq!(positions.precision(), PositionPrecision::F32);
+
+        // From 32 to 64 bit is lossless, so converting back results in the original positions
+        positions.convert(PositionPrecision::F64);
+        assert_eq!(positions.precision(), PositionPrecision::F64);
+        assert_eq!(positions.len(), 2);
+        let Positions::F64(ref values) = positions else {
+            unreachable!()
+        };
+        assert_eq!(values[0], vec3(1.5, -2.25, 3.1f32 as f64));
+        assert_ne!(values[0].z, 3.1);
+        positions.convert(PositionPrecision::F64);
+        assert_eq!(positions.precision(), PositionPrecision::F64);
+        positions.convert(PositionPrecision::F32);
+        assert_eq!(positions.precision(), PositionPrecision::F32);
+        assert_eq!(positions.to_f32(), original);
+
+        // From 64 to 32 bit rounds to the nearest 32 bit float
+        let mut positions = Positions::F64(vec![vec3(1.0 + 1e-10, 16777217.0, 0.1)]);
+        positions.convert(PositionPrecision::F32);
+        assert_eq!(positions.to_f32(), vec![vec3(1.0, 16777216.0, 0.1)]);
+        assert_eq!(
+            positions.to_f64(),
+            vec![vec3(1.0, 16777216.0, 0.1f32 as f64)]
+        );
+    }
+
+    #[test]
+    pub fn convert_indices() {
+        let indices = Indices::U32(vec![0, 255, 65535, 3]);
+        assert_eq!(indices.max_index(), Some(65535));
+        assert_eq!(indices.to_u16().unwrap(), Some(vec![0, 255, 65535, 3]));
+        assert!(matches!(
+            indices.to_u8(),
+            Err(crate::Error::IndexOverflow(65535, _))
+        ));
+
+        let indices = Indices::U32(vec![0, 65536, 1]);
+        assert!(matches!(
+            indices.to_u16(),
+            Err(crate::Error::IndexOverflow(65536, _))
+        ));
+        assert_eq!(indices.to_u32(), Some(vec![0, 65536, 1]));
+
+        let indices = Indices::U16(vec![2, 256]);
+        assert!(matches!(
+            indices.to_u8(),
+            Err(crate::Error::IndexOverflow(256, _))
+        ));
+        assert_eq!(Indices::U8(vec![7, 1]).to_u16().unwrap(), Some(vec![7, 1]));
+        assert_eq!(Indices::U16(vec![255]).to_u8().unwrap(), Some(vec![255]));
+
+        assert!(Indices::None.to_u8().unwrap().is_none());
+        assert!(Indices::None.to_u16().unwrap().is_none());
+        assert_eq!(Indices::None.max_index(), None);
+        assert_eq!(Indices::U8(Vec::new()).max_index(), None);
+    }
+
+    #[test]
+    pub fn optimize_indices() {
+        let mut indices = Indices::U32(vec![0, 1, 255]);
+        indices.optimize(256).unwrap();
+        assert!(matches!(indices, Indices::U8(ref i) if *i == [0, 1, 255]));
+
+        let mut indices = Indices::U32(vec![0, 1, 256]);
+        indices.optimize(257).unwrap();
+        assert!(matches!(indices, Indices::U16(ref i) if *i == [0, 1, 256]));
+
+        let mut indices = Indices::U8(vec![0, 1, 2]);
+        indices.optimize(3).unwrap();
+        assert!(matches!(indices, Indices::U8(ref i) if *i == [0, 1, 2]));
+
+        let mut indices = Indices::U16(vec![0, 65535, 2]);
+        indices.optimize(65536).unwrap();
+        assert!(matches!(indices, Indices::U16(_)));
+
+        let mut indices = Indices::U32(vec![0, 65536, 2]);
+        indices.optimize(65537).unwrap();
+        assert!(matches!(indices, Indices::U32(ref i) if *i == [0, 65536, 2]));
+
+        // An index which is out of range is an error and leaves the indices unchanged
+        let mut indices = Indices::U32(vec![0, 1, 3]);
+        assert!(matches!(
+            indices.optimize(3),
+            Err(crate::Error::InvalidIndices(3, 3))
+        ));
+        assert!(matches!(indices, Indices::U32(_)));
+
+        let mut indices = Indices::None;
+        indices.optimize(3).unwrap();
+        assert!(matches!(indices, Indices::None));
+    }
+
+    #[test]
+    pub fn expand_indices() {
+        let mut indices = Indices::None;
+        indices.expand(256);
+        assert!(matches!(indices, Indices::U8(_)));
+        assert_eq!(indices.to_u32(), Some((0..256).collect()));
+
+        let mut indices = Indices::None;
+        indices.expand(257);
+        assert!(matches!(indices, Indices::U16(_)));
+        assert_eq!(indices.max_index(), Some(256));
+
+        let mut indices = Indices::None;
+        indices.expand(65537);
+        assert!(matches!(indices, Indices::U32(_)));
+        assert_eq!(indices.len(), Some(65537));
+        assert_eq!(indices.max_index(), Some(65536));
+
+        // Explicit indices are left unchanged, so expanding and optimizing round-trips
+        let mut indices = Indices::U16(vec![2, 0, 1]);
+        indices.expand(3);
+        assert!(matches!(indices, Indices::U16(ref i) if *i == [2, 0, 1]));
+        let mut indices = Indices::None;
+        indices.expand(3);
+        let expanded = indices.clone();
+        indices.optimize(3).unwrap();
+        assert_eq!(indices.to_u32(), expanded.to_u32());
+        assert_eq!(indices.to_u32(), Some(vec![0, 1, 2]));
+    }
+}
@@ -1,4 +1,48 @@
-use crate::{prelude::*, Error, Indices, Positions, Result};
+use crate::{prelude::*, Error, Indices, PositionPrecision, Positions, Result};
+
+///
+/// A morph target (also called blend shape) of a [TriMesh], which contains offsets that are added to the vertex data of the mesh scaled by a weight.
+///
+#[derive(Clone, Debug, Default)]
+pub struct MorphTarget {
+    /// The offsets of the positions of the vertices.
+    pub positions: Option<Vec<Vec3>>,
+    /// The offsets of the normals of the vertices.
+    pub normals: Option<Vec<Vec3>>,
+    /// The offsets of the tangents of the vertices, the handedness is not affected by morphing.
+    pub tangents: Option<Vec<Vec3>>,
+    /// The default weight of this morph target, which is used when the weights are not animated.
+    pub weight: f32,
+}
+
+///
+/// One of the three coordinate axes, for example used as the projection direction in [TriMesh::generate_planar_uvs].
+///
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+///
+/// A problem with the geometry of a [TriMesh], see [TriMesh::issues].
+///
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MeshIssue {
+    /// The number of indices is not divisible by 3, the given number of trailing indices are ignored.
+    TrailingIndices(usize),
+    /// The position of the vertex with the given index is NaN or infinite.
+    NonFinitePosition(usize),
+    /// The triangle with the given index refers to the given vertex index which is not less than the number of vertices.
+    IndexOutOfRange(usize, usize),
+    /// The triangle with the given index has zero area.
+    DegenerateTriangle(usize),
+    /// The vertex with the given index is not used by any triangle.
+    UnusedVertex(usize),
+}
+
 ///
 /// A CPU-side version of a triangle mesh.
 ///
@@ -16,9 +60,18 @@ pub struct TriMesh {
     pub tangents: Option<Vec<Vec4>>,
     /// The uv coordinates of the vertices.
     pub uvs: Option<Vec<Vec2>>,
+    /// The uv coordinates of the vertices for additional uv sets, for example used for lightmaps.
+    /// The first element is the second uv set, since the first uv set is [TriMesh::uvs], see also [TriMesh::uv_set].
+    pub additional_uvs: Vec<Vec<Vec2>>,
     /// The colors of the vertices.
     /// The colors are assumed to be in linear space.
     pub colors: Option<Vec<Color>>,
+    /// The indices into [Skin::joints](crate::Skin::joints) of the (up to) four joints which influence each vertex.
+    pub joint_indices: Option<Vec<Vector4<u16>>>,
+    /// The weights of the joints given by [TriMesh::joint_indices] for each vertex.
+    pub joint_weights: Option<Vec<Vec4>>,
+    /// The morph targets of this mesh, see [TriMesh::apply_morph].
+    pub morph_targets: Option<Vec<MorphTarget>>,
 }
 
 impl std::default::Default for TriMesh {
@@ -29,7 +82,11 @@ impl std::default::Default for TriMesh {
             normals: None,
             tangents: None,
             uvs: None,
+            additional_uvs: Vec::new(),
             colors: None,
+            joint_indices: None,
+            joint_weights: None,
+            morph_targets: None,
         }
     }
 }
@@ -42,7 +99,16 @@ impl std::fmt::Debug for TriMesh {
         d.field("normals", &self.normals.as_ref().map(|v| v.len()));
         d.field("tangents", &self.tangents.as_ref().map(|v| v.len()));
         d.field("uvs", &self.uvs.as_ref().map(|v| v.len()));
+        d.field("additional_uvs", &self.additional_uvs.len());
         d.field("colors", &self.colors.as_ref().map(|v| v.len()));
+        d.field(
+            "joint_indices",
+            &self.joint_indices.as_ref().map(|v| v.len()),
+        );
+        d.field(
+            "joint_weights",
+            &self.joint_weights.as_ref().map(|v| v.len()),
+        );
         d.finish()
     }
 }
@@ -61,6 +127,75 @@ impl TriMesh {
             .unwrap_or(self.positions.len() / 3)
     }
 
+    ///
+    /// Returns the uv coordinates of the uv set with the given index, where index 0 is [TriMesh::uvs] and the rest is [TriMesh::additional_uvs].
+    ///
+    pub fn uv_set(&self, index: usize) -> Option<&Vec<Vec2>> {
+        if index == 0 {
+            self.uvs.as_ref()
+        } else {
+            self.additional_uvs.get(index - 1)
+        }
+    }
+
+    ///
+    /// Returns a copy of this mesh where the [TriMesh::morph_targets] are blended into the vertex data using the given weights, one for each morph target.
+    /// Missing weights are treated as zero, so the default weights of the morph targets should be given explicitly if that is desired.
+    /// The returned mesh contains no morph targets while this mesh is unchanged, so blending several times with different weights, for example animated weights, is stable.
+    ///
+    pub fn apply_morph(&self, weights: &[f32]) -> Self {
+        let mut mesh = Self {
+            morph_targets: None,
+            ..self.clone()
+        };
+        let weighted_targets = self
+            .morph_targets
+            .iter()
+            .flatten()
+            .zip(weights.iter().copied())
+            .filter(|(_, weight)| *weight != 0.0);
+        for (target, weight) in weighted_targets {
+            if let Some(offsets) = target.positions.as_ref() {
+                match mesh.positions {
+                    Positions::F32(ref mut positions) => {
+                        for (pos, offset) in positions.iter_mut().zip(offsets) {
+                            *pos += offset * weight;
+                        }
+                    }
+                    Positions::F64(ref mut positions) => {
+                        for (pos, offset) in positions.iter_mut().zip(offsets) {
+                            *pos += offset.cast::<f64>().unwrap() * weight as f64;
+                        }
+                    }
+                }
+            }
+            if let (Some(normals), Some(offsets)) = (mesh.normals.as_mut(), target.normals.as_ref())
+            {
+                for (normal, offset) in normals.iter_mut().zip(offsets) {
+                    *normal += offset * weight;
+                }
+            }
+            if let (Some(tangents), Some(offsets)) =
+                (mesh.tangents.as_mut(), target.tangents.as_ref())
+            {
+                for (tangent, offset) in tangents.iter_mut().zip(offsets) {
+                    *tangent += (offset * weight).extend(0.0);
+                }
+            }
+        }
+        if let Some(normals) = mesh.normals.as_mut() {
+            for normal in normals.iter_mut() {
+                *normal = normal.normalize();
+            }
+        }
+        if let Some(tangents) = mesh.tangents.as_mut() {
+            for tangent in tangents.iter_mut() {
+                *tangent = tangent.truncate().normalize().extend(tangent.w);
+            }
+        }
+        mesh
+    }
+
     ///
     /// Transforms the mesh by the given transformation.
     ///
@@ -101,6 +236,52 @@ impl TriMesh {
         Ok(())
     }
 
+    ///
+    /// Converts the indices to the smallest data type which can represent all of them, see [Indices::optimize].
+    /// Returns an error if an index is out of range.
+    ///
+    pub fn compact_indices(&mut self) -> Result<()> {
+        let vertex_count = self.vertex_count();
+        self.indices.optimize(vertex_count)
+    }
+
+    ///
+    /// Converts the positions of the mesh to the given precision, see [Positions::convert].
+    /// The normals, tangents and morph target offsets are always stored with `f32` precision.
+    ///
+    pub fn convert_positions(&mut self, precision: PositionPrecision) {
+        self.positions.convert(precision);
+    }
+
+    ///
+    /// Translates the center of the bounding box of the mesh to the origin and uniformly scales the mesh such that the largest side of the bounding box has a length of 1.
+    /// Returns the applied transformation, see [AxisAlignedBoundingBox::normalizing_transformation].
+    /// The normals and tangents are unaffected since the scale is uniform, while the position offsets of the morph targets are scaled.
+    ///
+    pub fn normalize(&mut self) -> Mat4 {
+        let transformation = self.compute_aabb().normalizing_transformation();
+        match self.positions {
+            Positions::F32(ref mut positions) => {
+                for pos in positions.iter_mut() {
+                    *pos = (transformation * pos.extend(1.0)).truncate();
+                }
+            }
+            Positions::F64(ref mut positions) => {
+                let t = transformation.cast::<f64>().unwrap();
+                for pos in positions.iter_mut() {
+                    *pos = (t * pos.extend(1.0)).truncate();
+                }
+            }
+        };
+        let scale = transformation.x.x;
+        for target in self.morph_targets.iter_mut().flatten() {
+            for offset in target.positions.iter_mut().flatten() {
+                *offset *= scale;
+            }
+        }
+        transformation
+    }
+
     ///
     /// Returns a square mesh spanning the xy-plane with positions in the range `[-1..1]` in the x and y axes.
     ///
@@ -329,7 +510,7 @@ impl TriMesh {
             ..Default::default()
         };
         mesh.compute_normals();
-        mesh.compute_tangents();
+        mesh.compute_tangents().unwrap();
         mesh
     }
 
@@ -478,14 +659,833 @@ impl TriMesh {
         self.normals = Some(normals);
     }
 
+    ///
+    /// Computes flat normals, ie. one normal for each triangle, for faceted shading.
+    /// To do this, the vertices are duplicated so that each triangle has its own three vertices and the indices are removed, which increases the vertex count to three times the triangle count.
+    /// The normals point towards the side where the vertices of a triangle are in counter-clockwise order.
+    /// All other vertex data is duplicated accordingly, except that existing tangents are made orthogonal to the new normals and the normal offsets of the morph targets are removed.
+    /// It will override the current normals if they already exist.
+    ///
+    pub fn compute_flat_normals(&mut self) {
+        let mut order = Vec::with_capacity(self.triangle_count() * 3);
+        self.for_each_triangle(|i0, i1, i2| order.extend([i0, i1, i2]));
+
+        self.positions = match self.positions {
+            Positions::F32(ref positions) => Positions::F32(gather(positions, &order)),
+            Positions::F64(ref positions) => Positions::F64(gather(positions, &order)),
+        };
+        self.indices = Indices::None;
+        let mut normals = Vec::with_capacity(order.len());
+        self.for_each_triangle(|i0, i1, i2| {
+            let normal = match self.positions {
+                Positions::F32(ref positions) => {
+                    (positions[i1] - positions[i0]).cross(positions[i2] - positions[i0])
+                }
+                Positions::F64(ref positions) => {
+                    let n = (positions[i1] - positions[i0]).cross(positions[i2] - positions[i0]);
+                    Vec3::new(n.x as f32, n.y as f32, n.z as f32)
+                }
+            };
+            let normal = if normal.magnitude2() > 0.0 {
+                normal.normalize()
+            } else {
+                Vec3::zero()
+            };
+            normals.extend([normal; 3]);
+        });
+
+        self.tangents = self.tangents.as_ref().map(|tangents| {
+            gather(tangents, &order)
+                .into_iter()
+                .zip(&normals)
+                .map(|(t, n)| {
+                    (t.truncate() - n * n.dot(t.truncate()))
+                        .normalize()
+                        .extend(t.w)
+                })
+                .collect()
+        });
+        self.normals = Some(normals);
+        self.uvs = self.uvs.as_ref().map(|uvs| gather(uvs, &order));
+        for uvs in self.additional_uvs.iter_mut() {
+            *uvs = gather(uvs, &order);
+        }
+        self.colors = self.colors.as_ref().map(|colors| gather(colors, &order));
+        self.joint_indices = self.joint_indices.as_ref().map(|j| gather(j, &order));
+        self.joint_weights = self.joint_weights.as_ref().map(|w| gather(w, &order));
+        for target in self.morph_targets.iter_mut().flatten() {
+            target.positions = target.positions.as_ref().map(|p| gather(p, &order));
+            target.normals = None;
+            target.tangents = target.tangents.as_ref().map(|t| gather(t, &order));
+        }
+    }
+
+    ///
+    /// Merges vertices whose positions are within the given distance of each other and whose other vertex data, ie. normals, tangents, uv coordinates, colors, joints and morph target offsets, are within the given tolerance,
+    /// and rebuilds the indices so that they refer to the merged vertices.
+    /// Vertices with different vertex data, for example at uv seams or hard edges, are not merged.
+    /// The vertex data of a merged vertex is the data of the first of the merged vertices.
+    /// A tolerance of zero only merges vertices with identical data.
+    ///
+    pub fn weld(&mut self, tolerance: f32) {
+        let positions = self.positions.to_f64();
+        let tolerance = tolerance.max(0.0);
+        fn near<V: InnerSpace<Scalar = f32>>(a: V, b: V, tolerance: f32) -> bool {
+            (a - b).magnitude() <= tolerance
+        }
+        let is_same = |i: usize, j: usize| {
+            (positions[i] - positions[j]).magnitude() <= tolerance as f64
+                && self.normals.iter().all(|n| near(n[i], n[j], tolerance))
+                && self
+                    .tangents
+                    .iter()
+                    .all(|t| near(t[i].truncate(), t[j].truncate(), tolerance) && t[i].w == t[j].w)
+                && self.uvs.iter().all(|uv| near(uv[i], uv[j], tolerance))
+                && self
+                    .additional_uvs
+                    .iter()
+                    .all(|uv| near(uv[i], uv[j], tolerance))
+                && self.colors.iter().all(|c| c[i] == c[j])
+                && self
+                    .joint_indices
+                    .iter()
+                    .all(|joints| joints[i] == joints[j])
+                && self
+                    .joint_weights
+                    .iter()
+                    .all(|w| near(w[i], w[j], tolerance))
+                && self.morph_targets.iter().flatten().all(|target| {
+                    [&target.positions, &target.normals, &target.tangents]
+                        .into_iter()
+                        .flatten()
+                        .all(|o| near(o[i], o[j], tolerance))
+                })
+        };
+
+        // A spatial hash with a cell size of the tolerance, so merged vertices are in neighbouring cells
+        let cell_size = if tolerance > 0.0 {
+            tolerance as f64
+        } else {
+            1.0
+        };
+        let cell = |p: Vector3<f64>| {
+            (
+                (p.x / cell_size).floor() as i64,
+                (p.y / cell_size).floor() as i64,
+                (p.z / cell_size).floor() as i64,
+            )
+        };
+        let mut cells = std::collections::HashMap::<(i64, i64, i64), Vec<usize>>::new();
+        let mut order = Vec::new();
+        let mut remap = Vec::with_capacity(positions.len());
+        for (i, position) in positions.iter().enumerate() {
+            let (x, y, z) = cell(*position);
+            let mut neighbours = (-1..=1).flat_map(|dx| {
+                (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (x + dx, y + dy, z + dz)))
+            });
+            let merged = neighbours.find_map(|key| {
+                cells
+                    .get(&key)?
+                    .iter()
+                    .copied()
+                    .find(|&new_index| is_same(i, order[new_index]))
+            });
+            remap.push(merged.unwrap_or_else(|| {
+                let new_index = order.len();
+                order.push(i);
+                cells.entry((x, y, z)).or_default().push(new_index);
+                new_index
+            }));
+        }
+
+        let mut indices = Vec::with_capacity(self.triangle_count() * 3);
+        self.for_each_triangle(|i0, i1, i2| {
+            indices.extend([remap[i0], remap[i1], remap[i2]]);
+        });
+        *self = self.gather_vertices(&order, indices);
+    }
+
+    ///
+    /// Splits the mesh into one mesh for each material, where `face_material` contains the material id of each triangle.
+    /// Returns the material ids together with meshes containing the triangles with that material id, sorted by material id.
+    /// Each mesh only contains the vertices used by its triangles, so the indices are remapped accordingly, and materials without any triangles are omitted.
+    /// Returns an error if there is not exactly one material id for each triangle.
+    ///
+    pub fn split_by(&self, face_material: &[u32]) -> Result<Vec<(u32, TriMesh)>> {
+        if face_material.len() != self.triangle_count() {
+            Err(Error::InvalidNumberOfIndices(face_material.len()))?;
+        }
+        let mut groups = std::collections::BTreeMap::<u32, Vec<usize>>::new();
+        let mut face = 0;
+        self.for_each_triangle(|i0, i1, i2| {
+            groups
+                .entry(face_material[face])
+                .or_default()
+                .extend([i0, i1, i2]);
+            face += 1;
+        });
+        Ok(groups
+            .into_iter()
+            .map(|(material, triangles)| {
+                let mut remap = std::collections::HashMap::new();
+                let mut order = Vec::new();
+                let indices = triangles
+                    .into_iter()
+                    .map(|i| {
+                        *remap.entry(i).or_insert_with(|| {
+                            order.push(i);
+                            order.len() - 1
+                        })
+                    })
+                    .collect();
+                (material, self.gather_vertices(&order, indices))
+            })
+            .collect())
+    }
+
+    ///
+    /// Returns a simplified copy of this mesh with approximately the given fraction of the triangles, using edge collapses ordered by the quadric error metric.
+    /// Each edge collapse merges two vertices into one vertex placed at either of the two vertices or in between, whichever gives the lowest error, and the vertex data is interpolated accordingly.
+    /// Vertices on a boundary, ie. an edge with only one adjacent triangle, are not moved, which preserves the mesh boundaries and also uv and normal seams since the vertices are split at those seams.
+    /// Therefore, a mesh without indices or with duplicated vertices should be welded first, see [TriMesh::weld].
+    /// The simplification also stops when the error of the next edge collapse exceeds `max_error`, which is approximately the distance between the merged vertex and the original surface, so use [f32::INFINITY] to only stop at the target triangle count.
+    /// Edge collapses that would flip a triangle are skipped.
+    ///
+    pub fn simplify(&self, target_ratio: f32, max_error: f32) -> TriMesh {
+        let mut mesh = self.clone();
+        let mut positions = self.positions.to_f64();
+        let mut triangles = Vec::with_capacity(self.triangle_count());
+        self.for_each_triangle(|i0, i1, i2| triangles.push([i0, i1, i2]));
+        let target_count = (triangles.len() as f32 * target_ratio.clamp(0.0, 1.0)).ceil() as usize;
+
+        let mut vertex_triangles = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![[0.0; 10]; positions.len()];
+        let mut edge_count = std::collections::HashMap::<(usize, usize), usize>::new();
+        for (t, triangle) in triangles.iter().enumerate() {
+            let quadric = plane_quadric(triangle.map(|i| positions[i]));
+            for (k, &i) in triangle.iter().enumerate() {
+                vertex_triangles[i].push(t);
+                for (q, value) in quadrics[i].iter_mut().zip(quadric) {
+                    *q += value;
+                }
+                let j = triangle[(k + 1) % 3];
+                *edge_count.entry((i.min(j), i.max(j))).or_default() += 1;
+            }
+        }
+        let mut is_locked = vec![false; positions.len()];
+        for (&(i, j), &count) in edge_count.iter() {
+            if count != 2 {
+                is_locked[i] = true;
+                is_locked[j] = true;
+            }
+        }
+
+        // The heap contains the error, the two vertices and their versions, so outdated collapses can be skipped
+        let mut versions = vec![0u32; positions.len()];
+        let mut heap = std::collections::BinaryHeap::new();
+        for &(i, j) in edge_count.keys() {
+            if let Some((error, _)) = best_collapse(&positions, &quadrics, &is_locked, i, j) {
+                heap.push(std::cmp::Reverse((error.max(0.0).to_bits(), i, j, 0, 0)));
+            }
+        }
+
+        let mut is_removed = vec![false; triangles.len()];
+        let mut triangle_count = triangles.len();
+        let max_error = (max_error as f64).powi(2);
+        while triangle_count > target_count {
+            let Some(std::cmp::Reverse((_, i, j, version_i, version_j))) = heap.pop() else {
+                break;
+            };
+            if versions[i] != version_i || versions[j] != version_j {
+                continue;
+            }
+            let Some((error, t)) = best_collapse(&positions, &quadrics, &is_locked, i, j) else {
+                continue;
+            };
+            if error > max_error {
+                break;
+            }
+            let position = positions[i] + (positions[j] - positions[i]) * t;
+
+            // Skip the collapse if it flips any of the remaining triangles
+            let flips = [i, j].iter().any(|&v| {
+                vertex_triangles[v].iter().any(|&t| {
+                    let triangle = triangles[t];
+                    if is_removed[t] || (triangle.contains(&i) && triangle.contains(&j)) {
+                        return false;
+                    }
+                    let moved = triangle.map(|k| if k == v { position } else { positions[k] });
+                    let original = triangle.map(|k| positions[k]);
+                    let normal = |p: [Vector3<f64>; 3]| (p[1] - p[0]).cross(p[2] - p[0]);
+                    normal(moved).dot(normal(original)) <= 0.0
+                })
+            });
+            if flips {
+                continue;
+            }
+
+            // Collapse j into i
+            positions[i] = position;
+            interpolate_vertex(&mut mesh, i, j, t as f32);
+            is_locked[i] |= is_locked[j];
+            quadrics[i] = add_quadrics(&quadrics[i], &quadrics[j]);
+            versions[i] += 1;
+            versions[j] += 1;
+            for t in std::mem::take(&mut vertex_triangles[j]) {
+                if is_removed[t] {
+                    continue;
+                }
+                if triangles[t].contains(&i) {
+                    is_removed[t] = true;
+                    triangle_count -= 1;
+                } else {
+                    for k in triangles[t].iter_mut().filter(|k| **k == j) {
+                        *k = i;
+                    }
+                    vertex_triangles[i].push(t);
+                }
+            }
+            vertex_triangles[i].retain(|&t| !is_removed[t]);
+            let mut neighbours = vertex_triangles[i]
+                .iter()
+                .flat_map(|&t| triangles[t])
+                .filter(|&k| k != i)
+                .collect::<Vec<_>>();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            for k in neighbours {
+                if let Some((error, _)) = best_collapse(&positions, &quadrics, &is_locked, i, k) {
+                    heap.push(std::cmp::Reverse((
+                        error.max(0.0).to_bits(),
+                        i,
+                        k,
+                        versions[i],
+                        versions[k],
+                    )));
+                }
+            }
+        }
+
+        mesh.positions = match self.positions {
+            Positions::F32(_) => {
+                Positions::F32(positions.iter().map(|p| p.cast::<f32>().unwrap()).collect())
+            }
+            Positions::F64(_) => Positions::F64(positions),
+        };
+        let mut remap = std::collections::HashMap::new();
+        let mut order = Vec::new();
+        let indices = triangles
+            .iter()
+            .zip(&is_removed)
+            .filter(|(_, is_removed)| !**is_removed)
+            .flat_map(|(triangle, _)| *triangle)
+            .map(|i| {
+                *remap.entry(i).or_insert_with(|| {
+                    order.push(i);
+                    order.len() - 1
+                })
+            })
+            .collect();
+        mesh.gather_vertices(&order, indices)
+    }
+
+    ///
+    /// Returns a smoother and denser copy of this mesh using the given number of levels of Loop subdivision, where each level splits each triangle into four.
+    /// The new vertices on the edges and the original vertices are positioned using the Loop subdivision rules, and the boundary rules are used for vertices on a boundary, ie. an edge with only one adjacent triangle, so open meshes keep their boundary curve.
+    /// Since split vertices form a boundary, a mesh without indices or with duplicated vertices should be welded first, see [TriMesh::weld].
+    /// The other vertex data of the new vertices is interpolated from the two vertices of the edge, while the joints are copied from one of them.
+    /// Finally, the normals are computed and the tangents are computed if the mesh had tangents and uv coordinates, otherwise the tangents are interpolated as well.
+    ///
+    pub fn subdivide_loop(&self, levels: u32) -> TriMesh {
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = mesh.subdivide_loop_once();
+        }
+        mesh.compute_normals();
+        if mesh.tangents.is_some() {
+            // Fails if there are no uv coordinates, in which case the interpolated tangents are kept
+            let _ = mesh.compute_tangents();
+        }
+        mesh
+    }
+
+    fn subdivide_loop_once(&self) -> TriMesh {
+        let positions = self.positions.to_f64();
+        let mut triangles = Vec::with_capacity(self.triangle_count());
+        self.for_each_triangle(|i0, i1, i2| triangles.push([i0, i1, i2]));
+
+        // Each edge is mapped to the index of the new vertex and the vertices opposite to the edge
+        let mut edges = std::collections::HashMap::<(usize, usize), (usize, Vec<usize>)>::new();
+        let mut edge_vertices = Vec::new();
+        for triangle in triangles.iter() {
+            for k in 0..3 {
+                let (i, j) = (triangle[k], triangle[(k + 1) % 3]);
+                let edge = edges.entry((i.min(j), i.max(j))).or_insert_with(|| {
+                    edge_vertices.push((i.min(j), i.max(j)));
+                    (positions.len() + edge_vertices.len() - 1, Vec::new())
+                });
+                edge.1.push(triangle[(k + 2) % 3]);
+            }
+        }
+
+        let mut neighbours = vec![Vec::new(); positions.len()];
+        let mut boundary_neighbours = vec![Vec::new(); positions.len()];
+        for (&(i, j), (_, opposite)) in edges.iter() {
+            neighbours[i].push(j);
+            neighbours[j].push(i);
+            if opposite.len() == 1 {
+                boundary_neighbours[i].push(j);
+                boundary_neighbours[j].push(i);
+            }
+        }
+        let mut new_positions = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                if !boundary_neighbours[i].is_empty() {
+                    match boundary_neighbours[i][..] {
+                        [a, b] => p * 0.75 + (positions[a] + positions[b]) * 0.125,
+                        // Corners of non-manifold meshes are kept in place
+                        _ => p,
+                    }
+                } else if neighbours[i].is_empty() {
+                    p
+                } else {
+                    let n = neighbours[i].len() as f64;
+                    let beta = if neighbours[i].len() == 3 {
+                        3.0 / 16.0
+                    } else {
+                        3.0 / (8.0 * n)
+                    };
+                    let sum = neighbours[i]
+                        .iter()
+                        .fold(Vector3::zero(), |sum, &j| sum + positions[j]);
+                    p * (1.0 - n * beta) + sum * beta
+                }
+            })
+            .collect::<Vec<_>>();
+        new_positions.extend(
+            edge_vertices
+                .iter()
+                .map(|&(i, j)| match edges[&(i, j)].1[..] {
+                    [c, d] => {
+                        (positions[i] + positions[j]) * 0.375
+                            + (positions[c] + positions[d]) * 0.125
+                    }
+                    _ => (positions[i] + positions[j]) * 0.5,
+                }),
+        );
+
+        let mut indices = Vec::with_capacity(triangles.len() * 12);
+        for [a, b, c] in triangles {
+            let edge = |i: usize, j: usize| edges[&(i.min(j), i.max(j))].0;
+            let (ab, bc, ca) = (edge(a, b), edge(b, c), edge(c, a));
+            indices.extend([a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+        }
+
+        fn midpoints<T: Copy>(
+            values: &[T],
+            edges: &[(usize, usize)],
+            mid: impl Fn(T, T) -> T,
+        ) -> Vec<T> {
+            let mut result = values.to_vec();
+            result.extend(edges.iter().map(|&(i, j)| mid(values[i], values[j])));
+            result
+        }
+        TriMesh {
+            positions: match self.positions {
+                Positions::F32(_) => Positions::F32(
+                    new_positions
+                        .iter()
+                        .map(|p| p.cast::<f32>().unwrap())
+                        .collect(),
+                ),
+                Positions::F64(_) => Positions::F64(new_positions),
+            },
+            indices: compact_indices(indices, positions.len() + edge_vertices.len()),
+            normals: self
+                .normals
+                .as_ref()
+                .map(|n| midpoints(n, &edge_vertices, |a, b| (a + b).normalize())),
+            tangents: self.tangents.as_ref().map(|t| {
+                midpoints(t, &edge_vertices, |a, b| {
+                    (a.truncate() + b.truncate()).normalize().extend(a.w)
+                })
+            }),
+            uvs: self
+                .uvs
+                .as_ref()
+                .map(|uvs| midpoints(uvs, &edge_vertices, |a, b| (a + b) * 0.5)),
+            additional_uvs: self
+                .additional_uvs
+                .iter()
+                .map(|uvs| midpoints(uvs, &edge_vertices, |a, b| (a + b) * 0.5))
+                .collect(),
+            colors: self.colors.as_ref().map(|colors| {
+                midpoints(colors, &edge_vertices, |a, b| {
+                    Color::from_rgba_slice(&((a.to_vec4() + b.to_vec4()) * 0.5).into())
+                })
+            }),
+            joint_indices: self
+                .joint_indices
+                .as_ref()
+                .map(|j| midpoints(j, &edge_vertices, |a, _| a)),
+            joint_weights: self
+                .joint_weights
+                .as_ref()
+                .map(|w| midpoints(w, &edge_vertices, |a, _| a)),
+            morph_targets: self.morph_targets.as_ref().map(|targets| {
+                targets
+                    .iter()
+                    .map(|target| {
+                        let offsets = |o: &Option<Vec<Vec3>>| {
+                            o.as_ref()
+                                .map(|o| midpoints(o, &edge_vertices, |a, b| (a + b) * 0.5))
+                        };
+                        MorphTarget {
+                            positions: offsets(&target.positions),
+                            normals: offsets(&target.normals),
+                            tangents: offsets(&target.tangents),
+                            weight: target.weight,
+                        }
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    ///
+    /// Combines the given meshes into one mesh by concatenating the vertex data and offsetting the indices.
+    /// If any of the meshes have a certain type of vertex data, the vertex data is filled in for the meshes that do not have it,
+    /// where normals and tangents are computed if possible, uv coordinates, joints and morph target offsets are zero and colors are white.
+    /// The positions are stored as 64 bit floats if any of the meshes has 64 bit positions, and the smallest index type which can index all vertices is used.
+    /// Note that the joints of all meshes must refer to the same skin.
+    ///
+    pub fn merge(meshes: &[TriMesh]) -> TriMesh {
+        Self::merge_meshes(meshes.to_vec())
+    }
+
+    ///
+    /// Combines the given meshes into one mesh like [TriMesh::merge], but first transforms each mesh by the given transformation, see [TriMesh::transform].
+    ///
+    pub fn merge_transformed(meshes: &[(TriMesh, Mat4)]) -> Result<TriMesh> {
+        let mut transformed = Vec::with_capacity(meshes.len());
+        for (mesh, transformation) in meshes {
+            let mut mesh = mesh.clone();
+            mesh.transform(transformation)?;
+            transformed.push(mesh);
+        }
+        Ok(Self::merge_meshes(transformed))
+    }
+
+    fn merge_meshes(mut meshes: Vec<TriMesh>) -> TriMesh {
+        if meshes.iter().any(|m| m.normals.is_some()) {
+            for mesh in meshes.iter_mut().filter(|m| m.normals.is_none()) {
+                mesh.compute_normals();
+            }
+        }
+        if meshes.iter().any(|m| m.tangents.is_some()) {
+            for mesh in meshes.iter_mut().filter(|m| m.tangents.is_none()) {
+                if mesh.compute_tangents().is_err() {
+                    mesh.tangents = Some(vec![vec4(1.0, 0.0, 0.0, 1.0); mesh.vertex_count()]);
+                }
+            }
+        }
+        fn concat<T: Copy>(
+            meshes: &[TriMesh],
+            values: impl Fn(&TriMesh) -> Option<&Vec<T>>,
+            default: T,
+        ) -> Option<Vec<T>> {
+            meshes.iter().any(|m| values(m).is_some()).then(|| {
+                meshes
+                    .iter()
+                    .flat_map(|m| {
+                        values(m)
+                            .map(|v| v[..m.vertex_count()].to_vec())
+                            .unwrap_or_else(|| vec![default; m.vertex_count()])
+                    })
+                    .collect()
+            })
+        }
+
+        let vertex_count = meshes.iter().map(|m| m.vertex_count()).sum();
+        let indices = if meshes.iter().all(|m| m.indices.len().is_none()) {
+            Indices::None
+        } else {
+            let mut indices = Vec::new();
+            let mut offset = 0;
+            for mesh in meshes.iter() {
+                mesh.for_each_triangle(|i0, i1, i2| {
+                    indices.extend([i0 + offset, i1 + offset, i2 + offset])
+                });
+                offset += mesh.vertex_count();
+            }
+            compact_indices(indices, vertex_count)
+        };
+        let morph_target_count = meshes
+            .iter()
+            .map(|m| m.morph_targets.as_ref().map(|t| t.len()).unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+        fn morph_target(mesh: &TriMesh, i: usize) -> Option<&MorphTarget> {
+            mesh.morph_targets.as_ref()?.get(i)
+        }
+        TriMesh {
+            positions: if meshes
+                .iter()
+                .any(|m| matches!(m.positions, Positions::F64(_)))
+            {
+                Positions::F64(meshes.iter().flat_map(|m| m.positions.to_f64()).collect())
+            } else {
+                Positions::F32(meshes.iter().flat_map(|m| m.positions.to_f32()).collect())
+            },
+            indices,
+            normals: concat(&meshes, |m| m.normals.as_ref(), Vec3::zero()),
+            tangents: concat(&meshes, |m| m.tangents.as_ref(), vec4(1.0, 0.0, 0.0, 1.0)),
+            uvs: concat(&meshes, |m| m.uvs.as_ref(), Vec2::zero()),
+            additional_uvs: (0..meshes
+                .iter()
+                .map(|m| m.additional_uvs.len())
+                .max()
+                .unwrap_or(0))
+                .map(|i| concat(&meshes, |m| m.additional_uvs.get(i), Vec2::zero()).unwrap())
+                .collect(),
+            colors: concat(&meshes, |m| m.colors.as_ref(), Color::WHITE),
+            joint_indices: concat(&meshes, |m| m.joint_indices.as_ref(), Vector4::zero()),
+            joint_weights: concat(&meshes, |m| m.joint_weights.as_ref(), Vec4::zero()),
+            morph_targets: (morph_target_count > 0).then(|| {
+                (0..morph_target_count)
+                    .map(|i| MorphTarget {
+                        positions: concat(
+                            &meshes,
+                            |m| morph_target(m, i)?.positions.as_ref(),
+                            Vec3::zero(),
+                        ),
+                        normals: concat(
+                            &meshes,
+                            |m| morph_target(m, i)?.normals.as_ref(),
+                            Vec3::zero(),
+                        ),
+                        tangents: concat(
+                            &meshes,
+                            |m| morph_target(m, i)?.tangents.as_ref(),
+                            Vec3::zero(),
+                        ),
+                        weight: meshes
+                            .iter()
+                            .find_map(|m| morph_target(m, i))
+                            .map(|t| t.weight)
+                            .unwrap_or(0.0),
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    ///
+    /// Returns a mesh with the vertex data of the vertices with the given indices in the given order and the given indices into the returned vertices.
+    ///
+    fn gather_vertices(&self, order: &[usize], indices: Vec<usize>) -> TriMesh {
+        TriMesh {
+            positions: match self.positions {
+                Positions::F32(ref positions) => Positions::F32(gather(positions, order)),
+                Positions::F64(ref positions) => Positions::F64(gather(positions, order)),
+            },
+            indices: compact_indices(indices, order.len()),
+            normals: self.normals.as_ref().map(|n| gather(n, order)),
+            tangents: self.tangents.as_ref().map(|t| gather(t, order)),
+            uvs: self.uvs.as_ref().map(|uvs| gather(uvs, order)),
+            additional_uvs: self
+                .additional_uvs
+                .iter()
+                .map(|uvs| gather(uvs, order))
+                .collect(),
+            colors: self.colors.as_ref().map(|colors| gather(colors, order)),
+            joint_indices: self.joint_indices.as_ref().map(|j| gather(j, order)),
+            joint_weights: self.joint_weights.as_ref().map(|w| gather(w, order)),
+            morph_targets: self.morph_targets.as_ref().map(|targets| {
+                targets
+                    .iter()
+                    .map(|target| MorphTarget {
+                        positions: target.positions.as_ref().map(|p| gather(p, order)),
+                        normals: target.normals.as_ref().map(|n| gather(n, order)),
+                        tangents: target.tangents.as_ref().map(|t| gather(t, order)),
+                        weight: target.weight,
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    ///
+    /// Reverses the orientation of all triangles, ie. turns the mesh inside out, by reversing the order of the vertices of each triangle.
+    /// The normals, including the normal offsets of the morph targets, are negated and the handedness of the tangents is flipped, so flipping twice results in the original mesh.
+    /// If the mesh has no indices, the vertex data is reordered instead of the indices.
+    ///
+    pub fn flip_winding(&mut self) {
+        fn swap<T>(values: &mut [T]) {
+            for triangle in values.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+        match self.indices {
+            Indices::U8(ref mut indices) => swap(indices),
+            Indices::U16(ref mut indices) => swap(indices),
+            Indices::U32(ref mut indices) => swap(indices),
+            Indices::None => {
+                match self.positions {
+                    Positions::F32(ref mut positions) => swap(positions),
+                    Positions::F64(ref mut positions) => swap(positions),
+                }
+                if let Some(n) = self.normals.as_mut() {
+                    swap(n);
+                }
+                if let Some(t) = self.tangents.as_mut() {
+                    swap(t);
+                }
+                if let Some(uvs) = self.uvs.as_mut() {
+                    swap(uvs);
+                }
+                for uvs in self.additional_uvs.iter_mut() {
+                    swap(uvs);
+                }
+                if let Some(colors) = self.colors.as_mut() {
+                    swap(colors);
+                }
+                if let Some(j) = self.joint_indices.as_mut() {
+                    swap(j);
+                }
+                if let Some(w) = self.joint_weights.as_mut() {
+                    swap(w);
+                }
+                for target in self.morph_targets.iter_mut().flatten() {
+                    if let Some(p) = target.positions.as_mut() {
+                        swap(p);
+                    }
+                    if let Some(n) = target.normals.as_mut() {
+                        swap(n);
+                    }
+                    if let Some(t) = target.tangents.as_mut() {
+                        swap(t);
+                    }
+                }
+            }
+        }
+        for normal in self.normals.iter_mut().flatten() {
+            *normal = -*normal;
+        }
+        for tangent in self.tangents.iter_mut().flatten() {
+            tangent.w = -tangent.w;
+        }
+        for target in self.morph_targets.iter_mut().flatten() {
+            for normal in target.normals.iter_mut().flatten() {
+                *normal = -*normal;
+            }
+        }
+    }
+
+    ///
+    /// Flips the v coordinate of all uv coordinates, including [TriMesh::additional_uvs], ie. maps `v` to `1.0 - v`.
+    /// This converts between a texture origin in the top left corner and in the bottom left corner, for example between DirectX and OpenGL conventions.
+    /// Does nothing if the mesh has no uv coordinates.
+    ///
+    /// ```
+    /// let mut mesh = three_d_asset::TriMesh::square();
+    /// let uvs = mesh.uvs.clone();
+    /// mesh.flip_uv_v();
+    /// assert_ne!(mesh.uvs, uvs);
+    /// mesh.flip_uv_v();
+    /// assert_eq!(mesh.uvs, uvs);
+    /// ```
+    ///
+    pub fn flip_uv_v(&mut self) {
+        for uv in self
+            .uvs
+            .iter_mut()
+            .chain(&mut self.additional_uvs)
+            .flatten()
+        {
+            uv.y = 1.0 - uv.y;
+        }
+    }
+
+    ///
+    /// Flips the u coordinate of all uv coordinates, including [TriMesh::additional_uvs], ie. maps `u` to `1.0 - u`.
+    /// Does nothing if the mesh has no uv coordinates.
+    ///
+    pub fn flip_uv_u(&mut self) {
+        for uv in self
+            .uvs
+            .iter_mut()
+            .chain(&mut self.additional_uvs)
+            .flatten()
+        {
+            uv.x = 1.0 - uv.x;
+        }
+    }
+
+    ///
+    /// Generates uv coordinates by projecting the positions along the given axis onto the sides of the axis aligned bounding box of the mesh, so the uv coordinates span the range `[0..1]`.
+    /// The projection is seen from the positive end of the axis with up being the positive y axis, or the negative z axis when projecting along the y axis, and the uv origin is in the top left corner like [TriMesh::square].
+    /// It will override the current uv coordinates if they already exist.
+    ///
+    pub fn generate_planar_uvs(&mut self, axis: Axis) {
+        let aabb = self.compute_aabb();
+        let (min, max, size) = (aabb.min(), aabb.max(), aabb.size());
+        let ratio = |value: f32, size: f32| if size > 0.0 { value / size } else { 0.0 };
+        self.uvs = Some(
+            self.positions
+                .to_f32()
+                .into_iter()
+                .map(|p| match axis {
+                    Axis::X => vec2(ratio(max.z - p.z, size.z), ratio(max.y - p.y, size.y)),
+                    Axis::Y => vec2(ratio(p.x - min.x, size.x), ratio(p.z - min.z, size.z)),
+                    Axis::Z => vec2(ratio(p.x - min.x, size.x), ratio(max.y - p.y, size.y)),
+                })
+                .collect(),
+        );
+    }
+
+    ///
+    /// Generates uv coordinates by mapping the direction from the center of the axis aligned bounding box of the mesh to each position to longitude and latitude,
+    /// where the u coordinate is the longitude around the y axis and the v coordinate is the latitude from the top (positive y) to the bottom (negative y).
+    /// The u coordinate wraps around from 1 to 0 at the seam in the negative z direction and the vertices at the seam are not duplicated,
+    /// so triangles crossing the seam interpolate across the entire texture in the u direction. Similarly, the u coordinate is arbitrary at the poles.
+    /// It will override the current uv coordinates if they already exist.
+    ///
+    pub fn generate_spherical_uvs(&mut self) {
+        let center = self.compute_aabb().center();
+        self.uvs = Some(
+            self.positions
+                .to_f32()
+                .into_iter()
+                .map(|p| {
+                    let direction = p - center;
+                    if direction.magnitude2() == 0.0 {
+                        return vec2(0.5, 0.5);
+                    }
+                    let direction = direction.normalize();
+                    vec2(
+                        0.5 + direction.x.atan2(direction.z) / (2.0 * std::f32::consts::PI),
+                        direction.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI,
+                    )
+                })
+                .collect(),
+        );
+    }
+
     ///
     /// Computes the per vertex tangents and updates the tangents of the mesh.
     /// It will override the current tangents if they already exist.
+    /// The tangent and bitangent directions of each triangle are computed from the positions and uv coordinates and accumulated for each vertex,
+    /// after which the tangent is made orthogonal to the normal and the fourth value of the tangent is set to the handedness (either -1.0 or 1.0) of the bitangent.
+    /// Triangles with degenerate uv coordinates are skipped and a vertex without any valid triangles gets an arbitrary tangent orthogonal to the normal.
+    /// Returns an error if the mesh does not have both normals and uv coordinates.
     ///
-    pub fn compute_tangents(&mut self) {
-        if self.normals.is_none() || self.uvs.is_none() {
-            panic!("mesh must have both normals and uv coordinates to be able to compute tangents");
-        }
+    pub fn compute_tangents(&mut self) -> Result<()> {
+        let (Some(normals), Some(uvs)) = (self.normals.as_ref(), self.uvs.as_ref()) else {
+            Err(Error::MissingNormalsOrUvs)?
+        };
         let mut tan1 = vec![Vec3::new(0.0, 0.0, 0.0); self.positions.len()];
         let mut tan2 = vec![Vec3::new(0.0, 0.0, 0.0); self.positions.len()];
 
@@ -501,9 +1501,9 @@ impl TriMesh {
                     )
                 }
             };
-            let uva = self.uvs.as_ref().unwrap()[i0];
-            let uvb = self.uvs.as_ref().unwrap()[i1];
-            let uvc = self.uvs.as_ref().unwrap()[i2];
+            let uva = uvs[i0];
+            let uvb = uvs[i1];
+            let uvc = uvs[i2];
 
             let ba = b - a;
             let ca = c - a;
@@ -511,11 +1511,11 @@ impl TriMesh {
             let uvba = uvb - uva;
             let uvca = uvc - uva;
 
-            let d = uvba.x * uvca.y - uvca.x * uvba.y;
-            if d.abs() > 0.00001 {
-                let r = 1.0 / d;
-                let sdir = (ba * uvca.y - ca * uvba.y) * r;
-                let tdir = (ca * uvba.x - ba * uvca.x) * r;
+            let r = 1.0 / (uvba.x * uvca.y - uvca.x * uvba.y);
+            let sdir = (ba * uvca.y - ca * uvba.y) * r;
+            let tdir = (ca * uvba.x - ba * uvca.x) * r;
+            let is_finite = |v: Vec3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+            if r.is_finite() && is_finite(sdir) && is_finite(tdir) {
                 tan1[i0] += sdir;
                 tan1[i1] += sdir;
                 tan1[i2] += sdir;
@@ -527,18 +1527,33 @@ impl TriMesh {
 
         let mut tangents = vec![Vec4::new(0.0, 0.0, 0.0, 0.0); self.positions.len()];
         self.for_each_vertex(|index| {
-            let normal = self.normals.as_ref().unwrap()[index];
-            let t = tan1[index];
-            let tangent = (t - normal * normal.dot(t)).normalize();
-            let handedness = if normal.cross(tangent).dot(tan2[index]) < 0.0 {
-                1.0
+            let normal = if normals[index].magnitude2() > 0.0 {
+                normals[index].normalize()
             } else {
+                vec3(0.0, 0.0, 1.0)
+            };
+            let t = tan1[index];
+            let mut tangent = t - normal * normal.dot(t);
+            if tangent.magnitude2() <= f32::EPSILON * t.magnitude2() {
+                // Use the axis which is the least aligned with the normal
+                let axis = if normal.x.abs() < 0.9 {
+                    vec3(1.0, 0.0, 0.0)
+                } else {
+                    vec3(0.0, 1.0, 0.0)
+                };
+                tangent = axis - normal * normal.dot(axis);
+            }
+            let tangent = tangent.normalize();
+            let handedness = if normal.cross(tangent).dot(tan2[index]) > 0.0 {
                 -1.0
+            } else {
+                1.0
             };
             tangents[index] = tangent.extend(handedness);
         });
 
         self.tangents = Some(tangents);
+        Ok(())
     }
 
     ///
@@ -594,6 +1609,96 @@ impl TriMesh {
         self.positions.compute_aabb()
     }
 
+    ///
+    /// Returns all problems with the geometry of this mesh, ie. positions which are NaN or infinite, indices which are out of range, triangles with zero area and vertices which are not used by any triangle.
+    /// In contrast to [TriMesh::validate], this does not check the lengths of the vertex data buffers other than the positions.
+    /// See [TriMesh::repair] to fix the problems.
+    ///
+    pub fn issues(&self) -> Vec<MeshIssue> {
+        let mut issues = Vec::new();
+        if let Some(trailing) = self.indices.len().map(|i| i % 3).filter(|t| *t != 0) {
+            issues.push(MeshIssue::TrailingIndices(trailing));
+        }
+        let positions = self.positions.to_f64();
+        let is_finite = |i: usize| {
+            let p = positions[i];
+            p.x.is_finite() && p.y.is_finite() && p.z.is_finite()
+        };
+        issues.extend(
+            (0..positions.len())
+                .filter(|i| !is_finite(*i))
+                .map(MeshIssue::NonFinitePosition),
+        );
+        let mut is_used = vec![false; positions.len()];
+        let mut triangle = 0;
+        self.for_each_triangle(|i0, i1, i2| {
+            let out_of_range = [i0, i1, i2]
+                .into_iter()
+                .filter(|i| *i >= positions.len())
+                .collect::<Vec<_>>();
+            if out_of_range.is_empty() {
+                for i in [i0, i1, i2] {
+                    is_used[i] = true;
+                }
+                let (p0, p1, p2) = (positions[i0], positions[i1], positions[i2]);
+                if (p1 - p0).cross(p2 - p0).magnitude2() == 0.0 {
+                    issues.push(MeshIssue::DegenerateTriangle(triangle));
+                }
+            } else {
+                issues.extend(
+                    out_of_range
+                        .into_iter()
+                        .map(|i| MeshIssue::IndexOutOfRange(triangle, i)),
+                );
+            }
+            triangle += 1;
+        });
+        issues.extend(
+            (0..positions.len())
+                .filter(|i| !is_used[*i])
+                .map(MeshIssue::UnusedVertex),
+        );
+        issues
+    }
+
+    ///
+    /// Returns a repaired copy of this mesh together with the problems found in this mesh, see [TriMesh::issues].
+    /// The repaired mesh does not contain the triangles which have zero area, indices out of range or positions which are NaN or infinite, and only contains the vertices used by the remaining triangles.
+    ///
+    pub fn repair(&self) -> (TriMesh, Vec<MeshIssue>) {
+        let issues = self.issues();
+        let mut is_removed = vec![false; self.triangle_count()];
+        for issue in issues.iter() {
+            if let MeshIssue::IndexOutOfRange(triangle, _)
+            | MeshIssue::DegenerateTriangle(triangle) = issue
+            {
+                is_removed[*triangle] = true;
+            }
+        }
+        let positions = self.positions.to_f64();
+        let mut remap = std::collections::HashMap::new();
+        let mut order = Vec::new();
+        let mut indices = Vec::new();
+        let mut triangle = 0;
+        self.for_each_triangle(|i0, i1, i2| {
+            let is_valid = !is_removed[triangle]
+                && [i0, i1, i2].iter().all(|&i| {
+                    let p = positions[i];
+                    p.x.is_finite() && p.y.is_finite() && p.z.is_finite()
+                });
+            if is_valid {
+                indices.extend([i0, i1, i2].map(|i| {
+                    *remap.entry(i).or_insert_with(|| {
+                        order.push(i);
+                        order.len() - 1
+                    })
+                }));
+            }
+            triangle += 1;
+        });
+        (self.gather_vertices(&order, indices), issues)
+    }
+
     ///
     /// Returns an error if the mesh is not valid.
     ///
@@ -629,7 +1734,953 @@ impl TriMesh {
         buffer_check(self.tangents.as_ref().map(|b| b.len()), "tangent")?;
         buffer_check(self.colors.as_ref().map(|b| b.len()), "color")?;
         buffer_check(self.uvs.as_ref().map(|b| b.len()), "uv coordinate")?;
+        for uvs in self.additional_uvs.iter() {
+            buffer_check(Some(uvs.len()), "uv coordinate")?;
+        }
+        buffer_check(self.joint_indices.as_ref().map(|b| b.len()), "joint index")?;
+        buffer_check(self.joint_weights.as_ref().map(|b| b.len()), "joint weight")?;
 
         Ok(())
     }
 }
+
+///
+/// Returns the given indices using the smallest index type which can index the given number of vertices.
+///
+fn compact_indices(indices: Vec<usize>, vertex_count: usize) -> Indices {
+    if vertex_count <= u8::MAX as usize + 1 {
+        Indices::U8(indices.into_iter().map(|i| i as u8).collect())
+    } else if vertex_count <= u16::MAX as usize + 1 {
+        Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+    } else {
+        Indices::U32(indices.into_iter().map(|i| i as u32).collect())
+    }
+}
+
+///
+/// Returns the values at the given indices in the given order.
+///
+fn gather<T: Copy>(values: &[T], order: &[usize]) -> Vec<T> {
+    order.iter().map(|i| values[*i]).collect()
+}
+
+///
+/// Returns the quadric, ie. the upper triangle of a symmetric 4x4 matrix, which measures the squared distance to the plane of the given triangle.
+///
+fn plane_quadric(triangle: [Vector3<f64>; 3]) -> [f64; 10] {
+    let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+    if normal.magnitude2() == 0.0 {
+        return [0.0; 10];
+    }
+    let normal = normal.normalize();
+    let (a, b, c) = (normal.x, normal.y, normal.z);
+    let d = -normal.dot(triangle[0]);
+    [
+        a * a,
+        a * b,
+        a * c,
+        a * d,
+        b * b,
+        b * c,
+        b * d,
+        c * c,
+        c * d,
+        d * d,
+    ]
+}
+
+fn add_quadrics(a: &[f64; 10], b: &[f64; 10]) -> [f64; 10] {
+    std::array::from_fn(|i| a[i] + b[i])
+}
+
+fn quadric_error(q: &[f64; 10], p: Vector3<f64>) -> f64 {
+    let (x, y, z) = (p.x, p.y, p.z);
+    q[0] * x * x
+        + 2.0 * q[1] * x * y
+        + 2.0 * q[2] * x * z
+        + 2.0 * q[3] * x
+        + q[4] * y * y
+        + 2.0 * q[5] * y * z
+        + 2.0 * q[6] * y
+        + q[7] * z * z
+        + 2.0 * q[8] * z
+        + q[9]
+}
+
+///
+/// Interpolates the vertex data, except the positions, of the vertex `i` towards the vertex `j` by the given factor and stores the result in the vertex `i`.
+///
+fn interpolate_vertex(mesh: &mut TriMesh, i: usize, j: usize, t: f32) {
+    if t == 0.0 {
+        return;
+    }
+    if let Some(normals) = mesh.normals.as_mut() {
+        normals[i] = normals[i].lerp(normals[j], t).normalize();
+    }
+    if let Some(tangents) = mesh.tangents.as_mut() {
+        let tangent = tangents[i].truncate().lerp(tangents[j].truncate(), t);
+        tangents[i] = tangent.normalize().extend(tangents[i].w);
+    }
+    for uvs in mesh.uvs.iter_mut().chain(&mut mesh.additional_uvs) {
+        uvs[i] = uvs[i].lerp(uvs[j], t);
+    }
+    if let Some(colors) = mesh.colors.as_mut() {
+        let color = colors[i].to_vec4().lerp(colors[j].to_vec4(), t);
+        colors[i] = Color::from_rgba_slice(&color.into());
+    }
+    // The joints cannot be interpolated, so use the joints of the closest vertex
+    if t > 0.5 {
+        if let Some(joint_indices) = mesh.joint_indices.as_mut() {
+            joint_indices[i] = joint_indices[j];
+        }
+        if let Some(joint_weights) = mesh.joint_weights.as_mut() {
+            joint_weights[i] = joint_weights[j];
+        }
+    }
+    for target in mesh.morph_targets.iter_mut().flatten() {
+        for offsets in [
+            &mut target.positions,
+            &mut target.normals,
+            &mut target.tangents,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            offsets[i] = offsets[i].lerp(offsets[j], t);
+        }
+    }
+}
+
+///
+/// Returns the error and the interpolation factor from the first to the second vertex of the best collapse of the edge between the two vertices, if the edge can be collapsed.
+///
+fn best_collapse(
+    positions: &[Vector3<f64>],
+    quadrics: &[[f64; 10]],
+    is_locked: &[bool],
+    i: usize,
+    j: usize,
+) -> Option<(f64, f64)> {
+    if is_locked[i] && is_locked[j] {
+        return None;
+    }
+    let quadric = add_quadrics(&quadrics[i], &quadrics[j]);
+    [0.0, 0.5, 1.0]
+        .into_iter()
+        .filter(|&t| !(is_locked[i] && t > 0.0 || is_locked[j] && t < 1.0))
+        .map(|t| {
+            let position = positions[i] + (positions[j] - positions[i]) * t;
+            (quadric_error(&quadric, position), t)
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    ///
+    /// Returns a welded grid with the given number of quads along each side spanning `[0..1]` in the x and y axes, where the z coordinate is a bump in the middle with the given height.
+    ///
+    fn grid(size: usize, height: f32) -> TriMesh {
+        let mut positions = Vec::new();
+        for y in 0..=size {
+            for x in 0..=size {
+                let (x, y) = (x as f32 / size as f32, y as f32 / size as f32);
+                let z = (x * std::f32::consts::PI).sin() * (y * std::f32::consts::PI).sin();
+                positions.push(vec3(x, y, height * z));
+            }
+        }
+        let mut indices = Vec::new();
+        for y in 0..size as u32 {
+            for x in 0..size as u32 {
+                let i = y * (size as u32 + 1) + x;
+                let j = i + size as u32 + 1;
+                indices.extend([i, i + 1, j + 1, j + 1, j, i]);
+            }
+        }
+        TriMesh {
+            positions: Positions::F32(positions),
+            indices: Indices::U32(indices),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Returns the positions of the vertices of each triangle, which is independent of the order of the vertices.
+    ///
+    fn triangle_positions(mesh: &TriMesh) -> Vec<[Vec3; 3]> {
+        let positions = mesh.positions.to_f32();
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            triangles.push([positions[i0], positions[i1], positions[i2]])
+        });
+        triangles
+    }
+
+    ///
+    /// Returns the cube from [TriMesh::cube] with the eight corners shared between the triangles using indices.
+    ///
+    fn indexed_cube() -> TriMesh {
+        let mut corners = Vec::<Vec3>::new();
+        let mut indices = Vec::new();
+        for p in TriMesh::cube().positions.to_f32() {
+            let index = corners.iter().position(|c| *c == p).unwrap_or_else(|| {
+                corners.push(p);
+                corners.len() - 1
+            });
+            indices.push(index as u8);
+        }
+        TriMesh {
+            positions: Positions::F32(corners),
+            indices: Indices::U8(indices),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    pub fn compute_flat_normals_cube() {
+        let mut mesh = indexed_cube();
+        assert_eq!(mesh.vertex_count(), 8);
+        mesh.colors = Some(
+            mesh.positions
+                .to_f32()
+                .iter()
+                .map(|p| Color::new((p.x > 0.0) as u8, (p.y > 0.0) as u8, (p.z > 0.0) as u8, 255))
+                .collect(),
+        );
+        mesh.compute_flat_normals();
+        assert_eq!(mesh.vertex_count(), 36);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert!(mesh.indices.len().is_none());
+
+        let positions = mesh.positions.to_f32();
+        let normals = mesh.normals.as_ref().unwrap();
+        let colors = mesh.colors.as_ref().unwrap();
+        let mut face_normals = Vec::new();
+        for triangle in 0..12 {
+            let [p0, p1, p2] = [0, 1, 2].map(|i| positions[3 * triangle + i]);
+            // The normal of each face of the cube points along the axis where all three vertices have the same coordinate
+            let expected = [Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()]
+                .into_iter()
+                .find_map(|axis| {
+                    let c = p0.dot(axis);
+                    (p1.dot(axis) == c && p2.dot(axis) == c).then(|| axis * c)
+                })
+                .unwrap();
+            for i in 0..3 {
+                assert_eq!(normals[3 * triangle + i], expected);
+            }
+            face_normals.push(expected);
+        }
+        for axis in [Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()] {
+            assert_eq!(face_normals.iter().filter(|n| **n == axis).count(), 2);
+            assert_eq!(face_normals.iter().filter(|n| **n == -axis).count(), 2);
+        }
+        // The other vertex data is duplicated together with the positions
+        for (p, c) in positions.iter().zip(colors) {
+            assert_eq!(
+                *c,
+                Color::new((p.x > 0.0) as u8, (p.y > 0.0) as u8, (p.z > 0.0) as u8, 255)
+            );
+        }
+    }
+
+    #[test]
+    pub fn weld_cube() {
+        let cube = TriMesh::cube();
+        assert_eq!(cube.vertex_count(), 36);
+
+        // The corners are not merged across the faces, since the normals and uv coordinates are different
+        let mut mesh = cube.clone();
+        mesh.weld(1e-4);
+        assert_eq!(mesh.vertex_count(), 24);
+        assert_eq!(triangle_positions(&mesh), triangle_positions(&cube));
+        let normals = mesh.normals.as_ref().unwrap();
+        let uvs = mesh.uvs.as_ref().unwrap();
+        let original = cube.positions.to_f32();
+        let mut i = 0;
+        mesh.for_each_triangle(|i0, i1, i2| {
+            for (k, index) in [i0, i1, i2].into_iter().enumerate() {
+                assert_eq!(normals[index], cube.normals.as_ref().unwrap()[i + k]);
+                assert_eq!(uvs[index], cube.uvs.as_ref().unwrap()[i + k]);
+            }
+            i += 3;
+        });
+
+        // Without the other vertex data, only the eight corners remain
+        let mut mesh = TriMesh {
+            positions: Positions::F32(original),
+            ..Default::default()
+        };
+        mesh.weld(0.0);
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert_eq!(triangle_positions(&mesh), triangle_positions(&cube));
+    }
+
+    #[test]
+    pub fn weld_tolerance() {
+        let mesh = TriMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(1.0, 0.0, 0.0005),
+                vec3(1.0, 1.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ]),
+            uvs: Some(vec![
+                vec2(0.0, 0.0),
+                vec2(1.0, 0.0),
+                vec2(0.0, 1.0),
+                vec2(1.0, 0.0),
+                vec2(1.0, 1.0),
+                vec2(0.0, 1.0005),
+            ]),
+            ..Default::default()
+        };
+
+        let mut welded = mesh.clone();
+        welded.weld(1e-3);
+        assert_eq!(welded.vertex_count(), 4);
+        assert_eq!(welded.indices.to_u32(), Some(vec![0, 1, 2, 1, 3, 2]));
+        // The merged vertices keep the data of the first vertex
+        assert_eq!(
+            welded.positions.to_f32(),
+            vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(1.0, 1.0, 0.0)
+            ]
+        );
+        assert_eq!(welded.uvs.as_ref().unwrap()[2], vec2(0.0, 1.0));
+
+        let mut welded = mesh.clone();
+        welded.weld(1e-4);
+        assert_eq!(welded.vertex_count(), 6);
+        assert_eq!(welded.indices.to_u32(), Some(vec![0, 1, 2, 3, 4, 5]));
+
+        // A uv seam is kept even if the positions are identical
+        let mut seam = mesh;
+        seam.positions = Positions::F32(vec![
+            vec3(0.0, 0.0, 0.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(1.0, 1.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+        ]);
+        seam.uvs.as_mut().unwrap()[3] = vec2(0.0, 0.0);
+        seam.weld(1e-3);
+        assert_eq!(seam.vertex_count(), 5);
+        assert_eq!(seam.indices.to_u32(), Some(vec![0, 1, 2, 3, 4, 2]));
+    }
+
+    #[test]
+    pub fn split_by_material() {
+        let mut mesh = indexed_cube();
+        mesh.colors = Some((0..8).map(|i| Color::new_opaque(i, 0, 0)).collect());
+        // The two triangles of each side of the cube are next to each other
+        let face_material = [3, 3, 1, 1, 3, 3, 7, 7, 7, 7, 3, 3];
+        let meshes = mesh.split_by(&face_material).unwrap();
+        assert_eq!(
+            meshes.iter().map(|(m, _)| *m).collect::<Vec<_>>(),
+            vec![1, 3, 7]
+        );
+        assert_eq!(
+            meshes
+                .iter()
+                .map(|(_, m)| m.triangle_count())
+                .collect::<Vec<_>>(),
+            vec![2, 6, 4]
+        );
+
+        let all_triangles = triangle_positions(&mesh);
+        for (material, part) in meshes.iter() {
+            // Only the used vertices are kept, so one side has four vertices and the indices are remapped accordingly
+            let positions = part.positions.to_f32();
+            if *material == 1 {
+                assert_eq!(part.vertex_count(), 4);
+            }
+            let mut used = vec![false; part.vertex_count()];
+            part.for_each_triangle(|i0, i1, i2| {
+                used[i0] = true;
+                used[i1] = true;
+                used[i2] = true;
+            });
+            assert!(used.into_iter().all(|u| u));
+            // The vertex data follows the positions
+            for (p, c) in positions.iter().zip(part.colors.as_ref().unwrap()) {
+                let original = mesh.positions.to_f32().iter().position(|o| o == p).unwrap();
+                assert_eq!(c.r, original as u8);
+            }
+            // The triangles are kept in their original order
+            let expected = all_triangles
+                .iter()
+                .zip(face_material)
+                .filter(|(_, m)| m == material)
+                .map(|(t, _)| *t)
+                .collect::<Vec<_>>();
+            assert_eq!(triangle_positions(part), expected);
+        }
+
+        assert!(matches!(
+            mesh.split_by(&face_material[1..]),
+            Err(Error::InvalidNumberOfIndices(11))
+        ));
+    }
+
+    #[test]
+    pub fn flip_winding() {
+        let mut mesh = TriMesh::square();
+        mesh.compute_tangents().unwrap();
+        mesh.morph_targets = Some(vec![MorphTarget {
+            positions: Some(vec![Vec3::unit_z(); 4]),
+            normals: Some(vec![Vec3::unit_x(); 4]),
+            tangents: None,
+            weight: 0.5,
+        }]);
+        let original = mesh.clone();
+        assert!(original.normals.is_some() && original.tangents.is_some());
+        let original_indices = original.indices.to_u32().unwrap();
+
+        mesh.flip_winding();
+        let indices = mesh.indices.to_u32().unwrap();
+        for (flipped, original) in indices.chunks(3).zip(original_indices.chunks(3)) {
+            assert_eq!(flipped, [original[0], original[2], original[1]]);
+        }
+        assert_eq!(mesh.positions.to_f32(), original.positions.to_f32());
+        for (n, o) in mesh
+            .normals
+            .iter()
+            .flatten()
+            .zip(original.normals.iter().flatten())
+        {
+            assert_eq!(*n, -*o);
+        }
+        for (t, o) in mesh
+            .tangents
+            .iter()
+            .flatten()
+            .zip(original.tangents.iter().flatten())
+        {
+            assert_eq!(*t, o.truncate().extend(-o.w));
+        }
+        let target = &mesh.morph_targets.as_ref().unwrap()[0];
+        assert_eq!(target.normals, Some(vec![-Vec3::unit_x(); 4]));
+        assert_eq!(target.positions, Some(vec![Vec3::unit_z(); 4]));
+
+        mesh.flip_winding();
+        assert_eq!(mesh.indices.to_u32().unwrap(), original_indices);
+        assert_eq!(mesh.normals, original.normals);
+        assert_eq!(mesh.tangents, original.tangents);
+        assert_eq!(
+            mesh.morph_targets.as_ref().unwrap()[0].normals,
+            Some(vec![Vec3::unit_x(); 4])
+        );
+    }
+
+    #[test]
+    pub fn flip_winding_without_indices() {
+        let original = TriMesh::cube();
+        let mut mesh = original.clone();
+        mesh.flip_winding();
+        assert!(mesh.indices.len().is_none());
+        let (positions, original_positions) =
+            (mesh.positions.to_f32(), original.positions.to_f32());
+        let (uvs, original_uvs) = (mesh.uvs.as_ref().unwrap(), original.uvs.as_ref().unwrap());
+        let (normals, original_normals) = (
+            mesh.normals.as_ref().unwrap(),
+            original.normals.as_ref().unwrap(),
+        );
+        for i in 0..positions.len() {
+            // The second and third vertex of each triangle are swapped
+            let j = match i % 3 {
+                0 => i,
+                1 => i + 1,
+                _ => i - 1,
+            };
+            assert_eq!(positions[i], original_positions[j]);
+            assert_eq!(uvs[i], original_uvs[j]);
+            assert_eq!(normals[i], -original_normals[j]);
+        }
+
+        mesh.flip_winding();
+        assert_eq!(mesh.positions.to_f32(), original_positions);
+        assert_eq!(mesh.normals, original.normals);
+        assert_eq!(mesh.uvs, original.uvs);
+    }
+
+    #[test]
+    pub fn generate_planar_uvs() {
+        let mut mesh = TriMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(2.0, 1.0, 4.0),
+                vec3(1.0, 0.5, 1.0),
+            ]),
+            ..Default::default()
+        };
+        mesh.generate_planar_uvs(Axis::X);
+        assert_eq!(
+            mesh.uvs,
+            Some(vec![vec2(1.0, 1.0), vec2(0.0, 0.0), vec2(0.75, 0.5)])
+        );
+        mesh.generate_planar_uvs(Axis::Y);
+        assert_eq!(
+            mesh.uvs,
+            Some(vec![vec2(0.0, 0.0), vec2(1.0, 1.0), vec2(0.5, 0.25)])
+        );
+        mesh.generate_planar_uvs(Axis::Z);
+        assert_eq!(
+            mesh.uvs,
+            Some(vec![vec2(0.0, 1.0), vec2(1.0, 0.0), vec2(0.5, 0.5)])
+        );
+
+        // The projection along the z axis matches the uv coordinates of the square
+        let mut square = TriMesh::square();
+        let uvs = square.uvs.clone();
+        square.generate_planar_uvs(Axis::Z);
+        assert_eq!(square.uvs, uvs);
+
+        // The square is flat in the z direction, so the projection along the x axis has a constant u coordinate
+        square.generate_planar_uvs(Axis::X);
+        assert!(square.uvs.unwrap().iter().all(|uv| uv.x == 0.0));
+    }
+
+    #[test]
+    pub fn generate_spherical_uvs() {
+        let mut mesh = TriMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, -1.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(-1.0, 0.0, 0.0),
+                vec3(0.0, 0.0, -1.0),
+                vec3(1.0, 0.0, -1.0),
+                vec3(-1.0, 0.0, -1.0),
+                vec3(0.0, 0.0, 0.0),
+            ]),
+            ..Default::default()
+        };
+        mesh.generate_spherical_uvs();
+        let uvs = mesh.uvs.unwrap();
+        // The poles
+        assert_eq!(uvs[0].y, 0.0);
+        assert_eq!(uvs[1].y, 1.0);
+        // Around the equator
+        assert_eq!(uvs[2], vec2(0.5, 0.5));
+        assert_eq!(uvs[3], vec2(0.75, 0.5));
+        assert_eq!(uvs[4], vec2(0.25, 0.5));
+        // The seam in the negative z direction, where the u coordinate wraps around from 1 to 0
+        assert_eq!(uvs[5], vec2(1.0, 0.5));
+        assert_eq!(uvs[6], vec2(0.875, 0.5));
+        assert_eq!(uvs[7], vec2(0.125, 0.5));
+        // The center
+        assert_eq!(uvs[8], vec2(0.5, 0.5));
+    }
+
+    #[test]
+    pub fn merge() {
+        let square = TriMesh::square();
+        let triangle = TriMesh {
+            positions: Positions::F64(vec![
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 1.0),
+                vec3(0.0, 1.0, 1.0),
+            ]),
+            colors: Some(vec![Color::RED, Color::GREEN, Color::BLUE]),
+            ..Default::default()
+        };
+        let merged = TriMesh::merge(&[square.clone(), triangle.clone()]);
+        merged.validate().unwrap();
+        assert_eq!(merged.vertex_count(), 7);
+        // The indices of the second mesh are offset by the vertex count of the first mesh, which also applies to a mesh without indices
+        assert!(matches!(merged.indices, Indices::U8(_)));
+        assert_eq!(
+            merged.indices.to_u32(),
+            Some(vec![0, 1, 2, 2, 3, 0, 4, 5, 6])
+        );
+        // The positions are 64 bit since one of the meshes has 64 bit positions
+        assert_eq!(merged.positions.precision(), PositionPrecision::F64);
+        assert_eq!(
+            merged.positions.to_f32()[4..],
+            triangle.positions.to_f32()[..]
+        );
+        // The normals are computed for the mesh without normals
+        assert_eq!(merged.normals, Some(vec![Vec3::unit_z(); 7]));
+        // The missing uv coordinates are zero, the missing colors are white and the tangents fall back to the x axis without uv coordinates
+        let uvs = merged.uvs.as_ref().unwrap();
+        assert_eq!(uvs[..4], square.uvs.as_ref().unwrap()[..]);
+        assert_eq!(uvs[4..], [Vec2::zero(); 3]);
+        assert_eq!(
+            merged.colors,
+            Some(vec![
+                Color::WHITE,
+                Color::WHITE,
+                Color::WHITE,
+                Color::WHITE,
+                Color::RED,
+                Color::GREEN,
+                Color::BLUE
+            ])
+        );
+        assert_eq!(
+            merged.tangents.as_ref().unwrap()[4..],
+            [vec4(1.0, 0.0, 0.0, 1.0); 3]
+        );
+        assert!(merged.joint_indices.is_none() && merged.morph_targets.is_none());
+
+        // Meshes without indices result in a mesh without indices
+        let merged = TriMesh::merge(&[triangle.clone(), triangle.clone()]);
+        assert!(merged.indices.len().is_none());
+        assert_eq!(merged.vertex_count(), 6);
+        assert!(merged.normals.is_none() && merged.uvs.is_none());
+
+        // The index type is large enough for all vertices
+        let large = TriMesh {
+            positions: Positions::F32(vec![Vec3::zero(); 300]),
+            ..Default::default()
+        };
+        let merged = TriMesh::merge(&[square.clone(), large]);
+        assert!(matches!(merged.indices, Indices::U16(_)));
+        assert_eq!(merged.indices.max_index(), Some(303));
+
+        let merged = TriMesh::merge_transformed(&[
+            (square, Mat4::identity()),
+            (triangle, Mat4::from_translation(vec3(1.0, 0.0, 0.0))),
+        ])
+        .unwrap();
+        assert_eq!(merged.positions.to_f32()[4], vec3(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    pub fn normalize() {
+        let mut mesh = TriMesh {
+            positions: Positions::F64(vec![
+                vec3(1.0, 2.0, 3.0),
+                vec3(3.0, 6.0, 4.0),
+                vec3(2.0, 2.0, 3.0),
+            ]),
+            normals: Some(vec![Vec3::unit_z(); 3]),
+            morph_targets: Some(vec![MorphTarget {
+                positions: Some(vec![vec3(4.0, 0.0, 0.0); 3]),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let transformation = mesh.normalize();
+        assert_eq!(
+            transformation,
+            Mat4::from_scale(0.25) * Mat4::from_translation(vec3(-2.0, -4.0, -3.5))
+        );
+        assert_eq!(
+            mesh.positions.to_f64(),
+            vec![
+                vec3(-0.25, -0.5, -0.125),
+                vec3(0.25, 0.5, 0.125),
+                vec3(0.0, -0.5, -0.125)
+            ]
+        );
+        assert_eq!(mesh.normals, Some(vec![Vec3::unit_z(); 3]));
+        assert_eq!(
+            mesh.morph_targets.as_ref().unwrap()[0].positions,
+            Some(vec![vec3(1.0, 0.0, 0.0); 3])
+        );
+
+        let mut empty = TriMesh::default();
+        assert_eq!(empty.normalize(), Mat4::identity());
+    }
+
+    #[test]
+    pub fn simplify_sphere() {
+        let sphere = TriMesh::sphere(32);
+        let simplified = sphere.simplify(0.25, f32::INFINITY);
+        simplified.validate().unwrap();
+        assert!(simplified.issues().is_empty());
+        let ratio = simplified.triangle_count() as f32 / sphere.triangle_count() as f32;
+        assert!((0.24..=0.26).contains(&ratio), "ratio {}", ratio);
+        // The vertices stay close to the surface of the sphere
+        for p in simplified.positions.to_f32() {
+            assert!((p.magnitude() - 1.0).abs() < 0.1, "{:?}", p);
+        }
+    }
+
+    #[test]
+    pub fn simplify_keeps_boundary() {
+        let mesh = grid(16, 0.2);
+        let is_boundary = |p: &Vec3| p.x == 0.0 || p.x == 1.0 || p.y == 0.0 || p.y == 1.0;
+        let boundary = mesh
+            .positions
+            .to_f32()
+            .into_iter()
+            .filter(is_boundary)
+            .collect::<Vec<_>>();
+        let simplified = mesh.simplify(0.1, f32::INFINITY);
+        simplified.validate().unwrap();
+        assert!(simplified.triangle_count() < mesh.triangle_count() / 2);
+        let positions = simplified.positions.to_f32();
+        for p in boundary.iter() {
+            assert!(
+                positions.contains(p),
+                "the boundary vertex {:?} is moved",
+                p
+            );
+        }
+        assert_eq!(
+            positions.iter().filter(|p| is_boundary(p)).count(),
+            boundary.len()
+        );
+    }
+
+    #[test]
+    pub fn simplify_with_zero_max_error() {
+        let sphere = TriMesh::sphere(16);
+        let simplified = sphere.simplify(0.25, 0.0);
+        assert_eq!(triangle_positions(&simplified), triangle_positions(&sphere));
+
+        // A flat mesh can be simplified without any error
+        let flat = grid(4, 0.0);
+        let simplified = flat.simplify(0.25, 0.0);
+        assert!(simplified.triangle_count() < flat.triangle_count());
+        assert!(simplified.positions.to_f32().iter().all(|p| p.z == 0.0));
+    }
+
+    #[test]
+    pub fn simplify_target_ratio_limits() {
+        let sphere = TriMesh::sphere(16);
+        let unchanged = sphere.simplify(1.0, f32::INFINITY);
+        assert_eq!(triangle_positions(&unchanged), triangle_positions(&sphere));
+
+        let simplified = sphere.simplify(0.0, f32::INFINITY);
+        simplified.validate().unwrap();
+        assert!(simplified.triangle_count() < sphere.triangle_count() / 10);
+        assert!(simplified.issues().is_empty());
+    }
+
+    ///
+    /// Returns the number of triangles adjacent to each edge.
+    ///
+    fn edge_triangle_counts(mesh: &TriMesh) -> std::collections::HashMap<(usize, usize), usize> {
+        let mut counts = std::collections::HashMap::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            for (i, j) in [(i0, i1), (i1, i2), (i2, i0)] {
+                *counts.entry((i.min(j), i.max(j))).or_default() += 1;
+            }
+        });
+        counts
+    }
+
+    #[test]
+    pub fn subdivide_loop_triangle_count() {
+        let mesh = TriMesh::square();
+        for levels in 0..4 {
+            let subdivided = mesh.subdivide_loop(levels);
+            assert_eq!(subdivided.triangle_count(), 2 * 4usize.pow(levels));
+            subdivided.validate().unwrap();
+        }
+        let sphere = TriMesh::sphere(8);
+        assert_eq!(
+            sphere.subdivide_loop(2).triangle_count(),
+            sphere.triangle_count() * 16
+        );
+    }
+
+    #[test]
+    pub fn subdivide_loop_closed() {
+        let sphere = TriMesh::sphere(8);
+        assert!(edge_triangle_counts(&sphere).values().all(|c| *c == 2));
+        let subdivided = sphere.subdivide_loop(2);
+        assert!(subdivided.issues().is_empty());
+        let (repaired, issues) = subdivided.repair();
+        assert!(issues.is_empty());
+        assert_eq!(repaired.triangle_count(), subdivided.triangle_count());
+        assert!(edge_triangle_counts(&subdivided).values().all(|c| *c == 2));
+        // The vertices move inside the sphere, but not far
+        for p in subdivided.positions.to_f32() {
+            assert!(p.magnitude() < 1.0 && p.magnitude() > 0.9, "{:?}", p);
+        }
+    }
+
+    #[test]
+    pub fn subdivide_loop_boundary() {
+        let quad = grid(4, 0.0);
+        let subdivided = quad.subdivide_loop(1);
+        let positions = subdivided.positions.to_f32();
+        let mut boundary = std::collections::HashSet::new();
+        for ((i, j), count) in edge_triangle_counts(&subdivided) {
+            if count == 1 {
+                boundary.extend([i, j]);
+            }
+        }
+        assert_eq!(boundary.len(), 32);
+        let is_on_line = |p: Vec3| p.x == 0.0 || p.x == 1.0 || p.y == 0.0 || p.y == 1.0;
+        for (i, p) in positions.iter().enumerate() {
+            assert_eq!(p.z, 0.0);
+            if !boundary.contains(&i) {
+                assert!(
+                    !is_on_line(*p),
+                    "the interior vertex {:?} is on the boundary",
+                    p
+                );
+            } else if !is_on_line(*p) {
+                // Only the corners are cut by the boundary curve, ie. moved by an eighth of the distance to the two neighbouring boundary vertices
+                assert!(
+                    [0.03125, 0.96875].contains(&p.x) && [0.03125, 0.96875].contains(&p.y),
+                    "the boundary vertex {:?} is moved off the boundary",
+                    p
+                );
+            }
+        }
+        assert_eq!(
+            boundary
+                .iter()
+                .filter(|i| !is_on_line(positions[**i]))
+                .count(),
+            4
+        );
+    }
+
+    #[test]
+    pub fn subdivide_loop_tangents() {
+        let subdivided = TriMesh::square().subdivide_loop(1);
+        let tangents = subdivided.tangents.unwrap();
+        assert_eq!(tangents.len(), subdivided.positions.len());
+        // The tangents are computed from the uv coordinates, which are interpolated linearly while the corners are moved
+        for t in tangents {
+            assert!((t.truncate().magnitude() - 1.0).abs() < 1e-6 && t.z == 0.0 && t.x > 0.9);
+            assert_eq!(t.w, 1.0);
+        }
+
+        let mut mesh = TriMesh::square();
+        mesh.uvs = None;
+        let subdivided = mesh.subdivide_loop(1);
+        assert_eq!(
+            subdivided.tangents.unwrap().len(),
+            subdivided.positions.len()
+        );
+    }
+
+    #[test]
+    pub fn compute_tangents_handedness() {
+        let mut square = TriMesh::square();
+        square.compute_tangents().unwrap();
+        assert!(square
+            .tangents
+            .unwrap()
+            .iter()
+            .all(|t| *t == vec4(1.0, 0.0, 0.0, 1.0)));
+
+        let mut mirrored = TriMesh::square();
+        mirrored.uvs = mirrored
+            .uvs
+            .map(|uvs| uvs.into_iter().map(|uv| vec2(1.0 - uv.x, uv.y)).collect());
+        mirrored.compute_tangents().unwrap();
+        assert!(mirrored
+            .tangents
+            .unwrap()
+            .iter()
+            .all(|t| *t == vec4(-1.0, 0.0, 0.0, -1.0)));
+    }
+
+    #[test]
+    pub fn compute_tangents_with_degenerate_uvs() {
+        let mut mesh = TriMesh::square();
+        mesh.positions = Positions::F32(
+            mesh.positions
+                .to_f32()
+                .into_iter()
+                .chain([vec3(0.0, -2.0, 0.0)])
+                .collect(),
+        );
+        mesh.indices = Indices::U8(vec![0, 1, 2, 2, 3, 0, 1, 0, 4]);
+        mesh.normals.as_mut().unwrap().push(vec3(0.0, 0.0, 1.0));
+        // The uv coordinates of the last triangle are on a line
+        let uvs = mesh.uvs.as_mut().unwrap();
+        uvs.push(uvs[0]);
+        mesh.compute_tangents().unwrap();
+
+        let tangents = mesh.tangents.unwrap();
+        assert_eq!(tangents.len(), 5);
+        assert!(tangents[..4].iter().all(|t| *t == vec4(1.0, 0.0, 0.0, 1.0)));
+        // A vertex without valid triangles gets an arbitrary tangent orthogonal to the normal
+        let t = tangents[4];
+        assert!(t.x.is_finite() && t.y.is_finite() && t.z == 0.0 && t.w.abs() == 1.0);
+        assert!((t.truncate().magnitude() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn compute_tangents_without_normals_or_uvs() {
+        let mut mesh = TriMesh::square();
+        mesh.normals = None;
+        assert!(matches!(
+            mesh.compute_tangents(),
+            Err(Error::MissingNormalsOrUvs)
+        ));
+        let mut mesh = TriMesh::square();
+        mesh.uvs = None;
+        mesh.tangents = None;
+        assert!(matches!(
+            mesh.compute_tangents(),
+            Err(Error::MissingNormalsOrUvs)
+        ));
+        assert!(mesh.tangents.is_none());
+    }
+
+    #[test]
+    pub fn issues_and_repair() {
+        let mesh = TriMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(f32::NAN, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(1.0, 1.0, 0.0),
+            ]),
+            indices: Indices::U8(vec![0, 1, 2, 1, 5, 2, 0, 1, 3, 0, 1, 9, 0, 1, 1, 0]),
+            uvs: Some((0..6).map(|i| vec2(i as f32, 0.0)).collect()),
+            ..Default::default()
+        };
+        let expected = vec![
+            MeshIssue::TrailingIndices(1),
+            MeshIssue::NonFinitePosition(3),
+            MeshIssue::IndexOutOfRange(3, 9),
+            MeshIssue::DegenerateTriangle(4),
+            MeshIssue::UnusedVertex(4),
+        ];
+        assert_eq!(mesh.issues(), expected);
+        assert!(mesh.validate().is_err());
+
+        let (repaired, issues) = mesh.repair();
+        assert_eq!(issues, expected);
+        repaired.validate().unwrap();
+        assert!(repaired.issues().is_empty());
+        assert_eq!(repaired.triangle_count(), 2);
+        assert_eq!(
+            triangle_positions(&repaired),
+            vec![
+                [
+                    vec3(0.0, 0.0, 0.0),
+                    vec3(1.0, 0.0, 0.0),
+                    vec3(0.0, 1.0, 0.0)
+                ],
+                [
+                    vec3(1.0, 0.0, 0.0),
+                    vec3(1.0, 1.0, 0.0),
+                    vec3(0.0, 1.0, 0.0)
+                ],
+            ]
+        );
+        // The vertex data is remapped together with the positions
+        let uvs = repaired.uvs.as_ref().unwrap();
+        assert_eq!(uvs.len(), 4);
+        for (p, uv) in repaired.positions.to_f32().iter().zip(uvs) {
+            let original = mesh.positions.to_f32().iter().position(|o| o == p).unwrap();
+            assert_eq!(*uv, vec2(original as f32, 0.0));
+        }
+    }
+}
@@ -8,9 +8,12 @@
 //! use three_d_asset::io::*;
 //! use three_d_asset::{Texture2D, Model};
 //!
+//! # #[cfg(all(feature = "png", feature = "obj"))]
+//! # {
 //! let mut assets = load(&["test_data/test.png", "test_data/cube.obj"]).unwrap();
 //! let texture: Texture2D = assets.deserialize("test.png").unwrap();
 //! let model: Model = assets.deserialize("cube.obj").unwrap();
+//! # }
 //! ```
 //!
 //! Or serialize and save assets:
@@ -29,8 +32,11 @@
 //!     height: 2,
 //!     ..Default::default()
 //! };
+//! # #[cfg(feature = "png")]
+//! # {
 //! let assets = texture.serialize("test_data/test.png").unwrap();
 //! save(&assets).unwrap();
+//! # }
 //! ```
 //!
 
@@ -40,26 +46,80 @@ pub use loader::*;
 mod raw_assets;
 pub use raw_assets::*;
 
+mod source;
+pub use source::*;
+
+#[cfg(feature = "reqwest")]
+mod cache;
+#[cfg(feature = "reqwest")]
+pub use cache::DownloadCache;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod saver;
 #[cfg(not(target_arch = "wasm32"))]
 pub use saver::*;
 
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+mod mmap;
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+pub use mmap::{load_mmap, MappedAssets};
+
+#[cfg(feature = "zip")]
+mod zip;
+#[cfg(feature = "zip")]
+pub use zip::load_from_zip;
+
 #[cfg(feature = "obj")]
 mod obj;
+#[cfg(feature = "obj")]
+pub use obj::{deserialize_obj_reader, ObjOptions};
 
 #[cfg(feature = "gltf")]
 mod gltf;
 
 #[cfg(feature = "image")]
 mod img;
+#[cfg(feature = "jpeg")]
+pub use img::JpegOptions;
+#[cfg(feature = "png")]
+pub use img::{PngCompression, PngOptions};
 
 #[cfg(feature = "vol")]
 mod vol;
 
+#[cfg(feature = "dds")]
+mod dds;
+
+#[cfg(feature = "ktx2")]
+mod ktx2;
+
+#[cfg(feature = "qoi")]
+mod qoi;
+
+#[cfg(feature = "svg")]
+mod svg;
+
+#[cfg(feature = "basis")]
+mod basis;
+#[cfg(feature = "basis")]
+pub use basis::TranscodeTarget;
+
+#[cfg(feature = "vox")]
+mod vox;
+
 #[cfg(feature = "pcd")]
 mod pcd;
 
+#[cfg(feature = "stl")]
+mod stl;
+#[cfg(feature = "stl")]
+pub use stl::{StlFormat, StlOptions};
+
+#[cfg(feature = "ply")]
+mod ply;
+#[cfg(feature = "ply")]
+pub use ply::PlyFormat;
+
 ///
 /// Loads and deserialize a single file. If the file depends on other files, those files are also loaded.
 ///
@@ -77,6 +137,106 @@ pub async fn load_and_deserialize_async<T: Deserialize>(
     load_async(&[&path]).await?.deserialize(path)
 }
 
+///
+/// Loads the image files in the given paths and stacks them along the depth into a [Texture3D](crate::Texture3D), see [Texture3D::from_slices](crate::Texture3D::from_slices).
+/// The slices are ordered by file name where numbers are compared by value, so `slice2.png` comes before `slice10.png`.
+/// With the `glob` feature flag, a pattern like `"volume/*.png"` can be used to load all slices in a folder.
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_texture3d(paths: &[impl AsRef<std::path::Path>]) -> crate::Result<crate::Texture3D> {
+    let mut raw_assets = load(paths)?;
+    let mut paths = raw_assets
+        .paths()
+        .map(|p| p.to_path_buf())
+        .collect::<Vec<_>>();
+    paths.sort_by(|a, b| {
+        let name = |p: &PathBuf| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_owned()
+        };
+        natural_cmp(&name(a), &name(b)).then_with(|| a.cmp(b))
+    });
+    let slices = paths
+        .iter()
+        .map(|path| raw_assets.deserialize(path))
+        .collect::<crate::Result<Vec<crate::Texture2D>>>()?;
+    crate::Texture3D::from_slices(&slices)
+}
+
+///
+/// Compares the two strings such that sequences of digits are compared by their numeric value.
+///
+#[cfg(not(target_arch = "wasm32"))]
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                    }
+                    digits.trim_start_matches('0').to_owned()
+                };
+                let (x, y) = (number(&mut a), number(&mut b));
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if ordering != std::cmp::Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                if ordering != std::cmp::Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+///
+/// Returns the file name used when writing the texture next to a model, which is the file name of the texture if it has one and otherwise the fallback name with a `.png` extension.
+/// Textures from different directories can have the same file name, so a name which is already in `file_names` gets a numbered suffix, for example `albedo_1.png`.
+///
+#[cfg(any(feature = "obj", feature = "gltf"))]
+fn texture_file_name(
+    texture: &crate::Texture2D,
+    fallback_name: &str,
+    file_names: &mut std::collections::HashSet<String>,
+) -> String {
+    use std::path::Path;
+    let file_name = Path::new(&texture.name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| !texture.name.starts_with("data:") && Path::new(n).extension().is_some())
+        .map(|n| n.to_owned())
+        .unwrap_or(format!("{}.png", fallback_name));
+    (0..)
+        .map(|i| {
+            if i == 0 {
+                file_name.clone()
+            } else {
+                let path = Path::new(&file_name);
+                format!(
+                    "{}_{}.{}",
+                    path.file_stem().and_then(|s| s.to_str()).unwrap_or(""),
+                    i,
+                    path.extension().and_then(|e| e.to_str()).unwrap_or("png")
+                )
+            }
+        })
+        .find(|name| file_names.insert(name.clone()))
+        .unwrap()
+}
+
 ///
 /// Save and serialize a single file.
 ///
@@ -101,6 +261,20 @@ pub trait Deserialize: Sized {
     ) -> crate::Result<Self>;
 }
 
+///
+/// Implemented for assets that can be deserialized using a set of options specific to a file format (see also [RawAssets::deserialize_with_options]).
+///
+pub trait DeserializeWithOptions<O>: Deserialize {
+    ///
+    /// See [RawAssets::deserialize_with_options].
+    ///
+    fn deserialize_with_options(
+        path: impl AsRef<std::path::Path>,
+        raw_assets: &mut RawAssets,
+        options: O,
+    ) -> crate::Result<Self>;
+}
+
 ///
 /// Implemented for assets that can be serialized before being saved (see also [save]).
 ///
@@ -112,6 +286,20 @@ pub trait Serialize: Sized {
     fn serialize(&self, path: impl AsRef<std::path::Path>) -> crate::Result<RawAssets>;
 }
 
+///
+/// Implemented for assets that can be serialized using a set of options specific to a file format (see also [Serialize]).
+///
+pub trait SerializeWithOptions<O>: Serialize {
+    ///
+    /// Serialize the asset into a list of raw assets in the same way as [Serialize::serialize], except that the given format specific options are used.
+    ///
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: O,
+    ) -> crate::Result<RawAssets>;
+}
+
 use crate::{Error, Geometry, Result};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -122,6 +310,57 @@ impl Deserialize for crate::Texture2D {
         #[allow(unused_variables)]
         let bytes = raw_assets.get(&path)?;
 
+        if bytes.starts_with(b"DDS ") {
+            #[cfg(not(feature = "dds"))]
+            return Err(Error::FeatureMissing("dds".to_string()));
+
+            #[cfg(feature = "dds")]
+            return dds::deserialize_dds(path, bytes);
+        }
+
+        if is_ktx2(&path, bytes) {
+            #[cfg(not(feature = "ktx2"))]
+            return Err(Error::FeatureMissing("ktx2".to_string()));
+
+            #[cfg(feature = "ktx2")]
+            return match ktx2::deserialize_ktx2(&path, bytes)? {
+                ktx2::Ktx2Texture::Texture2D(texture) => Ok(texture),
+                _ => Err(Error::FailedConvertion(
+                    "a 2D texture".to_owned(),
+                    path.to_str().unwrap().to_owned(),
+                )),
+            };
+        }
+
+        if bytes.starts_with(b"qoif") {
+            #[cfg(not(feature = "qoi"))]
+            return Err(Error::FeatureMissing("qoi".to_string()));
+
+            #[cfg(feature = "qoi")]
+            return qoi::deserialize_qoi(path, bytes);
+        }
+
+        if path.extension().map(|e| e == "svg").unwrap_or(false)
+            || raw_assets.mime_type(&path) == Some("image/svg+xml")
+        {
+            #[cfg(not(feature = "svg"))]
+            return Err(Error::FeatureMissing("svg".to_string()));
+
+            #[cfg(feature = "svg")]
+            return Ok(Self {
+                name: path.to_str().unwrap().to_owned(),
+                ..Self::from_svg_bytes(bytes, None, None)?
+            });
+        }
+
+        if path.extension().map(|e| e == "basis").unwrap_or(false) {
+            #[cfg(not(feature = "basis"))]
+            return Err(Error::FeatureMissing("basis".to_string()));
+
+            #[cfg(feature = "basis")]
+            return Self::deserialize_with_options(path, raw_assets, TranscodeTarget::default());
+        }
+
         #[cfg(not(feature = "image"))]
         return Err(Error::FeatureMissing(
             path.extension()
@@ -135,10 +374,37 @@ impl Deserialize for crate::Texture2D {
     }
 }
 
+#[cfg(feature = "basis")]
+impl DeserializeWithOptions<TranscodeTarget> for crate::Texture2D {
+    fn deserialize_with_options(
+        path: impl AsRef<Path>,
+        raw_assets: &mut RawAssets,
+        options: TranscodeTarget,
+    ) -> Result<Self> {
+        let path = raw_assets.match_path(path.as_ref())?;
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "basis" => {
+                let mut texture = Self::transcode_from_basis(raw_assets.get(&path)?, options)?;
+                texture.name = path.to_str().unwrap().to_owned();
+                Ok(texture)
+            }
+            _ => Err(Error::FailedDeserialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
 impl Serialize for crate::Texture2D {
     fn serialize(&self, path: impl AsRef<Path>) -> Result<RawAssets> {
         let path = path.as_ref();
 
+        if path.extension().map(|e| e == "qoi").unwrap_or(false) {
+            #[cfg(not(feature = "qoi"))]
+            return Err(Error::FeatureMissing("qoi".to_string()));
+
+            #[cfg(feature = "qoi")]
+            return qoi::serialize_qoi(self, path);
+        }
+
         #[cfg(not(feature = "image"))]
         return Err(Error::FeatureMissing(
             path.extension()
@@ -152,6 +418,36 @@ impl Serialize for crate::Texture2D {
     }
 }
 
+#[cfg(feature = "png")]
+impl SerializeWithOptions<PngOptions> for crate::Texture2D {
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: PngOptions,
+    ) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "png" => img::serialize_png(self, path, options),
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "jpeg")]
+impl SerializeWithOptions<JpegOptions> for crate::Texture2D {
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: JpegOptions,
+    ) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "jpeg" | "jpg" => img::serialize_jpeg(self, path, options),
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
 impl Deserialize for crate::Scene {
     fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
         let path = raw_assets.match_path(path.as_ref())?;
@@ -161,14 +457,14 @@ impl Deserialize for crate::Scene {
                 return Err(Error::FeatureMissing("gltf".to_string()));
 
                 #[cfg(feature = "gltf")]
-                gltf::deserialize_gltf(raw_assets, &path)
+                gltf::deserialize_gltf(raw_assets, &path).map(|scenes| scenes.into())
             }
             "obj" => {
                 #[cfg(not(feature = "obj"))]
                 return Err(Error::FeatureMissing("obj".to_string()));
 
                 #[cfg(feature = "obj")]
-                obj::deserialize_obj(raw_assets, &path)
+                obj::deserialize_obj(raw_assets, &path, ObjOptions::default())
             }
             "pcd" => {
                 #[cfg(not(feature = "pcd"))]
@@ -177,11 +473,166 @@ impl Deserialize for crate::Scene {
                 #[cfg(feature = "pcd")]
                 pcd::deserialize_pcd(raw_assets, &path)
             }
+            "stl" => {
+                #[cfg(not(feature = "stl"))]
+                return Err(Error::FeatureMissing("stl".to_string()));
+
+                #[cfg(feature = "stl")]
+                stl::deserialize_stl(raw_assets, &path, StlOptions::default())
+            }
+            "ply" => {
+                #[cfg(not(feature = "ply"))]
+                return Err(Error::FeatureMissing("ply".to_string()));
+
+                #[cfg(feature = "ply")]
+                ply::deserialize_ply(raw_assets, &path)
+            }
+            _ => Err(Error::FailedDeserialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+impl Deserialize for crate::Scenes {
+    fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
+        let path = raw_assets.match_path(path.as_ref())?;
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "gltf" | "glb" => {
+                #[cfg(not(feature = "gltf"))]
+                return Err(Error::FeatureMissing("gltf".to_string()));
+
+                #[cfg(feature = "gltf")]
+                gltf::deserialize_gltf(raw_assets, &path)
+            }
+            _ => Ok(Self {
+                scenes: vec![crate::Scene::deserialize(path, raw_assets)?],
+                default_scene: 0,
+            }),
+        }
+    }
+}
+
+impl Deserialize for Vec<crate::PbrMaterial> {
+    fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
+        let path = raw_assets.match_path(path.as_ref())?;
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "mtl" => {
+                #[cfg(not(feature = "obj"))]
+                return Err(Error::FeatureMissing("obj".to_string()));
+
+                #[cfg(feature = "obj")]
+                obj::deserialize_mtl(raw_assets, &path)
+            }
+            _ => Err(Error::FailedDeserialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "obj")]
+impl DeserializeWithOptions<ObjOptions> for crate::Scene {
+    fn deserialize_with_options(
+        path: impl AsRef<Path>,
+        raw_assets: &mut RawAssets,
+        options: ObjOptions,
+    ) -> Result<Self> {
+        let path = raw_assets.match_path(path.as_ref())?;
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "obj" => obj::deserialize_obj(raw_assets, &path, options),
+            _ => Err(Error::FailedDeserialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "stl")]
+impl DeserializeWithOptions<StlOptions> for crate::Scene {
+    fn deserialize_with_options(
+        path: impl AsRef<Path>,
+        raw_assets: &mut RawAssets,
+        options: StlOptions,
+    ) -> Result<Self> {
+        let path = raw_assets.match_path(path.as_ref())?;
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "stl" => stl::deserialize_stl(raw_assets, &path, options),
             _ => Err(Error::FailedDeserialize(path.to_str().unwrap().to_string())),
         }
     }
 }
 
+#[cfg(feature = "stl")]
+impl SerializeWithOptions<StlFormat> for crate::Model {
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: StlFormat,
+    ) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "stl" => {
+                let mut solids = Vec::new();
+                for primitive in self.geometries.iter() {
+                    if let Geometry::Triangles(mesh) = &primitive.geometry {
+                        let mut mesh = mesh.clone();
+                        mesh.transform(&primitive.transformation)?;
+                        solids.push((primitive.name.as_str(), mesh));
+                    }
+                }
+                stl::serialize_stl(
+                    path,
+                    &solids.iter().map(|(n, m)| (*n, m)).collect::<Vec<_>>(),
+                    options,
+                )
+            }
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+impl Serialize for crate::Model {
+    fn serialize(&self, path: impl AsRef<Path>) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "stl" => {
+                #[cfg(not(feature = "stl"))]
+                return Err(Error::FeatureMissing("stl".to_string()));
+
+                #[cfg(feature = "stl")]
+                self.serialize_with_options(path, StlFormat::default())
+            }
+            "obj" => {
+                #[cfg(not(feature = "obj"))]
+                return Err(Error::FeatureMissing("obj".to_string()));
+
+                #[cfg(feature = "obj")]
+                {
+                    let mut meshes = Vec::new();
+                    for primitive in self.geometries.iter() {
+                        if let Geometry::Triangles(mesh) = &primitive.geometry {
+                            let mut mesh = mesh.clone();
+                            mesh.transform(&primitive.transformation)?;
+                            meshes.push((primitive.name.as_str(), mesh, primitive.material_index));
+                        }
+                    }
+                    obj::serialize_obj(
+                        path,
+                        &meshes
+                            .iter()
+                            .map(|(n, m, i)| (*n, m, *i))
+                            .collect::<Vec<_>>(),
+                        &self.materials,
+                    )
+                }
+            }
+            "gltf" | "glb" => {
+                #[cfg(not(feature = "gltf"))]
+                return Err(Error::FeatureMissing("gltf".to_string()));
+
+                #[cfg(feature = "gltf")]
+                gltf::serialize_gltf(path, self)
+            }
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
 impl Deserialize for crate::Model {
     fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
         let scene = crate::Scene::deserialize(path, raw_assets)?;
@@ -189,6 +640,30 @@ impl Deserialize for crate::Model {
     }
 }
 
+#[cfg(feature = "obj")]
+impl DeserializeWithOptions<ObjOptions> for crate::Model {
+    fn deserialize_with_options(
+        path: impl AsRef<Path>,
+        raw_assets: &mut RawAssets,
+        options: ObjOptions,
+    ) -> Result<Self> {
+        let scene = crate::Scene::deserialize_with_options(path, raw_assets, options)?;
+        Ok(scene.into())
+    }
+}
+
+#[cfg(feature = "stl")]
+impl DeserializeWithOptions<StlOptions> for crate::Model {
+    fn deserialize_with_options(
+        path: impl AsRef<Path>,
+        raw_assets: &mut RawAssets,
+        options: StlOptions,
+    ) -> Result<Self> {
+        let scene = crate::Scene::deserialize_with_options(path, raw_assets, options)?;
+        Ok(scene.into())
+    }
+}
+
 impl Deserialize for crate::VoxelGrid {
     fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
         let path = raw_assets.match_path(path.as_ref())?;
@@ -200,42 +675,177 @@ impl Deserialize for crate::VoxelGrid {
                 #[cfg(feature = "vol")]
                 vol::deserialize_vol(raw_assets, &path)
             }
+            "vox" => {
+                #[cfg(not(feature = "vox"))]
+                return Err(Error::FeatureMissing("vox".to_string()));
+
+                #[cfg(feature = "vox")]
+                vox::deserialize_vox(raw_assets, &path)
+            }
             _ => Err(Error::FailedDeserialize(path.to_str().unwrap().to_string())),
         }
     }
 }
 
+impl Serialize for crate::VoxelGrid {
+    fn serialize(&self, path: impl AsRef<Path>) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "vol" => {
+                #[cfg(not(feature = "vol"))]
+                return Err(Error::FeatureMissing("vol".to_string()));
+
+                #[cfg(feature = "vol")]
+                vol::serialize_vol(self, path)
+            }
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
 impl Deserialize for crate::Texture3D {
     fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
         let path = raw_assets.match_path(path.as_ref())?;
+        if is_ktx2(&path, raw_assets.get(&path)?) {
+            #[cfg(not(feature = "ktx2"))]
+            return Err(Error::FeatureMissing("ktx2".to_string()));
+
+            #[cfg(feature = "ktx2")]
+            return match ktx2::deserialize_ktx2(&path, raw_assets.get(&path)?)? {
+                ktx2::Ktx2Texture::Texture3D(texture) => Ok(texture),
+                _ => Err(Error::FailedConvertion(
+                    "a 3D texture".to_owned(),
+                    path.to_str().unwrap().to_owned(),
+                )),
+            };
+        }
         let voxel_grid = crate::VoxelGrid::deserialize(path, raw_assets)?;
         Ok(voxel_grid.voxels)
     }
 }
 
+impl Deserialize for crate::TextureCube {
+    fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
+        let path = raw_assets.match_path(path.as_ref())?;
+        #[allow(unused_variables)]
+        let bytes = raw_assets.get(&path)?;
+        if is_ktx2(&path, bytes) {
+            #[cfg(not(feature = "ktx2"))]
+            return Err(Error::FeatureMissing("ktx2".to_string()));
+
+            #[cfg(feature = "ktx2")]
+            return match ktx2::deserialize_ktx2(&path, bytes)? {
+                ktx2::Ktx2Texture::TextureCube(texture) => Ok(texture),
+                _ => Err(Error::FailedConvertion(
+                    "a cube texture".to_owned(),
+                    path.to_str().unwrap().to_owned(),
+                )),
+            };
+        }
+        Err(Error::FailedDeserialize(path.to_str().unwrap().to_string()))
+    }
+}
+
 impl Deserialize for crate::TriMesh {
     fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
         let path = path.as_ref();
         let model = crate::Model::deserialize(path, raw_assets)?;
-        model
-            .geometries
-            .into_iter()
-            .find_map(|p| {
-                if let Geometry::Triangles(mesh) = p.geometry {
-                    Some(mesh)
-                } else {
-                    None
-                }
-            })
-            .ok_or_else(|| {
-                Error::FailedConvertion(
-                    "a triangle mesh".to_owned(),
-                    path.to_str().unwrap().to_owned(),
-                )
-            })
+        first_tri_mesh(model, path)
+    }
+}
+
+#[cfg(feature = "stl")]
+impl DeserializeWithOptions<StlOptions> for crate::TriMesh {
+    fn deserialize_with_options(
+        path: impl AsRef<Path>,
+        raw_assets: &mut RawAssets,
+        options: StlOptions,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let model = crate::Model::deserialize_with_options(path, raw_assets, options)?;
+        first_tri_mesh(model, path)
+    }
+}
+
+impl Serialize for crate::TriMesh {
+    fn serialize(&self, path: impl AsRef<Path>) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "stl" => {
+                #[cfg(not(feature = "stl"))]
+                return Err(Error::FeatureMissing("stl".to_string()));
+
+                #[cfg(feature = "stl")]
+                self.serialize_with_options(path, StlFormat::default())
+            }
+            "ply" => {
+                #[cfg(not(feature = "ply"))]
+                return Err(Error::FeatureMissing("ply".to_string()));
+
+                #[cfg(feature = "ply")]
+                self.serialize_with_options(path, PlyFormat::default())
+            }
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "ply")]
+impl SerializeWithOptions<PlyFormat> for crate::TriMesh {
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: PlyFormat,
+    ) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "ply" => ply::serialize_tri_mesh(path, self, options),
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "stl")]
+impl SerializeWithOptions<StlFormat> for crate::TriMesh {
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: StlFormat,
+    ) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "stl" => stl::serialize_stl(path, &[("", self)], options),
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
     }
 }
 
+fn is_ktx2(path: &Path, bytes: &[u8]) -> bool {
+    const IDENTIFIER: [u8; 12] = [
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    bytes.starts_with(&IDENTIFIER) || path.extension().map(|e| e == "ktx2").unwrap_or(false)
+}
+
+fn first_tri_mesh(model: crate::Model, path: &Path) -> Result<crate::TriMesh> {
+    model
+        .geometries
+        .into_iter()
+        .find_map(|p| {
+            if let Geometry::Triangles(mesh) = p.geometry {
+                Some(mesh)
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| {
+            Error::FailedConvertion(
+                "a triangle mesh".to_owned(),
+                path.to_str().unwrap().to_owned(),
+            )
+        })
+}
+
 impl Deserialize for crate::PointCloud {
     fn deserialize(path: impl AsRef<Path>, raw_assets: &mut RawAssets) -> Result<Self> {
         let path = path.as_ref();
@@ -259,28 +869,60 @@ impl Deserialize for crate::PointCloud {
     }
 }
 
-fn get_dependencies(raw_assets: &RawAssets) -> Vec<PathBuf> {
-    #[allow(unused_mut)]
-    let mut dependencies = HashSet::new();
-    for (path, _) in raw_assets.iter() {
+impl Serialize for crate::PointCloud {
+    fn serialize(&self, path: impl AsRef<Path>) -> Result<RawAssets> {
+        let path = path.as_ref();
         match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
-            "gltf" | "glb" => {
-                #[cfg(feature = "gltf")]
-                dependencies.extend(gltf::dependencies(raw_assets, path));
-            }
-            "obj" => {
-                #[cfg(feature = "obj")]
-                dependencies.extend(obj::dependencies_obj(raw_assets, path));
-            }
-            "mtl" => {
-                #[cfg(feature = "obj")]
-                dependencies.extend(obj::dependencies_mtl(raw_assets, path));
+            "ply" => {
+                #[cfg(not(feature = "ply"))]
+                return Err(Error::FeatureMissing("ply".to_string()));
+
+                #[cfg(feature = "ply")]
+                self.serialize_with_options(path, PlyFormat::default())
             }
-            _ => {}
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
         }
     }
+}
+
+#[cfg(feature = "ply")]
+impl SerializeWithOptions<PlyFormat> for crate::PointCloud {
+    fn serialize_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: PlyFormat,
+    ) -> Result<RawAssets> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+            "ply" => ply::serialize_point_cloud(path, self, options),
+            _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
+        }
+    }
+}
+
+fn get_dependencies(raw_assets: &RawAssets) -> Vec<PathBuf> {
+    let mut dependencies = HashSet::new();
+    for (path, bytes) in raw_assets.iter() {
+        dependencies.extend(file_dependencies(path, bytes));
+    }
     dependencies
         .into_iter()
         .filter(|d| !raw_assets.contains_key(d))
         .collect()
 }
+
+///
+/// Returns the paths of the files that the file with the given path and content depends on, for example the buffers and textures of a glTF file.
+///
+#[allow(unused_variables)]
+fn file_dependencies(path: &Path, bytes: &[u8]) -> HashSet<PathBuf> {
+    match path.extension().map(|e| e.to_str().unwrap()).unwrap_or("") {
+        #[cfg(feature = "gltf")]
+        "gltf" | "glb" => gltf::dependencies(path, bytes),
+        #[cfg(feature = "obj")]
+        "obj" => obj::dependencies_obj(path, bytes),
+        #[cfg(feature = "obj")]
+        "mtl" => obj::dependencies_mtl(path, bytes),
+        _ => HashSet::new(),
+    }
+}
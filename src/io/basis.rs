@@ -0,0 +1,157 @@
+use crate::{texture::*, Error, Result};
+use basis_universal::transcoding::{TranscodeParameters, Transcoder, TranscoderTextureFormat};
+
+///
+/// The format that Basis Universal data is transcoded into (see [Texture2D::transcode_from_basis]).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscodeTarget {
+    /// Transcode into [TextureData::Bc7RgbaU8], which is supported by most desktop GPUs.
+    #[default]
+    Bc7,
+    /// Transcode into [TextureData::Bc3RgbaU8], which is supported by older desktop GPUs.
+    Bc3,
+    /// Transcode into [TextureData::Etc2RgbaU8], which is supported by most mobile GPUs.
+    Etc2,
+    /// Transcode into [TextureData::Astc4x4RgbaU8], which is supported by newer mobile GPUs.
+    Astc,
+    /// Transcode into the uncompressed [TextureData::RgbaU8].
+    Rgba32,
+}
+
+impl Texture2D {
+    ///
+    /// Transcodes the first image in the given .basis file, including all of its mip levels, into the given target format.
+    /// Both ETC1S and UASTC encoded files are supported.
+    ///
+    /// **Note:** Basis Universal data inside a .ktx2 container is not supported.
+    ///
+    pub fn transcode_from_basis(bytes: &[u8], target: TranscodeTarget) -> Result<Self> {
+        let mut transcoder = Transcoder::new();
+        if !transcoder.validate_header(bytes) {
+            Err(Error::BasisTranscoding("invalid header".to_owned()))?;
+        }
+        let format = match target {
+            TranscodeTarget::Bc7 => TranscoderTextureFormat::BC7_RGBA,
+            TranscodeTarget::Bc3 => TranscoderTextureFormat::BC3_RGBA,
+            TranscodeTarget::Etc2 => TranscoderTextureFormat::ETC2_RGBA,
+            TranscodeTarget::Astc => TranscoderTextureFormat::ASTC_4x4_RGBA,
+            TranscodeTarget::Rgba32 => TranscoderTextureFormat::RGBA32,
+        };
+        if !transcoder
+            .basis_texture_format(bytes)
+            .can_transcode_to_format(format)
+        {
+            Err(Error::BasisTranscoding(format!(
+                "the {:?} data cannot be transcoded into {:?}",
+                transcoder.basis_texture_format(bytes),
+                target
+            )))?;
+        }
+        let description = transcoder
+            .image_level_description(bytes, 0, 0)
+            .ok_or_else(|| Error::BasisTranscoding("no images".to_owned()))?;
+        transcoder
+            .prepare_transcoding(bytes)
+            .map_err(|_| Error::BasisTranscoding("failed to prepare transcoding".to_owned()))?;
+
+        let mut levels = Vec::new();
+        for level_index in 0..transcoder.image_level_count(bytes, 0) {
+            let data = transcoder
+                .transcode_image_level(
+                    bytes,
+                    format,
+                    TranscodeParameters {
+                        image_index: 0,
+                        level_index,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|e| Error::BasisTranscoding(format!("{:?}", e)))?;
+            levels.push(match target {
+                TranscodeTarget::Bc7 => TextureData::Bc7RgbaU8(blocks(&data)),
+                TranscodeTarget::Bc3 => TextureData::Bc3RgbaU8(blocks(&data)),
+                TranscodeTarget::Etc2 => TextureData::Etc2RgbaU8(blocks(&data)),
+                TranscodeTarget::Astc => TextureData::Astc4x4RgbaU8(blocks(&data)),
+                TranscodeTarget::Rgba32 => TextureData::RgbaU8(blocks(&data)),
+            });
+        }
+        transcoder.end_transcoding();
+
+        let data = levels.remove(0);
+        Ok(Texture2D {
+            data,
+            width: description.original_width,
+            height: description.original_height,
+            mip_levels: levels,
+            ..Default::default()
+        })
+    }
+}
+
+fn blocks<const N: usize>(data: &[u8]) -> Vec<[u8; N]> {
+    data.chunks(N).map(|c| c.try_into().unwrap()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::TranscodeTarget;
+    use crate::{Texture2D, TextureData};
+    use basis_universal::{encoding::*, BasisTextureFormat};
+
+    fn encode(format: BasisTextureFormat) -> Vec<u8> {
+        let mut params = CompressorParams::new();
+        params.set_basis_format(format);
+        params.set_generate_mipmaps(true);
+        params.set_print_status_to_stdout(false);
+        params
+            .source_image_mut(0)
+            .init(&[200, 100, 50, 255].repeat(16 * 8), 16, 8, 4);
+        let mut compressor = Compressor::new(1);
+        unsafe {
+            assert!(compressor.init(&params));
+            compressor.process().unwrap();
+        }
+        compressor.basis_file().to_vec()
+    }
+
+    #[test]
+    pub fn transcode_from_basis() {
+        for format in [BasisTextureFormat::ETC1S, BasisTextureFormat::UASTC4x4] {
+            let bytes = encode(format);
+            let tex = Texture2D::transcode_from_basis(&bytes, TranscodeTarget::default()).unwrap();
+            assert_eq!((tex.width, tex.height), (16, 8));
+            assert_eq!(tex.mip_levels.len(), 4);
+            assert!(matches!(&tex.data, TextureData::Bc7RgbaU8(b) if b.len() == 8));
+
+            let tex = Texture2D::transcode_from_basis(&bytes, TranscodeTarget::Etc2).unwrap();
+            assert!(matches!(&tex.data, TextureData::Etc2RgbaU8(b) if b.len() == 8));
+            let tex = Texture2D::transcode_from_basis(&bytes, TranscodeTarget::Astc).unwrap();
+            assert!(matches!(&tex.data, TextureData::Astc4x4RgbaU8(b) if b.len() == 8));
+
+            let mut tex = Texture2D::transcode_from_basis(&bytes, TranscodeTarget::Bc3).unwrap();
+            tex.decompress().unwrap();
+            let tex_rgba =
+                Texture2D::transcode_from_basis(&bytes, TranscodeTarget::Rgba32).unwrap();
+            for t in [tex, tex_rgba] {
+                if let TextureData::RgbaU8(data) = &t.data {
+                    assert_eq!(data.len(), 16 * 8);
+                    for (a, b) in data[0].iter().zip([200u8, 100, 50, 255]) {
+                        assert!((*a as i32 - b as i32).abs() < 8);
+                    }
+                } else {
+                    panic!("Wrong texture data: {:?}", t.data)
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn deserialize_basis() {
+        let tex: Texture2D = crate::io::RawAssets::new()
+            .insert("test.basis", encode(BasisTextureFormat::UASTC4x4))
+            .deserialize("")
+            .unwrap();
+        assert!(matches!(tex.data, TextureData::Bc7RgbaU8(_)));
+    }
+}
@@ -0,0 +1,139 @@
+//!
+//! Functionality for caching downloaded assets on disk.
+//!
+
+use crate::Result;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+///
+/// A cache which stores downloaded resources on disk, keyed by their URL, so that they are not downloaded again on subsequent loads.
+/// Use it with [load_async_with_cache](crate::io::load_async_with_cache).
+///
+/// **Note:** The cache is only used on native, on web the resources are always downloaded and the browser cache applies as usual.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadCache {
+    directory: PathBuf,
+    time_to_live: Option<Duration>,
+}
+
+impl DownloadCache {
+    ///
+    /// Constructs a new cache which stores the downloaded resources in the given directory. The directory is created when needed.
+    /// The cached resources never expire, use [DownloadCache::with_time_to_live] to set an expiry time.
+    ///
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            time_to_live: None,
+        }
+    }
+
+    ///
+    /// Sets the time after which a cached resource is expired and downloaded again.
+    ///
+    pub fn with_time_to_live(mut self, time_to_live: Duration) -> Self {
+        self.time_to_live = Some(time_to_live);
+        self
+    }
+
+    ///
+    /// Returns the directory where the cached resources are stored.
+    ///
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    ///
+    /// Removes all of the cached resources.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn clear(&self) -> Result<()> {
+        if self.directory.is_dir() {
+            for entry in std::fs::read_dir(&self.directory)? {
+                let path = entry?.path();
+                if path.is_file() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the cached bytes for the given URL if they exist and are not expired.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let path = self.path(url);
+        let age = std::fs::metadata(&path).ok()?.modified().ok()?.elapsed();
+        if self
+            .time_to_live
+            .is_some_and(|time_to_live| age.map_or(true, |age| age > time_to_live))
+        {
+            return None;
+        }
+        std::fs::read(path).ok()
+    }
+
+    ///
+    /// Stores the given bytes for the given URL. Failing to store the bytes is ignored since the cache is only an optimization, but it is logged if the `log` feature is enabled.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) fn store(&self, url: &str, bytes: &[u8]) {
+        #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+        if let Err(e) = std::fs::create_dir_all(&self.directory)
+            .and_then(|_| std::fs::write(self.path(url), bytes))
+        {
+            #[cfg(feature = "log")]
+            log::warn!("failed to cache {}: {}", url, e);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn path(&self, url: &str) -> PathBuf {
+        // FNV-1a is used since the hash must be stable across runs
+        let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        let extension = Path::new(url.split(['?', '#']).next().unwrap_or(url))
+            .extension()
+            .and_then(|e| e.to_str())
+            .filter(|e| e.len() <= 8)
+            .unwrap_or("bin");
+        self.directory.join(format!("{:016x}.{}", hash, extension))
+    }
+}
+
+impl Default for DownloadCache {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join("three-d-asset-cache"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn cache() {
+        let directory =
+            std::env::temp_dir().join(format!("three-d-asset-cache-test-{}", std::process::id()));
+        let cache = DownloadCache::new(&directory);
+        let url = "https://example.com/assets/texture.png?version=2";
+        assert_eq!(cache.get(url), None);
+        cache.store(url, &[1, 2, 3]);
+        assert_eq!(cache.get(url), Some(vec![1, 2, 3]));
+        assert_eq!(cache.get("https://example.com/assets/other.png"), None);
+        assert_eq!(cache.path(url).extension().unwrap(), "png");
+
+        let expired = cache.clone().with_time_to_live(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(expired.get(url), None);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.get(url), None);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
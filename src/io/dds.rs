@@ -0,0 +1,238 @@
+use crate::{texture::*, Error, Result};
+use std::path::Path;
+
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+///
+/// Deserialize a .dds file into a [Texture2D] without decompressing the texel data.
+/// All mip levels in the file are stored in [Texture2D::mip_levels].
+///
+/// **Note:** Only the first image of cube maps and texture arrays is returned.
+///
+pub fn deserialize_dds(path: impl AsRef<Path>, bytes: &[u8]) -> Result<Texture2D> {
+    let name = path
+        .as_ref()
+        .to_str()
+        .filter(|s| !s.starts_with("data:"))
+        .unwrap_or("default")
+        .to_owned();
+    if !bytes.starts_with(b"DDS ") || read_u32(bytes, 4)? != 124 {
+        Err(Error::DdsCorruptData)?;
+    }
+    let flags = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 16)?;
+    // The mip chain ends at a 1x1 level, so a larger mip count in the header is ignored
+    let max_mip_count = 32 - width.max(height).max(1).leading_zeros();
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(bytes, 28)?.clamp(1, max_mip_count)
+    } else {
+        1
+    };
+    let pixel_format_flags = read_u32(bytes, 80)?;
+    let four_cc = bytes.get(84..88).ok_or(Error::DdsCorruptData)?;
+
+    // Only the DXGI formats distinguish between sRGB and linear data, the legacy formats are assumed to be sRGB encoded.
+    let color_space = if pixel_format_flags & DDPF_FOURCC != 0 && four_cc == b"DX10" {
+        match read_u32(bytes, 128)? {
+            29 | 91 | 72 | 78 | 99 => ColorSpace::Srgb,
+            _ => ColorSpace::Linear,
+        }
+    } else {
+        ColorSpace::Srgb
+    };
+    let mut offset: usize = 128;
+    let format = if pixel_format_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DXT1" => Format::Bc1,
+            b"DXT5" => Format::Bc3,
+            b"ATI2" | b"BC5U" => Format::Bc5,
+            b"DX10" => {
+                offset += 20;
+                match read_u32(bytes, 128)? {
+                    28 | 29 => Format::Rgba,
+                    87 | 91 => Format::Bgra,
+                    71 | 72 => Format::Bc1,
+                    77 | 78 => Format::Bc3,
+                    83 => Format::Bc5,
+                    98 | 99 => Format::Bc7,
+                    dxgi_format => Err(Error::DdsUnsupportedFormat(format!(
+                        "DXGI format {}",
+                        dxgi_format
+                    )))?,
+                }
+            }
+            _ => Err(Error::DdsUnsupportedFormat(
+                String::from_utf8_lossy(four_cc).to_string(),
+            ))?,
+        }
+    } else if pixel_format_flags & DDPF_RGB != 0 && read_u32(bytes, 88)? == 32 {
+        match (read_u32(bytes, 92)?, read_u32(bytes, 100)?) {
+            (0xff, 0xff0000) => Format::Rgba,
+            (0xff0000, 0xff) => Format::Bgra,
+            _ => Err(Error::DdsUnsupportedFormat(
+                "uncompressed format with unsupported channel masks".to_owned(),
+            ))?,
+        }
+    } else {
+        Err(Error::DdsUnsupportedFormat(
+            "uncompressed format with less than 4 channels".to_owned(),
+        ))?
+    };
+
+    let mut levels = Vec::new();
+    for level in 0..mip_count {
+        let w = (width >> level).max(1) as usize;
+        let h = (height >> level).max(1) as usize;
+        let size = match format {
+            Format::Rgba | Format::Bgra => w.checked_mul(h).and_then(|n| n.checked_mul(4)),
+            Format::Bc1 => w
+                .div_ceil(4)
+                .checked_mul(h.div_ceil(4))
+                .and_then(|n| n.checked_mul(8)),
+            Format::Bc3 | Format::Bc5 | Format::Bc7 => w
+                .div_ceil(4)
+                .checked_mul(h.div_ceil(4))
+                .and_then(|n| n.checked_mul(16)),
+        }
+        .ok_or(Error::DdsCorruptData)?;
+        let data = offset
+            .checked_add(size)
+            .and_then(|end| bytes.get(offset..end))
+            .ok_or(Error::DdsCorruptData)?;
+        offset += size;
+        levels.push(match format {
+            Format::Rgba => {
+                TextureData::RgbaU8(data.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect())
+            }
+            Format::Bgra => {
+                TextureData::RgbaU8(data.chunks(4).map(|c| [c[2], c[1], c[0], c[3]]).collect())
+            }
+            Format::Bc1 => TextureData::Bc1RgbaU8(blocks(data)),
+            Format::Bc3 => TextureData::Bc3RgbaU8(blocks(data)),
+            Format::Bc5 => TextureData::Bc5RgU8(blocks(data)),
+            Format::Bc7 => TextureData::Bc7RgbaU8(blocks(data)),
+        });
+    }
+    let data = levels.remove(0);
+    Ok(Texture2D {
+        name,
+        data,
+        width,
+        height,
+        color_space,
+        mip_levels: levels,
+        ..Default::default()
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Rgba,
+    Bgra,
+    Bc1,
+    Bc3,
+    Bc5,
+    Bc7,
+}
+
+fn blocks<const N: usize>(data: &[u8]) -> Vec<[u8; N]> {
+    data.chunks(N).map(|c| c.try_into().unwrap()).collect()
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let b = offset
+        .checked_add(4)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or(Error::DdsCorruptData)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Texture2D, TextureData};
+
+    #[test]
+    pub fn deserialize_bc1_dds() {
+        let mut tex: Texture2D = crate::io::load_and_deserialize("test_data/test_bc1.dds").unwrap();
+        assert_eq!(tex.width, 8);
+        assert_eq!(tex.height, 8);
+        assert_eq!(tex.mip_levels.len(), 3);
+        if let TextureData::Bc1RgbaU8(blocks) = &tex.data {
+            assert_eq!(blocks.len(), 4);
+            assert_eq!(blocks[0], [0x00, 0xf8, 0x1f, 0x00, 0, 0, 0, 0]);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+        assert!(tex
+            .mip_levels
+            .iter()
+            .all(|l| matches!(l, TextureData::Bc1RgbaU8(b) if b.len() == 1)));
+
+        tex.decompress().unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[255, 0, 0, 255]; 64]));
+        assert_eq!(
+            tex.mip_levels[0],
+            TextureData::RgbaU8(vec![[255, 0, 0, 255]; 16])
+        );
+        assert_eq!(
+            tex.mip_levels[1],
+            TextureData::RgbaU8(vec![[255, 0, 0, 255]; 4])
+        );
+        assert_eq!(
+            tex.mip_levels[2],
+            TextureData::RgbaU8(vec![[255, 0, 0, 255]; 1])
+        );
+    }
+
+    #[test]
+    pub fn deserialize_bc7_dds() {
+        let mut tex: Texture2D = crate::io::load_and_deserialize("test_data/test_bc7.dds").unwrap();
+        assert_eq!(tex.width, 6);
+        assert_eq!(tex.height, 4);
+        assert!(tex.mip_levels.is_empty());
+        if let TextureData::Bc7RgbaU8(blocks) = &tex.data {
+            assert_eq!(blocks.len(), 2);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+        tex.decompress().unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[255; 4]; 24]));
+    }
+
+    #[test]
+    pub fn decompress_truncated_blocks() {
+        let data = TextureData::Bc1RgbaU8(vec![[0x00, 0xf8, 0x1f, 0x00, 0, 0, 0, 0]; 3]);
+        assert!(matches!(
+            data.decompress(8, 8),
+            Err(crate::Error::InvalidBufferLength(_, 4, 3))
+        ));
+        assert_eq!(
+            data.decompress(8, 4).unwrap(),
+            TextureData::RgbaU8(vec![[255, 0, 0, 255]; 32])
+        );
+    }
+
+    #[test]
+    pub fn deserialize_dds_with_corrupt_header() {
+        let bytes = std::fs::read("test_data/test_bc1.dds").unwrap();
+
+        // The mip count is limited by the size of the texture
+        let mut corrupt = bytes.clone();
+        corrupt[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+        let tex = super::deserialize_dds("test.dds", &corrupt).unwrap();
+        assert_eq!(tex.mip_levels.len(), 3);
+
+        for size in [u32::MAX, 1 << 31] {
+            let mut corrupt = bytes.clone();
+            corrupt[12..16].copy_from_slice(&size.to_le_bytes());
+            corrupt[16..20].copy_from_slice(&size.to_le_bytes());
+            assert!(matches!(
+                super::deserialize_dds("test.dds", &corrupt),
+                Err(crate::Error::DdsCorruptData)
+            ));
+        }
+    }
+}
@@ -1,56 +1,89 @@
-use crate::{animation::*, geometry::*, io::*, material::*, Error, Node, Result, Scene};
-use ::gltf::Gltf;
+use crate::{
+    animation::*, camera::*, geometry::*, io::*, light::*, material::*, Error, Model, Node, Result,
+    Scene, Scenes,
+};
+use ::gltf::{Document, Gltf};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
-pub fn dependencies(raw_assets: &RawAssets, path: &PathBuf) -> HashSet<PathBuf> {
+pub fn dependencies(path: &Path, bytes: &[u8]) -> HashSet<PathBuf> {
     let mut dependencies = HashSet::new();
-    if let Ok(Gltf { document, .. }) = Gltf::from_slice(raw_assets.get(path).unwrap()) {
+    if let Ok(Gltf { document, .. }) = Gltf::from_slice_without_validation(bytes) {
         let base_path = path.parent().unwrap_or(Path::new(""));
         for buffer in document.buffers() {
-            match buffer.source() {
-                ::gltf::buffer::Source::Uri(uri) => {
-                    if uri.starts_with("data:") {
-                        dependencies.insert(PathBuf::from(uri));
-                    } else {
-                        dependencies.insert(base_path.join(uri));
-                    }
-                }
-                _ => {}
+            if let ::gltf::buffer::Source::Uri(uri) = buffer.source() {
+                dependencies.insert(resolve_uri(base_path, uri));
             };
         }
 
         for texture in document.textures() {
-            match texture.source().source() {
-                ::gltf::image::Source::Uri { uri, .. } => {
-                    if uri.starts_with("data:") {
-                        use std::str::FromStr;
-                        dependencies.insert(PathBuf::from_str(uri).unwrap());
-                    } else {
-                        dependencies.insert(base_path.join(uri));
-                    }
-                }
-                _ => {}
+            if let ::gltf::image::Source::Uri { uri, .. } = texture.source().source() {
+                dependencies.insert(resolve_uri(base_path, uri));
             };
         }
     }
     dependencies
 }
 
-pub fn deserialize_gltf(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Scene> {
-    let Gltf { document, mut blob } = Gltf::from_slice(&raw_assets.remove(path)?)?;
+///
+/// Resolves the given uri relative to the directory of the .gltf file, ie. the percent-escapes are decoded and `.` and `..` segments are removed.
+/// Data URIs, absolute URLs and absolute paths are returned unchanged.
+///
+fn resolve_uri(base_path: &Path, uri: &str) -> PathBuf {
+    if uri.starts_with("data:") || uri.contains("://") || uri.starts_with('/') {
+        return PathBuf::from(uri);
+    }
+    let mut path = base_path.to_path_buf();
+    for segment in percent_decode(uri).split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if path.file_name().is_some() => {
+                path.pop();
+            }
+            _ => path.push(segment),
+        }
+    }
+    path
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+pub fn deserialize_gltf(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Scenes> {
+    let Gltf { document, mut blob } =
+        Gltf::from_slice_without_validation(&raw_assets.remove(path)?)?;
+    let document = validate(document)?;
     let base_path = path.parent().unwrap_or(Path::new(""));
+    for extension in document.extensions_used() {
+        if !SUPPORTED_EXTENSIONS.contains(&extension) {
+            raw_assets.warn(
+                path,
+                format!("unsupported extension {} is ignored", extension),
+            );
+        }
+    }
 
     let mut buffers = Vec::new();
     for buffer in document.buffers() {
         let mut data = match buffer.source() {
-            ::gltf::buffer::Source::Uri(uri) => {
-                if uri.starts_with("data:") {
-                    raw_assets.remove(uri)?
-                } else {
-                    raw_assets.remove(base_path.join(uri))?
-                }
-            }
+            ::gltf::buffer::Source::Uri(uri) => raw_assets.remove(resolve_uri(base_path, uri))?,
             ::gltf::buffer::Source::Bin => blob.take().ok_or(Error::GltfMissingData)?,
         };
         if data.len() < buffer.length() {
@@ -64,12 +97,9 @@ pub fn deserialize_gltf(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Sc
 
     let mut materials = Vec::new();
     for material in document.materials() {
-        if let Some(_) = material.index() {
+        if material.index().is_some() {
             materials.push(parse_material(
-                raw_assets,
-                &base_path,
-                &mut buffers,
-                &material,
+                raw_assets, base_path, &document, &buffers, &material,
             )?);
         }
     }
@@ -84,7 +114,18 @@ pub fn deserialize_gltf(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Sc
                 .map(|s| s.to_string())
                 .unwrap_or(format!("index {}", gltf_node.index()));
             let children = if let Some(mesh) = gltf_node.mesh() {
-                parse_model(&mesh, &buffers)?
+                let skin_index = gltf_node.skin().map(|skin| skin.index());
+                // The morph weights of the node take precedence over the morph weights of the mesh.
+                let weights = gltf_node.weights().or(mesh.weights()).unwrap_or(&[]);
+                let mut warnings = Vec::new();
+                let mut children = parse_model(&document, &mesh, weights, &buffers, &mut warnings)?;
+                for warning in warnings {
+                    raw_assets.warn(path, warning);
+                }
+                for child in children.iter_mut() {
+                    child.skin_index = skin_index;
+                }
+                children
             } else {
                 Vec::new()
             };
@@ -104,11 +145,7 @@ pub fn deserialize_gltf(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Sc
         let mut loop_time = 0.0f32;
         for channel in animation.channels() {
             let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
-            let interpolation = match channel.sampler().interpolation() {
-                ::gltf::animation::Interpolation::Step => Interpolation::Nearest,
-                ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
-                ::gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
-            };
+            let interpolation = parse_interpolation(channel.sampler().interpolation());
             let target_node = channel.target().node().index();
             let key = (
                 target_node,
@@ -137,88 +174,456 @@ pub fn deserialize_gltf(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Sc
                 });
             let kf = &mut key_frames[i].2 .1;
 
-            match reader.read_outputs().unwrap() {
-                ::gltf::animation::util::ReadOutputs::Rotations(rotations) => {
-                    kf.rotations = Some(
-                        rotations
-                            .into_f32()
-                            .into_iter()
-                            .map(|r| Quat::from_sv(r[3], vec3(r[0], r[1], r[2])))
-                            .collect(),
-                    );
-                }
-                ::gltf::animation::util::ReadOutputs::Translations(translations) => {
-                    kf.translations = Some(
-                        translations
-                            .into_iter()
-                            .map(|r| vec3(r[0], r[1], r[2]))
-                            .collect(),
-                    );
-                }
-                ::gltf::animation::util::ReadOutputs::Scales(scales) => {
-                    kf.scales = Some(scales.into_iter().map(|r| vec3(r[0], r[1], r[2])).collect());
-                }
-                ::gltf::animation::util::ReadOutputs::MorphTargetWeights(weights) => {
-                    let weights = weights.into_f32().collect::<Vec<_>>();
-                    let count = weights.len() / kf.times.len();
-                    kf.weights = Some(
-                        weights
-                            .chunks(count)
-                            .map(|c| c.into_iter().map(|v| *v).collect::<Vec<_>>())
-                            .collect(),
-                    );
-                }
-            }
+            parse_outputs(reader.read_outputs().unwrap(), kf);
         }
         for (target_node, _, mut kf) in key_frames {
-            nodes[target_node].as_mut().map(|n| {
+            if let Some(n) = nodes[target_node].as_mut() {
                 kf.1.loop_time = Some(loop_time);
                 n.animations.push(kf);
-            });
+            }
         }
     }
 
-    let gltf_scene = document.scenes().nth(0).unwrap();
-    let mut scene = Scene {
-        name: gltf_scene
-            .name()
-            .unwrap_or(&format!("Scene {}", gltf_scene.index()))
-            .to_owned(),
-        materials,
-        children: Vec::new(),
-    };
-    for c in gltf_scene.nodes() {
-        if let Some(mut node) = nodes[c.index()].take() {
-            visit(c, &mut nodes, &mut node.children);
-            scene.children.push(node);
+    let skins = parse_skins(&document, &buffers);
+    let mut scenes = Vec::new();
+    for gltf_scene in document.scenes() {
+        let mut scene = Scene {
+            name: gltf_scene
+                .name()
+                .unwrap_or(&format!("Scene {}", gltf_scene.index()))
+                .to_owned(),
+            materials: materials.clone(),
+            children: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: skins.clone(),
+        };
+        for c in gltf_scene.nodes() {
+            if let Some(node) = visit(c, Mat4::identity(), &nodes, &mut scene) {
+                scene.children.push(node);
+            }
         }
+        scenes.push(scene);
+    }
+    // A glTF file without scenes is a library of nodes, in which case all root nodes are placed in one scene.
+    if scenes.is_empty() {
+        let mut scene = Scene {
+            name: "Scene".to_owned(),
+            materials,
+            children: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins,
+        };
+        let child_nodes = document
+            .nodes()
+            .flat_map(|n| n.children().map(|c| c.index()).collect::<Vec<_>>())
+            .collect::<HashSet<_>>();
+        for gltf_node in document.nodes() {
+            if !child_nodes.contains(&gltf_node.index()) {
+                if let Some(node) = visit(gltf_node, Mat4::identity(), &nodes, &mut scene) {
+                    scene.children.push(node);
+                }
+            }
+        }
+        scenes.push(scene);
+    }
+    Ok(Scenes {
+        scenes,
+        default_scene: document.default_scene().map(|s| s.index()).unwrap_or(0),
+    })
+}
+
+fn parse_interpolation(interpolation: ::gltf::animation::Interpolation) -> Interpolation {
+    match interpolation {
+        ::gltf::animation::Interpolation::Step => Interpolation::Nearest,
+        ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+        ::gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
     }
-    Ok(scene)
 }
 
-fn visit(gltf_node: ::gltf::Node, nodes: &mut Vec<Option<Node>>, children: &mut Vec<Node>) {
+fn parse_outputs(outputs: ::gltf::animation::util::ReadOutputs, kf: &mut KeyFrames) {
+    match outputs {
+        ::gltf::animation::util::ReadOutputs::Rotations(rotations) => {
+            kf.rotations = Some(
+                rotations
+                    .into_f32()
+                    .map(|r| Quat::from_sv(r[3], vec3(r[0], r[1], r[2])))
+                    .collect(),
+            );
+        }
+        ::gltf::animation::util::ReadOutputs::Translations(translations) => {
+            kf.translations = Some(
+                translations
+                    .into_iter()
+                    .map(|r| vec3(r[0], r[1], r[2]))
+                    .collect(),
+            );
+        }
+        ::gltf::animation::util::ReadOutputs::Scales(scales) => {
+            kf.scales = Some(scales.into_iter().map(|r| vec3(r[0], r[1], r[2])).collect());
+        }
+        ::gltf::animation::util::ReadOutputs::MorphTargetWeights(weights) => {
+            let weights = weights.into_f32().collect::<Vec<_>>();
+            let values_per_key_frame = if kf.interpolation == Interpolation::CubicSpline {
+                3
+            } else {
+                1
+            };
+            let count = weights.len() / (values_per_key_frame * kf.times.len());
+            kf.weights = Some(weights.chunks(count).map(|c| c.to_vec()).collect());
+        }
+    }
+}
+
+///
+/// Parses the skins together with the animations of their joints.
+/// Animations of nodes that are not joints of the skin are ignored, as are animations of nodes in between the joints.
+///
+fn parse_skins(document: &Document, buffers: &[::gltf::buffer::Data]) -> Vec<Skin> {
+    let mut parents = vec![None; document.nodes().len()];
+    for gltf_node in document.nodes() {
+        for c in gltf_node.children() {
+            parents[c.index()] = Some(gltf_node.index());
+        }
+    }
+    let nodes = document.nodes().collect::<Vec<_>>();
+
+    let mut skins = Vec::new();
+    for skin in document.skins() {
+        let joint_nodes = skin.joints().map(|j| j.index()).collect::<Vec<_>>();
+        let inverse_bind_matrices = skin
+            .reader(|buffer| Some(&buffers[buffer.index()]))
+            .read_inverse_bind_matrices()
+            .map(|values| values.map(Mat4::from).collect::<Vec<_>>())
+            .unwrap_or(vec![Mat4::identity(); joint_nodes.len()]);
+        let joints = joint_nodes
+            .iter()
+            .zip(inverse_bind_matrices)
+            .map(|(&node_index, inverse_bind_matrix)| {
+                let mut parent = None;
+                let mut base_transformation = Mat4::identity();
+                let mut p = parents[node_index];
+                while let Some(i) = p {
+                    if let Some(j) = joint_nodes.iter().position(|n| *n == i) {
+                        parent = Some(j);
+                        break;
+                    }
+                    base_transformation =
+                        parse_transform(nodes[i].transform()) * base_transformation;
+                    p = parents[i];
+                }
+                let (translation, rotation, scale) = nodes[node_index].transform().decomposed();
+                Joint {
+                    name: nodes[node_index]
+                        .name()
+                        .map(|s| s.to_string())
+                        .unwrap_or(format!("index {}", node_index)),
+                    parent,
+                    base_transformation,
+                    translation: translation.into(),
+                    rotation: Quat::from_sv(
+                        rotation[3],
+                        vec3(rotation[0], rotation[1], rotation[2]),
+                    ),
+                    scale: scale.into(),
+                    inverse_bind_matrix,
+                }
+            })
+            .collect();
+
+        let mut animations = Vec::new();
+        for animation in document.animations() {
+            let mut duration = 0.0f32;
+            let mut channels = Vec::new();
+            for channel in animation.channels() {
+                let Some(j) = joint_nodes
+                    .iter()
+                    .position(|n| *n == channel.target().node().index())
+                else {
+                    continue;
+                };
+                let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+                let times = reader.read_inputs().unwrap().collect::<Vec<_>>();
+                duration = duration.max(*times.last().unwrap_or(&0.0));
+                let mut kf = KeyFrames {
+                    times,
+                    interpolation: parse_interpolation(channel.sampler().interpolation()),
+                    ..Default::default()
+                };
+                parse_outputs(reader.read_outputs().unwrap(), &mut kf);
+                channels.push((j, kf));
+            }
+            if !channels.is_empty() {
+                animations.push(SkeletalAnimation {
+                    name: animation.name().map(|s| s.to_owned()),
+                    duration,
+                    channels,
+                });
+            }
+        }
+
+        skins.push(Skin {
+            name: skin
+                .name()
+                .map(|s| s.to_string())
+                .unwrap_or(format!("Skin {}", skin.index())),
+            joints,
+            animations,
+        });
+    }
+    skins
+}
+
+///
+/// Builds the tree of nodes below the given glTF node and places the cameras and lights of the tree in the scene.
+/// The nodes are cloned since a node can be part of several scenes.
+///
+fn visit(
+    gltf_node: ::gltf::Node,
+    transformation: Mat4,
+    nodes: &[Option<Node>],
+    scene: &mut Scene,
+) -> Option<Node> {
+    // A node with a scale of all zeroes is ignored together with its children.
+    let mut node = nodes[gltf_node.index()].clone()?;
+    let transformation = transformation * node.transformation;
+    if let Some(camera) = gltf_node.camera() {
+        node.camera_index = Some(scene.cameras.len());
+        scene.cameras.push(parse_camera(&camera, transformation));
+    }
+    if let Some(light) = gltf_node.light() {
+        node.light_index = Some(scene.lights.len());
+        scene.lights.push(parse_light(&light, transformation));
+    }
     for c in gltf_node.children() {
-        if let Some(mut node) = nodes[c.index()].take() {
-            visit(c, nodes, &mut node.children);
-            children.push(node);
+        if let Some(child) = visit(c, transformation, nodes, scene) {
+            node.children.push(child);
         }
     }
+    Some(node)
 }
 
-fn parse_model(mesh: &::gltf::mesh::Mesh, buffers: &[::gltf::buffer::Data]) -> Result<Vec<Node>> {
+///
+/// Converts a glTF camera into a [Camera] placed with the given world transformation.
+/// A glTF camera looks in the negative z-direction with the positive y-direction as up in the space of the node it belongs to.
+///
+/// If the glTF camera has no aspect ratio, the viewport of the camera is `Viewport::new_at_origo(1, 1)`, ie. the aspect ratio is 1,
+/// otherwise it is a viewport with a height of 1000 pixels and a width matching the aspect ratio.
+/// In any case, [Camera::set_viewport] should be called with the actual viewport before the camera is used for rendering.
+/// If the glTF perspective camera has an infinite far plane, the far plane is placed at `100000 * z_near`.
+///
+fn parse_camera(camera: &::gltf::Camera, transformation: Mat4) -> Camera {
+    let position = (transformation * vec4(0.0, 0.0, 0.0, 1.0)).truncate();
+    let target = position + (transformation * vec4(0.0, 0.0, -1.0, 0.0)).truncate();
+    let up = (transformation * vec4(0.0, 1.0, 0.0, 0.0)).truncate();
+    let viewport = |aspect_ratio: Option<f32>| {
+        aspect_ratio
+            .map(|aspect_ratio| {
+                Viewport::new_at_origo(((1000.0 * aspect_ratio).round() as u32).max(1), 1000)
+            })
+            .unwrap_or(Viewport::new_at_origo(1, 1))
+    };
+    match camera.projection() {
+        ::gltf::camera::Projection::Perspective(perspective) => Camera::new_perspective(
+            viewport(perspective.aspect_ratio()),
+            position,
+            target,
+            up,
+            radians(perspective.yfov()),
+            perspective.znear(),
+            perspective.zfar().unwrap_or(100000.0 * perspective.znear()),
+        ),
+        ::gltf::camera::Projection::Orthographic(orthographic) => Camera::new_orthographic(
+            viewport(Some(orthographic.xmag() / orthographic.ymag())),
+            position,
+            target,
+            up,
+            2.0 * orthographic.ymag(),
+            orthographic.znear(),
+            orthographic.zfar(),
+        ),
+    }
+}
+
+///
+/// Converts a glTF punctual light into a [Light] placed with the given world transformation.
+/// A glTF light emits light in the negative z-direction in the space of the node it belongs to.
+/// The intensity is not rescaled, see [Light::intensity].
+///
+fn parse_light(light: &::gltf::khr_lights_punctual::Light, transformation: Mat4) -> Light {
+    let position = (transformation * vec4(0.0, 0.0, 0.0, 1.0)).truncate();
+    let direction = (transformation * vec4(0.0, 0.0, -1.0, 0.0))
+        .truncate()
+        .normalize();
+    let light_type = match light.kind() {
+        ::gltf::khr_lights_punctual::Kind::Directional => LightType::Directional { direction },
+        ::gltf::khr_lights_punctual::Kind::Point => LightType::Point {
+            position,
+            range: light.range(),
+        },
+        ::gltf::khr_lights_punctual::Kind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        } => LightType::Spot {
+            position,
+            direction,
+            range: light.range(),
+            inner_cone_angle: radians(inner_cone_angle),
+            outer_cone_angle: radians(outer_cone_angle),
+        },
+    };
+    Light {
+        name: light
+            .name()
+            .map(|s| s.to_owned())
+            .unwrap_or(format!("Light {}", light.index())),
+        color: Color::from_rgb_slice(&light.color()),
+        intensity: light.intensity(),
+        light_type,
+    }
+}
+
+///
+/// Validates the document, except that the mesh quantization extension is allowed to be required since it is supported by [parse_model],
+/// the texture transform and clear coat extensions are allowed to be required since they are supported by [parse_material],
+/// and when the `draco` feature is enabled, the Draco mesh compression extension is also allowed to be required and the accessors of Draco compressed primitives are allowed to be without a buffer view.
+///
+fn validate(document: Document) -> Result<Document> {
+    use ::gltf::json::validation::Validate;
+    let mut json = document.into_json();
+    json.extensions_required.retain(|e| {
+        e != MESH_QUANTIZATION_EXTENSION
+            && e != TEXTURE_TRANSFORM_EXTENSION
+            && e != CLEARCOAT_EXTENSION
+    });
+    if json
+        .extensions_required
+        .iter()
+        .any(|e| e == DRACO_EXTENSION)
+    {
+        #[cfg(not(feature = "draco"))]
+        return Err(Error::FeatureMissing("draco".to_string()));
+        #[cfg(feature = "draco")]
+        json.extensions_required.retain(|e| e != DRACO_EXTENSION);
+    }
+
+    let mut errors = Vec::new();
+    json.validate(&json, ::gltf::json::Path::new, &mut |path, error| {
+        errors.push((path(), error))
+    });
+    #[cfg(feature = "draco")]
+    {
+        let draco_accessors = json
+            .meshes
+            .iter()
+            .flat_map(|mesh| mesh.primitives.iter())
+            .filter(|primitive| {
+                primitive
+                    .extensions
+                    .as_ref()
+                    .is_some_and(|e| e.others.contains_key(DRACO_EXTENSION))
+            })
+            .flat_map(|primitive| {
+                primitive
+                    .attributes
+                    .values()
+                    .chain(primitive.indices.iter())
+            })
+            .map(|accessor| format!("accessors[{}].bufferView", accessor.value()))
+            .collect::<HashSet<_>>();
+        errors.retain(|(path, error)| {
+            *error != ::gltf::json::validation::Error::Missing
+                || !draco_accessors.contains(path.as_str())
+        });
+    }
+    if !errors.is_empty() {
+        Err(::gltf::Error::Validation(errors))?;
+    }
+    Ok(Document::from_json_without_validation(json))
+}
+
+const DRACO_EXTENSION: &str = "KHR_draco_mesh_compression";
+const MESH_QUANTIZATION_EXTENSION: &str = "KHR_mesh_quantization";
+const TEXTURE_TRANSFORM_EXTENSION: &str = "KHR_texture_transform";
+const CLEARCOAT_EXTENSION: &str = "KHR_materials_clearcoat";
+
+///
+/// The extensions which are supported when deserializing, other extensions are ignored with a warning.
+///
+const SUPPORTED_EXTENSIONS: &[&str] = &[
+    #[cfg(feature = "draco")]
+    DRACO_EXTENSION,
+    MESH_QUANTIZATION_EXTENSION,
+    TEXTURE_TRANSFORM_EXTENSION,
+    CLEARCOAT_EXTENSION,
+    "KHR_materials_ior",
+    "KHR_materials_transmission",
+    "KHR_materials_volume",
+    "KHR_materials_emissive_strength",
+    "KHR_materials_unlit",
+    "KHR_lights_punctual",
+];
+
+fn parse_model(
+    #[allow(unused_variables)] document: &Document,
+    mesh: &::gltf::mesh::Mesh,
+    weights: &[f32],
+    buffers: &[::gltf::buffer::Data],
+    warnings: &mut Vec<String>,
+) -> Result<Vec<Node>> {
     let mut children = Vec::new();
     for primitive in mesh.primitives() {
+        #[cfg(feature = "draco")]
+        if let Some(extension) = primitive.extension_value(DRACO_EXTENSION) {
+            children.push(Node {
+                geometry: Some(Geometry::Triangles(parse_draco_primitive(
+                    document, &primitive, extension, buffers,
+                )?)),
+                material_index: primitive.material().index(),
+                ..Default::default()
+            });
+            continue;
+        }
         let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
-        if let Some(read_positions) = reader.read_positions() {
-            let positions: Vec<_> = read_positions.map(|p| p.into()).collect();
+        if let Some(positions) = primitive
+            .get(&::gltf::Semantic::Positions)
+            .and_then(|a| read_f32::<3>(a, buffers))
+        {
+            let positions: Vec<_> = positions.into_iter().map(|p| p.into()).collect();
 
-            let normals = reader
-                .read_normals()
-                .map(|values| values.map(|n| n.into()).collect());
+            // Quantized normals and tangents are not exactly unit length.
+            let normals = primitive.get(&::gltf::Semantic::Normals).and_then(|a| {
+                let quantized = a.data_type() != ::gltf::accessor::DataType::F32;
+                read_f32::<3>(a, buffers).map(|values| {
+                    values
+                        .into_iter()
+                        .map(|n| {
+                            if quantized {
+                                Vec3::from(n).normalize()
+                            } else {
+                                n.into()
+                            }
+                        })
+                        .collect()
+                })
+            });
 
-            let tangents = reader
-                .read_tangents()
-                .map(|values| values.map(|t| t.into()).collect());
+            let tangents = primitive.get(&::gltf::Semantic::Tangents).and_then(|a| {
+                let quantized = a.data_type() != ::gltf::accessor::DataType::F32;
+                read_f32::<4>(a, buffers).map(|values| {
+                    values
+                        .into_iter()
+                        .map(|t| {
+                            if quantized {
+                                vec3(t[0], t[1], t[2]).normalize().extend(t[3])
+                            } else {
+                                t.into()
+                            }
+                        })
+                        .collect()
+                })
+            });
 
             let indices = reader
                 .read_indices()
@@ -229,16 +634,63 @@ fn parse_model(mesh: &::gltf::mesh::Mesh, buffers: &[::gltf::buffer::Data]) -> R
                 })
                 .unwrap_or(Indices::None);
 
-            let colors = reader.read_colors(0).map(|values| {
-                values
-                    .into_rgba_u8()
-                    .map(|c| Color::new(c[0], c[1], c[2], c[3]))
-                    .collect()
-            });
+            // Colors with three components are opaque.
+            let colors = primitive
+                .get(&::gltf::Semantic::Colors(0))
+                .and_then(|a| match a.dimensions() {
+                    ::gltf::accessor::Dimensions::Vec3 => read_f32::<3>(a, buffers).map(|values| {
+                        values
+                            .into_iter()
+                            .map(|c| [c[0], c[1], c[2], 1.0])
+                            .collect()
+                    }),
+                    ::gltf::accessor::Dimensions::Vec4 => read_f32::<4>(a, buffers),
+                    _ => None,
+                })
+                .map(|values| {
+                    values
+                        .into_iter()
+                        .map(|c| {
+                            let [r, g, b, a] = c.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+                            Color::new(r, g, b, a)
+                        })
+                        .collect()
+                });
+
+            let read_uvs = |set: u32| {
+                primitive
+                    .get(&::gltf::Semantic::TexCoords(set))
+                    .and_then(|a| read_f32::<2>(a, buffers))
+                    .map(|values| values.into_iter().map(|uv| uv.into()).collect())
+            };
+            let uvs = read_uvs(0);
+            let additional_uvs = (1..).map_while(read_uvs).collect();
+
+            let joint_indices = reader
+                .read_joints(0)
+                .map(|values| values.into_u16().map(|j| j.into()).collect());
 
-            let uvs = reader
-                .read_tex_coords(0)
-                .map(|values| values.into_f32().map(|uv| uv.into()).collect());
+            let joint_weights = reader
+                .read_weights(0)
+                .map(|values| values.into_f32().map(|w| w.into()).collect());
+
+            let morph_targets = primitive
+                .morph_targets()
+                .enumerate()
+                .map(|(i, target)| {
+                    let read = |accessor: Option<::gltf::Accessor>| {
+                        accessor
+                            .and_then(|a| read_f32::<3>(a, buffers))
+                            .map(|values| values.into_iter().map(|v| v.into()).collect())
+                    };
+                    MorphTarget {
+                        positions: read(target.positions()),
+                        normals: read(target.normals()),
+                        tangents: read(target.tangents()),
+                        weight: weights.get(i).copied().unwrap_or(0.0),
+                    }
+                })
+                .collect::<Vec<_>>();
 
             children.push(Node {
                 geometry: Some(Geometry::Triangles(TriMesh {
@@ -248,15 +700,201 @@ fn parse_model(mesh: &::gltf::mesh::Mesh, buffers: &[::gltf::buffer::Data]) -> R
                     indices,
                     colors,
                     uvs,
+                    additional_uvs,
+                    joint_indices,
+                    joint_weights,
+                    morph_targets: if morph_targets.is_empty() {
+                        None
+                    } else {
+                        Some(morph_targets)
+                    },
                 })),
                 material_index: primitive.material().index(),
                 ..Default::default()
             });
+        } else {
+            warnings.push(format!(
+                "primitive {} of mesh {} has no positions and is skipped",
+                primitive.index(),
+                mesh.name()
+                    .map(|s| s.to_string())
+                    .unwrap_or(format!("index {}", mesh.index()))
+            ));
         }
     }
     Ok(children)
 }
 
+///
+/// Decodes a primitive compressed with the `KHR_draco_mesh_compression` extension.
+/// The decoded indices are stored with the component type of the indices accessor of the primitive.
+///
+#[cfg(feature = "draco")]
+fn parse_draco_primitive(
+    document: &Document,
+    primitive: &::gltf::Primitive,
+    extension: &::gltf::json::Value,
+    buffers: &[::gltf::buffer::Data],
+) -> Result<TriMesh> {
+    use draco_oxide_core::{attribute::*, types::*};
+    fn values<T: DataValue, const N: usize>(attribute: &Attribute) -> Vec<[T; N]>
+    where
+        NdVector<N, T>: Vector<N, Component = T>,
+    {
+        (0..attribute.len())
+            .map(|i| {
+                let value: NdVector<N, T> = attribute.get(PointIdx::from(i));
+                std::array::from_fn(|j| *value.get(j))
+            })
+            .collect()
+    }
+
+    let view = extension
+        .get("bufferView")
+        .and_then(|v| v.as_u64())
+        .and_then(|i| document.views().nth(i as usize))
+        .ok_or(Error::GltfCorruptData)?;
+    let bytes = buffers[view.buffer().index()]
+        .get(view.offset()..view.offset() + view.length())
+        .ok_or(Error::GltfCorruptData)?;
+    let mesh = draco_oxide_decoder::decode_mesh(bytes)?;
+
+    let attribute = |semantic: &str| -> Result<Option<&Attribute>> {
+        let Some(id) = extension
+            .get("attributes")
+            .and_then(|a| a.get(semantic))
+            .and_then(|v| v.as_u64())
+        else {
+            return Ok(None);
+        };
+        mesh.attributes
+            .iter()
+            .find(|a| a.get_id().as_usize() == id as usize)
+            .filter(|a| a.get_component_type() == ComponentDataType::F32)
+            .map(Some)
+            .ok_or(Error::GltfCorruptData)
+    };
+    let positions = attribute("POSITION")?.ok_or(Error::GltfMissingData)?;
+    if positions.get_num_components() != 3 {
+        Err(Error::GltfCorruptData)?;
+    }
+    let normals = attribute("NORMAL")?
+        .filter(|a| a.get_num_components() == 3)
+        .map(|a| values::<f32, 3>(a).into_iter().map(|n| n.into()).collect());
+    let tangents = attribute("TANGENT")?
+        .filter(|a| a.get_num_components() == 4)
+        .map(|a| values::<f32, 4>(a).into_iter().map(|t| t.into()).collect());
+    let uvs = |set: usize| -> Result<Option<Vec<Vec2>>> {
+        Ok(attribute(&format!("TEXCOORD_{}", set))?
+            .filter(|a| a.get_num_components() == 2)
+            .map(|a| {
+                values::<f32, 2>(a)
+                    .into_iter()
+                    .map(|uv| uv.into())
+                    .collect()
+            }))
+    };
+    let mut additional_uvs = Vec::new();
+    while let Some(values) = uvs(additional_uvs.len() + 1)? {
+        additional_uvs.push(values);
+    }
+    let uvs = uvs(0)?;
+    let colors = match attribute("COLOR_0")? {
+        Some(a) if a.get_num_components() == 3 => Some(
+            values::<f32, 3>(a)
+                .into_iter()
+                .map(|c| Color::from_rgb_slice(&c))
+                .collect(),
+        ),
+        Some(a) if a.get_num_components() == 4 => Some(
+            values::<f32, 4>(a)
+                .into_iter()
+                .map(|c| Color::from_rgba_slice(&c))
+                .collect(),
+        ),
+        _ => None,
+    };
+
+    let indices = mesh
+        .faces
+        .iter()
+        .flat_map(|f| f.map(usize::from))
+        .collect::<Vec<_>>();
+    let indices = match primitive.indices().map(|i| i.data_type()) {
+        Some(::gltf::accessor::DataType::U8) => {
+            Indices::U8(indices.into_iter().map(|i| i as u8).collect())
+        }
+        Some(::gltf::accessor::DataType::U16) => {
+            Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+        }
+        _ => Indices::U32(indices.into_iter().map(|i| i as u32).collect()),
+    };
+    Ok(TriMesh {
+        positions: Positions::F32(
+            values::<f32, 3>(positions)
+                .into_iter()
+                .map(|p| p.into())
+                .collect(),
+        ),
+        normals,
+        tangents,
+        indices,
+        colors,
+        uvs,
+        additional_uvs,
+        ..Default::default()
+    })
+}
+
+///
+/// Reads the values of the accessor as floating point values.
+/// Integer values, which are used by the `KHR_mesh_quantization` extension, are mapped to the range `[0, 1]` (unsigned) or `[-1, 1]` (signed) if the accessor is normalized
+/// and otherwise converted directly.
+///
+fn read_f32<const N: usize>(
+    accessor: ::gltf::Accessor,
+    buffers: &[::gltf::buffer::Data],
+) -> Option<Vec<[f32; N]>>
+where
+    [f32; N]: ::gltf::accessor::Item,
+    [i8; N]: ::gltf::accessor::Item,
+    [u8; N]: ::gltf::accessor::Item,
+    [i16; N]: ::gltf::accessor::Item,
+    [u16; N]: ::gltf::accessor::Item,
+    [u32; N]: ::gltf::accessor::Item,
+{
+    use ::gltf::accessor::{DataType, Item, Iter};
+    fn read<T: Copy, const N: usize>(
+        accessor: ::gltf::Accessor,
+        buffers: &[::gltf::buffer::Data],
+        convert: impl Fn(T) -> f32,
+    ) -> Option<Vec<[f32; N]>>
+    where
+        [T; N]: Item,
+    {
+        Some(
+            Iter::<[T; N]>::new(accessor, |buffer| Some(&buffers[buffer.index()].0[..]))?
+                .map(|v| v.map(&convert))
+                .collect(),
+        )
+    }
+    let normalized = accessor.normalized();
+    match accessor.data_type() {
+        DataType::F32 => read(accessor, buffers, |v: f32| v),
+        DataType::I8 if normalized => read(accessor, buffers, |v: i8| (v as f32 / 127.0).max(-1.0)),
+        DataType::U8 if normalized => read(accessor, buffers, |v: u8| v as f32 / 255.0),
+        DataType::I16 if normalized => {
+            read(accessor, buffers, |v: i16| (v as f32 / 32767.0).max(-1.0))
+        }
+        DataType::U16 if normalized => read(accessor, buffers, |v: u16| v as f32 / 65535.0),
+        DataType::I8 => read(accessor, buffers, |v: i8| v as f32),
+        DataType::U8 => read(accessor, buffers, |v: u8| v as f32),
+        DataType::I16 => read(accessor, buffers, |v: i16| v as f32),
+        DataType::U16 => read(accessor, buffers, |v: u16| v as f32),
+        DataType::U32 => read(accessor, buffers, |v: u32| v as f32),
+    }
+}
+
 fn material_name(material: &::gltf::material::Material) -> String {
     material.name().map(|s| s.to_string()).unwrap_or(
         material
@@ -269,30 +907,52 @@ fn material_name(material: &::gltf::material::Material) -> String {
 fn parse_material(
     raw_assets: &mut RawAssets,
     path: &Path,
+    document: &Document,
     buffers: &[::gltf::buffer::Data],
     material: &::gltf::material::Material,
 ) -> Result<PbrMaterial> {
     let pbr = material.pbr_metallic_roughness();
     let color = pbr.base_color_factor();
-    let albedo_texture = if let Some(info) = pbr.base_color_texture() {
-        Some(parse_texture(raw_assets, path, buffers, info.texture())?)
-    } else {
-        None
-    };
-    let metallic_roughness_texture = if let Some(info) = pbr.metallic_roughness_texture() {
-        Some(parse_texture(raw_assets, path, buffers, info.texture())?)
-    } else {
-        None
-    };
-    let (normal_texture, normal_scale) = if let Some(normal) = material.normal_texture() {
+    let (albedo_texture, (albedo_uv_set, albedo_texture_transform)) =
+        if let Some(info) = pbr.base_color_texture() {
+            (
+                Some(parse_texture(raw_assets, path, buffers, info.texture())?),
+                parse_texture_transform(
+                    info.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                    info.tex_coord(),
+                ),
+            )
+        } else {
+            (None, (0, TextureTransform::default()))
+        };
+    let (
+        metallic_roughness_texture,
+        (metallic_roughness_uv_set, metallic_roughness_texture_transform),
+    ) = if let Some(info) = pbr.metallic_roughness_texture() {
         (
-            Some(parse_texture(raw_assets, path, buffers, normal.texture())?),
-            normal.scale(),
+            Some(parse_texture(raw_assets, path, buffers, info.texture())?),
+            parse_texture_transform(
+                info.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                info.tex_coord(),
+            ),
         )
     } else {
-        (None, 1.0)
+        (None, (0, TextureTransform::default()))
     };
-    let (occlusion_texture, occlusion_strength) =
+    let (normal_texture, normal_scale, (normal_uv_set, normal_texture_transform)) =
+        if let Some(normal) = material.normal_texture() {
+            (
+                Some(parse_texture(raw_assets, path, buffers, normal.texture())?),
+                normal.scale(),
+                parse_texture_transform(
+                    normal.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                    normal.tex_coord(),
+                ),
+            )
+        } else {
+            (None, 1.0, (0, TextureTransform::default()))
+        };
+    let (occlusion_texture, occlusion_strength, (occlusion_uv_set, occlusion_texture_transform)) =
         if let Some(occlusion) = material.occlusion_texture() {
             (
                 Some(parse_texture(
@@ -302,50 +962,232 @@ fn parse_material(
                     occlusion.texture(),
                 )?),
                 occlusion.strength(),
+                parse_texture_transform(
+                    occlusion.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                    occlusion.tex_coord(),
+                ),
             )
         } else {
-            (None, 1.0)
+            (None, 1.0, (0, TextureTransform::default()))
         };
-    let emissive_texture = if let Some(info) = material.emissive_texture() {
-        Some(parse_texture(raw_assets, path, buffers, info.texture())?)
-    } else {
-        None
-    };
-    let transmission_texture =
+    let (emissive_texture, (emissive_uv_set, emissive_texture_transform)) =
+        if let Some(info) = material.emissive_texture() {
+            (
+                Some(parse_texture(raw_assets, path, buffers, info.texture())?),
+                parse_texture_transform(
+                    info.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                    info.tex_coord(),
+                ),
+            )
+        } else {
+            (None, (0, TextureTransform::default()))
+        };
+    let (transmission_texture, (transmission_uv_set, transmission_texture_transform)) =
         if let Some(Some(info)) = material.transmission().map(|t| t.transmission_texture()) {
-            Some(parse_texture(raw_assets, path, buffers, info.texture())?)
+            (
+                Some(parse_texture(raw_assets, path, buffers, info.texture())?),
+                parse_texture_transform(
+                    info.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                    info.tex_coord(),
+                ),
+            )
+        } else {
+            (None, (0, TextureTransform::default()))
+        };
+    let volume = material.volume();
+    let (thickness_texture, (thickness_uv_set, thickness_texture_transform)) =
+        if let Some(Some(info)) = volume.as_ref().map(|v| v.thickness_texture()) {
+            (
+                Some(parse_texture(raw_assets, path, buffers, info.texture())?),
+                parse_texture_transform(
+                    info.extension_value(TEXTURE_TRANSFORM_EXTENSION),
+                    info.tex_coord(),
+                ),
+            )
         } else {
-            None
+            (None, (0, TextureTransform::default()))
         };
-    Ok(PbrMaterial {
+    let clearcoat = material.extension_value(CLEARCOAT_EXTENSION);
+    let clearcoat_value = |name: &str| {
+        clearcoat
+            .and_then(|c| c.get(name))
+            .and_then(|v| v.as_f64())
+            .map(|v| v as f32)
+    };
+    let parse_clearcoat_texture = |raw_assets: &mut RawAssets, name: &str| {
+        parse_extension_texture(
+            raw_assets,
+            path,
+            document,
+            buffers,
+            clearcoat.and_then(|c| c.get(name)),
+        )
+    };
+    let (clearcoat_texture, (clearcoat_uv_set, clearcoat_texture_transform)) =
+        parse_clearcoat_texture(raw_assets, "clearcoatTexture")?;
+    let (
+        clearcoat_roughness_texture,
+        (clearcoat_roughness_uv_set, clearcoat_roughness_texture_transform),
+    ) = parse_clearcoat_texture(raw_assets, "clearcoatRoughnessTexture")?;
+    let (clearcoat_normal_texture, (clearcoat_normal_uv_set, clearcoat_normal_texture_transform)) =
+        parse_clearcoat_texture(raw_assets, "clearcoatNormalTexture")?;
+    let alpha_mode = match material.alpha_mode() {
+        ::gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+        ::gltf::material::AlphaMode::Mask => {
+            AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+        }
+        ::gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+    };
+    #[allow(deprecated)]
+    let mut pbr_material = PbrMaterial {
         name: material_name(material),
         albedo: Color::from_rgba_slice(&color),
         albedo_texture,
+        albedo_uv_set,
+        albedo_texture_transform,
+        alpha_texture: None,
+        alpha_uv_set: 0,
+        alpha_texture_transform: TextureTransform::default(),
         metallic: pbr.metallic_factor(),
         roughness: pbr.roughness_factor(),
         metallic_roughness_texture,
+        metallic_roughness_uv_set,
+        metallic_roughness_texture_transform,
         normal_texture,
+        normal_uv_set,
+        normal_texture_transform,
         normal_scale,
         occlusion_texture,
+        occlusion_uv_set,
+        occlusion_texture_transform,
         occlusion_strength,
         occlusion_metallic_roughness_texture: None,
         emissive: Color::from_rgb_slice(&material.emissive_factor()),
+        emissive_strength: material.emissive_strength().unwrap_or(1.0),
         emissive_texture,
+        emissive_uv_set,
+        emissive_texture_transform,
         transmission: material
             .transmission()
             .map(|t| t.transmission_factor())
             .unwrap_or(0.0),
         transmission_texture,
+        transmission_uv_set,
+        transmission_texture_transform,
+        thickness: volume.as_ref().map(|v| v.thickness_factor()).unwrap_or(0.0),
+        thickness_texture,
+        thickness_uv_set,
+        thickness_texture_transform,
+        attenuation_color: volume
+            .as_ref()
+            .map(|v| Color::from_rgb_slice(&v.attenuation_color()))
+            .unwrap_or(Color::WHITE),
+        attenuation_distance: volume
+            .as_ref()
+            .map(|v| v.attenuation_distance())
+            .unwrap_or(f32::INFINITY),
         index_of_refraction: material.ior().unwrap_or(1.5),
-        alpha_cutout: material.alpha_cutoff(),
+        clearcoat: clearcoat_value("clearcoatFactor").unwrap_or(0.0),
+        clearcoat_texture,
+        clearcoat_uv_set,
+        clearcoat_texture_transform,
+        clearcoat_roughness: clearcoat_value("clearcoatRoughnessFactor").unwrap_or(0.0),
+        clearcoat_roughness_texture,
+        clearcoat_roughness_uv_set,
+        clearcoat_roughness_texture_transform,
+        clearcoat_normal_scale: clearcoat
+            .and_then(|c| c.get("clearcoatNormalTexture"))
+            .and_then(|t| t.get("scale"))
+            .and_then(|v| v.as_f64())
+            .unwrap_or(1.0) as f32,
+        clearcoat_normal_texture,
+        clearcoat_normal_uv_set,
+        clearcoat_normal_texture_transform,
+        alpha_cutout: match alpha_mode {
+            AlphaMode::Mask(cutoff) => Some(cutoff),
+            _ => None,
+        },
+        alpha_mode,
+        is_double_sided: material.double_sided(),
+        is_unlit: material.unlit(),
         lighting_model: LightingModel::Cook(
             NormalDistributionFunction::TrowbridgeReitzGGX,
             GeometryFunction::SmithSchlickGGX,
         ),
-    })
+    };
+    pbr_material.assign_texture_color_spaces();
+    Ok(pbr_material)
 }
 
-fn parse_texture<'a>(
+///
+/// Returns the uv set and the texture transform of a texture reference given the `KHR_texture_transform` extension value, if any,
+/// since the extension can override the uv set.
+///
+fn parse_texture_transform(
+    extension: Option<&::gltf::json::Value>,
+    uv_set: u32,
+) -> (usize, TextureTransform) {
+    let Some(extension) = extension else {
+        return (uv_set as usize, TextureTransform::default());
+    };
+    let vector = |name: &str, default: Vec2| {
+        extension
+            .get(name)
+            .and_then(|v| v.as_array())
+            .and_then(|v| {
+                Some(vec2(
+                    v.first()?.as_f64()? as f32,
+                    v.get(1)?.as_f64()? as f32,
+                ))
+            })
+            .unwrap_or(default)
+    };
+    (
+        extension
+            .get("texCoord")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(uv_set as usize),
+        TextureTransform {
+            offset: vector("offset", vec2(0.0, 0.0)),
+            rotation: extension
+                .get("rotation")
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0) as f32,
+            scale: vector("scale", vec2(1.0, 1.0)),
+        },
+    )
+}
+
+///
+/// Parses a texture reference which is only available as json, for example in a material extension which is not supported by the gltf crate.
+///
+fn parse_extension_texture(
+    raw_assets: &mut RawAssets,
+    path: &Path,
+    document: &Document,
+    buffers: &[::gltf::buffer::Data],
+    info: Option<&::gltf::json::Value>,
+) -> Result<(Option<Texture2D>, (usize, TextureTransform))> {
+    let Some(info) = info else {
+        return Ok((None, (0, TextureTransform::default())));
+    };
+    let texture = info
+        .get("index")
+        .and_then(|v| v.as_u64())
+        .and_then(|index| document.textures().nth(index as usize))
+        .ok_or(Error::GltfCorruptData)?;
+    Ok((
+        Some(parse_texture(raw_assets, path, buffers, texture)?),
+        parse_texture_transform(
+            info.get("extensions")
+                .and_then(|e| e.get(TEXTURE_TRANSFORM_EXTENSION)),
+            info.get("texCoord").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+        ),
+    ))
+}
+
+fn parse_texture(
     raw_assets: &mut RawAssets,
     path: &Path,
     buffers: &[::gltf::buffer::Data],
@@ -354,15 +1196,9 @@ fn parse_texture<'a>(
     let gltf_image = gltf_texture.source();
     let gltf_source = gltf_image.source();
     let tex = match gltf_source {
-        ::gltf::image::Source::Uri { uri, .. } => {
-            if uri.starts_with("data:") {
-                raw_assets.deserialize(uri)?
-            } else {
-                raw_assets.deserialize(path.join(uri))?
-            }
-        }
+        ::gltf::image::Source::Uri { uri, .. } => raw_assets.deserialize(resolve_uri(path, uri))?,
         ::gltf::image::Source::View { view, .. } => {
-            if view.stride() != None {
+            if view.stride().is_some() {
                 unimplemented!();
             }
             #[allow(unused_variables)]
@@ -382,6 +1218,413 @@ fn parse_transform(transform: ::gltf::scene::Transform) -> Mat4 {
     Mat4::from_cols(c0.into(), c1.into(), c2.into(), c3.into())
 }
 
+///
+/// Serializes the triangle meshes and materials of a [Model] to glTF.
+/// A `.glb` path results in a single binary file with the buffer and the textures embedded,
+/// otherwise the buffer is written to a sibling `.bin` file and the textures to sibling image files.
+/// Material extensions, skins and animations are not serialized.
+///
+pub fn serialize_gltf<'a>(path: &Path, model: &'a Model) -> Result<RawAssets> {
+    use ::gltf::json;
+    use json::validation::Checked::Valid;
+    let is_binary = path.extension().map(|e| e == "glb").unwrap_or(false);
+    let base_path = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
+    let mut raw_assets = RawAssets::new();
+    let mut writer = GltfWriter::default();
+    writer.root.asset.generator = Some("three-d-asset".to_owned());
+
+    let mut textures: Vec<(&'a Texture2D, json::Index<json::Texture>)> = Vec::new();
+    let mut file_names = HashSet::new();
+    let mut texture_info = |writer: &mut GltfWriter,
+                            raw_assets: &mut RawAssets,
+                            texture: &'a Texture2D,
+                            fallback_name: String|
+     -> Result<json::Index<json::Texture>> {
+        // Equal textures are shared between materials, while different textures are written separately even if they have the same name
+        if let Some((_, index)) = textures.iter().find(|(t, _)| *t == texture) {
+            return Ok(*index);
+        }
+        let file_name = super::texture_file_name(texture, &fallback_name, &mut file_names);
+        let image = if is_binary {
+            // Embedded images must be png or jpeg, so they are always encoded as png
+            let file_name = Path::new(&file_name).with_extension("png");
+            let bytes = texture.serialize(&file_name)?.remove(&file_name)?;
+            json::Image {
+                buffer_view: Some(writer.view(&bytes, None)),
+                mime_type: Some(json::image::MimeType("image/png".to_owned())),
+                name: Some(file_name.to_str().unwrap().to_owned()),
+                uri: None,
+                extensions: None,
+                extras: Default::default(),
+            }
+        } else {
+            raw_assets.extend(texture.serialize(base_path.join(&file_name))?);
+            json::Image {
+                buffer_view: None,
+                mime_type: None,
+                name: None,
+                uri: Some(file_name),
+                extensions: None,
+                extras: Default::default(),
+            }
+        };
+        let source = writer.root.push(image);
+        let index = writer.root.push(json::Texture {
+            name: None,
+            sampler: None,
+            source,
+            extensions: None,
+            extras: Default::default(),
+        });
+        textures.push((texture, index));
+        Ok(index)
+    };
+
+    for material in model.materials.iter() {
+        let mut info = |writer: &mut GltfWriter,
+                        texture: &'a Option<Texture2D>,
+                        uv_set: usize,
+                        slot: &str|
+         -> Result<Option<json::texture::Info>> {
+            let Some(texture) = texture else {
+                return Ok(None);
+            };
+            Ok(Some(json::texture::Info {
+                index: texture_info(
+                    writer,
+                    &mut raw_assets,
+                    texture,
+                    format!("{}_{}", material.name, slot),
+                )?,
+                tex_coord: uv_set as u32,
+                extensions: None,
+                extras: Default::default(),
+            }))
+        };
+        // The combined occlusion, metallic and roughness texture is used for both glTF slots
+        let (metallic_roughness_texture, occlusion_texture) =
+            if material.occlusion_metallic_roughness_texture.is_some() {
+                let info = info(
+                    &mut writer,
+                    &material.occlusion_metallic_roughness_texture,
+                    material.metallic_roughness_uv_set,
+                    "occlusion_metallic_roughness",
+                )?;
+                (info.clone(), info)
+            } else {
+                (
+                    info(
+                        &mut writer,
+                        &material.metallic_roughness_texture,
+                        material.metallic_roughness_uv_set,
+                        "metallic_roughness",
+                    )?,
+                    info(
+                        &mut writer,
+                        &material.occlusion_texture,
+                        material.occlusion_uv_set,
+                        "occlusion",
+                    )?,
+                )
+            };
+        let pbr_metallic_roughness = json::material::PbrMetallicRoughness {
+            base_color_factor: json::material::PbrBaseColorFactor(material.albedo.to_rgba_slice()),
+            base_color_texture: info(
+                &mut writer,
+                &material.albedo_texture,
+                material.albedo_uv_set,
+                "albedo",
+            )?,
+            metallic_factor: json::material::StrengthFactor(material.metallic),
+            roughness_factor: json::material::StrengthFactor(material.roughness),
+            metallic_roughness_texture,
+            extensions: None,
+            extras: Default::default(),
+        };
+        let normal_texture = info(
+            &mut writer,
+            &material.normal_texture,
+            material.normal_uv_set,
+            "normal",
+        )?
+        .map(|info| json::material::NormalTexture {
+            index: info.index,
+            scale: material.normal_scale,
+            tex_coord: info.tex_coord,
+            extensions: None,
+            extras: Default::default(),
+        });
+        let occlusion_texture = occlusion_texture.map(|info| json::material::OcclusionTexture {
+            index: info.index,
+            strength: json::material::StrengthFactor(material.occlusion_strength),
+            tex_coord: info.tex_coord,
+            extensions: None,
+            extras: Default::default(),
+        });
+        let emissive_texture = info(
+            &mut writer,
+            &material.emissive_texture,
+            material.emissive_uv_set,
+            "emissive",
+        )?;
+        let [r, g, b, _] = material.emissive.to_rgba_slice();
+        // Materials that only set the deprecated alpha cutout are written as masked.
+        #[allow(deprecated)]
+        let alpha_mode = match (material.alpha_mode, material.alpha_cutout) {
+            (AlphaMode::Opaque, Some(cutoff)) => AlphaMode::Mask(cutoff),
+            (alpha_mode, _) => alpha_mode,
+        };
+        writer.root.push(json::Material {
+            alpha_cutoff: match alpha_mode {
+                AlphaMode::Mask(cutoff) => Some(json::material::AlphaCutoff(cutoff)),
+                _ => None,
+            },
+            alpha_mode: Valid(match alpha_mode {
+                AlphaMode::Opaque => json::material::AlphaMode::Opaque,
+                AlphaMode::Mask(_) => json::material::AlphaMode::Mask,
+                AlphaMode::Blend => json::material::AlphaMode::Blend,
+            }),
+            double_sided: material.is_double_sided,
+            name: Some(material.name.clone()),
+            pbr_metallic_roughness,
+            normal_texture,
+            occlusion_texture,
+            emissive_texture,
+            emissive_factor: json::material::EmissiveFactor([r, g, b]),
+            extensions: None,
+            extras: Default::default(),
+        });
+    }
+
+    let mut nodes = Vec::new();
+    for primitive in model.geometries.iter() {
+        let Geometry::Triangles(mesh) = &primitive.geometry else {
+            continue;
+        };
+        let mut attributes = std::collections::BTreeMap::new();
+        let positions = mesh.positions.to_f32();
+        let mut min = vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for p in positions.iter() {
+            min = vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        let accessor = writer.accessor(
+            &f32_bytes(positions.iter().flat_map(|p| [p.x, p.y, p.z])),
+            positions.len(),
+            json::accessor::ComponentType::F32,
+            json::accessor::Type::Vec3,
+        );
+        writer.root.accessors[accessor.value()].min =
+            Some(json::Value::from(vec![min.x, min.y, min.z]));
+        writer.root.accessors[accessor.value()].max =
+            Some(json::Value::from(vec![max.x, max.y, max.z]));
+        attributes.insert(Valid(json::mesh::Semantic::Positions), accessor);
+        if let Some(normals) = mesh.normals.as_ref() {
+            let accessor = writer.accessor(
+                &f32_bytes(normals.iter().flat_map(|n| [n.x, n.y, n.z])),
+                normals.len(),
+                json::accessor::ComponentType::F32,
+                json::accessor::Type::Vec3,
+            );
+            attributes.insert(Valid(json::mesh::Semantic::Normals), accessor);
+        }
+        if let Some(tangents) = mesh.tangents.as_ref() {
+            let accessor = writer.accessor(
+                &f32_bytes(tangents.iter().flat_map(|t| [t.x, t.y, t.z, t.w])),
+                tangents.len(),
+                json::accessor::ComponentType::F32,
+                json::accessor::Type::Vec4,
+            );
+            attributes.insert(Valid(json::mesh::Semantic::Tangents), accessor);
+        }
+        for (set, uvs) in mesh
+            .uvs
+            .iter()
+            .chain(mesh.additional_uvs.iter())
+            .enumerate()
+        {
+            let accessor = writer.accessor(
+                &f32_bytes(uvs.iter().flat_map(|uv| [uv.x, uv.y])),
+                uvs.len(),
+                json::accessor::ComponentType::F32,
+                json::accessor::Type::Vec2,
+            );
+            attributes.insert(Valid(json::mesh::Semantic::TexCoords(set as u32)), accessor);
+        }
+        if let Some(colors) = mesh.colors.as_ref() {
+            let bytes: Vec<u8> = colors.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect();
+            let accessor = writer.accessor(
+                &bytes,
+                colors.len(),
+                json::accessor::ComponentType::U8,
+                json::accessor::Type::Vec4,
+            );
+            writer.root.accessors[accessor.value()].normalized = true;
+            attributes.insert(Valid(json::mesh::Semantic::Colors(0)), accessor);
+        }
+        let indices = match &mesh.indices {
+            Indices::None => None,
+            Indices::U8(values) => Some((
+                values.clone(),
+                values.len(),
+                json::accessor::ComponentType::U8,
+            )),
+            Indices::U16(values) => Some((
+                values.iter().flat_map(|i| i.to_le_bytes()).collect(),
+                values.len(),
+                json::accessor::ComponentType::U16,
+            )),
+            Indices::U32(values) => Some((
+                values.iter().flat_map(|i| i.to_le_bytes()).collect(),
+                values.len(),
+                json::accessor::ComponentType::U32,
+            )),
+        }
+        .map(|(bytes, count, component_type)| {
+            writer.accessor(&bytes, count, component_type, json::accessor::Type::Scalar)
+        });
+        let mesh = writer.root.push(json::Mesh {
+            extensions: None,
+            extras: Default::default(),
+            name: Some(primitive.name.clone()),
+            primitives: vec![json::mesh::Primitive {
+                attributes,
+                extensions: None,
+                extras: Default::default(),
+                indices,
+                material: primitive.material_index.map(|i| json::Index::new(i as u32)),
+                mode: Valid(json::mesh::Mode::Triangles),
+                targets: None,
+            }],
+            weights: None,
+        });
+        nodes.push(writer.root.push(json::Node {
+            matrix: if primitive.transformation == Mat4::identity() {
+                None
+            } else {
+                Some(*primitive.transformation.as_ref())
+            },
+            mesh: Some(mesh),
+            name: Some(primitive.name.clone()),
+            ..Default::default()
+        }));
+    }
+    let scene = writer.root.push(json::Scene {
+        extensions: None,
+        extras: Default::default(),
+        name: Some(model.name.clone()),
+        nodes,
+    });
+    writer.root.scene = Some(scene);
+
+    let GltfWriter { mut root, data } = writer;
+    if !data.is_empty() {
+        root.push(json::Buffer {
+            byte_length: data.len().into(),
+            name: None,
+            uri: if is_binary {
+                None
+            } else {
+                let bin_name = format!("{}.bin", stem);
+                raw_assets.insert(base_path.join(&bin_name), data.clone());
+                Some(bin_name)
+            },
+            extensions: None,
+            extras: Default::default(),
+        });
+    }
+    let json = json::serialize::to_vec(&root)
+        .map_err(|_| Error::FailedSerialize(path.to_str().unwrap().to_string()))?;
+    if is_binary {
+        let glb = ::gltf::binary::Glb {
+            header: ::gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                // Computed when writing
+                length: 0,
+            },
+            json: json.into(),
+            bin: if data.is_empty() {
+                None
+            } else {
+                Some(data.into())
+            },
+        };
+        raw_assets.insert(path, glb.to_vec()?);
+    } else {
+        raw_assets.insert(path, json);
+    }
+    Ok(raw_assets)
+}
+
+///
+/// The json document and the binary buffer of a glTF file which is being serialized.
+/// All buffer views refer to the first and only buffer.
+///
+#[derive(Default)]
+struct GltfWriter {
+    root: ::gltf::json::Root,
+    data: Vec<u8>,
+}
+
+impl GltfWriter {
+    fn view(
+        &mut self,
+        bytes: &[u8],
+        target: Option<::gltf::json::buffer::Target>,
+    ) -> ::gltf::json::Index<::gltf::json::buffer::View> {
+        let byte_offset = self.data.len();
+        self.data.extend_from_slice(bytes);
+        self.data.resize(self.data.len().next_multiple_of(4), 0);
+        self.root.push(::gltf::json::buffer::View {
+            buffer: ::gltf::json::Index::new(0),
+            byte_length: bytes.len().into(),
+            byte_offset: Some(byte_offset.into()),
+            byte_stride: None,
+            name: None,
+            target: target.map(::gltf::json::validation::Checked::Valid),
+            extensions: None,
+            extras: Default::default(),
+        })
+    }
+
+    fn accessor(
+        &mut self,
+        bytes: &[u8],
+        count: usize,
+        component_type: ::gltf::json::accessor::ComponentType,
+        type_: ::gltf::json::accessor::Type,
+    ) -> ::gltf::json::Index<::gltf::json::Accessor> {
+        use ::gltf::json::{buffer::Target, validation::Checked::Valid};
+        let target = if type_ == ::gltf::json::accessor::Type::Scalar {
+            Target::ElementArrayBuffer
+        } else {
+            Target::ArrayBuffer
+        };
+        let buffer_view = self.view(bytes, Some(target));
+        self.root.push(::gltf::json::Accessor {
+            buffer_view: Some(buffer_view),
+            byte_offset: None,
+            count: count.into(),
+            component_type: Valid(::gltf::json::accessor::GenericComponentType(component_type)),
+            extensions: None,
+            extras: Default::default(),
+            type_: Valid(type_),
+            min: None,
+            max: None,
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+}
+
+fn f32_bytes(values: impl IntoIterator<Item = f32>) -> Vec<u8> {
+    values.into_iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -407,6 +1650,20 @@ mod test {
                 "test_data/Cube_MetallicRoughness.png"
             ))
         );
+        assert_eq!(
+            model.materials[0]
+                .albedo_texture
+                .as_ref()
+                .map(|t| t.color_space),
+            Some(crate::ColorSpace::Srgb)
+        );
+        assert_eq!(
+            model.materials[0]
+                .metallic_roughness_texture
+                .as_ref()
+                .map(|t| t.color_space),
+            Some(crate::ColorSpace::Linear)
+        );
     }
 
     #[test]
@@ -499,6 +1756,30 @@ mod test {
         let model: Model = crate::io::load_and_deserialize("test_data/AnimatedMorph.gltf").unwrap();
         assert_eq!(model.geometries.len(), 1);
         assert_eq!(model.materials.len(), 0);
+        let primitive = &model.geometries[0];
+        let crate::Geometry::Triangles(mesh) = &primitive.geometry else {
+            panic!("Wrong geometry")
+        };
+        mesh.validate().unwrap();
+        let morph_targets = mesh.morph_targets.as_ref().unwrap();
+        assert_eq!(morph_targets.len(), 2);
+        assert_eq!(morph_targets[0].weight, 1.0);
+        assert_eq!(morph_targets[1].weight, 0.5);
+        assert_eq!(
+            morph_targets[1].positions.as_ref().unwrap()[2],
+            vec3(1.0, 1.0, 0.0)
+        );
+
+        let weights = morph_targets.iter().map(|t| t.weight).collect::<Vec<_>>();
+        let morphed = mesh.apply_morph(&weights);
+        assert!(morphed.morph_targets.is_none());
+        assert_eq!(morphed.positions.to_f32()[2], vec3(0.0, 2.0, 0.0));
+        assert_eq!(mesh.positions.to_f32()[2], vec3(0.5, 0.5, 0.0));
+
+        let weights = primitive.animations[0].weights(2.0).unwrap();
+        assert_eq!(weights, vec![1.0, 1.0]);
+        let morphed = mesh.apply_morph(&weights);
+        assert_eq!(morphed.positions.to_f32()[2], vec3(0.5, 2.5, 0.0));
     }
 
     #[test]
@@ -506,5 +1787,704 @@ mod test {
         let model: Model = crate::io::load_and_deserialize("test_data/AnimatedSkin.gltf").unwrap();
         assert_eq!(model.geometries.len(), 1);
         assert_eq!(model.materials.len(), 0);
+        assert_eq!(model.geometries[0].skin_index, Some(0));
+        let crate::Geometry::Triangles(mesh) = &model.geometries[0].geometry else {
+            panic!("Wrong geometry")
+        };
+        mesh.validate().unwrap();
+        assert_eq!(
+            mesh.joint_indices.as_ref().unwrap()[4],
+            Vector4::new(0, 1, 0, 0)
+        );
+        assert_eq!(
+            mesh.joint_weights.as_ref().unwrap()[4],
+            vec4(0.5, 0.5, 0.0, 0.0)
+        );
+
+        assert_eq!(model.skins.len(), 1);
+        let skin = &model.skins[0];
+        assert_eq!(skin.joints.len(), 2);
+        assert_eq!(skin.joints[0].parent, None);
+        assert_eq!(skin.joints[1].parent, Some(0));
+        assert_eq!(skin.joint_matrices(), vec![Mat4::identity(); 2]);
+
+        assert_eq!(skin.animations.len(), 1);
+        let animation = &skin.animations[0];
+        assert_eq!(animation.duration, 5.5);
+        let bend = |matrices: Vec<Mat4>| {
+            // The tip of the mesh rotates around the second joint at (0, 1, 0).
+            (matrices[1] * vec4(0.0, 2.0, 0.0, 1.0)).truncate()
+        };
+        assert!(
+            (bend(animation.sample(skin, 0.0, Wrapping::Repeat)) - vec3(0.0, 2.0, 0.0)).magnitude()
+                < 0.001
+        );
+        assert!(
+            (bend(animation.sample(skin, 1.0, Wrapping::Repeat)) - vec3(-1.0, 1.0, 0.0))
+                .magnitude()
+                < 0.001
+        );
+        assert!(
+            (bend(animation.sample(skin, 6.5, Wrapping::Repeat)) - vec3(-1.0, 1.0, 0.0))
+                .magnitude()
+                < 0.001
+        );
+        assert!(
+            (bend(animation.sample(skin, 10.0, Wrapping::MirroredRepeat)) - vec3(-1.0, 1.0, 0.0))
+                .magnitude()
+                < 0.001
+        );
+        assert!(
+            (bend(animation.sample(skin, 6.5, Wrapping::ClampToEdge)) - vec3(0.0, 2.0, 0.0))
+                .magnitude()
+                < 0.001
+        );
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_cameras() {
+        let model: Model = crate::io::load_and_deserialize("test_data/Cameras.gltf").unwrap();
+        assert_eq!(model.cameras.len(), 3);
+
+        let camera = &model.cameras[0];
+        assert!((camera.position() - vec3(0.0, 0.0, 5.0)).magnitude() < 0.0001);
+        assert!((camera.view_direction() - vec3(0.0, 0.0, -1.0)).magnitude() < 0.0001);
+        assert_eq!(camera.viewport(), Viewport::new_at_origo(1, 1));
+        assert_eq!(camera.z_near(), 0.1);
+        assert!((camera.z_far() - 10000.0).abs() < 0.01);
+        assert!(
+            matches!(camera.projection_type(), ProjectionType::Perspective { field_of_view_y } if field_of_view_y.0 == 0.8)
+        );
+
+        // The same camera placed by a child node of a rotated parent node.
+        let camera = &model.cameras[1];
+        assert!((camera.position() - vec3(10.0, 2.0, 0.0)).magnitude() < 0.0001);
+        assert!((camera.view_direction() - vec3(-1.0, 0.0, 0.0)).magnitude() < 0.0001);
+        assert!((camera.up() - vec3(0.0, 1.0, 0.0)).magnitude() < 0.0001);
+
+        let camera = &model.cameras[2];
+        assert!((camera.position() - vec3(0.0, 3.0, 0.0)).magnitude() < 0.0001);
+        assert!((camera.view_direction() - vec3(0.0, -1.0, 0.0)).magnitude() < 0.0001);
+        assert!((camera.up() - vec3(0.0, 0.0, -1.0)).magnitude() < 0.0001);
+        assert_eq!(camera.viewport(), Viewport::new_at_origo(2000, 1000));
+        assert_eq!(camera.z_near(), 0.5);
+        assert_eq!(camera.z_far(), 50.0);
+        assert!(
+            matches!(camera.projection_type(), ProjectionType::Orthographic { height } if *height == 4.0)
+        );
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_scenes() {
+        let scenes: crate::Scenes =
+            crate::io::load_and_deserialize("test_data/Scenes.gltf").unwrap();
+        assert_eq!(scenes.scenes.len(), 2);
+        assert_eq!(scenes.default_scene, 1);
+        assert_eq!(scenes.scenes[0].name, "First");
+        assert_eq!(scenes.scenes[0].children.len(), 1);
+        assert_eq!(scenes.scenes[0].children[0].name, "Shared");
+
+        let scene = &scenes.scenes[1];
+        assert_eq!(scene.name, "Second");
+        assert_eq!(
+            scene
+                .children
+                .iter()
+                .map(|n| n.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Root", "Shared"]
+        );
+        let root = &scene.children[0];
+        assert_eq!(
+            root.transformation,
+            Mat4::from_translation(vec3(0.0, 1.0, 0.0))
+        );
+        assert_eq!(root.children.len(), 2);
+        let camera_node = &root.children[0];
+        assert_eq!(camera_node.camera_index, Some(0));
+        assert_eq!(scene.cameras[0].position(), &vec3(0.0, 1.0, 2.0));
+        let light_node = &root.children[1];
+        assert_eq!(light_node.light_index, Some(0));
+        assert_eq!(light_node.children[0].name, "Leaf");
+        assert_eq!(
+            light_node.children[0].transformation,
+            Mat4::from_translation(vec3(0.0, 0.0, 4.0))
+        );
+        let LightType::Point { position, .. } = scene.lights[0].light_type else {
+            panic!("Wrong light type: {:?}", scene.lights[0].light_type)
+        };
+        assert_eq!(position, vec3(0.0, 1.0, 3.0));
+
+        let scene: Scene = crate::io::load_and_deserialize("test_data/Scenes.gltf").unwrap();
+        assert_eq!(scene.name, "Second");
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_lights() {
+        let model: Model = crate::io::load_and_deserialize("test_data/Lights.gltf").unwrap();
+        assert_eq!(model.lights.len(), 3);
+
+        let light = &model.lights[0];
+        assert_eq!(light.name, "Sun");
+        assert_eq!(light.color, Color::new_opaque(255, 255, 127));
+        assert_eq!(light.intensity, 3.0);
+        let LightType::Directional { direction } = light.light_type else {
+            panic!("Wrong light type: {:?}", light.light_type)
+        };
+        assert!((direction - vec3(0.0, -1.0, 0.0)).magnitude() < 0.0001);
+
+        // The parent node scale moves the light but does not rescale the range or intensity.
+        let light = &model.lights[1];
+        assert_eq!(light.name, "Light 1");
+        assert_eq!(light.color, Color::WHITE);
+        assert_eq!(light.intensity, 100.0);
+        let LightType::Point { position, range } = light.light_type else {
+            panic!("Wrong light type: {:?}", light.light_type)
+        };
+        assert!((position - vec3(3.0, 2.0, 3.0)).magnitude() < 0.0001);
+        assert_eq!(range, Some(20.0));
+
+        let light = &model.lights[2];
+        assert_eq!(light.intensity, 500.0);
+        let LightType::Spot {
+            position,
+            direction,
+            range,
+            inner_cone_angle,
+            outer_cone_angle,
+        } = light.light_type
+        else {
+            panic!("Wrong light type: {:?}", light.light_type)
+        };
+        assert!((position - vec3(1.0, 2.0, 3.0)).magnitude() < 0.0001);
+        assert!((direction - vec3(-1.0, 0.0, 0.0)).magnitude() < 0.0001);
+        assert_eq!(range, None);
+        assert_eq!(inner_cone_angle, radians(0.2));
+        assert_eq!(outer_cone_angle, radians(0.5));
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_uv_sets() {
+        let model: Model = crate::io::load_and_deserialize("test_data/uv_sets.glb").unwrap();
+        let crate::Geometry::Triangles(mesh) = &model.geometries[0].geometry else {
+            panic!("Wrong geometry")
+        };
+        mesh.validate().unwrap();
+        assert_eq!(mesh.additional_uvs.len(), 1);
+        assert_eq!(mesh.uv_set(0).unwrap()[1], vec2(1.0, 0.0));
+        assert_eq!(mesh.uv_set(1).unwrap()[1], vec2(0.75, 0.5));
+        assert!(mesh.uv_set(2).is_none());
+
+        let material = &model.materials[0];
+        assert!(material.albedo_texture.is_some());
+        assert_eq!(material.albedo_uv_set, 0);
+        assert!(material.occlusion_texture.is_some());
+        assert_eq!(material.occlusion_uv_set, 1);
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_vertex_colors() {
+        let model: Model = crate::io::load_and_deserialize("test_data/vertex_colors.glb").unwrap();
+        let colors = model
+            .geometries
+            .iter()
+            .map(|primitive| {
+                let crate::Geometry::Triangles(mesh) = &primitive.geometry else {
+                    panic!("Wrong geometry")
+                };
+                mesh.validate().unwrap();
+                mesh.colors.clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            colors[0],
+            Some(vec![
+                Color::new(255, 0, 0, 255),
+                Color::new(0, 128, 0, 255),
+                Color::new(255, 255, 0, 255)
+            ])
+        );
+        assert_eq!(
+            colors[1],
+            Some(vec![
+                Color::new(255, 0, 0, 128),
+                Color::new(0, 128, 0, 255),
+                Color::new(1, 2, 3, 4)
+            ])
+        );
+        assert_eq!(colors[1], colors[2]);
+        assert_eq!(colors[3], None);
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_alpha_modes() {
+        let model: Model = crate::io::load_and_deserialize("test_data/alpha_modes.gltf").unwrap();
+        assert_eq!(
+            model
+                .materials
+                .iter()
+                .map(|m| (m.alpha_mode, m.is_double_sided))
+                .collect::<Vec<_>>(),
+            vec![
+                (AlphaMode::Opaque, false),
+                (AlphaMode::Mask(0.5), true),
+                (AlphaMode::Mask(0.25), false),
+                (AlphaMode::Blend, true),
+            ]
+        );
+        #[allow(deprecated)]
+        let cutouts = model
+            .materials
+            .iter()
+            .map(|m| m.alpha_cutout)
+            .collect::<Vec<_>>();
+        assert_eq!(cutouts, vec![None, Some(0.5), Some(0.25), None]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    pub fn serialize_gltf_with_deprecated_alpha_cutout() {
+        use crate::io::Serialize;
+        let model = Model {
+            name: "model".to_owned(),
+            geometries: vec![crate::Primitive {
+                name: "square".to_owned(),
+                transformation: Mat4::identity(),
+                animations: Vec::new(),
+                geometry: crate::Geometry::Triangles(crate::TriMesh::square()),
+                material_index: Some(0),
+                skin_index: None,
+            }],
+            materials: vec![PbrMaterial {
+                alpha_cutout: Some(0.75),
+                ..Default::default()
+            }],
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+        };
+        let reloaded: Model = model
+            .serialize("square.glb")
+            .unwrap()
+            .deserialize("square.glb")
+            .unwrap();
+        assert_eq!(reloaded.materials[0].alpha_mode, AlphaMode::Mask(0.75));
+        assert_eq!(reloaded.materials[0].alpha_cutout, Some(0.75));
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_emissive_strength() {
+        let model: Model =
+            crate::io::load_and_deserialize("test_data/emissive_strength.gltf").unwrap();
+        assert_eq!(model.materials[0].emissive, Color::new_opaque(255, 127, 0));
+        assert_eq!(model.materials[0].emissive_strength, 5.0);
+        assert_eq!(model.materials[1].emissive, Color::new_opaque(0, 0, 255));
+        assert_eq!(model.materials[1].emissive_strength, 1.0);
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_unlit_material() {
+        let model: Model = crate::io::load_and_deserialize("test_data/unlit.gltf").unwrap();
+        assert!(model.materials[0].is_unlit);
+        assert!(!model.materials[1].is_unlit);
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_transmission_and_volume() {
+        let model: Model = crate::io::load_and_deserialize("test_data/volume.gltf").unwrap();
+        let glass = &model.materials[0];
+        assert_eq!(glass.transmission, 0.8);
+        assert!(glass.transmission_texture.is_some());
+        assert_eq!(glass.thickness, 2.0);
+        assert!(glass.thickness_texture.is_some());
+        assert_eq!(glass.thickness_uv_set, 1);
+        assert_eq!(glass.attenuation_color, Color::RED);
+        assert_eq!(glass.attenuation_distance, 0.5);
+
+        let opaque = &model.materials[1];
+        assert_eq!(opaque.transmission, 0.0);
+        assert!(opaque.transmission_texture.is_none());
+        assert_eq!(opaque.thickness, 0.0);
+        assert!(opaque.thickness_texture.is_none());
+        assert_eq!(opaque.attenuation_color, Color::WHITE);
+        assert_eq!(opaque.attenuation_distance, f32::INFINITY);
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_clearcoat() {
+        let model: Model = crate::io::load_and_deserialize("test_data/clearcoat.gltf").unwrap();
+        let car_paint = &model.materials[0];
+        assert_eq!(car_paint.clearcoat, 1.0);
+        assert_eq!(
+            car_paint
+                .clearcoat_texture
+                .as_ref()
+                .map(|t| std::path::PathBuf::from(&t.name)),
+            Some(std::path::PathBuf::from("test_data/Cube_BaseColor.png"))
+        );
+        assert_eq!(car_paint.clearcoat_roughness, 0.25);
+        assert_eq!(
+            car_paint
+                .clearcoat_roughness_texture
+                .as_ref()
+                .map(|t| std::path::PathBuf::from(&t.name)),
+            Some(std::path::PathBuf::from(
+                "test_data/Cube_MetallicRoughness.png"
+            ))
+        );
+        assert_eq!(car_paint.clearcoat_roughness_uv_set, 1);
+        assert_eq!(car_paint.clearcoat_normal_scale, 0.5);
+        assert!(car_paint.clearcoat_normal_texture.is_some());
+        assert_eq!(
+            car_paint.clearcoat_normal_texture_transform.scale,
+            vec2(4.0, 4.0)
+        );
+
+        let plastic = &model.materials[1];
+        assert_eq!(plastic.clearcoat, 0.0);
+        assert!(plastic.clearcoat_texture.is_none());
+        assert!(plastic.clearcoat_roughness_texture.is_none());
+        assert!(plastic.clearcoat_normal_texture.is_none());
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_texture_transform() {
+        let model: Model =
+            crate::io::load_and_deserialize("test_data/texture_transform.gltf").unwrap();
+        let material = &model.materials[0];
+
+        let transform = material.albedo_texture_transform;
+        assert_eq!(transform.offset, vec2(0.5, 0.25));
+        assert_eq!(transform.rotation, 1.5707964);
+        assert_eq!(transform.scale, vec2(2.0, 4.0));
+        let uv = transform.matrix() * vec3(1.0, 1.0, 1.0);
+        assert!((uv - vec3(4.5, -1.75, 1.0)).magnitude() < 0.0001);
+        assert_eq!(material.albedo_uv_set, 0);
+
+        assert_eq!(
+            material.metallic_roughness_texture_transform,
+            TextureTransform::default()
+        );
+        assert_eq!(
+            material.metallic_roughness_texture_transform.matrix(),
+            Mat3::identity()
+        );
+
+        assert_eq!(material.normal_texture_transform.scale, vec2(8.0, 8.0));
+        assert_eq!(material.normal_uv_set, 1);
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_quantization() {
+        let model: Model = crate::io::load_and_deserialize("test_data/quantized.glb").unwrap();
+        assert_eq!(model.geometries.len(), 1);
+        let primitive = &model.geometries[0];
+        let crate::Geometry::Triangles(mesh) = &primitive.geometry else {
+            panic!("Wrong geometry")
+        };
+        mesh.validate().unwrap();
+        let positions = mesh.positions.to_f32();
+        let normals = mesh.normals.as_ref().unwrap();
+        let tangents = mesh.tangents.as_ref().unwrap();
+        let uvs = mesh.uvs.as_ref().unwrap();
+        let mut i = 0;
+        for x in [-1.0f32, 1.0] {
+            for y in [-1.0f32, 1.0] {
+                for z in [-1.0f32, 1.0] {
+                    let p = vec3(x, y, z);
+                    // The node scale maps the quantized positions to a cube of size 1.
+                    let position = primitive.transformation * positions[i].extend(1.0);
+                    assert!((position.truncate() - 0.5 * p).magnitude() < 0.0001);
+                    assert!((normals[i].magnitude() - 1.0).abs() < 0.0001);
+                    assert!((normals[i] - p.normalize()).magnitude() < 0.01);
+                    assert!((tangents[i].truncate().magnitude() - 1.0).abs() < 0.0001);
+                    assert!(
+                        (tangents[i].truncate() - vec3(-y, x, 0.0).normalize()).magnitude() < 0.01
+                    );
+                    assert_eq!(tangents[i].w, 1.0);
+                    assert_eq!(uvs[i], vec2(x.max(0.0), y.max(0.0)));
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "draco")]
+    #[test]
+    pub fn deserialize_gltf_with_draco() {
+        let model: Model = crate::io::load_and_deserialize("test_data/draco.glb").unwrap();
+        assert_eq!(model.geometries.len(), 2);
+
+        // The first primitive is a Draco compressed cube.
+        let crate::Geometry::Triangles(mesh) = &model.geometries[0].geometry else {
+            panic!("Wrong geometry")
+        };
+        mesh.validate().unwrap();
+        let Indices::U16(indices) = &mesh.indices else {
+            panic!("Wrong indices: {:?}", mesh.indices)
+        };
+        assert_eq!(indices.len(), 36);
+        let positions = mesh.positions.to_f32();
+        let normals = mesh.normals.as_ref().unwrap();
+        let uvs = mesh.uvs.as_ref().unwrap();
+        assert_eq!(positions.len(), 24);
+        for t in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[t[i] as usize]);
+            let normal = (b - a).cross(c - a).normalize();
+            for &i in t {
+                let i = i as usize;
+                assert!((normals[i] - normal).magnitude() < 0.01);
+                assert!((positions[i].dot(normal) - 0.5).abs() < 0.01);
+                assert!(uvs[i].x.min(1.0 - uvs[i].x).abs() < 0.01);
+                assert!(uvs[i].y.min(1.0 - uvs[i].y).abs() < 0.01);
+            }
+        }
+
+        // The second primitive is not compressed.
+        let crate::Geometry::Triangles(mesh) = &model.geometries[1].geometry else {
+            panic!("Wrong geometry")
+        };
+        assert_eq!(mesh.indices.to_u32(), Some(vec![0, 1, 2]));
+        assert_eq!(mesh.positions.len(), 3);
+    }
+
+    #[cfg(not(feature = "draco"))]
+    #[test]
+    pub fn deserialize_gltf_with_draco_without_feature() {
+        let result: crate::Result<Model> = crate::io::load_and_deserialize("test_data/draco.glb");
+        assert!(matches!(result, Err(crate::Error::FeatureMissing(f)) if f == "draco"));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn serialize_gltf() {
+        use crate::io::Serialize;
+        let model: Model = crate::io::load_and_deserialize("test_data/Cube.gltf").unwrap();
+        for path in ["exported/Cube.gltf", "exported/Cube.glb"] {
+            let mut raw_assets = model.serialize(path).unwrap();
+            if path.ends_with(".gltf") {
+                assert!(raw_assets.get("exported/Cube.bin").is_ok());
+                assert!(raw_assets.get("exported/Cube_BaseColor.png").is_ok());
+            } else {
+                assert!(raw_assets.get("exported/Cube.bin").is_err());
+            }
+
+            let reloaded: Model = raw_assets.deserialize(path).unwrap();
+            assert_eq!(reloaded.geometries.len(), model.geometries.len());
+            for (primitive, reloaded_primitive) in
+                model.geometries.iter().zip(reloaded.geometries.iter())
+            {
+                assert_eq!(primitive.transformation, reloaded_primitive.transformation);
+                assert_eq!(primitive.material_index, reloaded_primitive.material_index);
+                let (crate::Geometry::Triangles(mesh), crate::Geometry::Triangles(reloaded_mesh)) =
+                    (&primitive.geometry, &reloaded_primitive.geometry)
+                else {
+                    panic!("expected triangle meshes");
+                };
+                assert_eq!(mesh.positions.to_f32(), reloaded_mesh.positions.to_f32());
+                assert_eq!(mesh.normals, reloaded_mesh.normals);
+                assert_eq!(mesh.tangents, reloaded_mesh.tangents);
+                assert_eq!(mesh.uvs, reloaded_mesh.uvs);
+                assert_eq!(mesh.colors, reloaded_mesh.colors);
+                assert_eq!(mesh.indices.to_u32(), reloaded_mesh.indices.to_u32());
+            }
+            assert_eq!(reloaded.materials.len(), model.materials.len());
+            for (material, reloaded_material) in
+                model.materials.iter().zip(reloaded.materials.iter())
+            {
+                assert_eq!(material.name, reloaded_material.name);
+                assert_eq!(material.albedo, reloaded_material.albedo);
+                assert_eq!(material.metallic, reloaded_material.metallic);
+                assert_eq!(material.roughness, reloaded_material.roughness);
+                assert_eq!(material.emissive, reloaded_material.emissive);
+                assert_eq!(material.alpha_mode, reloaded_material.alpha_mode);
+                let albedo = material.albedo_texture.as_ref().unwrap();
+                let reloaded_albedo = reloaded_material.albedo_texture.as_ref().unwrap();
+                assert_eq!(albedo.width, reloaded_albedo.width);
+                assert_eq!(albedo.height, reloaded_albedo.height);
+                assert!(reloaded_material.metallic_roughness_texture.is_some());
+            }
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn serialize_gltf_with_colliding_texture_names() {
+        use crate::io::Serialize;
+        let texture = |name: &str, texel: [u8; 4]| Texture2D {
+            name: name.to_owned(),
+            data: crate::TextureData::RgbaU8(vec![texel]),
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        let primitive = |material_index| crate::Primitive {
+            name: "square".to_owned(),
+            transformation: Mat4::identity(),
+            animations: Vec::new(),
+            geometry: crate::Geometry::Triangles(crate::TriMesh::square()),
+            material_index: Some(material_index),
+            skin_index: None,
+        };
+        let model = Model {
+            name: "model".to_owned(),
+            geometries: vec![primitive(0), primitive(1)],
+            materials: vec![
+                PbrMaterial {
+                    albedo_texture: Some(texture("a/albedo.png", [255, 0, 0, 255])),
+                    ..Default::default()
+                },
+                PbrMaterial {
+                    albedo_texture: Some(texture("b/albedo.png", [0, 255, 0, 255])),
+                    emissive_texture: Some(texture("a/albedo.png", [255, 0, 0, 255])),
+                    ..Default::default()
+                },
+            ],
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+        };
+        let mut raw_assets = model.serialize("exported/model.gltf").unwrap();
+        assert!(raw_assets.get("exported/albedo.png").is_ok());
+        assert!(raw_assets.get("exported/albedo_1.png").is_ok());
+        assert!(raw_assets.get("exported/albedo_2.png").is_err());
+
+        let reloaded: Model = raw_assets.deserialize("model.gltf").unwrap();
+        let albedo =
+            |material: &PbrMaterial| material.albedo_texture.as_ref().unwrap().data.clone();
+        assert_eq!(
+            albedo(&reloaded.materials[0]),
+            crate::TextureData::RgbaU8(vec![[255, 0, 0, 255]])
+        );
+        assert_eq!(
+            albedo(&reloaded.materials[1]),
+            crate::TextureData::RgbaU8(vec![[0, 255, 0, 255]])
+        );
+        assert_eq!(
+            reloaded.materials[1]
+                .emissive_texture
+                .as_ref()
+                .unwrap()
+                .name,
+            reloaded.materials[0].albedo_texture.as_ref().unwrap().name
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn serialize_gltf_with_different_textures_with_the_same_name() {
+        use crate::io::Serialize;
+        let albedo = Texture2D {
+            data: crate::TextureData::RgbaU8(vec![[255, 0, 0, 255]]),
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        let normal = albedo.normal_map_from_height(1.0).unwrap();
+        assert_eq!(albedo.name, normal.name);
+        let model = Model {
+            name: "model".to_owned(),
+            geometries: vec![crate::Primitive {
+                name: "square".to_owned(),
+                transformation: Mat4::identity(),
+                animations: Vec::new(),
+                geometry: crate::Geometry::Triangles(crate::TriMesh::square()),
+                material_index: Some(0),
+                skin_index: None,
+            }],
+            materials: vec![PbrMaterial {
+                name: "material".to_owned(),
+                albedo_texture: Some(albedo.clone()),
+                normal_texture: Some(normal.clone()),
+                ..Default::default()
+            }],
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+        };
+        for path in ["model.gltf", "model.glb"] {
+            let mut raw_assets = model.serialize(path).unwrap();
+            let reloaded: Model = raw_assets.deserialize(path).unwrap();
+            let material = &reloaded.materials[0];
+            assert_eq!(material.albedo_texture.as_ref().unwrap().data, albedo.data);
+            assert_eq!(material.normal_texture.as_ref().unwrap().data, normal.data);
+        }
+    }
+
+    #[test]
+    pub fn resolve_relative_uris() {
+        let gltf = std::str::from_utf8(include_bytes!("../../test_data/Cube.gltf"))
+            .unwrap()
+            .replace("\"Cube.bin\"", "\"./buffers/cube%20data.bin\"")
+            .replace("\"Cube_BaseColor.png\"", "\"../textures/albedo.png\"")
+            .replace(
+                "\"Cube_MetallicRoughness.png\"",
+                "\"buffers/../../textures/metallic%20roughness.png\"",
+            );
+        let mut raw_assets = crate::io::RawAssets::new();
+        raw_assets
+            .insert("assets/models/scene.gltf", gltf.into_bytes())
+            .insert(
+                "assets/models/buffers/cube data.bin",
+                include_bytes!("../../test_data/Cube.bin").to_vec(),
+            )
+            .insert(
+                "assets/textures/albedo.png",
+                include_bytes!("../../test_data/Cube_BaseColor.png").to_vec(),
+            )
+            .insert(
+                "other/textures/albedo.png",
+                include_bytes!("../../test_data/test.png").to_vec(),
+            )
+            .insert(
+                "assets/textures/metallic roughness.png",
+                include_bytes!("../../test_data/Cube_MetallicRoughness.png").to_vec(),
+            );
+
+        let dependencies = super::dependencies(
+            Path::new("assets/models/scene.gltf"),
+            raw_assets.get("assets/models/scene.gltf").unwrap(),
+        );
+        assert!(dependencies.contains(Path::new("assets/models/buffers/cube data.bin")));
+        assert!(dependencies.contains(Path::new("assets/textures/albedo.png")));
+        assert!(dependencies.contains(Path::new("assets/textures/metallic roughness.png")));
+
+        let model: Model = raw_assets.deserialize("scene.gltf").unwrap();
+        assert_eq!(model.geometries.len(), 1);
+        assert_eq!(
+            model.materials[0].albedo_texture.as_ref().unwrap().name,
+            "assets/textures/albedo.png"
+        );
+        assert_eq!(
+            model.materials[0]
+                .metallic_roughness_texture
+                .as_ref()
+                .unwrap()
+                .name,
+            "assets/textures/metallic roughness.png"
+        );
+    }
+
+    #[test]
+    pub fn deserialize_gltf_with_warnings() {
+        let mut raw_assets = crate::io::load(&["test_data/Cube.gltf"]).unwrap();
+        let gltf = std::str::from_utf8(&raw_assets.remove("Cube.gltf").unwrap())
+            .unwrap()
+            .replace(
+                "\"asset\"",
+                "\"extensionsUsed\": [\"EXT_unknown\"], \"asset\"",
+            );
+        raw_assets.insert("test_data/Cube.gltf", gltf.into_bytes());
+
+        let (model, warnings): (Model, _) =
+            raw_assets.deserialize_with_warnings("Cube.gltf").unwrap();
+        assert_eq!(model.geometries.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].path, Path::new("test_data/Cube.gltf"));
+        assert_eq!(
+            warnings[0].message,
+            "unsupported extension EXT_unknown is ignored"
+        );
+        assert!(raw_assets.warnings().is_empty());
     }
 }
@@ -10,68 +10,348 @@ pub fn deserialize_img(path: impl AsRef<Path>, bytes: &[u8]) -> Result<Texture2D
         .filter(|s| !s.starts_with("data:"))
         .unwrap_or("default")
         .to_owned();
-    let mut reader = Reader::new(Cursor::new(bytes))
-        .with_guessed_format()
-        .expect("Cursor io never fails");
+    #[cfg(feature = "hdr")]
+    if bytes.starts_with(b"#?RADIANCE") || bytes.starts_with(b"#?RGBE") {
+        return deserialize_hdr(name, bytes);
+    }
+    let mut reader = Reader::new(Cursor::new(bytes));
+    // TGA files do not have a magic number, so the extension or the signature in the optional footer is used instead.
+    if ImageFormat::from_path(&path).ok() == Some(ImageFormat::Tga)
+        || bytes.ends_with(b"TRUEVISION-XFILE.\0")
+    {
+        reader.set_format(ImageFormat::Tga);
+    } else {
+        reader = reader.with_guessed_format().expect("Cursor io never fails");
+    }
 
     if reader.format().is_none() {
         reader.set_format(ImageFormat::from_path(path)?);
     }
-    #[cfg(feature = "hdr")]
-    if reader.format() == Some(image::ImageFormat::Hdr) {
-        use image::codecs::hdr::*;
-        let decoder = HdrDecoder::new(&*bytes)?;
-        let metadata = decoder.metadata();
-        let img = decoder.read_image_native()?;
-        return Ok(Texture2D {
-            name,
-            data: TextureData::RgbF32(
-                img.iter()
-                    .map(|rgbe| {
-                        let Rgb(values) = rgbe.to_hdr();
-                        [values[0], values[1], values[2]]
-                    })
-                    .collect::<Vec<_>>(),
-            ),
-            width: metadata.width,
-            height: metadata.height,
-            ..Default::default()
-        });
+    #[cfg(feature = "exr")]
+    if reader.format() == Some(image::ImageFormat::OpenExr) {
+        return deserialize_exr(name, bytes);
     }
+    #[cfg(feature = "tiff")]
+    if reader.format() == Some(image::ImageFormat::Tiff) {
+        if let Some(tex) = deserialize_tiff(&name, bytes)? {
+            return Ok(tex);
+        }
+    }
+    // Animated WebP files are decoded into the first frame only.
+    // Images with 16 bits per channel, for example 16-bit PNG heightmaps, are decoded without reducing the precision.
     let img: DynamicImage = reader.decode()?;
-    let width = img.width();
-    let height = img.height();
-    let data = match img {
-        DynamicImage::ImageLuma8(_) => TextureData::RU8(img.into_bytes()),
-        DynamicImage::ImageLumaA8(img) => TextureData::RgU8(
-            img.into_raw()
-                .chunks(2)
-                .map(|c| [c[0], c[1]])
-                .collect::<Vec<_>>(),
-        ),
-        DynamicImage::ImageRgb8(img) => TextureData::RgbU8(
-            img.into_raw()
-                .chunks(3)
-                .map(|c| [c[0], c[1], c[2]])
-                .collect::<Vec<_>>(),
-        ),
-        DynamicImage::ImageRgba8(img) => TextureData::RgbaU8(
-            img.into_raw()
-                .chunks(4)
-                .map(|c| [c[0], c[1], c[2], c[3]])
+    Ok(Texture2D { name, ..img.into() })
+}
+
+///
+/// Converts an image from the image crate into a texture with the [TextureData] variant which has the same number of channels and precision.
+/// Floating point data is assumed to be linear while integer data is assumed to be sRGB encoded, see [Texture2D::color_space].
+///
+impl From<DynamicImage> for Texture2D {
+    fn from(img: DynamicImage) -> Self {
+        let width = img.width();
+        let height = img.height();
+        let data = match img {
+            DynamicImage::ImageLuma8(_) => TextureData::RU8(img.into_bytes()),
+            DynamicImage::ImageLumaA8(img) => TextureData::RgU8(texels(&img.into_raw())),
+            DynamicImage::ImageRgb8(img) => TextureData::RgbU8(texels(&img.into_raw())),
+            DynamicImage::ImageRgba8(img) => TextureData::RgbaU8(texels(&img.into_raw())),
+            DynamicImage::ImageLuma16(img) => TextureData::RU16(img.into_raw()),
+            DynamicImage::ImageLumaA16(img) => TextureData::RgU16(texels(&img.into_raw())),
+            DynamicImage::ImageRgb16(img) => TextureData::RgbU16(texels(&img.into_raw())),
+            DynamicImage::ImageRgba16(img) => TextureData::RgbaU16(texels(&img.into_raw())),
+            DynamicImage::ImageRgb32F(img) => TextureData::RgbF32(texels(&img.into_raw())),
+            DynamicImage::ImageRgba32F(img) => TextureData::RgbaF32(texels(&img.into_raw())),
+            img => TextureData::RgbaF32(texels(&img.to_rgba32f().into_raw())),
+        };
+        Texture2D {
+            color_space: color_space(&data),
+            data,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Converts a texture into an image from the image crate with the same number of channels and precision.
+/// Block compressed data is decompressed first, and since the image crate only supports floating point images with three or four channels,
+/// 16-bit floating point data is converted to 32-bit and one and two channel floating point data is converted to RGB and RGBA, see [Texture2D::convert].
+///
+impl TryFrom<Texture2D> for DynamicImage {
+    type Error = Error;
+    fn try_from(tex: Texture2D) -> Result<Self> {
+        dynamic_image(&tex)
+    }
+}
+
+///
+/// Floating point data is assumed to be linear while integer data is assumed to be sRGB encoded.
+///
+fn color_space(data: &TextureData) -> ColorSpace {
+    match data {
+        TextureData::RF32(_)
+        | TextureData::RgF32(_)
+        | TextureData::RgbF32(_)
+        | TextureData::RgbaF32(_) => ColorSpace::Linear,
+        _ => ColorSpace::Srgb,
+    }
+}
+
+fn texels<T: Copy, const N: usize>(values: &[T]) -> Vec<[T; N]> {
+    values
+        .chunks(N)
+        .map(|c| std::array::from_fn(|i| c[i]))
+        .collect()
+}
+
+///
+/// Deserialize a .tiff/.tif image with 8-bit, 16-bit or floating point samples into the [TextureData] variant with the same number of channels and precision,
+/// 64-bit floating point samples are converted to 32-bit.
+/// Returns `None` if the image is palette based, has a different color model or bit depth, in which case it is decoded by the image crate instead.
+///
+#[cfg(feature = "tiff")]
+fn deserialize_tiff(name: &str, bytes: &[u8]) -> Result<Option<Texture2D>> {
+    use tiff::{decoder::*, ColorType};
+    let mut decoder = Decoder::new(Cursor::new(bytes))?;
+    let (width, height) = decoder.dimensions()?;
+    let (channels, bits) = match decoder.colortype()? {
+        ColorType::Gray(bits) => (1, bits),
+        ColorType::GrayA(bits) => (2, bits),
+        ColorType::RGB(bits) => (3, bits),
+        ColorType::RGBA(bits) => (4, bits),
+        _ => return Ok(None),
+    };
+    if ![8, 16, 32, 64].contains(&bits) {
+        return Ok(None);
+    }
+    let result = match decoder.read_image()? {
+        DecodingResult::F64(values) => {
+            DecodingResult::F32(values.into_iter().map(|v| v as f32).collect())
+        }
+        result => result,
+    };
+    let data = match (result, channels) {
+        (DecodingResult::U8(values), 1) => TextureData::RU8(values),
+        (DecodingResult::U8(values), 2) => TextureData::RgU8(texels(&values)),
+        (DecodingResult::U8(values), 3) => TextureData::RgbU8(texels(&values)),
+        (DecodingResult::U8(values), _) => TextureData::RgbaU8(texels(&values)),
+        (DecodingResult::U16(values), 1) => TextureData::RU16(values),
+        (DecodingResult::U16(values), 2) => TextureData::RgU16(texels(&values)),
+        (DecodingResult::U16(values), 3) => TextureData::RgbU16(texels(&values)),
+        (DecodingResult::U16(values), _) => TextureData::RgbaU16(texels(&values)),
+        (DecodingResult::F32(values), 1) => TextureData::RF32(values),
+        (DecodingResult::F32(values), 2) => TextureData::RgF32(texels(&values)),
+        (DecodingResult::F32(values), 3) => TextureData::RgbF32(texels(&values)),
+        (DecodingResult::F32(values), _) => TextureData::RgbaF32(texels(&values)),
+        _ => return Ok(None),
+    };
+    Ok(Some(Texture2D {
+        name: name.to_owned(),
+        color_space: color_space(&data),
+        data,
+        width,
+        height,
+        ..Default::default()
+    }))
+}
+
+#[cfg(feature = "gif")]
+impl Texture2D {
+    ///
+    /// Decodes all frames of the given .gif file into textures with [TextureData::RgbaU8] data and the size of the full image, together with the delay before the next frame.
+    /// Each frame is composited on top of the previous frames according to the disposal method of the previous frame, so each texture contains the image as it should be displayed.
+    /// Texels that are not covered by any frame are transparent.
+    ///
+    /// Deserializing a .gif file into a [Texture2D] (see [crate::io::RawAssets::deserialize]) returns only the first frame.
+    ///
+    pub fn frames_from_bytes(bytes: &[u8]) -> Result<Vec<(Texture2D, std::time::Duration)>> {
+        use image::codecs::gif::GifDecoder;
+        let frames = GifDecoder::new(Cursor::new(bytes))?
+            .into_frames()
+            .collect_frames()?;
+        Ok(frames
+            .into_iter()
+            .map(|frame| {
+                let delay = frame.delay().into();
+                let image = frame.into_buffer();
+                let texture = Texture2D {
+                    width: image.width(),
+                    height: image.height(),
+                    data: TextureData::RgbaU8(texels(&image.into_raw())),
+                    ..Default::default()
+                };
+                (texture, delay)
+            })
+            .collect())
+    }
+}
+
+///
+/// Deserialize a Radiance .hdr/.pic image with either a `#?RADIANCE` or `#?RGBE` header into [TextureData::RgbF32].
+///
+#[cfg(feature = "hdr")]
+fn deserialize_hdr(name: String, bytes: &[u8]) -> Result<Texture2D> {
+    use image::codecs::hdr::*;
+    // The decoder only accepts the `#?RADIANCE` signature, but the rest of the format is identical.
+    let bytes = match bytes.strip_prefix(b"#?RGBE") {
+        Some(rest) => std::borrow::Cow::Owned([SIGNATURE, rest].concat()),
+        None => std::borrow::Cow::Borrowed(bytes),
+    };
+    let decoder = HdrDecoder::new(bytes.as_ref())?;
+    let metadata = decoder.metadata();
+    let img = decoder.read_image_native()?;
+    Ok(Texture2D {
+        name,
+        data: TextureData::RgbF32(
+            img.iter()
+                .map(|rgbe| {
+                    let Rgb(values) = rgbe.to_hdr();
+                    [values[0], values[1], values[2]]
+                })
                 .collect::<Vec<_>>(),
         ),
-        _ => unimplemented!(),
-    };
+        width: metadata.width,
+        height: metadata.height,
+        color_space: ColorSpace::Linear,
+        ..Default::default()
+    })
+}
+
+///
+/// Deserialize an .exr image into [TextureData::RF32], [TextureData::RgbF32] or [TextureData::RgbaF32] depending on the channels present.
+/// Half float and integer channels are converted to `f32` and the values are not clamped.
+///
+#[cfg(feature = "exr")]
+fn deserialize_exr(name: String, bytes: &[u8]) -> Result<Texture2D> {
+    use exr::prelude::{read, ReadChannels, ReadLayers};
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .all_channels()
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(Cursor::new(bytes))?;
+    let layer = image.layer_data;
+    let channels = layer
+        .channel_data
+        .list
+        .iter()
+        .map(|c| {
+            (
+                c.name.to_string(),
+                c.sample_data.values_as_f32().collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    let data = exr_texture_data(channels, layer.size.area())?;
     Ok(Texture2D {
         name,
         data,
-        width,
-        height,
+        width: layer.size.width() as u32,
+        height: layer.size.height() as u32,
+        color_space: ColorSpace::Linear,
         ..Default::default()
     })
 }
 
+///
+/// Combines the named channels of an .exr image into the [TextureData] variant with the matching channels,
+/// where an image with only luminance or other channels results in [TextureData::RF32].
+/// Returns an error if a channel has fewer samples than the image has pixels, for example if it is subsampled.
+///
+#[cfg(feature = "exr")]
+fn exr_texture_data(channels: Vec<(String, Vec<f32>)>, pixel_count: usize) -> Result<TextureData> {
+    for (name, values) in channels.iter() {
+        if values.len() != pixel_count {
+            Err(Error::ExrChannelMismatch(
+                name.clone(),
+                values.len(),
+                pixel_count,
+            ))?;
+        }
+    }
+    let channel = |name: &str| {
+        channels
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, values)| values)
+    };
+    Ok(
+        match (channel("R"), channel("G"), channel("B"), channel("A")) {
+            (Some(r), Some(g), Some(b), Some(a)) => {
+                TextureData::RgbaF32((0..pixel_count).map(|i| [r[i], g[i], b[i], a[i]]).collect())
+            }
+            (Some(r), Some(g), Some(b), None) => {
+                TextureData::RgbF32((0..pixel_count).map(|i| [r[i], g[i], b[i]]).collect())
+            }
+            (Some(r), Some(g), None, _) => {
+                TextureData::RgF32((0..pixel_count).map(|i| [r[i], g[i]]).collect())
+            }
+            (r, ..) => TextureData::RF32(
+                channel("Y")
+                    .or(r)
+                    .or_else(|| channels.first().map(|(_, values)| values))
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+        },
+    )
+}
+///
+/// The trade-off between encoding speed and file size when serializing to .png (see [PngOptions]).
+///
+#[cfg(feature = "png")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PngCompression {
+    /// Fast encoding with minimal compression.
+    #[default]
+    Fast,
+    /// A balance between encoding speed and file size.
+    Balanced,
+    /// The smallest file size at the cost of slow encoding.
+    Best,
+}
+
+///
+/// Options for serializing a [Texture2D] into a .png file (see [SerializeWithOptions](crate::io::SerializeWithOptions)).
+///
+#[cfg(feature = "png")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PngOptions {
+    /// The trade-off between encoding speed and file size.
+    pub compression: PngCompression,
+    /// Whether to store texture data with 8 bits per channel using 16 bits per channel.
+    pub force_16_bit: bool,
+    /// Whether to mark the image as sRGB encoded by adding an `sRGB` chunk.
+    /// Usually only desired if [Texture2D::color_space] is [ColorSpace::Srgb], but it is not added by default to keep the output minimal.
+    pub srgb: bool,
+}
+
+///
+/// Options for serializing a [Texture2D] into a .jpeg file (see [SerializeWithOptions](crate::io::SerializeWithOptions)).
+///
+#[cfg(feature = "jpeg")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JpegOptions {
+    /// The quality in the range `[1..100]` where a higher quality results in a larger file. The default is 100.
+    pub quality: u8,
+    /// The color that texels are blended on top of according to their alpha value, since .jpeg files have no alpha channel.
+    /// The alpha value of the color is ignored. The default is white.
+    pub background: crate::Color,
+}
+
+#[cfg(feature = "jpeg")]
+impl Default for JpegOptions {
+    fn default() -> Self {
+        Self {
+            quality: 100,
+            background: crate::Color::WHITE,
+        }
+    }
+}
+
 pub fn serialize_img(tex: &Texture2D, path: &Path) -> Result<RawAssets> {
     #![allow(unreachable_code)]
     #![allow(unused_variables)]
@@ -80,13 +360,13 @@ pub fn serialize_img(tex: &Texture2D, path: &Path) -> Result<RawAssets> {
             #[cfg(not(feature = "png"))]
             return Err(Error::FeatureMissing("png".to_string()));
             #[cfg(feature = "png")]
-            image::ImageOutputFormat::Png
+            return serialize_png(tex, path, PngOptions::default());
         }
         "jpeg" | "jpg" => {
             #[cfg(not(feature = "jpeg"))]
             return Err(Error::FeatureMissing("jpeg".to_string()));
             #[cfg(feature = "jpeg")]
-            image::ImageOutputFormat::Jpeg(100)
+            return serialize_jpeg(tex, path, JpegOptions::default());
         }
         "bmp" => {
             #[cfg(not(feature = "bmp"))]
@@ -114,7 +394,93 @@ pub fn serialize_img(tex: &Texture2D, path: &Path) -> Result<RawAssets> {
         }
         _ => return Err(Error::FailedSerialize(path.to_str().unwrap().to_string())),
     };
-    let img = match &tex.data {
+    let img = dynamic_image(tex)?;
+    let mut bytes: Vec<u8> = Vec::new();
+    img.write_to(&mut Cursor::new(&mut bytes), format)?;
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, bytes);
+    Ok(raw_assets)
+}
+
+///
+/// Serializes the texture to .png using the given options.
+/// Texture data with 16 bits per channel is always stored with 16 bits per channel.
+///
+#[cfg(feature = "png")]
+pub fn serialize_png(tex: &Texture2D, path: &Path, options: PngOptions) -> Result<RawAssets> {
+    use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+    let mut img = dynamic_image(tex)?;
+    if options.force_16_bit {
+        img = match img {
+            DynamicImage::ImageLuma8(_) => DynamicImage::ImageLuma16(img.to_luma16()),
+            DynamicImage::ImageLumaA8(_) => DynamicImage::ImageLumaA16(img.to_luma_alpha16()),
+            DynamicImage::ImageRgb8(_) => DynamicImage::ImageRgb16(img.to_rgb16()),
+            DynamicImage::ImageRgba8(_) => DynamicImage::ImageRgba16(img.to_rgba16()),
+            img => img,
+        };
+    }
+    let compression = match options.compression {
+        PngCompression::Fast => CompressionType::Fast,
+        PngCompression::Balanced => CompressionType::Default,
+        PngCompression::Best => CompressionType::Best,
+    };
+    let mut bytes: Vec<u8> = Vec::new();
+    img.write_with_encoder(PngEncoder::new_with_quality(
+        &mut bytes,
+        compression,
+        FilterType::Adaptive,
+    ))?;
+    if options.srgb {
+        // The sRGB chunk with perceptual rendering intent must come before the image data,
+        // so it is inserted right after the 8 byte signature and the 25 byte IHDR chunk.
+        const SRGB_CHUNK: [u8; 13] = [
+            0, 0, 0, 1, b's', b'R', b'G', b'B', 0, 0xAE, 0xCE, 0x1C, 0xE9,
+        ];
+        bytes.splice(33..33, SRGB_CHUNK);
+    }
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, bytes);
+    Ok(raw_assets)
+}
+
+///
+/// Serializes the texture to .jpeg using the given options.
+/// Texture data with an alpha channel is blended on top of [JpegOptions::background],
+/// single channel texture data, for example [TextureData::RU8], results in a single channel grayscale .jpeg file.
+///
+#[cfg(feature = "jpeg")]
+pub fn serialize_jpeg(tex: &Texture2D, path: &Path, options: JpegOptions) -> Result<RawAssets> {
+    use image::codecs::jpeg::JpegEncoder;
+    let mut img = dynamic_image(tex)?;
+    if img.color().has_alpha() {
+        let is_grayscale = img.color().channel_count() == 2;
+        let background = [
+            options.background.r,
+            options.background.g,
+            options.background.b,
+        ];
+        let mut rgb = RgbImage::new(img.width(), img.height());
+        for (target, source) in rgb.pixels_mut().zip(img.to_rgba8().pixels()) {
+            let alpha = source[3] as f32 / 255.0;
+            *target = Rgb(std::array::from_fn(|i| {
+                (source[i] as f32 * alpha + background[i] as f32 * (1.0 - alpha)).round() as u8
+            }));
+        }
+        img = DynamicImage::ImageRgb8(rgb);
+        if is_grayscale {
+            img = DynamicImage::ImageLuma8(img.to_luma8());
+        }
+    }
+    let mut bytes: Vec<u8> = Vec::new();
+    img.write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, options.quality))?;
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, bytes);
+    Ok(raw_assets)
+}
+
+fn dynamic_image(tex: &Texture2D) -> Result<DynamicImage> {
+    let data = tex.data.decompress(tex.width, tex.height)?;
+    Ok(match &data {
         TextureData::RU8(data) => DynamicImage::ImageLuma8(
             ImageBuffer::from_raw(tex.width, tex.height, data.clone()).unwrap(),
         ),
@@ -142,17 +508,69 @@ pub fn serialize_img(tex: &Texture2D, path: &Path) -> Result<RawAssets> {
             )
             .unwrap(),
         ),
-        _ => unimplemented!(),
-    };
-    let mut bytes: Vec<u8> = Vec::new();
-    img.write_to(&mut Cursor::new(&mut bytes), format)?;
-    let mut raw_assets = RawAssets::new();
-    raw_assets.insert(path, bytes);
-    Ok(raw_assets)
+        TextureData::RU16(data) => DynamicImage::ImageLuma16(
+            ImageBuffer::from_raw(tex.width, tex.height, data.clone()).unwrap(),
+        ),
+        TextureData::RgU16(data) => DynamicImage::ImageLumaA16(
+            ImageBuffer::from_raw(
+                tex.width,
+                tex.height,
+                data.iter().flat_map(|v| *v).collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        ),
+        TextureData::RgbU16(data) => DynamicImage::ImageRgb16(
+            ImageBuffer::from_raw(
+                tex.width,
+                tex.height,
+                data.iter().flat_map(|v| *v).collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        ),
+        TextureData::RgbaU16(data) => DynamicImage::ImageRgba16(
+            ImageBuffer::from_raw(
+                tex.width,
+                tex.height,
+                data.iter().flat_map(|v| *v).collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        ),
+        TextureData::RgbF32(data) => DynamicImage::ImageRgb32F(
+            ImageBuffer::from_raw(
+                tex.width,
+                tex.height,
+                data.iter().flat_map(|v| *v).collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        ),
+        TextureData::RgbaF32(data) => DynamicImage::ImageRgba32F(
+            ImageBuffer::from_raw(
+                tex.width,
+                tex.height,
+                data.iter().flat_map(|v| *v).collect::<Vec<_>>(),
+            )
+            .unwrap(),
+        ),
+        _ => {
+            let mut tex = Texture2D {
+                data,
+                width: tex.width,
+                height: tex.height,
+                ..Default::default()
+            };
+            tex.convert(if tex.data.kind().channel_count() % 2 == 1 {
+                TextureDataKind::RgbF32
+            } else {
+                TextureDataKind::RgbaF32
+            })?;
+            dynamic_image(&tex)?
+        }
+    })
 }
 
 #[cfg(test)]
 mod test {
+    #[allow(dead_code)]
     fn tex() -> crate::Texture2D {
         crate::Texture2D {
             data: crate::TextureData::RgbaU8(vec![
@@ -167,6 +585,7 @@ mod test {
         }
     }
 
+    #[allow(dead_code)]
     fn test_deserialize(format: &str) {
         let path = format!("test_data/test.{}", format);
         let tex: crate::Texture2D = crate::io::load_and_deserialize(&path).unwrap();
@@ -196,6 +615,7 @@ mod test {
         assert_eq!(tex.height, 2);
     }
 
+    #[allow(dead_code)]
     fn test_serialize(format: &str) {
         let path = format!("test_data/test.{}", format);
         use crate::io::Serialize;
@@ -215,6 +635,95 @@ mod test {
         test_deserialize("png");
     }
 
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn png_with_options() {
+        use crate::io::{PngCompression, PngOptions, SerializeWithOptions};
+        let heightmap = crate::Texture2D {
+            data: crate::TextureData::RU16(vec![0, 257, 1000, 65535]),
+            width: 2,
+            height: 2,
+            ..Default::default()
+        };
+        let mut raw_assets = heightmap
+            .serialize_with_options(
+                "heightmap.png",
+                PngOptions {
+                    compression: PngCompression::Best,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(!raw_assets.get("heightmap.png").unwrap()[..64]
+            .windows(4)
+            .any(|w| w == b"sRGB"));
+        let reloaded: crate::Texture2D = raw_assets.deserialize("heightmap.png").unwrap();
+        assert_eq!(
+            reloaded.data,
+            crate::TextureData::RU16(vec![0, 257, 1000, 65535])
+        );
+
+        let mut raw_assets = tex()
+            .serialize_with_options(
+                "test.png",
+                PngOptions {
+                    force_16_bit: true,
+                    srgb: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(&raw_assets.get("test.png").unwrap()[37..41], b"sRGB");
+        let reloaded: crate::Texture2D = raw_assets.deserialize("test.png").unwrap();
+        assert_eq!(
+            reloaded.data,
+            crate::TextureData::RgbaU16(vec![
+                [0, 0, 0, 65535],
+                [65535, 0, 0, 65535],
+                [0, 65535, 0, 65535],
+                [0, 0, 65535, 65535],
+            ])
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn png_16bit() {
+        use crate::io::Serialize;
+        use crate::TextureData;
+        let directory =
+            std::env::temp_dir().join(format!("three-d-asset-png16-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let heights = (0..16u16)
+            .map(|i| i.wrapping_mul(4099).wrapping_add(i % 3))
+            .collect::<Vec<_>>();
+        for data in [
+            TextureData::RU16(heights.clone()),
+            TextureData::RgU16(heights.iter().map(|&h| [h, !h]).collect()),
+            TextureData::RgbU16(heights.iter().map(|&h| [h, !h, h / 3]).collect()),
+            TextureData::RgbaU16(
+                heights
+                    .iter()
+                    .map(|&h| [h, !h, h / 3, 65535 - h / 7])
+                    .collect(),
+            ),
+        ] {
+            let heightmap = crate::Texture2D {
+                data,
+                width: 4,
+                height: 4,
+                ..Default::default()
+            };
+            let path = directory.join("heightmap.png");
+            crate::io::save(&heightmap.serialize(&path).unwrap()).unwrap();
+            let reloaded: crate::Texture2D = crate::io::load_and_deserialize(&path).unwrap();
+            assert_eq!(reloaded.width, 4);
+            assert_eq!(reloaded.height, 4);
+            assert_eq!(reloaded.data, heightmap.data);
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
     #[cfg(feature = "jpeg")]
     #[test]
     pub fn jpeg() {
@@ -224,6 +733,68 @@ mod test {
         test_deserialize("jpg");
     }
 
+    #[cfg(feature = "jpeg")]
+    #[test]
+    pub fn jpeg_with_options() {
+        use crate::io::{JpegOptions, Serialize, SerializeWithOptions};
+        let gradient = crate::Texture2D {
+            data: crate::TextureData::RgbU8(
+                (0..64 * 64)
+                    .map(|i| [(i % 64 * 4) as u8, (i / 64 * 4) as u8, (i % 7 * 30) as u8])
+                    .collect(),
+            ),
+            width: 64,
+            height: 64,
+            ..Default::default()
+        };
+        let size = |quality| {
+            gradient
+                .serialize_with_options(
+                    "gradient.jpeg",
+                    JpegOptions {
+                        quality,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+                .get("gradient.jpeg")
+                .unwrap()
+                .len()
+        };
+        assert!(size(10) < size(90));
+
+        let transparent = crate::Texture2D {
+            data: crate::TextureData::RgbaU8(vec![[255, 255, 255, 0]; 16]),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        let mut raw_assets = transparent
+            .serialize_with_options(
+                "transparent.jpg",
+                JpegOptions {
+                    background: crate::Color::new_opaque(0, 0, 255),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let reloaded: crate::Texture2D = raw_assets.deserialize("transparent.jpg").unwrap();
+        let crate::TextureData::RgbU8(data) = reloaded.data else {
+            panic!("Wrong texture data: {:?}", reloaded.data)
+        };
+        assert!(data.iter().all(|c| c[0] < 8 && c[1] < 8 && c[2] > 247));
+
+        let grayscale = crate::Texture2D {
+            data: crate::TextureData::RU8(vec![0, 85, 170, 255]),
+            width: 2,
+            height: 2,
+            ..Default::default()
+        };
+        let mut raw_assets = grayscale.serialize("grayscale.jpeg").unwrap();
+        let reloaded: crate::Texture2D = raw_assets.deserialize("grayscale.jpeg").unwrap();
+        assert!(matches!(reloaded.data, crate::TextureData::RU8(_)));
+    }
+
     #[cfg(feature = "gif")]
     #[test]
     pub fn gif() {
@@ -231,6 +802,43 @@ mod test {
         test_deserialize("gif");
     }
 
+    #[cfg(feature = "gif")]
+    #[test]
+    pub fn gif_frames() {
+        use std::time::Duration;
+        let frames =
+            crate::Texture2D::frames_from_bytes(include_bytes!("../../test_data/animation.gif"))
+                .unwrap();
+        assert_eq!(frames.len(), 3);
+        let (r, b, g, t) = ([255, 0, 0, 255], [0, 0, 255, 255], [0, 255, 0, 255], [0; 4]);
+        assert_eq!(
+            frames.iter().map(|f| f.1).collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300)
+            ]
+        );
+        let expected = [
+            vec![r; 16],
+            // The transparent texel in the second frame shows the first frame.
+            [vec![b, r, r, r, b, b], vec![r; 10]].concat(),
+            // The second frame is disposed to the background before the third frame is drawn.
+            [vec![t, t, r, r, t, t], vec![r; 9], vec![g]].concat(),
+        ];
+        for ((tex, _), expected) in frames.into_iter().zip(expected) {
+            assert_eq!((tex.width, tex.height), (4, 4));
+            assert_eq!(tex.data, crate::TextureData::RgbaU8(expected));
+        }
+
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/animation.gif").unwrap();
+        assert_eq!(
+            tex.data,
+            crate::TextureData::RgbaU8(vec![[255, 0, 0, 255]; 16])
+        );
+    }
+
     #[cfg(feature = "tga")]
     #[test]
     pub fn tga() {
@@ -238,6 +846,48 @@ mod test {
         test_deserialize("tga");
     }
 
+    #[cfg(feature = "tga")]
+    #[test]
+    pub fn tga_rle_and_grayscale() {
+        let tex: crate::Texture2D = crate::io::RawAssets::new()
+            .insert(
+                "data",
+                include_bytes!("../../test_data/test_rle.tga").to_vec(),
+            )
+            .deserialize("")
+            .unwrap();
+        assert_eq!(
+            tex.data,
+            crate::TextureData::RgbaU8(vec![
+                [255, 0, 0, 128],
+                [0, 255, 0, 64],
+                [0, 0, 255, 255],
+                [0, 0, 255, 255],
+            ])
+        );
+
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/test_gray.tga").unwrap();
+        assert_eq!(tex.data, crate::TextureData::RU8(vec![10, 200]));
+        assert_eq!((tex.width, tex.height), (2, 1));
+    }
+
+    #[cfg(feature = "tga")]
+    #[test]
+    pub fn tga_alpha() {
+        use crate::io::{Deserialize, Serialize};
+        let mut tex = tex();
+        tex.data = crate::TextureData::RgbaU8(vec![
+            [0, 0, 0, 0],
+            [255, 0, 0, 64],
+            [0, 255, 0, 128],
+            [0, 0, 255, 255],
+        ]);
+        let mut raw_assets = tex.serialize("alpha.tga").unwrap();
+        let result = crate::Texture2D::deserialize("alpha.tga", &mut raw_assets).unwrap();
+        assert_eq!(result.data, tex.data);
+    }
+
     #[cfg(feature = "tiff")]
     #[test]
     pub fn tiff() {
@@ -247,6 +897,61 @@ mod test {
         test_deserialize("tif");
     }
 
+    #[cfg(feature = "tiff")]
+    #[test]
+    pub fn tiff_16bit_tiled() {
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/test_tiled_16bit.tif").unwrap();
+        assert_eq!((tex.width, tex.height), (20, 18));
+        if let crate::TextureData::RgbU16(data) = &tex.data {
+            assert_eq!(data.len(), 20 * 18);
+            for (i, texel) in data.iter().enumerate() {
+                let (x, y) = (i as u16 % 20, i as u16 / 20);
+                assert_eq!(*texel, [x * 3000, y * 3500, 65535 - x * y * 100]);
+            }
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+
+        let mut raw_assets = crate::io::Serialize::serialize(&tex, "test.tiff").unwrap();
+        let result: crate::Texture2D = raw_assets.deserialize("test.tiff").unwrap();
+        assert_eq!(result.data, tex.data);
+    }
+
+    #[cfg(feature = "tiff")]
+    #[test]
+    pub fn tiff_float() {
+        use tiff::encoder::{colortype::*, compression::*, TiffEncoder};
+        let rgb = (0..12).map(|i| i as f32 * 0.25 - 1.0).collect::<Vec<_>>();
+        let mut bytes = Vec::new();
+        TiffEncoder::new(std::io::Cursor::new(&mut bytes))
+            .unwrap()
+            .write_image_with_compression::<RGB32Float, _>(2, 2, Lzw, &rgb)
+            .unwrap();
+        let tex: crate::Texture2D = crate::io::RawAssets::new()
+            .insert("rgb.tiff", bytes)
+            .deserialize("")
+            .unwrap();
+        assert_eq!(tex.data, crate::TextureData::RgbF32(super::texels(&rgb)));
+
+        let mut bytes = Vec::new();
+        TiffEncoder::new(std::io::Cursor::new(&mut bytes))
+            .unwrap()
+            .write_image_with_compression::<Gray32Float, _>(
+                3,
+                1,
+                Deflate::default(),
+                &[0.5, 1e6, -3.0],
+            )
+            .unwrap();
+        let tex: crate::Texture2D = crate::io::RawAssets::new()
+            .insert("gray.tif", bytes)
+            .deserialize("")
+            .unwrap();
+        assert_eq!((tex.width, tex.height), (3, 1));
+        assert_eq!(tex.data, crate::TextureData::RF32(vec![0.5, 1e6, -3.0]));
+    }
+
     #[cfg(feature = "bmp")]
     #[test]
     pub fn bmp() {
@@ -254,6 +959,49 @@ mod test {
         test_deserialize("bmp");
     }
 
+    #[cfg(feature = "webp")]
+    #[test]
+    pub fn webp() {
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/test_lossy.webp").unwrap();
+        assert_eq!((tex.width, tex.height), (100, 100));
+        if let crate::TextureData::RgbU8(data) = tex.data {
+            assert_eq!(data[0], [255, 0, 2]);
+            assert_eq!(data[5000], [193, 0, 60]);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/test_lossless.webp").unwrap();
+        assert_eq!((tex.width, tex.height), (300, 300));
+        assert!(matches!(tex.data, crate::TextureData::RgbaU8(_)));
+    }
+
+    #[cfg(feature = "webp")]
+    #[test]
+    pub fn webp_alpha() {
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/test_lossy_alpha.webp").unwrap();
+        assert_eq!((tex.width, tex.height), (100, 100));
+        if let crate::TextureData::RgbaU8(data) = tex.data {
+            assert_eq!(data[0], [0, 15, 255, 0]);
+            assert_eq!(data.iter().filter(|p| p[3] < 255).count(), 6296);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+
+        // Only the first frame of an animation is loaded.
+        let tex: crate::Texture2D =
+            crate::io::load_and_deserialize("test_data/test_anim.webp").unwrap();
+        assert_eq!((tex.width, tex.height), (200, 200));
+        if let crate::TextureData::RgbaU8(data) = tex.data {
+            assert_eq!(data.iter().filter(|p| p[3] < 255).count(), 36798);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+    }
+
     #[cfg(feature = "hdr")]
     #[test]
     pub fn hdr() {
@@ -266,4 +1014,211 @@ mod test {
         assert_eq!(tex.width, 1024);
         assert_eq!(tex.height, 512);
     }
+
+    #[cfg(feature = "hdr")]
+    #[test]
+    pub fn hdr_with_rgbe_header() {
+        let bytes = include_bytes!("../../test_data/test.hdr");
+        let bytes = [b"#?RGBE".as_slice(), &bytes[b"#?RADIANCE".len()..]].concat();
+        let tex: crate::Texture2D = crate::io::RawAssets::new()
+            .insert("env.pic", bytes)
+            .deserialize("")
+            .unwrap();
+        if let crate::TextureData::RgbF32(data) = tex.data {
+            assert_eq!(data.len(), 1024 * 512);
+            assert_eq!(data[0], [0.16503906, 0.24609375, 0.20019531]);
+        } else {
+            panic!("Wrong texture data")
+        }
+        assert_eq!(tex.width, 1024);
+        assert_eq!(tex.height, 512);
+    }
+
+    #[cfg(feature = "exr")]
+    fn exr_bytes(channels: Vec<(&str, exr::prelude::FlatSamples)>) -> Vec<u8> {
+        use exr::prelude::*;
+        let channels = channels
+            .into_iter()
+            .map(|(name, samples)| AnyChannel::new(name, samples))
+            .collect();
+        let image = Image::from_layer(Layer::new(
+            (2, 1),
+            LayerAttributes::default(),
+            Encoding::default(),
+            AnyChannels::sort(channels),
+        ));
+        let mut bytes = Vec::new();
+        image
+            .write()
+            .to_buffered(std::io::Cursor::new(&mut bytes))
+            .unwrap();
+        bytes
+    }
+
+    #[cfg(feature = "exr")]
+    fn exr_deserialize(bytes: Vec<u8>) -> crate::Texture2D {
+        crate::io::RawAssets::new()
+            .insert("env.exr", bytes)
+            .deserialize("")
+            .unwrap()
+    }
+
+    #[cfg(feature = "exr")]
+    #[test]
+    pub fn exr() {
+        use exr::prelude::{f16, FlatSamples};
+        let tex = exr_deserialize(exr_bytes(vec![
+            ("R", FlatSamples::F32(vec![0.5, 12.5])),
+            ("G", FlatSamples::F32(vec![-1.0, 2.0])),
+            (
+                "B",
+                FlatSamples::F16(vec![f16::from_f32(0.25), f16::from_f32(100.0)]),
+            ),
+        ]));
+        assert_eq!(tex.width, 2);
+        assert_eq!(tex.height, 1);
+        if let crate::TextureData::RgbF32(data) = tex.data {
+            assert_eq!(data, vec![[0.5, -1.0, 0.25], [12.5, 2.0, 100.0]]);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+
+        let tex = exr_deserialize(exr_bytes(vec![
+            (
+                "R",
+                FlatSamples::F16(vec![f16::from_f32(1.0), f16::from_f32(2.0)]),
+            ),
+            (
+                "G",
+                FlatSamples::F16(vec![f16::from_f32(3.0), f16::from_f32(4.0)]),
+            ),
+            (
+                "B",
+                FlatSamples::F16(vec![f16::from_f32(5.0), f16::from_f32(6.0)]),
+            ),
+            (
+                "A",
+                FlatSamples::F16(vec![f16::from_f32(0.5), f16::from_f32(1.0)]),
+            ),
+        ]));
+        if let crate::TextureData::RgbaF32(data) = tex.data {
+            assert_eq!(data, vec![[1.0, 3.0, 5.0, 0.5], [2.0, 4.0, 6.0, 1.0]]);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+
+        let tex = exr_deserialize(exr_bytes(vec![("Y", FlatSamples::F32(vec![7.0, 8.0]))]));
+        if let crate::TextureData::RF32(data) = tex.data {
+            assert_eq!(data, vec![7.0, 8.0]);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+    }
+
+    #[cfg(feature = "exr")]
+    #[test]
+    pub fn exr_red_green() {
+        use exr::prelude::FlatSamples;
+        let tex = exr_deserialize(exr_bytes(vec![
+            ("R", FlatSamples::F32(vec![1.0, 2.0])),
+            ("G", FlatSamples::F32(vec![3.0, 4.0])),
+        ]));
+        assert_eq!(
+            tex.data,
+            crate::TextureData::RgF32(vec![[1.0, 3.0], [2.0, 4.0]])
+        );
+    }
+
+    #[cfg(feature = "exr")]
+    #[test]
+    pub fn exr_subsampled_channel() {
+        let channels = vec![
+            ("R".to_owned(), vec![1.0, 2.0]),
+            ("G".to_owned(), vec![3.0]),
+            ("B".to_owned(), vec![5.0, 6.0]),
+        ];
+        assert_eq!(
+            super::exr_texture_data(channels, 2)
+                .unwrap_err()
+                .to_string(),
+            "the G channel of the .exr file has 1 samples, but the image has 2 pixels"
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn load_texture3d() {
+        use crate::io::Serialize;
+        let directory =
+            std::env::temp_dir().join(format!("three-d-asset-slices-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut paths = Vec::new();
+        for i in [10u8, 2, 1] {
+            let mut slice = tex();
+            slice.data = crate::TextureData::RgbaU8(vec![[i, 0, 0, 255]; 4]);
+            let path = directory.join(format!("slice{}.png", i));
+            crate::io::save(&slice.serialize(&path).unwrap()).unwrap();
+            paths.push(path);
+        }
+        let texture = crate::io::load_texture3d(&paths).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(texture.width, 2);
+        assert_eq!(texture.height, 2);
+        assert_eq!(texture.depth, 3);
+        if let crate::TextureData::RgbaU8(data) = texture.data {
+            let reds = data.chunks(4).map(|slice| slice[0][0]).collect::<Vec<_>>();
+            assert_eq!(reds, vec![1, 2, 10]);
+        } else {
+            panic!("Wrong texture data: {:?}", texture.data)
+        }
+
+        let mut small = tex();
+        small.width = 1;
+        small.data = crate::TextureData::RgbaU8(vec![[0, 0, 0, 255]; 2]);
+        let error = crate::Texture3D::from_slices(&[tex(), small]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "slice 1 of the 3D texture is a 1x2 RgbaU8 texture, but the first slice is a 2x2 RgbaU8 texture"
+        );
+        assert!(crate::Texture3D::from_slices(&[]).is_err());
+    }
+
+    #[test]
+    pub fn dynamic_image() {
+        use crate::{f16, Texture2D, TextureData};
+        use image::DynamicImage;
+        let img = DynamicImage::try_from(tex()).unwrap();
+        assert_eq!(img.color(), image::ColorType::Rgba8);
+        assert_eq!(Texture2D::from(img), tex());
+
+        let texture = Texture2D {
+            data: TextureData::RgU16(vec![[0, 65535], [1, 2], [3, 4], [5, 6]]),
+            ..tex()
+        };
+        let img = DynamicImage::try_from(texture.clone()).unwrap();
+        assert_eq!(img.color(), image::ColorType::La16);
+        assert_eq!(Texture2D::from(img), texture);
+
+        let texture = Texture2D {
+            data: TextureData::RgbF32(vec![[0.5, 2.0, -1.0]; 4]),
+            ..tex()
+        };
+        let img = DynamicImage::try_from(texture.clone()).unwrap();
+        assert_eq!(img.color(), image::ColorType::Rgb32F);
+        let converted = Texture2D::from(img);
+        assert_eq!(converted.data, texture.data);
+        assert_eq!(converted.color_space, crate::ColorSpace::Linear);
+
+        let texture = Texture2D {
+            data: TextureData::RgF16(vec![[f16::from_f32(0.25), f16::from_f32(1.0)]; 4]),
+            ..tex()
+        };
+        let img = DynamicImage::try_from(texture).unwrap();
+        assert_eq!(img.color(), image::ColorType::Rgba32F);
+        assert_eq!(
+            Texture2D::from(img).data,
+            TextureData::RgbaF32(vec![[0.25, 0.25, 0.25, 1.0]; 4])
+        );
+    }
 }
@@ -0,0 +1,532 @@
+use crate::{texture::*, Error, Result};
+use std::path::Path;
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+
+///
+/// The texture contained in a .ktx2 file.
+///
+pub enum Ktx2Texture {
+    Texture2D(Texture2D),
+    TextureCube(TextureCube),
+    Texture3D(Texture3D),
+}
+
+///
+/// Deserialize a .ktx2 file into a [Texture2D], a [TextureCube] if the file contains six faces or a [Texture3D] if the file contains
+/// a depth or more than one array layer. Zstandard supercompressed levels are decompressed, but block compressed data is kept as is.
+/// All mip levels in the file are stored in [Texture2D::mip_levels] and [TextureCube::mip_levels].
+///
+/// **Note:** Only the first layer of cube map arrays and only the base level of 3D textures are returned.
+///
+pub fn deserialize_ktx2(path: impl AsRef<Path>, bytes: &[u8]) -> Result<Ktx2Texture> {
+    let name = path
+        .as_ref()
+        .to_str()
+        .filter(|s| !s.starts_with("data:"))
+        .unwrap_or("default")
+        .to_owned();
+    if !bytes.starts_with(&IDENTIFIER) {
+        Err(Error::Ktx2CorruptData)?;
+    }
+    let header = Header::parse(bytes)?;
+    if header.pixel_width == 0 || (header.face_count != 1 && header.face_count != 6) {
+        Err(Error::Ktx2CorruptData)?;
+    }
+    let layers = header.layer_count.max(1) as usize;
+    let faces = header.face_count as usize;
+    let (block_size, block_bytes) = format_size(header.vk_format)?;
+
+    let mut levels = Vec::new();
+    for level in 0..header.level_count.max(1) as usize {
+        let index = level
+            .checked_mul(24)
+            .and_then(|i| i.checked_add(80))
+            .ok_or(Error::Ktx2CorruptData)?;
+        let offset = read_usize(bytes, index)?;
+        let length = read_usize(bytes, index + 8)?;
+        let uncompressed_length = read_usize(bytes, index + 16)?;
+        let data = offset
+            .checked_add(length)
+            .and_then(|end| bytes.get(offset..end))
+            .ok_or(Error::Ktx2CorruptData)?;
+
+        // The expected size of the level is computed from the header, so the level data cannot be shorter or longer than the images it contains.
+        let level_size = |size: u32| size.checked_shr(level as u32).unwrap_or(0).max(1) as usize;
+        let slices = level_size(header.pixel_depth);
+        let images = layers
+            .checked_mul(faces)
+            .and_then(|n| n.checked_mul(slices))
+            .filter(|&n| n > 0)
+            .ok_or(Error::Ktx2CorruptData)?;
+        let image_length = level_size(header.pixel_width)
+            .div_ceil(block_size)
+            .checked_mul(level_size(header.pixel_height).div_ceil(block_size))
+            .and_then(|n| n.checked_mul(block_bytes))
+            .ok_or(Error::Ktx2CorruptData)?;
+        let expected_length = image_length
+            .checked_mul(images)
+            .ok_or(Error::Ktx2CorruptData)?;
+
+        let data = match header.supercompression_scheme {
+            SUPERCOMPRESSION_NONE => data.to_vec(),
+            SUPERCOMPRESSION_ZSTD => {
+                if uncompressed_length != expected_length {
+                    Err(Error::Ktx2CorruptData)?;
+                }
+                // The decoded data is not allowed to grow beyond the expected length, so a small file cannot expand into a huge allocation.
+                let mut decompressed = Vec::new();
+                std::io::Read::read_to_end(
+                    &mut std::io::Read::take(
+                        ruzstd::decoding::StreamingDecoder::new(data)
+                            .map_err(|_| Error::Ktx2CorruptData)?,
+                        expected_length as u64 + 1,
+                    ),
+                    &mut decompressed,
+                )
+                .map_err(|_| Error::Ktx2CorruptData)?;
+                decompressed
+            }
+            scheme => Err(Error::Ktx2UnsupportedFormat(format!(
+                "supercompression scheme {}",
+                scheme
+            )))?,
+        };
+        if data.len() != expected_length {
+            Err(Error::Ktx2CorruptData)?;
+        }
+        levels.push(
+            data.chunks(image_length)
+                .map(|image| texture_data(header.vk_format, image))
+                .collect::<Result<Vec<_>>>()?,
+        );
+    }
+
+    let width = header.pixel_width;
+    let height = header.pixel_height.max(1);
+    Ok(if faces == 6 {
+        let mut levels = levels.into_iter().map(|images| {
+            let mut images = images.into_iter();
+            std::array::from_fn(|_| images.next().unwrap())
+        });
+        Ktx2Texture::TextureCube(TextureCube {
+            name,
+            faces: levels.next().unwrap(),
+            width,
+            height,
+            mip_levels: levels.collect(),
+            ..Default::default()
+        })
+    } else if header.pixel_depth > 0 || header.layer_count > 1 {
+        let images = levels.remove(0);
+        let depth = images.len() as u32;
+        Ktx2Texture::Texture3D(Texture3D {
+            name,
+            data: concatenate(images)?,
+            width,
+            height,
+            depth,
+            ..Default::default()
+        })
+    } else {
+        let mut levels = levels.into_iter().map(|mut images| images.remove(0));
+        Ktx2Texture::Texture2D(Texture2D {
+            name,
+            data: levels.next().unwrap(),
+            width,
+            height,
+            color_space: color_space(header.vk_format),
+            mip_levels: levels.collect(),
+            ..Default::default()
+        })
+    })
+}
+
+struct Header {
+    vk_format: u32,
+    pixel_width: u32,
+    pixel_height: u32,
+    pixel_depth: u32,
+    layer_count: u32,
+    face_count: u32,
+    level_count: u32,
+    supercompression_scheme: u32,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        let header = Self {
+            vk_format: read_u32(bytes, 12)?,
+            pixel_width: read_u32(bytes, 20)?,
+            pixel_height: read_u32(bytes, 24)?,
+            pixel_depth: read_u32(bytes, 28)?,
+            layer_count: read_u32(bytes, 32)?,
+            face_count: read_u32(bytes, 36)?,
+            level_count: read_u32(bytes, 40)?,
+            supercompression_scheme: read_u32(bytes, 44)?,
+        };
+        // The format is only described by the data format descriptor when it is undefined, ie. when the data needs to be transcoded.
+        if header.vk_format == 0 {
+            let dfd_offset = read_u32(bytes, 48)? as usize;
+            let color_model = dfd_offset
+                .checked_add(12)
+                .and_then(|offset| bytes.get(offset))
+                .ok_or(Error::Ktx2CorruptData)?;
+            Err(Error::Ktx2UnsupportedFormat(format!(
+                "undefined format with color model {}",
+                color_model
+            )))?;
+        }
+        Ok(header)
+    }
+}
+
+///
+/// Returns the width and height of a block in texels and the number of bytes in a block, where uncompressed formats have blocks of a single texel.
+///
+fn format_size(vk_format: u32) -> Result<(usize, usize)> {
+    Ok(match vk_format {
+        9 | 15 => (1, 1),
+        16 | 22 | 76 => (1, 2),
+        23 | 29 => (1, 3),
+        37 | 43 | 83 | 100 => (1, 4),
+        90 => (1, 6),
+        97 | 103 => (1, 8),
+        106 => (1, 12),
+        109 => (1, 16),
+        131..=134 => (4, 8),
+        137 | 138 | 141 | 145 | 146 | 151 | 152 | 157 | 158 => (4, 16),
+        vk_format => Err(Error::Ktx2UnsupportedFormat(format!(
+            "Vulkan format {}",
+            vk_format
+        )))?,
+    })
+}
+
+fn texture_data(vk_format: u32, bytes: &[u8]) -> Result<TextureData> {
+    fn u8s<const N: usize>(bytes: &[u8]) -> Vec<[u8; N]> {
+        bytes
+            .chunks_exact(N)
+            .map(|c| c.try_into().unwrap())
+            .collect()
+    }
+    fn f16s<const N: usize>(bytes: &[u8]) -> Vec<[f16; N]> {
+        bytes
+            .chunks_exact(N * 2)
+            .map(|c| std::array::from_fn(|i| f16::from_le_bytes([c[i * 2], c[i * 2 + 1]])))
+            .collect()
+    }
+    fn f32s<const N: usize>(bytes: &[u8]) -> Vec<[f32; N]> {
+        bytes
+            .chunks_exact(N * 4)
+            .map(|c| {
+                std::array::from_fn(|i| {
+                    f32::from_le_bytes([c[i * 4], c[i * 4 + 1], c[i * 4 + 2], c[i * 4 + 3]])
+                })
+            })
+            .collect()
+    }
+    Ok(match vk_format {
+        9 | 15 => TextureData::RU8(bytes.to_vec()),
+        16 | 22 => TextureData::RgU8(u8s(bytes)),
+        23 | 29 => TextureData::RgbU8(u8s(bytes)),
+        37 | 43 => TextureData::RgbaU8(u8s(bytes)),
+        76 => TextureData::RF16(f16s::<1>(bytes).into_iter().map(|v| v[0]).collect()),
+        83 => TextureData::RgF16(f16s(bytes)),
+        90 => TextureData::RgbF16(f16s(bytes)),
+        97 => TextureData::RgbaF16(f16s(bytes)),
+        100 => TextureData::RF32(f32s::<1>(bytes).into_iter().map(|v| v[0]).collect()),
+        103 => TextureData::RgF32(f32s(bytes)),
+        106 => TextureData::RgbF32(f32s(bytes)),
+        109 => TextureData::RgbaF32(f32s(bytes)),
+        131..=134 => TextureData::Bc1RgbaU8(u8s(bytes)),
+        137 | 138 => TextureData::Bc3RgbaU8(u8s(bytes)),
+        141 => TextureData::Bc5RgU8(u8s(bytes)),
+        145 | 146 => TextureData::Bc7RgbaU8(u8s(bytes)),
+        151 | 152 => TextureData::Etc2RgbaU8(u8s(bytes)),
+        157 | 158 => TextureData::Astc4x4RgbaU8(u8s(bytes)),
+        vk_format => Err(Error::Ktx2UnsupportedFormat(format!(
+            "Vulkan format {}",
+            vk_format
+        )))?,
+    })
+}
+
+///
+/// Returns [ColorSpace::Srgb] for the Vulkan formats with sRGB encoding and [ColorSpace::Linear] for all other formats.
+///
+fn color_space(vk_format: u32) -> ColorSpace {
+    match vk_format {
+        15 | 22 | 29 | 43 | 132 | 134 | 138 | 146 | 152 | 158 => ColorSpace::Srgb,
+        _ => ColorSpace::Linear,
+    }
+}
+
+///
+/// Concatenates the images of a 3D texture or texture array into one [TextureData].
+///
+fn concatenate(images: Vec<TextureData>) -> Result<TextureData> {
+    let mut images = images.into_iter();
+    let mut data = images.next().ok_or(Error::Ktx2CorruptData)?;
+    for image in images {
+        match (&mut data, image) {
+            (TextureData::RU8(a), TextureData::RU8(b)) => a.extend(b),
+            (TextureData::RgU8(a), TextureData::RgU8(b)) => a.extend(b),
+            (TextureData::RgbU8(a), TextureData::RgbU8(b)) => a.extend(b),
+            (TextureData::RgbaU8(a), TextureData::RgbaU8(b)) => a.extend(b),
+            (TextureData::RF16(a), TextureData::RF16(b)) => a.extend(b),
+            (TextureData::RgF16(a), TextureData::RgF16(b)) => a.extend(b),
+            (TextureData::RgbF16(a), TextureData::RgbF16(b)) => a.extend(b),
+            (TextureData::RgbaF16(a), TextureData::RgbaF16(b)) => a.extend(b),
+            (TextureData::RF32(a), TextureData::RF32(b)) => a.extend(b),
+            (TextureData::RgF32(a), TextureData::RgF32(b)) => a.extend(b),
+            (TextureData::RgbF32(a), TextureData::RgbF32(b)) => a.extend(b),
+            (TextureData::RgbaF32(a), TextureData::RgbaF32(b)) => a.extend(b),
+            (TextureData::Bc1RgbaU8(a), TextureData::Bc1RgbaU8(b)) => a.extend(b),
+            (TextureData::Bc3RgbaU8(a), TextureData::Bc3RgbaU8(b)) => a.extend(b),
+            (TextureData::Bc5RgU8(a), TextureData::Bc5RgU8(b)) => a.extend(b),
+            (TextureData::Bc7RgbaU8(a), TextureData::Bc7RgbaU8(b)) => a.extend(b),
+            (TextureData::Etc2RgbaU8(a), TextureData::Etc2RgbaU8(b)) => a.extend(b),
+            (TextureData::Astc4x4RgbaU8(a), TextureData::Astc4x4RgbaU8(b)) => a.extend(b),
+            _ => Err(Error::Ktx2CorruptData)?,
+        }
+    }
+    Ok(data)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let b = offset
+        .checked_add(4)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or(Error::Ktx2CorruptData)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64> {
+    Ok(read_u32(bytes, offset)? as u64 | (read_u32(bytes, offset + 4)? as u64) << 32)
+}
+
+fn read_usize(bytes: &[u8], offset: usize) -> Result<usize> {
+    usize::try_from(read_u64(bytes, offset)?).map_err(|_| Error::Ktx2CorruptData)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::RawAssets;
+    use crate::{Texture2D, Texture3D, TextureCube, TextureData};
+
+    ///
+    /// Creates a .ktx2 file with the given header values and levels, largest level first.
+    ///
+    fn ktx2(
+        vk_format: u32,
+        size: [u32; 3],
+        layers: u32,
+        faces: u32,
+        zstd: bool,
+        levels: &[Vec<u8>],
+    ) -> Vec<u8> {
+        let compressed = levels
+            .iter()
+            .map(|l| {
+                if zstd {
+                    ruzstd::encoding::compress_to_vec(
+                        &l[..],
+                        ruzstd::encoding::CompressionLevel::Fastest,
+                    )
+                } else {
+                    l.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut bytes = super::IDENTIFIER.to_vec();
+        for v in [vk_format, 1, size[0], size[1], size[2], layers, faces] {
+            bytes.extend(v.to_le_bytes());
+        }
+        bytes.extend((levels.len() as u32).to_le_bytes());
+        bytes.extend((if zstd { 2u32 } else { 0 }).to_le_bytes());
+        let dfd_offset = 80 + 24 * levels.len() as u32;
+        bytes.extend(dfd_offset.to_le_bytes());
+        bytes.extend(28u32.to_le_bytes());
+        bytes.extend([0u8; 24]);
+        let mut offset = dfd_offset as u64 + 28;
+        for (level, uncompressed) in compressed.iter().zip(levels) {
+            bytes.extend(offset.to_le_bytes());
+            bytes.extend((level.len() as u64).to_le_bytes());
+            bytes.extend((uncompressed.len() as u64).to_le_bytes());
+            offset += level.len() as u64;
+        }
+        bytes.extend(28u32.to_le_bytes());
+        bytes.extend([0u8; 24]);
+        for level in compressed {
+            bytes.extend(level);
+        }
+        bytes
+    }
+
+    #[test]
+    pub fn deserialize_ktx2() {
+        let bytes = ktx2(
+            37,
+            [2, 2, 0],
+            0,
+            1,
+            false,
+            &[[1, 2, 3, 4].repeat(4), vec![5, 6, 7, 8]],
+        );
+        let tex: Texture2D = RawAssets::new()
+            .insert("test.ktx2", bytes)
+            .deserialize("")
+            .unwrap();
+        assert_eq!(tex.width, 2);
+        assert_eq!(tex.height, 2);
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[1, 2, 3, 4]; 4]));
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[5, 6, 7, 8]])]
+        );
+    }
+
+    #[test]
+    pub fn deserialize_corrupt_level_index_ktx2() {
+        let is_corrupt = |bytes: Vec<u8>| {
+            matches!(
+                super::deserialize_ktx2("test.ktx2", &bytes),
+                Err(crate::Error::Ktx2CorruptData)
+            )
+        };
+        let level = [1, 2, 3, 4].repeat(4);
+        for (index, value) in [(80, u64::MAX), (88, u64::MAX), (80, 1 << 40)] {
+            let mut bytes = ktx2(37, [2, 2, 0], 0, 1, false, std::slice::from_ref(&level));
+            bytes[index..index + 8].copy_from_slice(&value.to_le_bytes());
+            assert!(is_corrupt(bytes));
+        }
+        for value in [u64::MAX, 1 << 40, 15] {
+            let mut bytes = ktx2(37, [2, 2, 0], 0, 1, true, std::slice::from_ref(&level));
+            bytes[96..104].copy_from_slice(&value.to_le_bytes());
+            assert!(is_corrupt(bytes));
+        }
+    }
+
+    #[test]
+    pub fn deserialize_wrong_level_length_ktx2() {
+        let is_corrupt = |bytes: Vec<u8>| {
+            matches!(
+                super::deserialize_ktx2("test.ktx2", &bytes),
+                Err(crate::Error::Ktx2CorruptData)
+            )
+        };
+        // The level length is not a multiple of the texel size
+        assert!(is_corrupt(ktx2(37, [1, 1, 0], 0, 1, false, &[vec![0; 5]])));
+        assert!(is_corrupt(ktx2(97, [1, 1, 0], 0, 1, false, &[vec![0; 7]])));
+        assert!(is_corrupt(ktx2(
+            109,
+            [1, 1, 0],
+            0,
+            1,
+            false,
+            &[vec![0; 15]]
+        )));
+        // The level contains more or fewer texels than the size in the header
+        assert!(is_corrupt(ktx2(37, [2, 2, 0], 0, 1, false, &[vec![0; 20]])));
+        assert!(is_corrupt(ktx2(
+            131,
+            [8, 8, 0],
+            0,
+            1,
+            false,
+            &[vec![0; 24]]
+        )));
+        assert!(is_corrupt(ktx2(37, [2, 2, 0], 0, 1, true, &[vec![0; 12]])));
+        // The number of images overflows
+        assert!(is_corrupt(ktx2(
+            9,
+            [1, 1, u32::MAX],
+            u32::MAX,
+            6,
+            false,
+            &[vec![0; 6]]
+        )));
+        // A zero width is not allowed
+        assert!(is_corrupt(ktx2(9, [0, 1, 0], 0, 1, false, &[vec![0]])));
+    }
+
+    #[test]
+    pub fn deserialize_zstd_bomb_ktx2() {
+        // The level decodes to more data than the uncompressed length in the level index
+        let mut bytes = ktx2(37, [2, 2, 0], 0, 1, true, &[vec![0; 1 << 20]]);
+        bytes[96..104].copy_from_slice(&16u64.to_le_bytes());
+        assert!(matches!(
+            super::deserialize_ktx2("test.ktx2", &bytes),
+            Err(crate::Error::Ktx2CorruptData)
+        ));
+    }
+
+    #[test]
+    pub fn deserialize_zstd_bc7_ktx2() {
+        let block = [
+            0xC0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let bytes = ktx2(
+            145,
+            [8, 4, 0],
+            0,
+            1,
+            true,
+            &[block.repeat(2), block.to_vec()],
+        );
+        let mut tex: Texture2D = RawAssets::new()
+            .insert("data", bytes)
+            .deserialize("")
+            .unwrap();
+        assert_eq!(tex.data, TextureData::Bc7RgbaU8(vec![block; 2]));
+        assert_eq!(tex.mip_levels, vec![TextureData::Bc7RgbaU8(vec![block])]);
+        tex.decompress().unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[255; 4]; 32]));
+    }
+
+    #[test]
+    pub fn deserialize_cube_ktx2() {
+        let level = (0..6u8).flat_map(|f| [f; 2]).collect::<Vec<_>>();
+        let bytes = ktx2(16, [1, 1, 0], 0, 6, false, &[level]);
+        let tex: TextureCube = RawAssets::new()
+            .insert("test.ktx2", bytes.clone())
+            .deserialize("")
+            .unwrap();
+        assert_eq!(tex.width, 1);
+        for (i, face) in tex.faces.iter().enumerate() {
+            assert_eq!(*face, TextureData::RgU8(vec![[i as u8; 2]; 1]));
+        }
+        assert!(RawAssets::new()
+            .insert("test.ktx2", bytes)
+            .deserialize::<Texture2D>("")
+            .is_err());
+    }
+
+    #[test]
+    pub fn deserialize_3d_ktx2() {
+        let bytes = ktx2(
+            100,
+            [1, 2, 3],
+            0,
+            1,
+            false,
+            &[(0..6).flat_map(|i| (i as f32).to_le_bytes()).collect()],
+        );
+        let tex: Texture3D = RawAssets::new()
+            .insert("test.ktx2", bytes)
+            .deserialize("")
+            .unwrap();
+        assert_eq!((tex.width, tex.height, tex.depth), (1, 2, 3));
+        if let TextureData::RF32(data) = tex.data {
+            assert_eq!(data, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+    }
+}
@@ -2,6 +2,8 @@
 //! Functionality for loading any type of asset runtime on both desktop and web.
 //!
 
+#[cfg(feature = "reqwest")]
+use crate::io::DownloadCache;
 use crate::{io::RawAssets, Error, Result};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -11,24 +13,107 @@ use std::path::{Path, PathBuf};
 ///
 /// Supported functionality:
 /// - Loading from disk (relative and absolute paths)
+/// - Loading all files on disk matching a glob pattern, for example `"textures/*.png"` or `"assets/**/*.gltf"` (requires the `glob` feature flag)
 /// - Parsing from data URLs (requires the `data-url` feature flag)
 ///
 /// If downloading resources is also needed, use the [load_async] method instead.
 ///
+/// Files on disk are read in parallel using a thread per file or, with the `rayon` feature flag, using the rayon thread pool.
+/// The number of threads in the rayon thread pool can be capped using the `RAYON_NUM_THREADS` environment variable, by configuring the global thread pool
+/// or by calling this function inside `ThreadPool::install` on a custom thread pool.
+///
 #[cfg(not(target_arch = "wasm32"))]
 pub fn load(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
-    let mut raw_assets = load_single(paths)?;
+    load_with_progress(paths, |_, _, _| {})
+}
+
+///
+/// Loads all of the resources in the given paths, in the same way as [load], and calls the `progress` callback after each resource is loaded
+/// with the number of loaded resources, the total number of resources and the path of the resource that was just loaded.
+/// The total number of resources is initially the number of unique paths and is increased when dependencies are found or glob patterns are expanded.
+///
+/// ```
+/// # use three_d_asset::io::*;
+/// let assets = load_with_progress(&["test_data/test.png"], |loaded, total, path| {
+///     println!("Loaded {} ({}/{})", path.display(), loaded, total);
+/// })
+/// .unwrap();
+/// ```
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_with_progress(
+    paths: &[impl AsRef<Path>],
+    mut progress: impl FnMut(usize, usize, &Path),
+) -> Result<RawAssets> {
+    let mut progress = Progress::new(paths, &mut progress);
+    let mut raw_assets = load_single(paths, &mut progress)?;
     let mut dependencies = super::get_dependencies(&raw_assets);
     while !dependencies.is_empty() {
-        let deps = load_single(&dependencies)?;
+        progress.total += dependencies.len();
+        let deps = load_single(&dependencies, &mut progress)?;
         dependencies = super::get_dependencies(&deps);
         raw_assets.extend(deps);
     }
     Ok(raw_assets)
 }
 
+///
+/// Reads each of the given readers to completion and returns the [RawAssets] resources where the bytes of each reader are stored at the given path.
+/// Dependencies are not loaded, so all of the resources needed to deserialize an asset must be given.
+/// Returns an error if one of the readers fails.
+///
+/// ```
+/// # use three_d_asset::io::*;
+/// let assets = load_from_readers([
+///     ("a.bin", std::io::Cursor::new(vec![1, 2])),
+///     ("b.bin", std::io::Cursor::new(vec![3])),
+/// ])
+/// .unwrap();
+/// assert_eq!(assets.get("b.bin").unwrap(), &[3]);
+/// ```
+///
 #[cfg(not(target_arch = "wasm32"))]
-fn load_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
+pub fn load_from_readers<P: AsRef<Path>, R: std::io::Read>(
+    readers: impl IntoIterator<Item = (P, R)>,
+) -> Result<RawAssets> {
+    let mut raw_assets = RawAssets::new();
+    for (path, mut reader) in readers {
+        raw_assets.insert_reader(path, &mut reader)?;
+    }
+    Ok(raw_assets)
+}
+
+///
+/// Keeps track of the number of loaded resources and reports each loaded resource to a callback.
+///
+struct Progress<'a> {
+    loaded: usize,
+    total: usize,
+    callback: &'a mut dyn FnMut(usize, usize, &Path),
+}
+
+impl<'a> Progress<'a> {
+    fn new(paths: &[impl AsRef<Path>], callback: &'a mut dyn FnMut(usize, usize, &Path)) -> Self {
+        Self {
+            loaded: 0,
+            total: paths
+                .iter()
+                .map(|p| p.as_ref())
+                .collect::<HashSet<_>>()
+                .len(),
+            callback,
+        }
+    }
+
+    fn report(&mut self, path: &Path) {
+        self.loaded += 1;
+        self.total = self.total.max(self.loaded);
+        (self.callback)(self.loaded, self.total, path);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_single(paths: &[impl AsRef<Path>], progress: &mut Progress) -> Result<RawAssets> {
     let mut data_urls = HashSet::new();
     let mut local_paths = HashSet::new();
     for path in paths.iter() {
@@ -40,8 +125,8 @@ fn load_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
         }
     }
     let mut raw_assets = RawAssets::new();
-    load_from_disk(local_paths, &mut raw_assets)?;
-    parse_data_urls(data_urls, &mut raw_assets)?;
+    load_from_disk(local_paths, &mut raw_assets, progress)?;
+    parse_data_urls(data_urls, &mut raw_assets, progress)?;
     Ok(raw_assets)
 }
 
@@ -52,12 +137,121 @@ fn load_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
 /// - Downloading from URLs relative to the base URL and absolute urls (requires the `http` or `reqwest` feature flag)
 /// - Parsing from data URLs (requires the `data-url` feature flag)
 /// - *** Native only *** Loading from disk (relative and absolute paths)
+/// - *** Native only *** Loading all files on disk matching a glob pattern (requires the `glob` feature flag)
 ///
 pub async fn load_async(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
-    let mut raw_assets = load_async_single(paths).await?;
+    load_async_with_progress(paths, |_, _, _| {}).await
+}
+
+///
+/// Async loads all of the resources in the given paths, in the same way as [load_async], and calls the `progress` callback after each resource is loaded
+/// with the number of loaded resources, the total number of resources and the path of the resource that was just loaded.
+/// The total number of resources is initially the number of unique paths and is increased when dependencies are found or glob patterns are expanded.
+///
+pub async fn load_async_with_progress(
+    paths: &[impl AsRef<Path>],
+    mut progress: impl FnMut(usize, usize, &Path),
+) -> Result<RawAssets> {
+    load_async_internal(paths, &RequestOptions::default(), &mut progress).await
+}
+
+///
+/// Async loads all of the resources in the given paths in the same way as [load_async],
+/// but sends the given HTTP headers, for example an `Authorization` header, when downloading the resource at each path.
+/// Dependencies, for example the buffers and textures of a `.gltf` file, are downloaded with the headers given for the path with the same origin, ie. scheme, host and port.
+/// The downloaded resources are stored in the [RawAssets] under their URL.
+///
+/// ```no_run
+/// # use three_d_asset::io::*;
+/// # #[cfg(feature = "reqwest")]
+/// # async fn load_private() {
+/// let assets = load_async_with_headers(&[(
+///     "https://example.com/private/model.gltf",
+///     &[("Authorization", "Bearer my-token")],
+/// )])
+/// .await
+/// .unwrap();
+/// # }
+/// ```
+///
+#[cfg(feature = "reqwest")]
+pub async fn load_async_with_headers(
+    requests: &[(impl AsRef<Path>, &[(&str, &str)])],
+) -> Result<RawAssets> {
+    let paths = requests.iter().map(|(path, _)| path).collect::<Vec<_>>();
+    let headers = requests
+        .iter()
+        .map(|(path, headers)| {
+            (
+                path.as_ref().to_path_buf(),
+                headers
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let options = RequestOptions {
+        headers,
+        ..Default::default()
+    };
+    load_async_internal(&paths, &options, &mut |_, _, _| {}).await
+}
+
+///
+/// Async loads all of the resources in the given paths in the same way as [load_async],
+/// but downloaded resources are stored in the given [DownloadCache] and are read from the cache instead of downloaded again on subsequent loads.
+/// The resources are stored in the [RawAssets] under their URL, whether they are read from the cache or downloaded.
+///
+/// **Note:** The cache is only used on native, on web this is the same as [load_async].
+///
+/// ```no_run
+/// # use three_d_asset::io::*;
+/// # #[cfg(feature = "reqwest")]
+/// # async fn load_cached() {
+/// let cache = DownloadCache::new("cache").with_time_to_live(std::time::Duration::from_secs(3600));
+/// let assets = load_async_with_cache(&["https://example.com/model.gltf"], &cache)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+///
+#[cfg(feature = "reqwest")]
+pub async fn load_async_with_cache(
+    paths: &[impl AsRef<Path>],
+    cache: &DownloadCache,
+) -> Result<RawAssets> {
+    let options = RequestOptions {
+        cache: Some(cache.clone()),
+        ..Default::default()
+    };
+    load_async_internal(paths, &options, &mut |_, _, _| {}).await
+}
+
+///
+/// Options for downloading resources.
+///
+#[derive(Default)]
+struct RequestOptions {
+    /// The headers for each url.
+    #[cfg(feature = "reqwest")]
+    headers: Vec<(PathBuf, Vec<(String, String)>)>,
+    /// The cache for downloaded resources.
+    #[cfg(feature = "reqwest")]
+    cache: Option<DownloadCache>,
+}
+
+async fn load_async_internal(
+    paths: &[impl AsRef<Path>],
+    options: &RequestOptions,
+    progress: &mut dyn FnMut(usize, usize, &Path),
+) -> Result<RawAssets> {
+    let mut progress = Progress::new(paths, progress);
+    let mut raw_assets = load_async_single(paths, options, &mut progress).await?;
     let mut dependencies = super::get_dependencies(&raw_assets);
     while !dependencies.is_empty() {
-        let deps = load_async_single(&dependencies).await?;
+        progress.total += dependencies.len();
+        let deps = load_async_single(&dependencies, options, &mut progress).await?;
         dependencies = super::get_dependencies(&deps);
         raw_assets.extend(deps);
     }
@@ -65,7 +259,11 @@ pub async fn load_async(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
 }
 
 #[cfg(target_arch = "wasm32")]
-async fn load_async_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
+async fn load_async_single(
+    paths: &[impl AsRef<Path>],
+    options: &RequestOptions,
+    progress: &mut Progress<'_>,
+) -> Result<RawAssets> {
     let base_path = base_path();
     let mut urls = HashSet::new();
     let mut data_urls = HashSet::new();
@@ -80,13 +278,17 @@ async fn load_async_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
         }
     }
     let mut raw_assets = RawAssets::new();
-    load_urls(urls, &mut raw_assets).await?;
-    parse_data_urls(data_urls, &mut raw_assets)?;
+    load_urls(urls, options, &mut raw_assets, progress).await?;
+    parse_data_urls(data_urls, &mut raw_assets, progress)?;
     Ok(raw_assets)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-async fn load_async_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
+async fn load_async_single(
+    paths: &[impl AsRef<Path>],
+    options: &RequestOptions,
+    progress: &mut Progress<'_>,
+) -> Result<RawAssets> {
     let mut urls = HashSet::new();
     let mut data_urls = HashSet::new();
     let mut local_paths = HashSet::new();
@@ -102,50 +304,218 @@ async fn load_async_single(paths: &[impl AsRef<Path>]) -> Result<RawAssets> {
     }
 
     let mut raw_assets = RawAssets::new();
-    load_urls(urls, &mut raw_assets).await?;
-    load_from_disk(local_paths, &mut raw_assets)?;
-    parse_data_urls(data_urls, &mut raw_assets)?;
+    load_urls(urls, options, &mut raw_assets, progress).await?;
+    load_from_disk(local_paths, &mut raw_assets, progress)?;
+    parse_data_urls(data_urls, &mut raw_assets, progress)?;
     Ok(raw_assets)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn load_from_disk(paths: HashSet<PathBuf>, raw_assets: &mut RawAssets) -> Result<()> {
-    let mut handles = Vec::new();
-    for path in paths {
-        handles.push((
-            path.clone(),
-            std::thread::spawn(move || std::fs::read(path)),
-        ));
+fn load_from_disk(
+    paths: HashSet<PathBuf>,
+    raw_assets: &mut RawAssets,
+    progress: &mut Progress,
+) -> Result<()> {
+    #[cfg(feature = "glob")]
+    let pattern_count = paths.len();
+    #[cfg(feature = "glob")]
+    let paths = paths
+        .into_iter()
+        .flat_map(|path| {
+            if is_glob_pattern(&path) {
+                expand_glob_pattern(&path)
+            } else {
+                vec![path]
+            }
+        })
+        .collect::<HashSet<_>>();
+    #[cfg(feature = "glob")]
+    {
+        progress.total = (progress.total + paths.len()).saturating_sub(pattern_count);
     }
 
-    for (path, handle) in handles.drain(..) {
-        let bytes = handle
-            .join()
-            .unwrap()
-            .map_err(|e| Error::FailedLoading(path.to_str().unwrap().to_string(), e))?;
+    for (path, bytes) in read_files(paths) {
+        let bytes =
+            bytes.map_err(|e| Error::FailedLoading(path.to_str().unwrap().to_string(), e))?;
+        progress.report(&path);
         raw_assets.insert(path, bytes);
     }
     Ok(())
 }
 
+///
+/// Reads the files in parallel using a thread per file and returns the content of each file when it is read.
+///
+#[cfg(all(not(feature = "rayon"), not(target_arch = "wasm32")))]
+fn read_files(
+    paths: HashSet<PathBuf>,
+) -> impl Iterator<Item = (PathBuf, std::io::Result<Vec<u8>>)> {
+    let handles = paths
+        .into_iter()
+        .map(|path| {
+            (
+                path.clone(),
+                std::thread::spawn(move || std::fs::read(path)),
+            )
+        })
+        .collect::<Vec<_>>();
+    handles
+        .into_iter()
+        .map(|(path, handle)| (path, handle.join().unwrap()))
+}
+
+///
+/// Reads the files in parallel using the current rayon thread pool and returns the content of each file when all files are read.
+/// The files are read in a scope, so if this is called from a thread in the pool, that thread reads files while waiting instead of blocking the pool.
+///
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+fn read_files(
+    paths: HashSet<PathBuf>,
+) -> impl Iterator<Item = (PathBuf, std::io::Result<Vec<u8>>)> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    rayon_core::scope(|scope| {
+        for path in paths {
+            let sender = sender.clone();
+            scope.spawn(move |_| {
+                let bytes = std::fs::read(&path);
+                sender.send((path, bytes)).unwrap();
+            });
+        }
+    });
+    drop(sender);
+    receiver.into_iter()
+}
+
+#[cfg(all(feature = "glob", not(target_arch = "wasm32")))]
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_str()
+        .map(|s| s.contains(['*', '?']))
+        .unwrap_or(false)
+}
+
+///
+/// Returns the paths of all files on disk which match the given pattern, where `*` matches any sequence of characters within a path component,
+/// `?` matches any single character and a `**` component matches any number of nested directories.
+/// Symbolic links to directories are not followed by a `**` component, so a link cycle does not cause an infinite traversal.
+///
+#[cfg(all(feature = "glob", not(target_arch = "wasm32")))]
+fn expand_glob_pattern(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let part = component.as_os_str().to_str().unwrap();
+        if !part.contains(['*', '?']) {
+            paths.iter_mut().for_each(|path| path.push(component));
+            continue;
+        }
+        let mut matches = Vec::new();
+        for directory in paths {
+            if part == "**" {
+                let mut directories = vec![directory];
+                while let Some(directory) = directories.pop() {
+                    directories.extend(read_directory(&directory).filter(|path| {
+                        std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_dir())
+                    }));
+                    matches.push(directory);
+                }
+            } else {
+                matches.extend(read_directory(&directory).filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| matches_glob(part.as_bytes(), name.as_bytes()))
+                }));
+            }
+        }
+        paths = matches;
+    }
+    paths.retain(|path| path.is_file());
+    paths
+}
+
+#[cfg(all(feature = "glob", not(target_arch = "wasm32")))]
+fn read_directory(directory: &Path) -> impl Iterator<Item = PathBuf> + '_ {
+    let path = if directory.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        directory
+    };
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(move |entry| directory.join(entry.file_name()))
+}
+
+#[cfg(all(feature = "glob", not(target_arch = "wasm32")))]
+fn matches_glob(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The position in the pattern after the last `*` and the position in the name where the characters matched by that `*` end,
+    // which is where the matching continues with one more character matched by the `*` if the rest of the pattern does not match.
+    // It is enough to only go back to the last `*`, so the running time is at most the product of the lengths instead of exponential.
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                star = Some((p, n));
+            }
+            Some(c) if *c == b'?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, n));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
 #[allow(unused_variables)]
-async fn load_urls(paths: HashSet<PathBuf>, raw_assets: &mut RawAssets) -> Result<()> {
+async fn load_urls(
+    paths: HashSet<PathBuf>,
+    options: &RequestOptions,
+    raw_assets: &mut RawAssets,
+    progress: &mut Progress<'_>,
+) -> Result<()> {
     #[cfg(feature = "reqwest")]
-    if paths.len() > 0 {
+    if !paths.is_empty() {
         let mut handles = Vec::new();
         let client = reqwest::Client::new();
         for path in paths {
             let url = reqwest::Url::parse(path.to_str().unwrap())
                 .map_err(|_| Error::FailedParsingUrl(path.to_str().unwrap().to_string()))?;
-            handles.push((path, client.get(url).send().await));
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(bytes) = options.cache.as_ref().and_then(|c| c.get(url.as_str())) {
+                progress.report(&path);
+                raw_assets.insert(path, bytes);
+                continue;
+            }
+            handles.push((path, url_request(&client, options, url).send().await));
         }
         for (path, handle) in handles.drain(..) {
-            let bytes = handle
-                .map_err(|e| Error::FailedLoadingUrl(path.to_str().unwrap().to_string(), e))?
+            let response = handle
+                .map_err(|e| Error::FailedLoadingUrl(path.to_str().unwrap().to_string(), e))?;
+            if !response.status().is_success() {
+                Err(Error::FailedLoadingUrlStatus(
+                    path.to_str().unwrap().to_string(),
+                    response.status(),
+                ))?;
+            }
+            let bytes = response
                 .bytes()
                 .await
                 .map_err(|e| Error::FailedLoadingUrl(path.to_str().unwrap().to_string(), e))?
                 .to_vec();
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(cache) = &options.cache {
+                cache.store(path.to_str().unwrap(), &bytes);
+            }
+            progress.report(&path);
             raw_assets.insert(path, bytes);
         }
     }
@@ -156,16 +526,59 @@ async fn load_urls(paths: HashSet<PathBuf>, raw_assets: &mut RawAssets) -> Resul
     Ok(())
 }
 
-fn parse_data_urls(paths: HashSet<PathBuf>, raw_assets: &mut RawAssets) -> Result<()> {
+///
+/// Returns a request for downloading the url with the headers given for the url, see [url_headers].
+///
+#[cfg(feature = "reqwest")]
+fn url_request(
+    client: &reqwest::Client,
+    options: &RequestOptions,
+    url: reqwest::Url,
+) -> reqwest::RequestBuilder {
+    let mut request = client.get(url.clone());
+    for (name, value) in url_headers(&options.headers, &url) {
+        request = request.header(name, value);
+    }
+    request
+}
+
+///
+/// Returns the headers given for the url or otherwise for a url with the same origin.
+///
+#[cfg(feature = "reqwest")]
+fn url_headers<'a>(
+    headers: &'a [(PathBuf, Vec<(String, String)>)],
+    url: &reqwest::Url,
+) -> &'a [(String, String)] {
+    let parsed = || {
+        headers.iter().filter_map(|(path, headers)| {
+            reqwest::Url::parse(path.to_str()?)
+                .ok()
+                .map(|url| (url, headers))
+        })
+    };
+    parsed()
+        .find(|(u, _)| u == url)
+        .or_else(|| parsed().find(|(u, _)| u.origin() == url.origin()))
+        .map(|(_, headers)| headers.as_slice())
+        .unwrap_or(&[])
+}
+
+fn parse_data_urls(
+    paths: HashSet<PathBuf>,
+    raw_assets: &mut RawAssets,
+    progress: &mut Progress,
+) -> Result<()> {
     for path in paths {
         let bytes = parse_data_url(path.to_str().unwrap())?;
+        progress.report(&path);
         raw_assets.insert(path, bytes);
     }
     Ok(())
 }
 
 #[allow(unused_variables)]
-fn parse_data_url(path: &str) -> Result<Vec<u8>> {
+pub(super) fn parse_data_url(path: &str) -> Result<Vec<u8>> {
     #[cfg(feature = "data-url")]
     {
         let url = data_url::DataUrl::process(path)
@@ -188,14 +601,14 @@ fn is_absolute_url(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-fn is_data_url(path: &Path) -> bool {
+pub(super) fn is_data_url(path: &Path) -> bool {
     path.to_str()
         .map(|s| s.starts_with("data:"))
         .unwrap_or(false)
 }
 
 #[cfg(target_arch = "wasm32")]
-fn base_path() -> PathBuf {
+pub(super) fn base_path() -> PathBuf {
     let base_url = web_sys::window()
         .unwrap()
         .document()
@@ -212,6 +625,195 @@ fn base_path() -> PathBuf {
 #[cfg(test)]
 mod test {
 
+    #[test]
+    pub fn load_with_progress() {
+        let mut reported = Vec::new();
+        let loaded = super::load_with_progress(
+            &[
+                "test_data/test.png",
+                "test_data/test.jpg",
+                "test_data/test.png",
+            ],
+            |loaded, total, path| reported.push((loaded, total, path.to_path_buf())),
+        )
+        .unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(reported.len(), 2);
+        assert_eq!((reported[0].0, reported[0].1), (1, 2));
+        assert_eq!((reported[1].0, reported[1].1), (2, 2));
+        assert!(reported.iter().all(|(_, _, path)| loaded.contains(path)));
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    pub fn load_with_progress_dependencies() {
+        let mut reported = Vec::new();
+        let loaded = super::load_with_progress(&["test_data/Cube.gltf"], |loaded, total, _| {
+            reported.push((loaded, total))
+        })
+        .unwrap();
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(reported, vec![(1, 1), (2, 4), (3, 4), (4, 4)]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    pub fn load_in_single_threaded_pool() {
+        let pool = rayon_core::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let loaded = pool
+            .install(|| {
+                super::load(&[
+                    "test_data/Cube_BaseColor.png",
+                    "test_data/Cube_MetallicRoughness.png",
+                    "test_data/test.jpg",
+                ])
+            })
+            .unwrap();
+        assert_eq!(loaded.len(), 3);
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    pub fn load_glob() {
+        let loaded = super::load(&["test_data/Cube_*.png"]).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.contains("test_data/Cube_BaseColor.png"));
+        assert!(loaded.contains("test_data/Cube_MetallicRoughness.png"));
+
+        let loaded = super::load(&["test_data/**/Cube.gl?f"]).unwrap();
+        assert!(loaded.contains("test_data/Cube.gltf"));
+        assert!(loaded.contains("test_data/Cube.bin"));
+
+        assert!(super::load(&["test_data/*.missing"]).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    pub fn matches_glob() {
+        use super::matches_glob;
+        for (pattern, name) in [
+            ("*", ""),
+            ("*", "abc"),
+            ("a*c", "abbbc"),
+            ("a?c", "abc"),
+            ("*.png", "test.png"),
+            ("*b*b*", "abab"),
+            ("**.png", "a.png"),
+            ("a*", "a"),
+        ] {
+            assert!(
+                matches_glob(pattern.as_bytes(), name.as_bytes()),
+                "{pattern} {name}"
+            );
+        }
+        for (pattern, name) in [
+            ("", "a"),
+            ("?", ""),
+            ("a*c", "abcd"),
+            ("*.png", "test.jpg"),
+            ("a?c", "ac"),
+            ("*b*b*", "ab"),
+        ] {
+            assert!(
+                !matches_glob(pattern.as_bytes(), name.as_bytes()),
+                "{pattern} {name}"
+            );
+        }
+        // Many stars which do not match is fast, since the matching does not backtrack to earlier stars
+        let pattern = "*a".repeat(50) + "b";
+        assert!(!matches_glob(
+            pattern.as_bytes(),
+            "a".repeat(200).as_bytes()
+        ));
+    }
+
+    #[cfg(all(feature = "glob", unix))]
+    #[test]
+    pub fn load_glob_with_symlink_cycle() {
+        let directory =
+            std::env::temp_dir().join(format!("three-d-asset-glob-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("nested")).unwrap();
+        std::fs::write(directory.join("nested").join("file.txt"), b"file").unwrap();
+        std::os::unix::fs::symlink(&directory, directory.join("nested").join("cycle")).unwrap();
+        let loaded = super::load(&[directory.join("**").join("*.txt")]);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains(directory.join("nested").join("file.txt")));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    pub fn url_request_headers() {
+        let options = super::RequestOptions {
+            headers: vec![
+                (
+                    "https://example.com/".into(),
+                    vec![
+                        ("Authorization".to_owned(), "origin".to_owned()),
+                        ("X-Other".to_owned(), "1".to_owned()),
+                    ],
+                ),
+                (
+                    "https://example.com/a.png".into(),
+                    vec![("Authorization".to_owned(), "exact".to_owned())],
+                ),
+            ],
+            ..Default::default()
+        };
+        let client = reqwest::Client::new();
+        let headers = |url: &str| {
+            super::url_request(&client, &options, reqwest::Url::parse(url).unwrap())
+                .build()
+                .unwrap()
+                .headers()
+                .clone()
+        };
+
+        // The headers of the exact url are used before the headers of a url with the same origin
+        let exact = headers("https://example.com/a.png");
+        assert_eq!(exact.get("Authorization").unwrap(), "exact");
+        assert!(exact.get("X-Other").is_none());
+
+        let same_origin = headers("https://example.com/textures/b.png");
+        assert_eq!(same_origin.get("Authorization").unwrap(), "origin");
+        assert_eq!(same_origin.get("X-Other").unwrap(), "1");
+
+        for url in [
+            "https://other.com/a.png",
+            "http://example.com/a.png",
+            "https://example.com:8080/a.png",
+        ] {
+            let other_origin = headers(url);
+            assert!(other_origin.get("Authorization").is_none(), "{url}");
+            assert!(other_origin.get("X-Other").is_none(), "{url}");
+        }
+    }
+
+    #[cfg(feature = "data-url")]
+    #[test]
+    pub fn load_percent_encoded_data_url() {
+        let data_url = "data:text/plain;charset=utf-8,hello%20world%21";
+        let loaded = super::load(&[data_url]).unwrap();
+        assert_eq!(loaded.get(data_url).unwrap(), b"hello world!");
+        assert_eq!(loaded.mime_type(data_url), Some("text/plain"));
+    }
+
+    #[cfg(all(feature = "data-url", feature = "svg"))]
+    #[test]
+    pub fn load_svg_data_url() {
+        let data_url = "data:image/svg+xml,%3Csvg%20xmlns%3D%22http%3A%2F%2Fwww.w3.org%2F2000%2Fsvg%22%20width%3D%224%22%20height%3D%222%22%3E%3C%2Fsvg%3E";
+        let texture: crate::Texture2D = super::load(&[data_url])
+            .unwrap()
+            .deserialize(data_url)
+            .unwrap();
+        assert_eq!((texture.width, texture.height), (4, 2));
+    }
+
     #[cfg(feature = "data-url")]
     #[test]
     pub fn load_data_url() {
@@ -0,0 +1,188 @@
+//!
+//! Functionality for loading assets by memory mapping files.
+//!
+
+use crate::{
+    io::{file_dependencies, raw_assets::find_matching_path, Deserialize, RawAssets},
+    Error, Result,
+};
+use memmap2::Mmap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+///
+/// Loads all of the files in the given paths by memory mapping them and returns the [MappedAssets].
+/// If a file depends on other files, for example the buffers and textures of a glTF file, those files are also loaded.
+///
+/// In contrast to [load](crate::io::load), the files are not read into owned buffers up front. Instead the operating system reads the pages of a file when they are accessed
+/// and can release them again when memory is needed elsewhere, which reduces the peak memory usage when loading large assets.
+///
+/// **Note:** A memory mapped file must not be modified or truncated by this or another process while the [MappedAssets] are alive.
+///
+/// ```
+/// # use three_d_asset::io::*;
+/// let assets = load_mmap(&["test_data/Cube_BaseColor.png"]).unwrap();
+/// let png_bytes = assets.get("Cube_BaseColor.png").unwrap();
+/// ```
+///
+pub fn load_mmap(paths: &[impl AsRef<Path>]) -> Result<MappedAssets> {
+    let mut mapped_assets = MappedAssets::default();
+    let mut paths = paths
+        .iter()
+        .map(|path| path.as_ref().to_path_buf())
+        .collect::<HashSet<_>>();
+    while !paths.is_empty() {
+        let mut dependencies = HashSet::new();
+        for path in paths {
+            let file = map_file(&path)
+                .map_err(|e| Error::FailedLoading(path.to_str().unwrap().to_string(), e))?;
+            dependencies.extend(file_dependencies(&path, &file));
+            mapped_assets.assets.insert(path, file);
+        }
+        paths = dependencies
+            .into_iter()
+            .filter(|path| !mapped_assets.assets.contains_key(path))
+            .collect();
+    }
+    Ok(mapped_assets)
+}
+
+///
+/// Contains memory mapped assets which are generated by the [load_mmap] function.
+///
+/// Use the [MappedAssets::get] function to access the raw byte array of an asset without copying it
+/// or [MappedAssets::deserialize] to deserialize an asset.
+///
+#[derive(Default)]
+pub struct MappedAssets {
+    assets: HashMap<PathBuf, Mmap>,
+}
+
+impl MappedAssets {
+    ///
+    /// Returns a reference to the raw byte array for the resource at the given path.
+    /// The path is matched in the same way as in [RawAssets::get].
+    ///
+    pub fn get(&self, path: impl AsRef<Path>) -> Result<&[u8]> {
+        let path = self.match_path(path.as_ref())?;
+        Ok(&self.assets[path])
+    }
+
+    ///
+    /// Returns an iterator over the paths of the assets.
+    ///
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.assets.keys().map(|path| path.as_path())
+    }
+
+    ///
+    /// Returns an iterator over the paths and raw byte arrays of the assets.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &[u8])> {
+        self.assets
+            .iter()
+            .map(|(path, file)| (path.as_path(), &file[..]))
+    }
+
+    ///
+    /// Returns the number of assets.
+    ///
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    ///
+    /// Returns whether there are no assets.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    ///
+    /// Deserialize the asset with the given path into a type that implements the [Deserialize] trait.
+    /// The path is matched in the same way as in [RawAssets::deserialize].
+    ///
+    /// **Note:** The deserializers take ownership of the bytes they parse, so the asset and the files it depends on are copied into a [RawAssets]
+    /// for the duration of the deserialization, while the rest of the assets stay mapped.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// # use three_d_asset::Texture2D;
+    /// let assets = load_mmap(&["test_data/Cube_BaseColor.png"]).unwrap();
+    /// # #[cfg(feature = "png")]
+    /// let texture: Texture2D = assets.deserialize("Cube_BaseColor.png").unwrap();
+    /// ```
+    ///
+    pub fn deserialize<T: Deserialize>(&self, path: impl AsRef<Path>) -> Result<T> {
+        let path = self.match_path(path.as_ref())?;
+        let mut raw_assets = RawAssets::new();
+        let mut paths = vec![path];
+        while let Some(path) = paths.pop() {
+            if raw_assets.contains_key(path) {
+                continue;
+            }
+            let bytes = &self.assets[path];
+            paths.extend(
+                file_dependencies(path, bytes)
+                    .iter()
+                    .filter_map(|dependency| self.assets.get_key_value(dependency))
+                    .map(|(dependency, _)| dependency.as_path()),
+            );
+            raw_assets.insert(path, bytes.to_vec());
+        }
+        raw_assets.deserialize(path)
+    }
+
+    fn match_path(&self, path: &Path) -> Result<&Path> {
+        if let Some((key, _)) = self.assets.get_key_value(path) {
+            return Ok(key);
+        }
+        find_matching_path(self.assets.keys().map(|key| key.as_path()), path, false)
+            .ok_or_else(|| Error::NotLoaded(path.to_str().unwrap().to_string()))
+    }
+}
+
+impl std::fmt::Debug for MappedAssets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("MappedAssets");
+        for (key, value) in self.assets.iter() {
+            d.field("path", key);
+            d.field("byte length", &value.len());
+        }
+        d.finish()
+    }
+}
+
+fn map_file(path: &Path) -> std::io::Result<Mmap> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: The mapping is read-only, and the documentation of load_mmap requires that the file is not modified while it is mapped.
+    unsafe { Mmap::map(&file) }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    pub fn load_mmap() {
+        let assets = super::load_mmap(&["test_data/Cube_BaseColor.png"]).unwrap();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(
+            assets.get("Cube_BaseColor.png").unwrap(),
+            std::fs::read("test_data/Cube_BaseColor.png").unwrap()
+        );
+        assert!(assets.get("missing.png").is_err());
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    pub fn deserialize_mmap_gltf() {
+        let assets = super::load_mmap(&["test_data/Cube.gltf"]).unwrap();
+        assert!(assets.get("Cube.bin").is_ok());
+        assert!(assets.get("Cube_BaseColor.png").is_ok());
+
+        let model: crate::Model = assets.deserialize("Cube.gltf").unwrap();
+        assert_eq!(model.geometries.len(), 1);
+        // The mapped assets are unchanged and can be deserialized again
+        let model: crate::Model = assets.deserialize("Cube.gltf").unwrap();
+        assert_eq!(model.geometries.len(), 1);
+    }
+}
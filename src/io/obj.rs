@@ -1,12 +1,18 @@
-use crate::{geometry::*, io::RawAssets, material::*, Node, Result, Scene};
+use crate::{
+    geometry::*,
+    io::{RawAssets, Serialize},
+    material::*,
+    Node, Result, Scene,
+};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-pub fn dependencies_obj(raw_assets: &RawAssets, path: &PathBuf) -> HashSet<PathBuf> {
+pub fn dependencies_obj(path: &Path, bytes: &[u8]) -> HashSet<PathBuf> {
     let mut dependencies = HashSet::new();
-    if let Ok(Ok(obj)) =
-        std::str::from_utf8(raw_assets.get(path).unwrap()).map(|s| wavefront_obj::obj::parse(s))
-    {
+    if let Ok(Ok(obj)) = std::str::from_utf8(bytes).map(|source| {
+        wavefront_obj::obj::parse(resolve_relative_indices(&extract_vertex_colors(source).0))
+    }) {
         let base_path = path.parent().unwrap_or(Path::new(""));
         if let Some(material_library) = obj.material_library {
             dependencies.insert(base_path.join(material_library));
@@ -15,135 +21,1029 @@ pub fn dependencies_obj(raw_assets: &RawAssets, path: &PathBuf) -> HashSet<PathB
     dependencies
 }
 
-pub fn dependencies_mtl(raw_assets: &RawAssets, path: &PathBuf) -> HashSet<PathBuf> {
+pub fn dependencies_mtl(path: &Path, bytes: &[u8]) -> HashSet<PathBuf> {
     let mut dependencies = HashSet::new();
     if let Ok(Ok(materials)) =
-        std::str::from_utf8(raw_assets.get(path).unwrap()).map(|s| wavefront_obj::mtl::parse(s))
+        std::str::from_utf8(bytes).map(|source| parse_mtl(source, &mut Vec::new()))
     {
         let base_path = path.parent().unwrap_or(Path::new(""));
-        for material in materials.materials {
-            material
-                .ambient_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .diffuse_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .specular_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .specular_exponent_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .displacement_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .dissolve_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .decal_map
-                .map(|p| dependencies.insert(base_path.join(p)));
-            material
-                .bump_map
-                .map(|p| dependencies.insert(base_path.join(p)));
+        for material in materials {
+            dependencies.extend(material.maps().map(|p| base_path.join(p)));
         }
     }
     dependencies
 }
 
-pub fn deserialize_obj(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Scene> {
-    let obj_bytes = raw_assets.remove(path)?;
-    let obj = wavefront_obj::obj::parse(std::str::from_utf8(&obj_bytes).unwrap())?;
+pub fn deserialize_mtl(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Vec<PbrMaterial>> {
+    let bytes = raw_assets.remove(path)?;
     let p = path.parent().unwrap_or(Path::new(""));
 
-    // Parse materials
+    let mut warnings = Vec::new();
+    let mtl_materials = parse_mtl(std::str::from_utf8(&bytes).unwrap(), &mut warnings)?;
+    for warning in warnings {
+        raw_assets.warn(path, warning);
+    }
     let mut materials = Vec::new();
-    if let Some(material_library) = obj.material_library {
-        let bytes = raw_assets.remove(p.join(material_library).to_str().unwrap())?;
-        for material in wavefront_obj::mtl::parse(std::str::from_utf8(&bytes).unwrap())?.materials {
-            let color = if material.color_diffuse.r != material.color_diffuse.g
-                || material.color_diffuse.g != material.color_diffuse.b
-            {
-                material.color_diffuse
-            } else if material.color_specular.r != material.color_specular.g
-                || material.color_specular.g != material.color_specular.b
-            {
-                material.color_specular
-            } else if material.color_ambient.r != material.color_ambient.g
-                || material.color_ambient.g != material.color_ambient.b
-            {
-                material.color_ambient
-            } else {
-                material.color_diffuse
-            };
+    for material in mtl_materials {
+        let color = if material.color_diffuse[0] != material.color_diffuse[1]
+            || material.color_diffuse[1] != material.color_diffuse[2]
+        {
+            material.color_diffuse
+        } else if material.color_specular[0] != material.color_specular[1]
+            || material.color_specular[1] != material.color_specular[2]
+        {
+            material.color_specular
+        } else if material.color_ambient[0] != material.color_ambient[1]
+            || material.color_ambient[1] != material.color_ambient[2]
+        {
+            material.color_ambient
+        } else {
+            material.color_diffuse
+        };
 
-            let normal_texture = if let Some(ref texture_name) = material.bump_map {
-                Some(raw_assets.deserialize(p.join(texture_name))?)
-            } else {
-                None
-            };
-            let albedo_texture = if let Some(ref texture_name) = material.diffuse_map {
-                Some(raw_assets.deserialize(p.join(texture_name))?)
+        let mut texture = |map: &Option<MtlMap>| -> Result<Option<Texture2D>> {
+            map.as_ref()
+                .map(|map| {
+                    let mut texture: Texture2D = raw_assets.deserialize(p.join(&map.path))?;
+                    if map.clamp {
+                        texture.wrap_s = Wrapping::ClampToEdge;
+                        texture.wrap_t = Wrapping::ClampToEdge;
+                    }
+                    Ok(texture)
+                })
+                .transpose()
+        };
+        let normal_texture = texture(&material.bump_map)?;
+        let albedo_texture = texture(&material.diffuse_map)?;
+        let alpha_texture = texture(&material.dissolve_map)?;
+        let transform =
+            |map: &Option<MtlMap>| map.as_ref().map(|map| map.transform).unwrap_or_default();
+
+        let alpha = material
+            .dissolve
+            .or(material.transparency.map(|t| 1.0 - t))
+            .unwrap_or(1.0);
+        let specular = material.color_specular.iter().sum::<f32>() / 3.0;
+        let roughness = if material.specular_exponent > 0.1 {
+            (1.999 / material.specular_exponent).sqrt().min(1.0)
+        } else {
+            1.0
+        };
+        let (metallic, roughness) = match material.illumination {
+            // Highlights off
+            0 | 1 => (0.0, 1.0),
+            // Reflections on, but no refraction
+            3 | 5 | 8 => (1.0, roughness),
+            // Refraction on, ie. glass
+            4 | 6 | 7 | 9 => (0.0, roughness),
+            _ => (specular, roughness),
+        };
+
+        let mut pbr_material = PbrMaterial {
+            name: material.name,
+            albedo: Color::from_rgba_slice(&[color[0], color[1], color[2], alpha]),
+            albedo_texture,
+            albedo_texture_transform: transform(&material.diffuse_map),
+            alpha_mode: if alpha < 1.0 || alpha_texture.is_some() {
+                AlphaMode::Blend
             } else {
-                None
-            };
+                AlphaMode::Opaque
+            },
+            alpha_texture,
+            alpha_texture_transform: transform(&material.dissolve_map),
+            metallic,
+            roughness,
+            normal_texture,
+            normal_texture_transform: transform(&material.bump_map),
+            normal_scale: material
+                .bump_map
+                .as_ref()
+                .map(|map| map.bump_multiplier)
+                .unwrap_or(1.0),
+            index_of_refraction: material.optical_density.unwrap_or(1.5),
+            lighting_model: LightingModel::Blinn,
+            ..Default::default()
+        };
+        pbr_material.assign_texture_color_spaces();
+        materials.push(pbr_material);
+    }
+    Ok(materials)
+}
 
-            materials.push(PbrMaterial {
-                name: material.name,
-                albedo: Color::from_rgba_slice(&[
-                    color.r as f32,
-                    color.g as f32,
-                    color.b as f32,
-                    material.alpha as f32,
-                ]),
-                albedo_texture,
-                metallic: ((material.color_specular.r
-                    + material.color_specular.g
-                    + material.color_specular.b)
-                    / 3.0) as f32,
-                roughness: if material.specular_coefficient > 0.1 {
-                    ((1.999 / material.specular_coefficient).sqrt() as f32).min(1.0)
-                } else {
-                    1.0
-                },
-                normal_texture,
-                lighting_model: LightingModel::Blinn,
-                ..Default::default()
-            });
+///
+/// Removes the optional colors from vertex statements on the form `v x y z r g b` since they are not supported by the obj parser.
+/// Returns the source without vertex colors together with the color of each vertex, if any of the vertices has a color.
+///
+fn extract_vertex_colors(source: &str) -> (Cow<'_, str>, Option<Vec<Option<Color>>>) {
+    let mut vertex_colors = Vec::new();
+    let mut stripped = String::with_capacity(source.len());
+    for line in source.lines() {
+        let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+        if tokens.next() == Some("v") {
+            let values = tokens.collect::<Vec<_>>();
+            if values.len() >= 6 {
+                let (position, color) = values.split_at(values.len() - 3);
+                if let Ok(color) = color
+                    .iter()
+                    .map(|v| v.parse::<f32>())
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                {
+                    let [r, g, b] = [color[0], color[1], color[2]]
+                        .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    vertex_colors.push(Some(Color::new_opaque(r, g, b)));
+                    stripped.push_str("v ");
+                    stripped.push_str(&position.join(" "));
+                    stripped.push('\n');
+                    continue;
+                }
+            }
+            vertex_colors.push(None);
+        }
+        stripped.push_str(line);
+        stripped.push('\n');
+    }
+    if vertex_colors.iter().any(|c| c.is_some()) {
+        (Cow::Owned(stripped), Some(vertex_colors))
+    } else {
+        (Cow::Borrowed(source), None)
+    }
+}
+
+///
+/// Replaces negative indices in face and line statements, which are relative to the end of the vertex, texture coordinate or normal list at that point in the file,
+/// with the corresponding absolute indices since they are not supported by the obj parser.
+///
+fn resolve_relative_indices(source: &str) -> Cow<'_, str> {
+    if !source.lines().any(|line| {
+        let mut tokens = line.split_whitespace();
+        matches!(tokens.next(), Some("f") | Some("l")) && tokens.any(|t| t.contains('-'))
+    }) {
+        return Cow::Borrowed(source);
+    }
+    let mut counts = [0i64; 3];
+    let mut resolved = String::with_capacity(source.len());
+    for line in source.lines() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => counts[0] += 1,
+            Some("vt") => counts[1] += 1,
+            Some("vn") => counts[2] += 1,
+            Some(keyword @ ("f" | "l")) => {
+                resolved.push_str(keyword);
+                for token in tokens {
+                    resolved.push(' ');
+                    let indices = token
+                        .split('/')
+                        .zip(counts)
+                        .map(|(index, count)| match index.parse::<i64>() {
+                            Ok(index) if index < 0 => (count + index + 1).to_string(),
+                            _ => index.to_string(),
+                        })
+                        .collect::<Vec<_>>();
+                    resolved.push_str(&indices.join("/"));
+                }
+                resolved.push('\n');
+                continue;
+            }
+            _ => {}
+        }
+        resolved.push_str(line);
+        resolved.push('\n');
+    }
+    Cow::Owned(resolved)
+}
+
+///
+/// Replaces face statements with more than three vertices with a face statement for each triangle in a triangulation of the polygon,
+/// since the obj parser only supports convex polygons.
+/// Convex polygons are triangulated using a triangle fan and non-convex polygons using ear clipping.
+/// The face indices are assumed to be absolute, see [resolve_relative_indices].
+///
+fn triangulate_polygons(source: &str) -> Cow<'_, str> {
+    let face_vertex_count = |line: &str| {
+        let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+        if tokens.next() == Some("f") {
+            tokens.count()
+        } else {
+            0
+        }
+    };
+    if !source.lines().any(|line| face_vertex_count(line) > 3) {
+        return Cow::Borrowed(source);
+    }
+    let mut positions = Vec::new();
+    let mut triangulated = String::with_capacity(source.len());
+    for line in source.lines() {
+        let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let mut values = tokens.map(|v| v.parse::<f64>().unwrap_or(0.0));
+                positions.push(vec3(
+                    values.next().unwrap_or(0.0),
+                    values.next().unwrap_or(0.0),
+                    values.next().unwrap_or(0.0),
+                ));
+            }
+            Some("f") if face_vertex_count(line) > 3 => {
+                let corners = tokens.collect::<Vec<_>>();
+                let polygon = corners
+                    .iter()
+                    .map(|corner| {
+                        corner
+                            .split('/')
+                            .next()
+                            .and_then(|i| i.parse::<usize>().ok())
+                            .and_then(|i| positions.get(i.wrapping_sub(1)))
+                            .copied()
+                    })
+                    .collect::<Option<Vec<_>>>();
+                // Let the obj parser report invalid indices
+                let Some(polygon) = polygon else {
+                    triangulated.push_str(line);
+                    triangulated.push('\n');
+                    continue;
+                };
+                for [i0, i1, i2] in triangulate_polygon(&polygon) {
+                    triangulated.push_str(&format!(
+                        "f {} {} {}\n",
+                        corners[i0], corners[i1], corners[i2]
+                    ));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        triangulated.push_str(line);
+        triangulated.push('\n');
+    }
+    Cow::Owned(triangulated)
+}
+
+///
+/// Returns the triangles, as indices into the given polygon, of a triangulation of a planar polygon.
+/// The triangles have the same winding order as the polygon.
+///
+fn triangulate_polygon(polygon: &[Vector3<f64>]) -> Vec<[usize; 3]> {
+    // Project the polygon onto the plane defined by the dominant axis of the polygon normal, computed using Newell's method
+    let normal = (0..polygon.len()).fold(Vector3::zero(), |normal, i| {
+        normal + polygon[i].cross(polygon[(i + 1) % polygon.len()])
+    });
+    let (a, b) = if normal.x.abs() > normal.y.abs() && normal.x.abs() > normal.z.abs() {
+        (1, 2)
+    } else if normal.y.abs() > normal.z.abs() {
+        (2, 0)
+    } else {
+        (0, 1)
+    };
+    let points = polygon.iter().map(|p| vec2(p[a], p[b])).collect::<Vec<_>>();
+    // The sign of the area of the projected polygon, so that convex corners have a positive cross product
+    let sign = (0..points.len())
+        .map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+        .sum::<f64>()
+        .signum();
+    let is_convex = |i0: usize, i1: usize, i2: usize| {
+        sign * (points[i1] - points[i0]).perp_dot(points[i2] - points[i1]) > 0.0
+    };
+
+    let mut remaining = (0..polygon.len()).collect::<Vec<_>>();
+    let n = remaining.len();
+    if (0..n).all(|i| is_convex(i, (i + 1) % n, (i + 2) % n)) {
+        return (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    }
+
+    let is_inside = |p: Vector2<f64>, i0: usize, i1: usize, i2: usize| {
+        let edge = |a: Vector2<f64>, b: Vector2<f64>| sign * (b - a).perp_dot(p - a) >= 0.0;
+        edge(points[i0], points[i1]) && edge(points[i1], points[i2]) && edge(points[i2], points[i0])
+    };
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let [i0, i1, i2] = [
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            ];
+            is_convex(i0, i1, i2)
+                && !remaining
+                    .iter()
+                    .filter(|&&j| j != i0 && j != i1 && j != i2)
+                    .any(|&j| is_inside(points[j], i0, i1, i2))
+        });
+        // Fall back to clipping any corner if the polygon is degenerate
+        let i = ear.unwrap_or(0);
+        triangles.push([
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+///
+/// A material as specified in a MTL file.
+///
+struct MtlMaterial {
+    name: String,
+    specular_exponent: f32,
+    color_ambient: [f32; 3],
+    color_diffuse: [f32; 3],
+    color_specular: [f32; 3],
+    optical_density: Option<f32>,
+    dissolve: Option<f32>,
+    transparency: Option<f32>,
+    illumination: u32,
+    ambient_map: Option<MtlMap>,
+    diffuse_map: Option<MtlMap>,
+    specular_map: Option<MtlMap>,
+    specular_exponent_map: Option<MtlMap>,
+    dissolve_map: Option<MtlMap>,
+    displacement_map: Option<MtlMap>,
+    decal_map: Option<MtlMap>,
+    bump_map: Option<MtlMap>,
+}
+
+///
+/// A texture map in a MTL file together with the supported options.
+///
+struct MtlMap {
+    path: String,
+    transform: TextureTransform,
+    bump_multiplier: f32,
+    clamp: bool,
+}
+
+impl MtlMaterial {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            specular_exponent: 0.0,
+            color_ambient: [0.0; 3],
+            color_diffuse: [1.0; 3],
+            color_specular: [0.0; 3],
+            optical_density: None,
+            dissolve: None,
+            transparency: None,
+            illumination: 2,
+            ambient_map: None,
+            diffuse_map: None,
+            specular_map: None,
+            specular_exponent_map: None,
+            dissolve_map: None,
+            displacement_map: None,
+            decal_map: None,
+            bump_map: None,
+        }
+    }
+
+    fn maps(&self) -> impl Iterator<Item = &String> {
+        [
+            &self.ambient_map,
+            &self.diffuse_map,
+            &self.specular_map,
+            &self.specular_exponent_map,
+            &self.dissolve_map,
+            &self.displacement_map,
+            &self.decal_map,
+            &self.bump_map,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|map| &map.path)
+    }
+}
+
+///
+/// Parses the materials in a MTL file. The statements of a material can be in any order and unsupported statements are ignored.
+/// A warning is added for each unsupported keyword and texture map option.
+///
+fn parse_mtl(source: &str, warnings: &mut Vec<String>) -> Result<Vec<MtlMaterial>> {
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+    let mut unsupported_keywords = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| {
+            crate::Error::Obj(wavefront_obj::ParseError {
+                line_number: i + 1,
+                message,
+            })
+        };
+        let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let arguments = tokens.collect::<Vec<_>>();
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial::new(arguments.join(" ")));
+            continue;
+        }
+        let material = materials
+            .last_mut()
+            .ok_or_else(|| error(format!("Expected `newmtl` but got {}.", keyword)))?;
+        let float = |i: usize| {
+            arguments
+                .get(i)
+                .and_then(|a| a.parse::<f32>().ok())
+                .ok_or_else(|| error(format!("Expected a number after {}.", keyword)))
+        };
+        // Options are placed in front of the value, so the value is the last argument
+        let last = || {
+            arguments
+                .last()
+                .map(|a| a.to_string())
+                .ok_or_else(|| error(format!("Expected a value after {}.", keyword)))
+        };
+        let color = || -> Result<[f32; 3]> {
+            let r = float(0)?;
+            // If only the red component is specified, the green and blue components are the same
+            Ok([r, float(1).unwrap_or(r), float(2).unwrap_or(r)])
+        };
+        match keyword {
+            "Ns" => material.specular_exponent = float(0)?,
+            "Ka" => material.color_ambient = color()?,
+            "Kd" => material.color_diffuse = color()?,
+            "Ks" => material.color_specular = color()?,
+            "Ni" => material.optical_density = Some(float(0)?),
+            "d" => {
+                material.dissolve = Some(
+                    last()?
+                        .parse()
+                        .map_err(|_| error("Expected a number after d.".to_owned()))?,
+                )
+            }
+            "Tr" => material.transparency = Some(float(0)?),
+            "illum" => {
+                material.illumination = last()?
+                    .parse()
+                    .map_err(|_| error("Expected an integer after illum.".to_owned()))?
+            }
+            "map_Ka" => material.ambient_map = Some(parse_mtl_map(&arguments, error, warnings)?),
+            "map_Kd" => material.diffuse_map = Some(parse_mtl_map(&arguments, error, warnings)?),
+            "map_Ks" => material.specular_map = Some(parse_mtl_map(&arguments, error, warnings)?),
+            "map_Ns" => {
+                material.specular_exponent_map = Some(parse_mtl_map(&arguments, error, warnings)?)
+            }
+            "map_d" => material.dissolve_map = Some(parse_mtl_map(&arguments, error, warnings)?),
+            "disp" => material.displacement_map = Some(parse_mtl_map(&arguments, error, warnings)?),
+            "decal" => material.decal_map = Some(parse_mtl_map(&arguments, error, warnings)?),
+            "bump" | "map_bump" | "map_Bump" => {
+                material.bump_map = Some(parse_mtl_map(&arguments, error, warnings)?)
+            }
+            _ => {
+                if !unsupported_keywords.contains(&keyword) {
+                    unsupported_keywords.push(keyword);
+                    warnings.push(format!("unsupported {} statements are ignored", keyword));
+                }
+            }
+        }
+    }
+    Ok(materials)
+}
+
+///
+/// Parses the arguments of a texture map statement, ie. a number of options followed by the path to the texture.
+/// The `-o`, `-s`, `-bm` and `-clamp` options are supported, other options are skipped and unknown options add a warning.
+///
+fn parse_mtl_map(
+    arguments: &[&str],
+    error: impl Fn(String) -> crate::Error,
+    warnings: &mut Vec<String>,
+) -> Result<MtlMap> {
+    let (path, mut options) = arguments
+        .split_last()
+        .ok_or_else(|| error("Expected a texture path.".to_owned()))?;
+    let mut offset = [0.0, 0.0];
+    let mut scale = [1.0, 1.0];
+    let mut bump_multiplier = 1.0;
+    let mut clamp = false;
+    while let Some((option, rest)) = options.split_first() {
+        // The number of values of an option which takes between one and three numbers
+        let count = rest
+            .iter()
+            .take(3)
+            .take_while(|v| v.parse::<f32>().is_ok())
+            .count();
+        let values = rest[..count]
+            .iter()
+            .map(|v| v.parse::<f32>().unwrap())
+            .collect::<Vec<_>>();
+        let value = |i: usize| {
+            values
+                .get(i)
+                .copied()
+                .ok_or_else(|| error(format!("Expected a number after {}.", option)))
+        };
+        let value_count = match *option {
+            "-o" => {
+                offset = [value(0)?, values.get(1).copied().unwrap_or(0.0)];
+                count
+            }
+            "-s" => {
+                scale = [value(0)?, values.get(1).copied().unwrap_or(1.0)];
+                count
+            }
+            "-t" => count,
+            "-bm" => {
+                bump_multiplier = value(0)?;
+                1
+            }
+            "-clamp" => {
+                clamp = rest.first() == Some(&"on");
+                1
+            }
+            "-mm" => 2,
+            "-blendu" | "-blendv" | "-boost" | "-cc" | "-texres" | "-imfchan" | "-type" => 1,
+            _ => {
+                let count = rest
+                    .iter()
+                    .take_while(|v| !v.starts_with('-') || v.parse::<f32>().is_ok())
+                    .count();
+                warnings.push(format!(
+                    "unknown texture map option {} {} is skipped",
+                    option,
+                    rest[..count].join(" ")
+                ));
+                count
+            }
+        };
+        options = &rest[value_count.min(rest.len())..];
+    }
+    // The uv coordinates are flipped vertically when loaded, so the transformation is flipped as well
+    Ok(MtlMap {
+        path: path.to_string(),
+        transform: TextureTransform {
+            offset: vec2(offset[0], 1.0 - scale[1] - offset[1]),
+            rotation: 0.0,
+            scale: vec2(scale[0], scale[1]),
+        },
+        bump_multiplier,
+        clamp,
+    })
+}
+
+///
+/// Options for deserializing an .obj file (see [DeserializeWithOptions](crate::io::DeserializeWithOptions)).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObjOptions {
+    /// Whether or not to merge the groups specified with `g` statements in an object.
+    /// If false, each group results in a separate [Node] named after the group,
+    /// otherwise each object, specified with an `o` statement, results in one [Node] per material named after the object.
+    pub merge_groups: bool,
+}
+
+pub fn deserialize_obj(
+    raw_assets: &mut RawAssets,
+    path: &PathBuf,
+    options: ObjOptions,
+) -> Result<Scene> {
+    let obj_bytes = raw_assets.remove(path)?;
+    let (source, vertex_colors) = extract_vertex_colors(std::str::from_utf8(&obj_bytes).unwrap());
+    let source = resolve_relative_indices(&source);
+    let source = triangulate_polygons(&source);
+    let obj = wavefront_obj::obj::parse(&source)?;
+    let p = path.parent().unwrap_or(Path::new(""));
+    // Normals are only generated from smoothing groups if the file specifies smoothing groups
+    let has_smoothing_groups = source
+        .lines()
+        .any(|line| line.split_whitespace().next() == Some("s"));
+    if let Some(vertex_colors) = vertex_colors.as_ref() {
+        let count = vertex_colors.iter().filter(|c| c.is_none()).count();
+        if count > 0 {
+            raw_assets.warn(
+                path,
+                format!(
+                    "{} of {} vertices have no color, white is used instead",
+                    count,
+                    vertex_colors.len()
+                ),
+            );
         }
     }
 
+    // Parse materials
+    let materials = if let Some(material_library) = obj.material_library {
+        deserialize_mtl(raw_assets, &p.join(material_library))?
+    } else {
+        Vec::new()
+    };
+
     // Parse meshes
     let mut nodes = Vec::new();
+    let mut vertex_offset = 0;
     for object in obj.objects.iter() {
-        // Objects consisting of several meshes with different materials
-        for mesh in object.geometry.iter() {
-            // All meshes with different materials
+        nodes.extend(parse_object(
+            object,
+            vertex_colors
+                .as_ref()
+                .map(|colors| &colors[vertex_offset..]),
+            has_smoothing_groups,
+            options,
+            &materials,
+        ));
+        vertex_offset += object.vertices.len();
+    }
+    Ok(Scene {
+        name: path.to_str().unwrap_or("default").to_owned(),
+        children: nodes,
+        materials,
+        ..Default::default()
+    })
+}
+
+///
+/// Deserializes a .obj file by reading it line by line from the given reader instead of from a [RawAssets], so the text of the file is never held in memory.
+/// Each object in the file is converted into nodes as soon as it has been read, which means that only the vertices and faces of one object are kept in memory at a time.
+/// The result is the same as when deserializing the file using [RawAssets::deserialize], except that normals are only generated from smoothing groups
+/// in objects that contain or come after the first `s` statement in the file.
+///
+/// The given path is used as the name of the scene and the material library specified by a `mtllib` statement is resolved relative to it.
+/// The material library and its textures are deserialized from the given raw assets, so they must be loaded beforehand.
+///
+/// ```
+/// # use three_d_asset::io::*;
+/// # use three_d_asset::Model;
+/// # #[cfg(feature = "obj")]
+/// # {
+/// let reader = std::io::BufReader::new(std::fs::File::open("test_data/suzanne.obj").unwrap());
+/// let mut raw_assets = load(&["test_data/suzanne.mtl"]).unwrap();
+/// let scene = deserialize_obj_reader(
+///     reader,
+///     "test_data/suzanne.obj",
+///     &mut raw_assets,
+///     ObjOptions::default(),
+/// )
+/// .unwrap();
+/// let model = Model::from(scene);
+/// # }
+/// ```
+///
+pub fn deserialize_obj_reader(
+    reader: impl std::io::BufRead,
+    path: impl AsRef<Path>,
+    raw_assets: &mut RawAssets,
+    options: ObjOptions,
+) -> Result<Scene> {
+    use wavefront_obj::obj::{Geometry as ObjGeometry, Object, Shape, TVertex, Vertex};
+    let path = path.as_ref();
+    let p = path.parent().unwrap_or(Path::new(""));
+    let new_object = |name: &str| Object {
+        name: name.to_owned(),
+        vertices: Vec::new(),
+        tex_vertices: Vec::new(),
+        normals: Vec::new(),
+        geometry: Vec::new(),
+    };
+
+    let mut materials = Vec::new();
+    let mut nodes = Vec::new();
+    let mut object = new_object("");
+    // The number of vertices, texture coordinates and normals in the file before the current object
+    let mut offsets = [0usize; 3];
+    let mut object_colors: Vec<Option<Color>> = Vec::new();
+    let mut vertex_count = 0;
+    let mut uncolored_count = 0;
+    let mut has_colors = false;
+    let mut has_smoothing_groups = false;
+    // The state of the geometry statements, which are reset at the start of each object
+    let mut has_geometry = false;
+    let mut geometry = ObjGeometry {
+        material_name: None,
+        shapes: Vec::new(),
+    };
+    let mut groups: Vec<String> = Vec::new();
+    let mut smoothing_groups: Vec<u32> = Vec::new();
+    let mut unsupported_keywords: Vec<String> = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line =
+            line.map_err(|e| crate::Error::FailedLoading(path.to_str().unwrap().to_string(), e))?;
+        let error = |message: String| {
+            crate::Error::Obj(wavefront_obj::ParseError {
+                line_number: i + 1,
+                message,
+            })
+        };
+        let mut tokens = line.split('#').next().unwrap_or("").split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let arguments = tokens.collect::<Vec<_>>();
+        let float = |i: usize| {
+            arguments
+                .get(i)
+                .and_then(|a| a.parse::<f64>().ok())
+                .ok_or_else(|| error(format!("Expected a number after {}.", keyword)))
+        };
+        let name = || {
+            arguments
+                .first()
+                .copied()
+                .ok_or_else(|| error(format!("Expected a name after {}.", keyword)))
+        };
+
+        // Vertex data after geometry statements and `o` statements start a new object
+        let starts_object =
+            keyword == "o" || (has_geometry && ["v", "vt", "vn"].contains(&keyword));
+        if starts_object {
+            push_geometry(&mut object, &mut geometry, None);
+            nodes.extend(parse_object(
+                &object,
+                has_colors.then_some(object_colors.as_slice()),
+                has_smoothing_groups,
+                options,
+                &materials,
+            ));
+            offsets[0] += object.vertices.len();
+            offsets[1] += object.tex_vertices.len();
+            offsets[2] += object.normals.len();
+            object_colors.clear();
+            object = new_object(if keyword == "o" { name()? } else { "" });
+            has_geometry = false;
+            groups.clear();
+            smoothing_groups.clear();
+        }
+        match keyword {
+            "o" => {}
+            "v" => {
+                object.vertices.push(Vertex {
+                    x: float(0)?,
+                    y: float(1)?,
+                    z: float(2)?,
+                });
+                let color = if arguments.len() >= 6 {
+                    arguments[arguments.len() - 3..]
+                        .iter()
+                        .map(|v| v.parse::<f32>())
+                        .collect::<std::result::Result<Vec<_>, _>>()
+                        .ok()
+                        .map(|color| {
+                            let [r, g, b] = [color[0], color[1], color[2]]
+                                .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+                            Color::new_opaque(r, g, b)
+                        })
+                } else {
+                    None
+                };
+                has_colors |= color.is_some();
+                vertex_count += 1;
+                if color.is_none() {
+                    uncolored_count += 1;
+                }
+                object_colors.push(color);
+            }
+            "vt" => {
+                let u = float(0)?;
+                let v = float(1).unwrap_or(0.0);
+                let w = float(2).unwrap_or(0.0);
+                object.tex_vertices.push(TVertex { u, v, w });
+            }
+            "vn" => object.normals.push(Vertex {
+                x: float(0)?,
+                y: float(1)?,
+                z: float(2)?,
+            }),
+            "mtllib" => materials = deserialize_mtl(raw_assets, &p.join(name()?))?,
+            "usemtl" => {
+                has_geometry = true;
+                push_geometry(&mut object, &mut geometry, Some(name()?.to_owned()));
+            }
+            "s" => {
+                has_geometry = true;
+                has_smoothing_groups = true;
+                smoothing_groups = if arguments.first() == Some(&"off") {
+                    Vec::new()
+                } else {
+                    arguments
+                        .iter()
+                        .map(|a| a.parse::<u32>())
+                        .collect::<std::result::Result<_, _>>()
+                        .map_err(|_| error("Expected an integer after s.".to_owned()))?
+                };
+            }
+            "g" => {
+                has_geometry = true;
+                groups = arguments.iter().map(|a| a.to_string()).collect();
+            }
+            "f" | "l" => {
+                has_geometry = true;
+                let counts = [
+                    offsets[0] + object.vertices.len(),
+                    offsets[1] + object.tex_vertices.len(),
+                    offsets[2] + object.normals.len(),
+                ];
+                let corners = arguments
+                    .iter()
+                    .map(|corner| parse_corner(corner, offsets, counts).map_err(&error))
+                    .collect::<Result<Vec<_>>>()?;
+                if corners.is_empty() {
+                    Err(error(format!("Expected vertex indices after {}.", keyword)))?;
+                }
+                let primitives = if keyword == "f" && corners.len() > 3 {
+                    let polygon = corners
+                        .iter()
+                        .map(|c| {
+                            let v = object.vertices[c.0];
+                            vec3(v.x, v.y, v.z)
+                        })
+                        .collect::<Vec<_>>();
+                    triangulate_polygon(&polygon)
+                        .into_iter()
+                        .flat_map(|[i0, i1, i2]| {
+                            to_primitives(&[corners[i0], corners[i1], corners[i2]])
+                        })
+                        .collect()
+                } else {
+                    to_primitives(&corners)
+                };
+                geometry
+                    .shapes
+                    .extend(primitives.into_iter().map(|primitive| Shape {
+                        primitive,
+                        groups: groups.clone(),
+                        smoothing_groups: smoothing_groups.clone(),
+                    }));
+            }
+            _ => {
+                if !unsupported_keywords.iter().any(|k| k == keyword) {
+                    unsupported_keywords.push(keyword.to_owned());
+                }
+            }
+        }
+    }
+    push_geometry(&mut object, &mut geometry, None);
+    nodes.extend(parse_object(
+        &object,
+        has_colors.then_some(object_colors.as_slice()),
+        has_smoothing_groups,
+        options,
+        &materials,
+    ));
+
+    for keyword in unsupported_keywords {
+        raw_assets.warn(
+            path,
+            format!("unsupported {} statements are ignored", keyword),
+        );
+    }
+    if has_colors {
+        if uncolored_count > 0 {
+            raw_assets.warn(
+                path,
+                format!(
+                    "{} of {} vertices have no color, white is used instead",
+                    uncolored_count, vertex_count
+                ),
+            );
+        }
+        // Objects read before the first vertex color is found have no colors, but should be white
+        for node in nodes.iter_mut() {
+            if let Some(Geometry::Triangles(mesh)) = node.geometry.as_mut() {
+                if mesh.colors.is_none() {
+                    mesh.colors = Some(vec![Color::WHITE; mesh.vertex_count()]);
+                }
+            }
+        }
+    }
+    Ok(Scene {
+        name: path.to_str().unwrap_or("default").to_owned(),
+        children: nodes,
+        materials,
+        ..Default::default()
+    })
+}
+
+///
+/// Adds the shapes of the given geometry to the object, if there are any, and resets the geometry to have no shapes and the given material.
+///
+fn push_geometry(
+    object: &mut wavefront_obj::obj::Object,
+    geometry: &mut wavefront_obj::obj::Geometry,
+    material_name: Option<String>,
+) {
+    let material_name = std::mem::replace(&mut geometry.material_name, material_name);
+    let shapes = std::mem::take(&mut geometry.shapes);
+    if !shapes.is_empty() {
+        object.geometry.push(wavefront_obj::obj::Geometry {
+            material_name,
+            shapes,
+        });
+    }
+}
+
+///
+/// Parses a corner of a face or line statement, ie. a vertex index optionally followed by a texture coordinate index and a normal index separated by `/`,
+/// and returns the indices relative to the start of the current object.
+/// Negative indices are relative to the number of vertices, texture coordinates or normals read so far, given by `counts`.
+///
+fn parse_corner(
+    corner: &str,
+    offsets: [usize; 3],
+    counts: [usize; 3],
+) -> std::result::Result<wavefront_obj::obj::VTNIndex, String> {
+    let mut indices = [None; 3];
+    let mut splits = corner.split('/');
+    for (k, split) in splits.by_ref().take(3).enumerate() {
+        if split.is_empty() {
+            continue;
+        }
+        let index = split
+            .parse::<i64>()
+            .map_err(|_| format!("Expected isize but got {}.", split))?;
+        let index = if index < 0 {
+            counts[k] as i64 + index + 1
+        } else {
+            index
+        };
+        if index <= offsets[k] as i64 || index > counts[k] as i64 {
+            Err(format!(
+                "Expected index in the range [{}, {}), but got {}.",
+                offsets[k] + 1,
+                counts[k] + 1,
+                split
+            ))?;
+        }
+        indices[k] = Some((index - 1) as usize - offsets[k]);
+    }
+    match indices {
+        [Some(v), t, n] if splits.next().is_none() => Ok((v, t, n)),
+        _ => Err("Expected at least 1 and at most 3 vertex indexes.".to_owned()),
+    }
+}
+
+///
+/// Converts the corners of a face or line statement into a point, a line or a fan of triangles in the same way as the obj parser.
+///
+fn to_primitives(corners: &[wavefront_obj::obj::VTNIndex]) -> Vec<wavefront_obj::obj::Primitive> {
+    use wavefront_obj::obj::Primitive;
+    match corners {
+        [] => Vec::new(),
+        [c] => vec![Primitive::Point(*c)],
+        [c0, c1] => vec![Primitive::Line(*c0, *c1)],
+        [rest @ .., last] => rest
+            .windows(2)
+            .map(|c| Primitive::Triangle(*last, c[0], c[1]))
+            .collect(),
+    }
+}
+
+///
+/// Returns a node for each part of the given object, where a part contains the faces with the same material and, unless the groups are merged, the same groups.
+/// The vertex colors, if any, are the colors of the vertices of the object.
+///
+fn parse_object(
+    object: &wavefront_obj::obj::Object,
+    vertex_colors: Option<&[Option<Color>]>,
+    has_smoothing_groups: bool,
+    options: ObjOptions,
+    materials: &[PbrMaterial],
+) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    // Objects consisting of several meshes with different materials
+    for mesh in object.geometry.iter() {
+        // All meshes with different materials, split into the groups the faces belong to
+        let mut parts: Vec<(&[String], Vec<&wavefront_obj::obj::Shape>)> = Vec::new();
+        for shape in mesh.shapes.iter() {
+            let groups = if options.merge_groups {
+                &[]
+            } else {
+                shape.groups.as_slice()
+            };
+            if let Some((_, shapes)) = parts.iter_mut().find(|(g, _)| *g == groups) {
+                shapes.push(shape);
+            } else {
+                parts.push((groups, vec![shape]));
+            }
+        }
+        for (groups, shapes) in parts {
             let mut positions = Vec::new();
             let mut normals: Vec<Vec3> = Vec::new();
             let mut uvs: Vec<Vec2> = Vec::new();
+            let mut colors: Vec<Color> = Vec::new();
             let mut indices = Vec::new();
 
-            let mut map: HashMap<usize, usize> = HashMap::new();
+            let mut map: HashMap<(usize, Option<(u32, usize)>), usize> = HashMap::new();
 
-            let mut process = |i: wavefront_obj::obj::VTNIndex| {
-                let mut index = map.get(&i.0).map(|v| *v);
+            let mut process = |i: wavefront_obj::obj::VTNIndex, smoothing: Option<(u32, usize)>| {
+                let mut index = map.get(&(i.0, smoothing)).copied();
 
                 let uvw = i.1.map(|tex_index| object.tex_vertices[tex_index]);
                 let normal = i.2.map(|normal_index| object.normals[normal_index]);
 
                 if let Some(ind) = index {
                     if let Some(tex) = uvw {
-                        if ((uvs[ind].x - tex.u as f32) as f32).abs() > std::f32::EPSILON
-                            || ((uvs[ind].y - tex.v as f32) as f32).abs() > std::f32::EPSILON
+                        if (uvs[ind].x - tex.u as f32).abs() > f32::EPSILON
+                            || (uvs[ind].y - tex.v as f32).abs() > f32::EPSILON
                         {
                             index = None;
                         }
                     }
                     if let Some(n) = normal {
-                        if ((normals[ind].x - n.x as f32) as f32).abs() > std::f32::EPSILON
-                            || ((normals[ind].y - n.y as f32) as f32).abs() > std::f32::EPSILON
-                            || ((normals[ind].z - n.z as f32) as f32).abs() > std::f32::EPSILON
+                        if (normals[ind].x - n.x as f32).abs() > f32::EPSILON
+                            || (normals[ind].y - n.y as f32).abs() > f32::EPSILON
+                            || (normals[ind].z - n.z as f32).abs() > f32::EPSILON
                         {
                             index = None;
                         }
@@ -152,29 +1052,74 @@ pub fn deserialize_obj(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Sce
 
                 if index.is_none() {
                     index = Some(positions.len());
-                    map.insert(i.0, index.unwrap());
+                    map.insert((i.0, smoothing), index.unwrap());
                     let position = object.vertices[i.0];
                     positions.push(Vector3::new(position.x, position.y, position.z));
+                    if let Some(vertex_colors) = vertex_colors.as_ref() {
+                        colors.push(vertex_colors[i.0].unwrap_or(Color::WHITE));
+                    }
 
                     if let Some(tex) = uvw {
                         uvs.push(Vec2::new(tex.u as f32, 1.0 - tex.v as f32));
                     }
                     if let Some(n) = normal {
                         normals.push(Vec3::new(n.x as f32, n.y as f32, n.z as f32));
+                    } else if smoothing.is_some() {
+                        normals.push(Vec3::zero());
                     }
                 }
 
                 indices.push(index.unwrap() as u32);
             };
-            for shape in mesh.shapes.iter() {
+            let mut smoothed_faces = Vec::new();
+            let mut triangle_count = 0;
+            for (face, shape) in shapes.iter().enumerate() {
                 // All triangles with same material
-                match shape.primitive {
-                    wavefront_obj::obj::Primitive::Triangle(i0, i1, i2) => {
-                        process(i0);
-                        process(i1);
-                        process(i2);
+                if let wavefront_obj::obj::Primitive::Triangle(i0, i1, i2) = shape.primitive {
+                    // The vertices of a face without normals are shared with the faces in the same smoothing group,
+                    // or not shared at all if the face is not in a smoothing group which results in flat shading
+                    let smoothing = if has_smoothing_groups
+                        && (i0.2.is_none() || i1.2.is_none() || i2.2.is_none())
+                    {
+                        Some(match shape.smoothing_groups.first() {
+                            Some(group) if *group != 0 => (*group, 0),
+                            _ => (0, face),
+                        })
+                    } else {
+                        None
+                    };
+                    let [i0, i1, i2] = if smoothing.is_some() {
+                        [i0, i1, i2].map(|i| (i.0, i.1, None))
+                    } else {
+                        [i0, i1, i2]
+                    };
+                    process(i0, smoothing);
+                    process(i1, smoothing);
+                    process(i2, smoothing);
+                    if smoothing.is_some() {
+                        smoothed_faces.push(3 * triangle_count);
+                    }
+                    triangle_count += 1;
+                }
+            }
+
+            // Generate the normals of the smoothed faces by averaging the area weighted face normals
+            for &i in smoothed_faces.iter() {
+                let [i0, i1, i2] = [indices[i], indices[i + 1], indices[i + 2]].map(|i| i as usize);
+                let normal = (positions[i1] - positions[i0])
+                    .cross(positions[i2] - positions[i0])
+                    .cast::<f32>()
+                    .unwrap();
+                normals[i0] += normal;
+                normals[i1] += normal;
+                normals[i2] += normal;
+            }
+            for &i in smoothed_faces.iter() {
+                for &index in indices[i..i + 3].iter() {
+                    let normal = &mut normals[index as usize];
+                    if normal.magnitude2() > 0.0 {
+                        *normal = normal.normalize();
                     }
-                    _ => {}
                 }
             }
 
@@ -192,30 +1137,184 @@ pub fn deserialize_obj(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Sce
                 } else {
                     None
                 },
-                colors: None,
+                colors: if colors.len() == vertex_count {
+                    Some(colors)
+                } else {
+                    None
+                },
                 tangents: None,
+                additional_uvs: Vec::new(),
+                joint_indices: None,
+                joint_weights: None,
+                morph_targets: None,
             };
             nodes.push(Node {
-                name: object.name.to_string(),
+                name: if groups.is_empty() {
+                    object.name.to_string()
+                } else {
+                    groups.join(" ")
+                },
                 geometry: Some(Geometry::Triangles(tri_mesh)),
                 material_index: mesh
                     .material_name
                     .as_ref()
-                    .map(|n| materials.iter().position(|m| &m.name == n))
-                    .flatten(),
+                    .and_then(|n| materials.iter().position(|m| &m.name == n)),
                 ..Default::default()
             });
         }
     }
-    Ok(Scene {
-        name: path.to_str().unwrap_or("default").to_owned(),
-        children: nodes,
-        materials,
-    })
+    nodes
+}
+
+pub fn serialize_obj(
+    path: &Path,
+    meshes: &[(&str, &TriMesh, Option<usize>)],
+    materials: &[PbrMaterial],
+) -> Result<RawAssets> {
+    use std::fmt::Write;
+    let mut raw_assets = RawAssets::new();
+    let base_path = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
+
+    let mut obj = String::new();
+    if !materials.is_empty() {
+        let mtl_name = format!("{}.mtl", stem);
+        writeln!(obj, "mtllib {}", mtl_name).unwrap();
+        raw_assets.extend(serialize_mtl(&base_path.join(mtl_name), materials)?);
+    }
+
+    let mut offsets = [1; 3];
+    for (name, mesh, material_index) in meshes {
+        writeln!(obj, "o {}", name).unwrap();
+        let positions = mesh.positions.to_f64();
+        for p in positions.iter() {
+            writeln!(obj, "v {} {} {}", p.x, p.y, p.z).unwrap();
+        }
+        if let Some(uvs) = mesh.uvs.as_ref() {
+            for uv in uvs {
+                // The uv coordinates are flipped vertically when loaded
+                writeln!(obj, "vt {} {}", uv.x, 1.0 - uv.y).unwrap();
+            }
+        }
+        if let Some(normals) = mesh.normals.as_ref() {
+            for n in normals {
+                writeln!(obj, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+            }
+        }
+        if let Some(material) = material_index.and_then(|i| materials.get(i)) {
+            writeln!(obj, "usemtl {}", material.name).unwrap();
+        }
+        let corner = |i: usize| {
+            let v = offsets[0] + i;
+            match (mesh.uvs.is_some(), mesh.normals.is_some()) {
+                (true, true) => format!("{}/{}/{}", v, offsets[1] + i, offsets[2] + i),
+                (true, false) => format!("{}/{}", v, offsets[1] + i),
+                (false, true) => format!("{}//{}", v, offsets[2] + i),
+                (false, false) => format!("{}", v),
+            }
+        };
+        mesh.for_each_triangle(|i0, i1, i2| {
+            writeln!(obj, "f {} {} {}", corner(i0), corner(i1), corner(i2)).unwrap();
+        });
+        offsets[0] += positions.len();
+        offsets[1] += mesh.uvs.as_ref().map(|uvs| uvs.len()).unwrap_or(0);
+        offsets[2] += mesh.normals.as_ref().map(|n| n.len()).unwrap_or(0);
+    }
+    raw_assets.insert(path, obj.into_bytes());
+    Ok(raw_assets)
+}
+
+fn serialize_mtl<'a>(path: &Path, materials: &'a [PbrMaterial]) -> Result<RawAssets> {
+    use std::fmt::Write;
+    let mut raw_assets = RawAssets::new();
+    let base_path = path.parent().unwrap_or(Path::new(""));
+    let mut texture_paths: Vec<(&'a Texture2D, String)> = Vec::new();
+    let mut file_names = HashSet::new();
+    let mut texture = |raw_assets: &mut RawAssets,
+                       texture: &'a Option<Texture2D>,
+                       fallback_name: String|
+     -> Result<Option<String>> {
+        let Some(texture) = texture else {
+            return Ok(None);
+        };
+        // Equal textures are shared between materials, while different textures are written separately even if they have the same name
+        if let Some((_, path)) = texture_paths.iter().find(|(t, _)| *t == texture) {
+            return Ok(Some(path.clone()));
+        }
+        let file_name = super::texture_file_name(texture, &fallback_name, &mut file_names);
+        raw_assets.extend(texture.serialize(base_path.join(&file_name))?);
+        texture_paths.push((texture, file_name.clone()));
+        Ok(Some(file_name))
+    };
+    let options = |transform: &TextureTransform| {
+        if *transform == TextureTransform::default() || transform.rotation != 0.0 {
+            String::new()
+        } else {
+            // The uv coordinates are flipped vertically when loaded, so the transformation is flipped as well
+            format!(
+                "-o {} {} -s {} {} ",
+                transform.offset.x,
+                1.0 - transform.scale.y - transform.offset.y,
+                transform.scale.x,
+                transform.scale.y
+            )
+        }
+    };
+
+    let mut mtl = String::new();
+    for material in materials {
+        let to_f32 = |c: u8| c as f32 / 255.0;
+        writeln!(mtl, "newmtl {}", material.name).unwrap();
+        // Inverse of the conversion from the specular exponent to roughness when loading
+        let specular_exponent = 1.999 / material.roughness.max(0.001).powi(2);
+        writeln!(mtl, "Ns {}", specular_exponent).unwrap();
+        let Color { r, g, b, a } = material.albedo;
+        writeln!(mtl, "Kd {} {} {}", to_f32(r), to_f32(g), to_f32(b)).unwrap();
+        let m = material.metallic;
+        writeln!(mtl, "Ks {} {} {}", m, m, m).unwrap();
+        let Color { r, g, b, .. } = material.emissive;
+        writeln!(mtl, "Ke {} {} {}", to_f32(r), to_f32(g), to_f32(b)).unwrap();
+        writeln!(mtl, "Ni {}", material.index_of_refraction).unwrap();
+        writeln!(mtl, "d {}", to_f32(a)).unwrap();
+        writeln!(mtl, "illum 2").unwrap();
+        if let Some(path) = texture(
+            &mut raw_assets,
+            &material.albedo_texture,
+            format!("{}_albedo", material.name),
+        )? {
+            let options = options(&material.albedo_texture_transform);
+            writeln!(mtl, "map_Kd {}{}", options, path).unwrap();
+        }
+        if let Some(path) = texture(
+            &mut raw_assets,
+            &material.alpha_texture,
+            format!("{}_alpha", material.name),
+        )? {
+            let options = options(&material.alpha_texture_transform);
+            writeln!(mtl, "map_d {}{}", options, path).unwrap();
+        }
+        if let Some(path) = texture(
+            &mut raw_assets,
+            &material.normal_texture,
+            format!("{}_normal", material.name),
+        )? {
+            let options = options(&material.normal_texture_transform);
+            writeln!(
+                mtl,
+                "map_Bump -bm {} {}{}",
+                material.normal_scale, options, path
+            )
+            .unwrap();
+        }
+        writeln!(mtl).unwrap();
+    }
+    raw_assets.insert(path, mtl.into_bytes());
+    Ok(raw_assets)
 }
 
 #[cfg(test)]
 mod test {
+    use crate::prelude::*;
 
     #[test]
     pub fn deserialize_obj() {
@@ -224,10 +1323,496 @@ mod test {
         assert_eq!(model.materials.len(), 0);
     }
 
+    #[test]
+    pub fn deserialize_obj_with_vertex_colors() {
+        let model: crate::Model = crate::io::load_and_deserialize("test_data/colored.obj").unwrap();
+        let crate::Geometry::Triangles(mesh) = &model.geometries[0].geometry else {
+            panic!("expected a triangle mesh");
+        };
+        assert_eq!(mesh.vertex_count(), 4);
+        let positions = mesh.positions.to_f32();
+        let colors = mesh.colors.as_ref().unwrap();
+        for (position, color) in [
+            (crate::vec3(0.0, 0.0, 0.0), crate::Color::RED),
+            (crate::vec3(1.0, 0.0, 0.0), crate::Color::GREEN),
+            (crate::vec3(1.0, 1.0, 0.0), crate::Color::BLUE),
+            (crate::vec3(0.0, 1.0, 0.0), crate::Color::WHITE),
+        ] {
+            let i = positions.iter().position(|p| *p == position).unwrap();
+            assert_eq!(colors[i], color);
+        }
+    }
+
+    #[test]
+    pub fn deserialize_obj_without_vertex_colors() {
+        let model: crate::Model = crate::io::load_and_deserialize("test_data/cube.obj").unwrap();
+        let crate::Geometry::Triangles(mesh) = &model.geometries[0].geometry else {
+            panic!("expected a triangle mesh");
+        };
+        assert!(mesh.colors.is_none());
+    }
+
+    #[test]
+    pub fn deserialize_obj_with_smoothing_groups() {
+        let model: crate::Model =
+            crate::io::load_and_deserialize("test_data/smoothing_groups.obj").unwrap();
+        let up = crate::vec3(0.0, 1.0, 0.0);
+        let back = crate::vec3(0.0, 0.0, -1.0);
+        for primitive in model.geometries.iter() {
+            let crate::Geometry::Triangles(mesh) = &primitive.geometry else {
+                panic!("expected a triangle mesh");
+            };
+            let normals = mesh.normals.as_ref().unwrap();
+            if primitive.name == "Smooth" {
+                assert_eq!(mesh.vertex_count(), 4);
+                let shared = (up + back).normalize();
+                assert_eq!(
+                    normals
+                        .iter()
+                        .filter(|n| (*n - shared).magnitude() < 0.0001)
+                        .count(),
+                    2
+                );
+            } else {
+                assert_eq!(mesh.vertex_count(), 6);
+                assert!(normals.iter().all(|n| *n == up || *n == back));
+            }
+        }
+    }
+
+    #[test]
+    pub fn deserialize_obj_with_relative_indices() {
+        let model: crate::Model =
+            crate::io::load_and_deserialize("test_data/relative_indices.obj").unwrap();
+        let crate::Geometry::Triangles(mesh) = &model.geometries[0].geometry else {
+            panic!("expected a triangle mesh");
+        };
+        assert_eq!(mesh.triangle_count(), 2);
+        let positions = mesh.positions.to_f32();
+        let indices = mesh.indices.to_u32().unwrap();
+        assert_eq!(
+            indices
+                .iter()
+                .map(|i| positions[*i as usize])
+                .collect::<Vec<_>>(),
+            // The winding order is preserved, but the obj parser starts each triangle with the last vertex
+            vec![
+                vec3(1.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 1.0, 0.0),
+            ]
+        );
+        let uvs = mesh.uvs.as_ref().unwrap();
+        for (position, uv) in positions.iter().zip(uvs) {
+            assert_eq!(*uv, vec2(position.x, 1.0 - position.y));
+        }
+        assert!(mesh
+            .normals
+            .as_ref()
+            .unwrap()
+            .iter()
+            .all(|n| *n == vec3(0.0, 0.0, 1.0)));
+    }
+
+    fn triangle_count(geometry: &crate::Geometry) -> usize {
+        match geometry {
+            crate::Geometry::Triangles(mesh) => mesh.triangle_count(),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    pub fn deserialize_obj_with_groups() {
+        let model: crate::Model = crate::io::load_and_deserialize("test_data/groups.obj").unwrap();
+        assert_eq!(
+            model
+                .geometries
+                .iter()
+                .map(|p| (
+                    p.name.as_str(),
+                    p.material_index.map(|i| model.materials[i].name.as_str())
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Left", Some("Red")),
+                ("Middle", Some("Red")),
+                ("Right", Some("Blue"))
+            ]
+        );
+        assert!(model
+            .geometries
+            .iter()
+            .all(|p| triangle_count(&p.geometry) == 1));
+    }
+
+    #[test]
+    pub fn deserialize_obj_with_merged_groups() {
+        use crate::io::DeserializeWithOptions;
+        let mut raw_assets = crate::io::load(&["test_data/groups.obj"]).unwrap();
+        let model = crate::Model::deserialize_with_options(
+            "test_data/groups.obj",
+            &mut raw_assets,
+            crate::io::ObjOptions { merge_groups: true },
+        )
+        .unwrap();
+        assert_eq!(
+            model
+                .geometries
+                .iter()
+                .map(|p| (p.name.as_str(), triangle_count(&p.geometry)))
+                .collect::<Vec<_>>(),
+            vec![("Parts", 2), ("Parts", 1)]
+        );
+    }
+
+    #[test]
+    pub fn deserialize_obj_with_polygons() {
+        let model: crate::Model =
+            crate::io::load_and_deserialize("test_data/polygons.obj").unwrap();
+        for (primitive, triangle_count, area) in [
+            (&model.geometries[0], 3, 7.5),
+            (&model.geometries[1], 4, 3.0),
+        ] {
+            let crate::Geometry::Triangles(mesh) = &primitive.geometry else {
+                panic!("expected a triangle mesh");
+            };
+            assert_eq!(mesh.triangle_count(), triangle_count);
+            let positions = mesh.positions.to_f32();
+            let uvs = mesh.uvs.as_ref().unwrap();
+            let normals = mesh.normals.as_ref().unwrap();
+            for i in 0..positions.len() {
+                assert_eq!(uvs[i], vec2(positions[i].x, 1.0 - positions[i].y));
+                assert_eq!(normals[i], vec3(0.0, 0.0, 1.0));
+            }
+            let mut total_area = 0.0;
+            for triangle in mesh.indices.to_u32().unwrap().chunks(3) {
+                let [p0, p1, p2] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+                // All triangles are counterclockwise, like the polygons, and inside the polygons
+                let area = 0.5 * (p1 - p0).cross(p2 - p0).z;
+                assert!(area > 0.0);
+                total_area += area;
+                let center = (p0 + p1 + p2) / 3.0;
+                assert!(primitive.name != "L" || center.x < 1.0 || center.y < 1.0);
+            }
+            assert!((total_area - area).abs() < 0.0001);
+        }
+    }
+
     #[test]
     pub fn deserialize_obj_with_material() {
         let model: crate::Model = crate::io::load_and_deserialize("test_data/suzanne.obj").unwrap();
         assert_eq!(model.geometries.len(), 1);
         assert_eq!(model.materials.len(), 1);
+        assert_eq!(model.materials[0].alpha_mode, crate::AlphaMode::Opaque);
+        assert!(!model.materials[0].is_double_sided);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn deserialize_mtl() {
+        let materials: Vec<crate::PbrMaterial> =
+            crate::io::load_and_deserialize("test_data/textured.mtl").unwrap();
+        assert_eq!(materials.len(), 2);
+
+        assert_eq!(materials[0].name, "Textured");
+        assert_eq!(
+            materials[0]
+                .albedo_texture
+                .as_ref()
+                .map(|t| std::path::PathBuf::from(&t.name)),
+            Some(std::path::PathBuf::from("test_data/test.png"))
+        );
+        assert!(materials[0].normal_texture.is_some());
+        assert_eq!(materials[0].metallic, 0.5);
+        assert!((materials[0].roughness - 0.0942573).abs() < 0.0001);
+
+        assert_eq!(materials[1].name, "Glass");
+        assert_eq!(
+            materials[1].albedo,
+            crate::Color::from_rgba_slice(&[0.2, 0.4, 0.6, 0.5])
+        );
+        assert!(materials[1].albedo_texture.is_none());
+        assert_eq!(materials[1].alpha_mode, crate::AlphaMode::Blend);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn deserialize_mtl_with_transparency_and_illumination() {
+        let materials: Vec<crate::PbrMaterial> =
+            crate::io::load_and_deserialize("test_data/translucent.mtl").unwrap();
+        assert_eq!(materials.len(), 4);
+
+        let window = &materials[0];
+        assert_eq!(window.albedo.a, 63);
+        assert_eq!(window.alpha_mode, crate::AlphaMode::Blend);
+        assert_eq!(window.index_of_refraction, 1.45);
+        assert_eq!(window.metallic, 0.0);
+        assert!(window.roughness < 0.1);
+
+        let chrome = &materials[1];
+        assert_eq!(
+            chrome.albedo,
+            crate::Color::from_rgb_slice(&[0.5, 0.5, 0.5])
+        );
+        assert_eq!(chrome.alpha_mode, crate::AlphaMode::Opaque);
+        assert_eq!(chrome.metallic, 1.0);
+        assert!(chrome.roughness < 0.1);
+
+        let leaves = &materials[2];
+        assert_eq!(
+            leaves
+                .alpha_texture
+                .as_ref()
+                .map(|t| std::path::PathBuf::from(&t.name)),
+            Some(std::path::PathBuf::from("test_data/test.png"))
+        );
+        assert_eq!(leaves.alpha_mode, crate::AlphaMode::Blend);
+        assert_eq!(leaves.metallic, 0.0);
+        assert_eq!(leaves.roughness, 1.0);
+
+        let matte = &materials[3];
+        assert_eq!(
+            matte.albedo,
+            crate::Color::from_rgba_slice(&[0.6, 0.3, 0.3, 1.0])
+        );
+        assert_eq!(matte.alpha_mode, crate::AlphaMode::Opaque);
+        assert!(matte.alpha_texture.is_none());
+        assert_eq!(matte.metallic, 0.0);
+        assert_eq!(matte.roughness, 1.0);
+        assert_eq!(matte.index_of_refraction, 1.5);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn deserialize_mtl_with_map_options() {
+        let materials: Vec<crate::PbrMaterial> =
+            crate::io::load_and_deserialize("test_data/map_options.mtl").unwrap();
+        let material = &materials[0];
+
+        let albedo_texture = material.albedo_texture.as_ref().unwrap();
+        assert_eq!(albedo_texture.wrap_s, crate::Wrapping::ClampToEdge);
+        assert_eq!(albedo_texture.wrap_t, crate::Wrapping::ClampToEdge);
+        let transform = material.albedo_texture_transform;
+        assert_eq!(transform.scale, vec2(2.0, 2.0));
+        // The uv coordinate (0.25, 0.25) in the file is (1.0, 0.5) after the transformation, both flipped vertically when loaded
+        assert_eq!(
+            transform.matrix() * vec3(0.25, 0.75, 1.0),
+            vec3(1.0, 0.5, 1.0)
+        );
+
+        assert!(material.normal_texture.is_some());
+        assert_eq!(material.normal_scale, 0.5);
+        assert_eq!(
+            material.normal_texture_transform,
+            crate::TextureTransform::default()
+        );
+        assert_eq!(
+            material.normal_texture.as_ref().unwrap().wrap_s,
+            crate::Wrapping::Repeat
+        );
+
+        assert!(material.alpha_texture.is_some());
+        assert_eq!(
+            material.alpha_texture_transform,
+            crate::TextureTransform::default()
+        );
+    }
+
+    #[test]
+    pub fn deserialize_obj_reader() {
+        for path in [
+            "test_data/cube.obj",
+            "test_data/colored.obj",
+            "test_data/groups.obj",
+            "test_data/polygons.obj",
+            "test_data/relative_indices.obj",
+            "test_data/smoothing_groups.obj",
+            "test_data/suzanne.obj",
+        ] {
+            for merge_groups in [false, true] {
+                let options = super::ObjOptions { merge_groups };
+                let mut raw_assets = crate::io::load(&[path]).unwrap();
+                let expected: crate::Scene =
+                    raw_assets.deserialize_with_options(path, options).unwrap();
+
+                raw_assets.extend(crate::io::load(&[path]).unwrap());
+                raw_assets.remove(path).unwrap();
+                let reader = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+                let scene =
+                    super::deserialize_obj_reader(reader, path, &mut raw_assets, options).unwrap();
+                assert_eq!(
+                    format!("{:?}", scene),
+                    format!("{:?}", expected),
+                    "{}",
+                    path
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn deserialize_obj_reader_with_several_objects() {
+        // The vertex colors of the second object make the first object white
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n\
+            o Second\nv 0 0 1 1 0 0\nv 1 0 1\nv 1 1 1\nv 0 1 1\nvt 0 0\nusemtl None\nf 4/1 5/1 6/1 7/1\n";
+        let mut raw_assets = crate::io::RawAssets::new();
+        raw_assets.insert("objects.obj", source.as_bytes().to_vec());
+        let expected: crate::Scene = raw_assets.deserialize("objects.obj").unwrap();
+        let scene = super::deserialize_obj_reader(
+            source.as_bytes(),
+            "objects.obj",
+            &mut raw_assets,
+            super::ObjOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(scene.children.len(), 2);
+        assert_eq!(format!("{:?}", scene), format!("{:?}", expected));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn deserialize_mtl_with_warnings() {
+        let mut raw_assets = crate::io::load(&["test_data/map_options.mtl"]).unwrap();
+        let (materials, warnings): (Vec<crate::PbrMaterial>, _) = raw_assets
+            .deserialize_with_warnings("map_options.mtl")
+            .unwrap();
+        assert_eq!(materials.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].path,
+            std::path::Path::new("test_data/map_options.mtl")
+        );
+        assert_eq!(
+            warnings[0].message,
+            "unknown texture map option -unknown 3 is skipped"
+        );
+    }
+
+    #[test]
+    pub fn deserialize_obj_with_missing_vertex_colors() {
+        let mut raw_assets = crate::io::RawAssets::new();
+        raw_assets.insert(
+            "partial_colors.obj",
+            b"v 0 0 0 1 0 0\nv 1 0 0\nv 0 1 0 0 0 1\nf 1 2 3\n".to_vec(),
+        );
+        let _: crate::Model = raw_assets.deserialize("partial_colors.obj").unwrap();
+        let warnings = raw_assets.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "1 of 3 vertices have no color, white is used instead"
+        );
+        assert!(raw_assets.warnings().is_empty());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn serialize_obj() {
+        use crate::io::Serialize;
+        let mut model: crate::Model =
+            crate::io::load_and_deserialize("test_data/groups.obj").unwrap();
+        model.materials[1].albedo_texture =
+            Some(crate::io::load_and_deserialize("test_data/test.png").unwrap());
+
+        let mut raw_assets = model.serialize("exported/groups.obj").unwrap();
+        assert!(raw_assets.get("exported/groups.mtl").is_ok());
+        assert!(raw_assets.get("exported/test.png").is_ok());
+
+        let reloaded: crate::Model = raw_assets.deserialize("exported/groups.obj").unwrap();
+        assert_eq!(reloaded.geometries.len(), model.geometries.len());
+        for (primitive, reloaded_primitive) in
+            model.geometries.iter().zip(reloaded.geometries.iter())
+        {
+            assert_eq!(primitive.name, reloaded_primitive.name);
+            let (crate::Geometry::Triangles(mesh), crate::Geometry::Triangles(reloaded_mesh)) =
+                (&primitive.geometry, &reloaded_primitive.geometry)
+            else {
+                panic!("expected triangle meshes");
+            };
+            assert_eq!(mesh.triangle_count(), reloaded_mesh.triangle_count());
+            let (aabb, reloaded_aabb) = (mesh.compute_aabb(), reloaded_mesh.compute_aabb());
+            assert_eq!(aabb.min(), reloaded_aabb.min());
+            assert_eq!(aabb.max(), reloaded_aabb.max());
+            assert_eq!(
+                primitive.material_index.map(|i| &model.materials[i].name),
+                reloaded_primitive
+                    .material_index
+                    .map(|i| &reloaded.materials[i].name)
+            );
+        }
+        for (material, reloaded_material) in model.materials.iter().zip(reloaded.materials.iter()) {
+            assert_eq!(material.name, reloaded_material.name);
+            assert_eq!(material.albedo, reloaded_material.albedo);
+            assert_eq!(material.metallic, reloaded_material.metallic);
+            assert_eq!(material.roughness, reloaded_material.roughness);
+            assert_eq!(
+                material.albedo_texture.is_some(),
+                reloaded_material.albedo_texture.is_some()
+            );
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn serialize_obj_with_colliding_texture_names() {
+        use crate::io::Serialize;
+        let texture = |name: &str, texel: [u8; 4]| crate::Texture2D {
+            name: name.to_owned(),
+            data: crate::TextureData::RgbaU8(vec![texel]),
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        let textures = [
+            texture("a/albedo.png", [255, 0, 0, 255]),
+            texture("b/albedo.png", [0, 255, 0, 255]),
+            texture("default", [0, 0, 255, 255]),
+            texture("default", [255, 255, 255, 255]),
+            texture("a/albedo.png", [255, 0, 0, 255]),
+        ];
+        let model = crate::Model {
+            name: "model".to_owned(),
+            geometries: (0..textures.len())
+                .map(|i| crate::Primitive {
+                    name: format!("square{}", i),
+                    transformation: crate::Mat4::identity(),
+                    animations: Vec::new(),
+                    geometry: crate::Geometry::Triangles(crate::TriMesh::square()),
+                    material_index: Some(i),
+                    skin_index: None,
+                })
+                .collect(),
+            materials: textures
+                .iter()
+                .enumerate()
+                .map(|(i, texture)| crate::PbrMaterial {
+                    name: format!("material{}", i),
+                    albedo_texture: Some(texture.clone()),
+                    ..Default::default()
+                })
+                .collect(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
+        };
+        let mut raw_assets = model.serialize("exported/model.obj").unwrap();
+        assert!(raw_assets.get("exported/albedo.png").is_ok());
+        assert!(raw_assets.get("exported/albedo_1.png").is_ok());
+        assert!(raw_assets.get("exported/albedo_2.png").is_err());
+        assert!(raw_assets.get("exported/material2_albedo.png").is_ok());
+        assert!(raw_assets.get("exported/material3_albedo.png").is_ok());
+
+        let reloaded: crate::Model = raw_assets.deserialize("exported/model.obj").unwrap();
+        for (texture, material) in textures.iter().zip(reloaded.materials.iter()) {
+            assert_eq!(
+                material.albedo_texture.as_ref().unwrap().data,
+                texture.data,
+                "{}",
+                material.name
+            );
+        }
     }
 }
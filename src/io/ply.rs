@@ -0,0 +1,695 @@
+use crate::{geometry::*, io::RawAssets, Error, Node, Result, Scene};
+use std::path::{Path, PathBuf};
+
+///
+/// The format used when serializing into a .ply file (see [SerializeWithOptions](crate::io::SerializeWithOptions)).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlyFormat {
+    /// The human readable text format, mostly useful for debugging.
+    Ascii,
+    /// The binary format with little endian byte order.
+    #[default]
+    BinaryLittleEndian,
+    /// The binary format with big endian byte order.
+    BinaryBigEndian,
+}
+
+pub fn deserialize_ply(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Scene> {
+    let bytes = raw_assets.remove(path)?;
+    let (header, body_start) = parse_header(&bytes)?;
+    let body = &bytes[body_start..];
+    let mut body = match header.format {
+        Format::Ascii => Body::Ascii(
+            std::str::from_utf8(body)
+                .map_err(|_| Error::PlyCorruptData)?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian => Body::Binary {
+            bytes: body,
+            offset: 0,
+            big_endian: false,
+        },
+        Format::BinaryBigEndian => Body::Binary {
+            bytes: body,
+            offset: 0,
+            big_endian: true,
+        },
+    };
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut colors = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    let mut has_faces = false;
+    let mut double_precision = false;
+    let mut row = Vec::new();
+    for element in header.elements.iter() {
+        match element.name.as_str() {
+            "vertex" => {
+                let find = |names: &[&str]| {
+                    element.properties.iter().position(|p| match p {
+                        Property::Scalar(name, _) => names.contains(&name.as_str()),
+                        _ => false,
+                    })
+                };
+                let find_all =
+                    |names: &[&[&str]]| names.iter().map(|n| find(n)).collect::<Option<Vec<_>>>();
+                let position_indices =
+                    find_all(&[&["x"], &["y"], &["z"]]).ok_or(Error::PlyCorruptData)?;
+                let normal_indices = find_all(&[&["nx"], &["ny"], &["nz"]]);
+                let color_indices = find_all(&[
+                    &["red", "r", "diffuse_red"],
+                    &["green", "g", "diffuse_green"],
+                    &["blue", "b", "diffuse_blue"],
+                ]);
+                let alpha_index = find(&["alpha", "a"]);
+                let uv_indices = find_all(&[
+                    &["s", "u", "texture_u", "texture_s"],
+                    &["t", "v", "texture_v", "texture_t"],
+                ]);
+                double_precision = matches!(
+                    element.properties[position_indices[0]],
+                    Property::Scalar(_, ScalarType::F64)
+                );
+                let color_type = |i: usize| match element.properties[i] {
+                    Property::Scalar(_, ty) => ty,
+                    _ => unreachable!(),
+                };
+
+                for _ in 0..element.count {
+                    body.read_row(&element.properties, &mut row)?;
+                    positions.push(Vector3::new(
+                        row[position_indices[0]],
+                        row[position_indices[1]],
+                        row[position_indices[2]],
+                    ));
+                    if let Some(ref i) = normal_indices {
+                        normals.push(vec3(row[i[0]] as f32, row[i[1]] as f32, row[i[2]] as f32));
+                    }
+                    if let Some(ref i) = color_indices {
+                        colors.push(Color::new(
+                            to_u8(row[i[0]], color_type(i[0])),
+                            to_u8(row[i[1]], color_type(i[1])),
+                            to_u8(row[i[2]], color_type(i[2])),
+                            alpha_index
+                                .map(|a| to_u8(row[a], color_type(a)))
+                                .unwrap_or(255),
+                        ));
+                    }
+                    if let Some(ref i) = uv_indices {
+                        uvs.push(vec2(row[i[0]] as f32, 1.0 - row[i[1]] as f32));
+                    }
+                }
+            }
+            "face" => {
+                has_faces = element.count > 0;
+                let index_property = element
+                    .properties
+                    .iter()
+                    .position(|p| match p {
+                        Property::List(name, _, _) => {
+                            name == "vertex_indices" || name == "vertex_index"
+                        }
+                        _ => false,
+                    })
+                    .ok_or(Error::PlyCorruptData)?;
+                let mut polygon = Vec::new();
+                for _ in 0..element.count {
+                    for (i, property) in element.properties.iter().enumerate() {
+                        match property {
+                            Property::List(_, count_type, item_type) if i == index_property => {
+                                let count = body.read(*count_type)? as usize;
+                                polygon.clear();
+                                for _ in 0..count {
+                                    let index = body.read(*item_type)?;
+                                    if !(0.0..=u32::MAX as f64).contains(&index)
+                                        || index.fract() != 0.0
+                                    {
+                                        Err(Error::PlyCorruptData)?;
+                                    }
+                                    polygon.push(index as u32);
+                                }
+                                // Polygons are triangulated as a fan.
+                                for j in 1..count.saturating_sub(1) {
+                                    indices.push(polygon[0]);
+                                    indices.push(polygon[j]);
+                                    indices.push(polygon[j + 1]);
+                                }
+                            }
+                            _ => body.skip(property)?,
+                        }
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        body.skip(property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    if indices.iter().any(|i| *i as usize >= positions.len()) {
+        Err(Error::PlyCorruptData)?;
+    }
+    let positions = if double_precision {
+        Positions::F64(positions)
+    } else {
+        Positions::F32(
+            positions
+                .into_iter()
+                .map(|p| vec3(p.x as f32, p.y as f32, p.z as f32))
+                .collect(),
+        )
+    };
+    let geometry = if has_faces {
+        Geometry::Triangles(TriMesh {
+            positions,
+            indices: Indices::U32(indices),
+            normals: non_empty(normals),
+            colors: non_empty(colors),
+            uvs: non_empty(uvs),
+            ..Default::default()
+        })
+    } else {
+        Geometry::Points(PointCloud {
+            positions,
+            colors: non_empty(colors),
+        })
+    };
+    Ok(Scene {
+        name: path.to_str().unwrap_or("default").to_owned(),
+        children: vec![Node {
+            geometry: Some(geometry),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+}
+
+pub fn serialize_tri_mesh(path: &Path, mesh: &TriMesh, format: PlyFormat) -> Result<RawAssets> {
+    let mut faces = Vec::with_capacity(mesh.triangle_count());
+    mesh.for_each_triangle(|i0, i1, i2| faces.push([i0 as u32, i1 as u32, i2 as u32]));
+    serialize_ply(
+        path,
+        &mesh.positions,
+        mesh.normals.as_deref(),
+        mesh.colors.as_deref(),
+        mesh.uvs.as_deref(),
+        Some(&faces),
+        format,
+    )
+}
+
+pub fn serialize_point_cloud(
+    path: &Path,
+    point_cloud: &PointCloud,
+    format: PlyFormat,
+) -> Result<RawAssets> {
+    serialize_ply(
+        path,
+        &point_cloud.positions,
+        None,
+        point_cloud.colors.as_deref(),
+        None,
+        None,
+        format,
+    )
+}
+
+fn serialize_ply(
+    path: &Path,
+    positions: &Positions,
+    normals: Option<&[Vec3]>,
+    colors: Option<&[Color]>,
+    uvs: Option<&[Vec2]>,
+    faces: Option<&[[u32; 3]]>,
+    format: PlyFormat,
+) -> Result<RawAssets> {
+    use std::fmt::Write;
+    let mut header = String::new();
+    writeln!(header, "ply").unwrap();
+    writeln!(
+        header,
+        "format {} 1.0",
+        match format {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        }
+    )
+    .unwrap();
+    writeln!(header, "element vertex {}", positions.len()).unwrap();
+    let position_type = match positions {
+        Positions::F32(_) => "float",
+        Positions::F64(_) => "double",
+    };
+    for name in ["x", "y", "z"] {
+        writeln!(header, "property {} {}", position_type, name).unwrap();
+    }
+    if normals.is_some() {
+        for name in ["nx", "ny", "nz"] {
+            writeln!(header, "property float {}", name).unwrap();
+        }
+    }
+    if colors.is_some() {
+        for name in ["red", "green", "blue", "alpha"] {
+            writeln!(header, "property uchar {}", name).unwrap();
+        }
+    }
+    if uvs.is_some() {
+        for name in ["s", "t"] {
+            writeln!(header, "property float {}", name).unwrap();
+        }
+    }
+    if let Some(faces) = faces {
+        writeln!(header, "element face {}", faces.len()).unwrap();
+        writeln!(header, "property list uchar uint vertex_indices").unwrap();
+    }
+    writeln!(header, "end_header").unwrap();
+
+    let mut writer = Writer {
+        bytes: header.into_bytes(),
+        format,
+    };
+    for i in 0..positions.len() {
+        match positions {
+            Positions::F32(p) => {
+                writer.f32(p[i].x);
+                writer.f32(p[i].y);
+                writer.f32(p[i].z);
+            }
+            Positions::F64(p) => {
+                writer.f64(p[i].x);
+                writer.f64(p[i].y);
+                writer.f64(p[i].z);
+            }
+        }
+        if let Some(normals) = normals {
+            writer.f32(normals[i].x);
+            writer.f32(normals[i].y);
+            writer.f32(normals[i].z);
+        }
+        if let Some(colors) = colors {
+            writer.u8(colors[i].r);
+            writer.u8(colors[i].g);
+            writer.u8(colors[i].b);
+            writer.u8(colors[i].a);
+        }
+        if let Some(uvs) = uvs {
+            writer.f32(uvs[i].x);
+            writer.f32(1.0 - uvs[i].y);
+        }
+        writer.end_line();
+    }
+    for face in faces.unwrap_or_default() {
+        writer.u8(3);
+        for index in face {
+            writer.u32(*index);
+        }
+        writer.end_line();
+    }
+
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, writer.bytes);
+    Ok(raw_assets)
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+    format: PlyFormat,
+}
+
+impl Writer {
+    fn write(&mut self, text: impl std::fmt::Display, le: &[u8], be: &[u8]) {
+        match self.format {
+            PlyFormat::Ascii => self.bytes.extend(format!("{} ", text).into_bytes()),
+            PlyFormat::BinaryLittleEndian => self.bytes.extend(le),
+            PlyFormat::BinaryBigEndian => self.bytes.extend(be),
+        }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.write(value, &[value], &[value]);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.write(value, &value.to_le_bytes(), &value.to_be_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.write(value, &value.to_le_bytes(), &value.to_be_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.write(value, &value.to_le_bytes(), &value.to_be_bytes());
+    }
+
+    fn end_line(&mut self) {
+        if self.format == PlyFormat::Ascii {
+            self.bytes.pop();
+            self.bytes.push(b'\n');
+        }
+    }
+}
+
+fn non_empty<T>(v: Vec<T>) -> Option<Vec<T>> {
+    if v.is_empty() {
+        None
+    } else {
+        Some(v)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => Err(Error::PlyCorruptData)?,
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Property {
+    Scalar(String, ScalarType),
+    /// A list with a name, the type of the count and the type of the items.
+    List(String, ScalarType, ScalarType),
+}
+
+#[derive(Debug, Clone)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+#[derive(Debug, Clone)]
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+}
+
+///
+/// Parses the header and returns it together with the byte offset to the start of the body.
+///
+fn parse_header(bytes: &[u8]) -> Result<(Header, usize)> {
+    let end = b"end_header";
+    let end_index = bytes
+        .windows(end.len())
+        .position(|w| w == end)
+        .ok_or(Error::PlyCorruptData)?;
+    let mut body_start = end_index + end.len();
+    while body_start < bytes.len() && bytes[body_start] != b'\n' {
+        body_start += 1;
+    }
+    body_start += 1;
+
+    let text = std::str::from_utf8(&bytes[..end_index]).map_err(|_| Error::PlyCorruptData)?;
+    let mut lines = text.lines();
+    if lines.next().map(|l| l.trim()) != Some("ply") {
+        Err(Error::PlyCorruptData)?;
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    for line in lines {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["format", f, _] => {
+                format = Some(match *f {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => Err(Error::PlyCorruptData)?,
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| Error::PlyCorruptData)?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => elements
+                .last_mut()
+                .ok_or(Error::PlyCorruptData)?
+                .properties
+                .push(Property::List(
+                    name.to_string(),
+                    ScalarType::parse(count_type)?,
+                    ScalarType::parse(item_type)?,
+                )),
+            ["property", ty, name] => elements
+                .last_mut()
+                .ok_or(Error::PlyCorruptData)?
+                .properties
+                .push(Property::Scalar(name.to_string(), ScalarType::parse(ty)?)),
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => Err(Error::PlyCorruptData)?,
+        }
+    }
+    Ok((
+        Header {
+            format: format.ok_or(Error::PlyCorruptData)?,
+            elements,
+        },
+        body_start.min(bytes.len()),
+    ))
+}
+
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary {
+        bytes: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl Body<'_> {
+    fn read(&mut self, ty: ScalarType) -> Result<f64> {
+        match self {
+            Self::Ascii(tokens) => tokens
+                .next()
+                .and_then(|t| t.parse::<f64>().ok())
+                .ok_or(Error::PlyCorruptData),
+            Self::Binary {
+                bytes,
+                offset,
+                big_endian,
+            } => {
+                let size = ty.size();
+                let b = bytes
+                    .get(*offset..*offset + size)
+                    .ok_or(Error::PlyCorruptData)?;
+                *offset += size;
+                let mut data = [0u8; 8];
+                data[..size].copy_from_slice(b);
+                if *big_endian {
+                    data[..size].reverse();
+                }
+                Ok(match ty {
+                    ScalarType::I8 => data[0] as i8 as f64,
+                    ScalarType::U8 => data[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([data[0], data[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([data[0], data[1]]) as f64,
+                    ScalarType::I32 => {
+                        i32::from_le_bytes([data[0], data[1], data[2], data[3]]) as f64
+                    }
+                    ScalarType::U32 => {
+                        u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as f64
+                    }
+                    ScalarType::F32 => {
+                        f32::from_le_bytes([data[0], data[1], data[2], data[3]]) as f64
+                    }
+                    ScalarType::F64 => f64::from_le_bytes(data),
+                })
+            }
+        }
+    }
+
+    ///
+    /// Reads all scalar properties into the row. Lists are skipped and a zero is inserted instead, so the row can be indexed by property index.
+    ///
+    fn read_row(&mut self, properties: &[Property], row: &mut Vec<f64>) -> Result<()> {
+        row.clear();
+        for property in properties {
+            match property {
+                Property::Scalar(_, ty) => row.push(self.read(*ty)?),
+                Property::List(..) => {
+                    self.skip(property)?;
+                    row.push(0.0);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, property: &Property) -> Result<()> {
+        match property {
+            Property::Scalar(_, ty) => {
+                self.read(*ty)?;
+            }
+            Property::List(_, count_type, item_type) => {
+                let count = self.read(*count_type)? as usize;
+                for _ in 0..count {
+                    self.read(*item_type)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_u8(value: f64, ty: ScalarType) -> u8 {
+    match ty {
+        ScalarType::F32 | ScalarType::F64 => (value * 255.0).round().clamp(0.0, 255.0) as u8,
+        ScalarType::I16 | ScalarType::U16 => (value / 257.0).round().clamp(0.0, 255.0) as u8,
+        _ => value.clamp(0.0, 255.0) as u8,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PlyFormat;
+    use crate::io::{Deserialize, Serialize, SerializeWithOptions};
+    use crate::{Color, Indices, PointCloud, Positions, TriMesh};
+
+    #[test]
+    pub fn deserialize_ascii_ply() {
+        let mesh: TriMesh = crate::io::load_and_deserialize("test_data/cube.ply").unwrap();
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert!(mesh.normals.is_none());
+        assert_eq!(mesh.colors.as_ref().unwrap()[1], Color::GREEN);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn deserialize_binary_ply() {
+        let mesh: TriMesh = crate::io::load_and_deserialize("test_data/cube_binary.ply").unwrap();
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.triangle_count(), 12);
+        assert!(matches!(mesh.indices, Indices::U32(_)));
+        assert_eq!(mesh.normals.as_ref().map(|n| n.len()), Some(8));
+        assert_eq!(mesh.uvs.as_ref().map(|n| n.len()), Some(8));
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn deserialize_big_endian_ply() {
+        let point_cloud: PointCloud =
+            crate::io::load_and_deserialize("test_data/points.ply").unwrap();
+        if let Positions::F64(positions) = point_cloud.positions {
+            assert_eq!(positions.len(), 4);
+            assert_eq!(positions[1], crate::prelude::vec3(-1.25, 0.0, 3.0));
+        } else {
+            panic!("expected double precision positions");
+        }
+        assert_eq!(point_cloud.colors.unwrap()[2], Color::new(20, 40, 60, 255));
+    }
+
+    #[test]
+    pub fn deserialize_ply_with_invalid_indices() {
+        let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n";
+        for face in ["3 0 1 -1", "3 0 1 3", "3 0 1 1.5"] {
+            let mut raw_assets = crate::io::RawAssets::new();
+            raw_assets.insert("invalid.ply", format!("{header}{face}\n").into_bytes());
+            assert!(matches!(
+                super::deserialize_ply(&mut raw_assets, &"invalid.ply".into()),
+                Err(crate::Error::PlyCorruptData)
+            ));
+        }
+        let mut raw_assets = crate::io::RawAssets::new();
+        raw_assets.insert("valid.ply", format!("{header}3 0 1 2\n").into_bytes());
+        let mesh = TriMesh::deserialize("valid.ply", &mut raw_assets).unwrap();
+        assert_eq!(mesh.indices.to_u32(), Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    pub fn serialize_ply() {
+        let mut mesh = TriMesh::sphere(8);
+        mesh.colors = Some(vec![Color::RED; mesh.vertex_count()]);
+        mesh.uvs = Some(
+            mesh.positions
+                .to_f32()
+                .iter()
+                .map(|p| crate::prelude::vec2(p.x, p.y))
+                .collect(),
+        );
+        for format in [
+            PlyFormat::Ascii,
+            PlyFormat::BinaryLittleEndian,
+            PlyFormat::BinaryBigEndian,
+        ] {
+            let mut raw_assets = mesh.serialize_with_options("sphere.ply", format).unwrap();
+            let result = TriMesh::deserialize("sphere.ply", &mut raw_assets).unwrap();
+            assert_eq!(result.positions.to_f32(), mesh.positions.to_f32());
+            assert_eq!(result.indices.to_u32(), mesh.indices.to_u32());
+            assert_eq!(result.normals, mesh.normals);
+            assert_eq!(result.colors, mesh.colors);
+            let uvs = result.uvs.unwrap();
+            for (a, b) in uvs.iter().zip(mesh.uvs.as_ref().unwrap()) {
+                assert!((a.x - b.x).abs() < 0.00001 && (a.y - b.y).abs() < 0.00001);
+            }
+        }
+    }
+
+    #[test]
+    pub fn serialize_ply_without_indices() {
+        let mesh = TriMesh::cube();
+        assert!(matches!(mesh.indices, Indices::None));
+        let mut raw_assets = mesh.serialize("cube.ply").unwrap();
+        let result = TriMesh::deserialize("cube.ply", &mut raw_assets).unwrap();
+        assert_eq!(result.indices.to_u32(), Some((0..36).collect()));
+    }
+
+    #[test]
+    pub fn serialize_ply_point_cloud() {
+        let point_cloud = PointCloud::cube();
+        let mut raw_assets = point_cloud
+            .serialize_with_options("points.ply", PlyFormat::Ascii)
+            .unwrap();
+        let text = String::from_utf8(raw_assets.get("points.ply").unwrap().to_vec()).unwrap();
+        assert!(!text.contains("element face"));
+        let result = PointCloud::deserialize("points.ply", &mut raw_assets).unwrap();
+        assert_eq!(result.positions.to_f32(), point_cloud.positions.to_f32());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{io::RawAssets, texture::*, Error, Result};
+use std::path::Path;
+
+const QOI_OP_INDEX: u8 = 0x00;
+const QOI_OP_DIFF: u8 = 0x40;
+const QOI_OP_LUMA: u8 = 0x80;
+const QOI_OP_RUN: u8 = 0xc0;
+const QOI_OP_RGB: u8 = 0xfe;
+const QOI_OP_RGBA: u8 = 0xff;
+const QOI_MASK: u8 = 0xc0;
+const QOI_END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+///
+/// Deserialize a .qoi file into a [Texture2D] with [TextureData::RgbU8] data if the file has 3 channels and [TextureData::RgbaU8] data if it has 4 channels.
+/// The colorspace field in the header determines [Texture2D::color_space].
+///
+pub fn deserialize_qoi(path: impl AsRef<Path>, bytes: &[u8]) -> Result<Texture2D> {
+    let name = path
+        .as_ref()
+        .to_str()
+        .filter(|s| !s.starts_with("data:"))
+        .unwrap_or("default")
+        .to_owned();
+    if bytes.len() < 14 + QOI_END_MARKER.len() || !bytes.starts_with(b"qoif") {
+        Err(Error::QoiCorruptData)?;
+    }
+    let width = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    let height = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+    let channels = bytes[12];
+    if channels != 3 && channels != 4 {
+        Err(Error::QoiCorruptData)?;
+    }
+    let end = bytes.len() - QOI_END_MARKER.len();
+    // A single op encodes at most a run of 62 pixels, so the header can not declare more pixels than that before the data is allocated.
+    let pixel_count = (width as usize)
+        .checked_mul(height as usize)
+        .filter(|&count| count <= (end - 14).saturating_mul(62))
+        .ok_or(Error::QoiCorruptData)?;
+
+    let mut pixels = Vec::with_capacity(pixel_count);
+    let mut index = [[0u8; 4]; 64];
+    let mut pixel = [0u8, 0, 0, 255];
+    let mut offset = 14;
+    let mut read = || {
+        let b = *bytes[..end].get(offset).ok_or(Error::QoiCorruptData)?;
+        offset += 1;
+        Ok::<_, Error>(b)
+    };
+    while pixels.len() < pixel_count {
+        let b = read()?;
+        let mut run = 1;
+        match b {
+            QOI_OP_RGB => {
+                pixel = [read()?, read()?, read()?, pixel[3]];
+            }
+            QOI_OP_RGBA => {
+                pixel = [read()?, read()?, read()?, read()?];
+            }
+            _ => match b & QOI_MASK {
+                QOI_OP_INDEX => {
+                    pixel = index[b as usize];
+                }
+                QOI_OP_DIFF => {
+                    pixel[0] = pixel[0].wrapping_add((b >> 4) & 0x03).wrapping_sub(2);
+                    pixel[1] = pixel[1].wrapping_add((b >> 2) & 0x03).wrapping_sub(2);
+                    pixel[2] = pixel[2].wrapping_add(b & 0x03).wrapping_sub(2);
+                }
+                QOI_OP_LUMA => {
+                    let b2 = read()?;
+                    let dg = (b & 0x3f).wrapping_sub(32);
+                    pixel[0] = pixel[0].wrapping_add(dg.wrapping_sub(8).wrapping_add(b2 >> 4));
+                    pixel[1] = pixel[1].wrapping_add(dg);
+                    pixel[2] = pixel[2].wrapping_add(dg.wrapping_sub(8).wrapping_add(b2 & 0x0f));
+                }
+                _ => {
+                    run = (b & 0x3f) as usize + 1;
+                }
+            },
+        }
+        index[hash(pixel)] = pixel;
+        for _ in 0..run.min(pixel_count - pixels.len()) {
+            pixels.push(pixel);
+        }
+    }
+
+    let data = if channels == 3 {
+        TextureData::RgbU8(pixels.into_iter().map(|p| [p[0], p[1], p[2]]).collect())
+    } else {
+        TextureData::RgbaU8(pixels)
+    };
+    Ok(Texture2D {
+        name,
+        data,
+        width,
+        height,
+        color_space: if bytes[13] == 1 {
+            ColorSpace::Linear
+        } else {
+            ColorSpace::Srgb
+        },
+        ..Default::default()
+    })
+}
+
+///
+/// Serialize the given [Texture2D] into a .qoi file. [TextureData::RgbU8] data is written with 3 channels and [TextureData::RgbaU8] data with 4 channels,
+/// block compressed data is decompressed first and all other formats are not supported.
+/// The texel data is marked as sRGB or linear in the header according to [Texture2D::color_space].
+///
+pub fn serialize_qoi(tex: &Texture2D, path: &Path) -> Result<RawAssets> {
+    let (channels, pixels) = match tex.data.decompress(tex.width, tex.height)? {
+        TextureData::RgbU8(data) => (3, data.iter().map(|p| [p[0], p[1], p[2], 255]).collect()),
+        TextureData::RgbaU8(data) => (4, data),
+        _ => Err(Error::FailedSerialize(path.to_str().unwrap().to_string()))?,
+    };
+    if pixels.len() != tex.width as usize * tex.height as usize {
+        Err(Error::InvalidBufferLength(
+            "Texture data".to_owned(),
+            tex.width as usize * tex.height as usize,
+            pixels.len(),
+        ))?;
+    }
+
+    let mut bytes = Vec::with_capacity(14 + pixels.len() * 2 + QOI_END_MARKER.len());
+    bytes.extend_from_slice(b"qoif");
+    bytes.extend_from_slice(&tex.width.to_be_bytes());
+    bytes.extend_from_slice(&tex.height.to_be_bytes());
+    bytes.push(channels);
+    bytes.push(match tex.color_space {
+        ColorSpace::Srgb => 0,
+        ColorSpace::Linear => 1,
+    });
+
+    let mut index = [[0u8; 4]; 64];
+    let mut previous = [0u8, 0, 0, 255];
+    let mut run = 0u8;
+    for (i, &pixel) in pixels.iter().enumerate() {
+        if pixel == previous {
+            run += 1;
+            if run == 62 || i == pixels.len() - 1 {
+                bytes.push(QOI_OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            bytes.push(QOI_OP_RUN | (run - 1));
+            run = 0;
+        }
+
+        let hash = hash(pixel);
+        if index[hash] == pixel {
+            bytes.push(QOI_OP_INDEX | hash as u8);
+        } else {
+            index[hash] = pixel;
+            if pixel[3] == previous[3] {
+                let dr = pixel[0].wrapping_sub(previous[0]) as i8;
+                let dg = pixel[1].wrapping_sub(previous[1]) as i8;
+                let db = pixel[2].wrapping_sub(previous[2]) as i8;
+                let dr_dg = dr.wrapping_sub(dg);
+                let db_dg = db.wrapping_sub(dg);
+                if (-2..2).contains(&dr) && (-2..2).contains(&dg) && (-2..2).contains(&db) {
+                    bytes.push(
+                        QOI_OP_DIFF
+                            | ((dr + 2) as u8) << 4
+                            | ((dg + 2) as u8) << 2
+                            | (db + 2) as u8,
+                    );
+                } else if (-32..32).contains(&dg)
+                    && (-8..8).contains(&dr_dg)
+                    && (-8..8).contains(&db_dg)
+                {
+                    bytes.push(QOI_OP_LUMA | (dg + 32) as u8);
+                    bytes.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                } else {
+                    bytes.extend_from_slice(&[QOI_OP_RGB, pixel[0], pixel[1], pixel[2]]);
+                }
+            } else {
+                bytes.push(QOI_OP_RGBA);
+                bytes.extend_from_slice(&pixel);
+            }
+        }
+        previous = pixel;
+    }
+    bytes.extend_from_slice(&QOI_END_MARKER);
+
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, bytes);
+    Ok(raw_assets)
+}
+
+fn hash(pixel: [u8; 4]) -> usize {
+    (pixel[0] as usize * 3 + pixel[1] as usize * 5 + pixel[2] as usize * 7 + pixel[3] as usize * 11)
+        % 64
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{io::*, ColorSpace, Texture2D, TextureData};
+
+    #[test]
+    pub fn deserialize_qoi() {
+        // A 3x2 image with an RGB, a LUMA, a DIFF, an INDEX and a RUN op.
+        let bytes = [
+            b"qoif".to_vec(),
+            vec![0, 0, 0, 3, 0, 0, 0, 2, 3, 0],
+            vec![0xfe, 100, 150, 200],
+            vec![0x80 | 42, 0x88],
+            vec![0x40 | 0b11_10_01],
+            vec![super::hash([100, 150, 200, 255]) as u8],
+            vec![0xc0 | 1],
+            super::QOI_END_MARKER.to_vec(),
+        ]
+        .concat();
+        let tex: Texture2D = RawAssets::new()
+            .insert("test.qoi", bytes)
+            .deserialize("")
+            .unwrap();
+        assert_eq!((tex.width, tex.height), (3, 2));
+        assert_eq!(
+            tex.data,
+            TextureData::RgbU8(vec![
+                [100, 150, 200],
+                [110, 160, 210],
+                [111, 160, 209],
+                [100, 150, 200],
+                [100, 150, 200],
+                [100, 150, 200],
+            ])
+        );
+    }
+
+    #[test]
+    pub fn deserialize_qoi_too_large_header() {
+        let bytes = [
+            b"qoif".to_vec(),
+            vec![0, 0, 0xff, 0xff, 0, 0, 0xff, 0xff, 4, 0],
+            vec![0xc0 | 61],
+            super::QOI_END_MARKER.to_vec(),
+        ]
+        .concat();
+        assert!(matches!(
+            super::deserialize_qoi("test.qoi", &bytes),
+            Err(crate::Error::QoiCorruptData)
+        ));
+
+        // The largest image a single run can describe is still accepted.
+        let mut bytes = bytes;
+        bytes[4..12].copy_from_slice(&[0, 0, 0, 62, 0, 0, 0, 1]);
+        let tex = super::deserialize_qoi("test.qoi", &bytes).unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[0, 0, 0, 255]; 62]));
+    }
+
+    #[test]
+    pub fn serialize_qoi() {
+        let mut data = Vec::new();
+        for y in 0..16u8 {
+            for x in 0..16u8 {
+                data.push([
+                    x * 16,
+                    y * 16,
+                    x.wrapping_mul(y),
+                    if x < 8 { 255 } else { y },
+                ]);
+            }
+        }
+        data.extend(vec![[1, 2, 3, 4]; 96]);
+        let tex = Texture2D {
+            data: TextureData::RgbaU8(data),
+            width: 16,
+            height: 22,
+            ..Default::default()
+        };
+        let mut raw_assets = tex.serialize("test.qoi").unwrap();
+        let bytes = raw_assets.get("test.qoi").unwrap();
+        assert!(bytes.len() < 16 * 22 * 4);
+        let result: Texture2D = raw_assets.deserialize("test.qoi").unwrap();
+        assert_eq!((result.width, result.height), (16, 22));
+        assert_eq!(result.data, tex.data);
+
+        let tex = Texture2D {
+            data: TextureData::RgbU8(vec![[255, 0, 0], [250, 3, 1], [0, 0, 255], [0, 0, 255]]),
+            width: 2,
+            height: 2,
+            ..Default::default()
+        };
+        let result: Texture2D = tex.serialize("test.qoi").unwrap().deserialize("").unwrap();
+        assert_eq!(result.data, tex.data);
+        assert_eq!(result.color_space, ColorSpace::Srgb);
+
+        let tex = Texture2D {
+            color_space: ColorSpace::Linear,
+            ..tex
+        };
+        let result: Texture2D = tex.serialize("test.qoi").unwrap().deserialize("").unwrap();
+        assert_eq!(result.color_space, ColorSpace::Linear);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{io::Deserialize, Error, Result};
+use crate::{
+    io::{Deserialize, DeserializeWithOptions},
+    Error, Result,
+};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -10,7 +13,29 @@ use std::path::{Path, PathBuf};
 /// or [RawAssets::deserialize] to deserialize an asset or [RawAssets::save] to save the assets.
 ///
 #[derive(Default)]
-pub struct RawAssets(HashMap<PathBuf, Vec<u8>>);
+pub struct RawAssets {
+    assets: HashMap<PathBuf, Vec<u8>>,
+    case_insensitive: bool,
+    warnings: Vec<Warning>,
+}
+
+///
+/// A non-fatal issue found while deserializing an asset, for example data which is not supported and therefore skipped.
+/// The warnings are collected by the [RawAssets] that the asset is deserialized from, see [RawAssets::deserialize_with_warnings].
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// The path of the asset that the warning concerns.
+    pub path: PathBuf,
+    /// A human readable description of the issue.
+    pub message: String,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
 
 impl RawAssets {
     ///
@@ -37,7 +62,10 @@ impl RawAssets {
     /// ```
     ///
     pub fn remove(&mut self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
-        Ok(self.0.remove(&self.match_path(path.as_ref())?).unwrap())
+        Ok(self
+            .assets
+            .remove(&self.match_path(path.as_ref())?)
+            .unwrap())
     }
 
     ///
@@ -57,27 +85,114 @@ impl RawAssets {
     /// ```
     ///
     pub fn get(&self, path: impl AsRef<Path>) -> Result<&[u8]> {
-        Ok(self.0.get(&self.match_path(path.as_ref())?).unwrap())
+        Ok(self.assets.get(&self.match_path(path.as_ref())?).unwrap())
     }
 
-    pub(crate) fn match_path(&self, path: &Path) -> Result<PathBuf> {
-        if self.0.contains_key(path) {
-            Ok(path.into())
+    ///
+    /// Returns the MIME type of the asset at the given path if the asset was loaded from a data URL, for example `image/png` for `data:image/png;base64,...`.
+    /// Returns `text/plain` if the data URL does not specify a MIME type and `None` if the asset was not loaded from a data URL.
+    /// The path is matched in the same way as in [RawAssets::get].
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let mut assets = RawAssets::new();
+    /// assets.insert("data:image/svg+xml,%3Csvg%3E%3C/svg%3E", Vec::new());
+    /// assert_eq!(assets.mime_type("data:image/svg+xml"), Some("image/svg+xml"));
+    /// ```
+    ///
+    pub fn mime_type(&self, path: impl AsRef<Path>) -> Option<&str> {
+        let path = self.find_path(path.as_ref())?.to_str()?;
+        let header = path.strip_prefix("data:")?.split(',').next()?;
+        let mime_type = header.split(';').next().unwrap_or("").trim();
+        Some(if mime_type.is_empty() {
+            "text/plain"
         } else {
-            let p = path.to_str().unwrap().replace('\\', "/");
-            let p = if p.ends_with(".jpeg") {
-                p[0..p.len() - 2].to_string()
-            } else if p.ends_with(".jpg") {
-                p[0..p.len() - 1].to_string()
-            } else {
-                p
-            };
-            self.0
-                .iter()
-                .find(|(k, _)| k.to_str().unwrap().contains(&p))
-                .map(|(k, _)| k.clone())
-                .ok_or(Error::NotLoaded(path.to_str().unwrap().to_string()))
+            mime_type
+        })
+    }
+
+    ///
+    /// Returns an iterator over the paths of all the assets.
+    /// The order of the paths is unspecified and may differ between two sets of raw assets with the same content.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let assets = load(&["test_data/test.png"]).unwrap();
+    /// let textures = assets.paths().filter(|path| path.extension().is_some_and(|e| e == "png"));
+    /// assert_eq!(textures.count(), 1);
+    /// ```
+    ///
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.assets.keys().map(|path| path.as_path())
+    }
+
+    ///
+    /// Returns an iterator over the paths and raw byte arrays of all the assets.
+    /// The order of the assets is unspecified and may differ between two sets of raw assets with the same content.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &[u8])> {
+        self.assets
+            .iter()
+            .map(|(path, bytes)| (path.as_path(), bytes.as_slice()))
+    }
+
+    ///
+    /// Returns whether an asset exists at the given path using the same matching as [RawAssets::get] and [RawAssets::remove].
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let assets = load(&["test_data/test.png"]).unwrap();
+    /// assert!(assets.contains("test.png"));
+    /// assert!(!assets.contains("test.jpg"));
+    /// ```
+    ///
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        self.find_path(path.as_ref()).is_some()
+    }
+
+    pub(crate) fn match_path(&self, path: &Path) -> Result<PathBuf> {
+        self.find_path(path)
+            .map(|p| p.to_path_buf())
+            .ok_or_else(|| Error::NotLoaded(path.to_str().unwrap().to_string()))
+    }
+
+    fn find_path(&self, path: &Path) -> Option<&Path> {
+        if let Some((key, _)) = self.assets.get_key_value(path) {
+            return Some(key);
         }
+        find_matching_path(
+            self.assets.keys().map(|key| key.as_path()),
+            path,
+            self.case_insensitive,
+        )
+    }
+
+    ///
+    /// Enables or disables case-insensitive path matching, which is disabled by default.
+    /// When enabled, the path given to for example [RawAssets::get], [RawAssets::remove] and [RawAssets::deserialize] is compared to the paths of the assets ignoring case
+    /// if no asset matches the path using the default case-sensitive matching.
+    /// This is useful for assets authored on case-insensitive file systems, for example a `.gltf` file referencing `Texture.PNG` while the file is named `texture.png`.
+    ///
+    /// **Note:** If several assets have paths which only differ by case, an exact match is always preferred, otherwise an arbitrary one of them is returned.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let mut assets = load(&["test_data/test.png"]).unwrap();
+    /// assert!(!assets.contains("TEST.PNG"));
+    /// assets.set_case_insensitive(true);
+    /// assert!(assets.contains("TEST.PNG"));
+    /// ```
+    ///
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) -> &mut Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    ///
+    /// Returns whether case-insensitive path matching is enabled, see [RawAssets::set_case_insensitive].
+    ///
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
     }
 
     ///
@@ -90,25 +205,131 @@ impl RawAssets {
     /// # let png_bytes = include_bytes!("../../test_data/test.png").to_vec();
     /// let mut assets = RawAssets::new();
     /// assets.insert("test.png", png_bytes);
+    /// # #[cfg(feature = "png")]
     /// let texture: Texture2D = assets.deserialize("test.png").unwrap();
     /// ```
     ///
     pub fn insert(&mut self, path: impl AsRef<Path>, bytes: Vec<u8>) -> &mut Self {
         let key = path.as_ref().to_str().unwrap().replace('\\', "/").into();
-        self.0.insert(key, bytes);
+        self.assets.insert(key, bytes);
         self
     }
 
+    ///
+    /// Reads the given reader to completion and inserts the bytes into the set of raw assets at the given path, in the same way as [RawAssets::insert].
+    /// This is useful if the assets are read from a custom source, for example a custom container format or a network stream.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let mut reader = std::io::Cursor::new(vec![1, 2, 3]);
+    /// let mut assets = RawAssets::new();
+    /// assets.insert_reader("data.bin", &mut reader).unwrap();
+    /// assert_eq!(assets.get("data.bin").unwrap(), &[1, 2, 3]);
+    /// ```
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn insert_reader(
+        &mut self,
+        path: impl AsRef<Path>,
+        reader: &mut impl std::io::Read,
+    ) -> Result<&mut Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(self.insert(path, bytes))
+    }
+
     ///
     /// Inserts all of the given raw assets into this set of raw assets.
+    /// Assets with the same path as an existing asset overwrite the existing asset, which is the same as [RawAssets::merge].
     ///
     pub fn extend(&mut self, mut raw_assets: Self) -> &mut Self {
-        for (k, v) in raw_assets.0.drain() {
+        for (k, v) in raw_assets.assets.drain() {
             self.insert(k, v);
         }
+        self.warnings.append(&mut raw_assets.warnings);
         self
     }
 
+    ///
+    /// Merges the given raw assets into this set of raw assets.
+    /// If both sets contain an asset with the same path, the asset in `other` overwrites the existing asset.
+    /// Use [RawAssets::merge_with] for custom conflict resolution.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let mut assets = load(&["test_data/test.png"]).unwrap();
+    /// assets.merge(load(&["test_data/test.jpg"]).unwrap());
+    /// assert!(assets.contains("test.png") && assets.contains("test.jpg"));
+    /// ```
+    ///
+    pub fn merge(&mut self, other: Self) -> &mut Self {
+        self.merge_with(other, |_, _, new| new)
+    }
+
+    ///
+    /// Merges the given raw assets into this set of raw assets.
+    /// If both sets contain an asset with the same path, the `resolve` closure is called with the path, the existing bytes and the bytes from `other`
+    /// and the returned bytes are stored for that path.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let mut assets = load(&["test_data/test.png"]).unwrap();
+    /// let png_length = assets.get("test.png").unwrap().len();
+    /// let mut other = RawAssets::new();
+    /// other.insert("test_data/test.png", Vec::new());
+    /// assets.merge_with(other, |_, existing, _| existing);
+    /// assert_eq!(assets.get("test.png").unwrap().len(), png_length);
+    /// ```
+    ///
+    pub fn merge_with(
+        &mut self,
+        mut other: Self,
+        mut resolve: impl FnMut(&Path, Vec<u8>, Vec<u8>) -> Vec<u8>,
+    ) -> &mut Self {
+        for (path, bytes) in other.assets.drain() {
+            let bytes = match self.assets.remove(&path) {
+                Some(existing) => resolve(&path, existing, bytes),
+                None => bytes,
+            };
+            self.assets.insert(path, bytes);
+        }
+        self.warnings.append(&mut other.warnings);
+        self
+    }
+
+    ///
+    /// Removes all assets from this set of raw assets for which the given predicate returns `false`, ie. this mutates the set of raw assets in place.
+    /// Use [RawAssets::filter] to keep this set of raw assets unchanged.
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// let mut assets = load(&["test_data/test.png", "test_data/test.jpg"]).unwrap();
+    /// assets.retain(|path, _| path.extension().is_some_and(|e| e == "png"));
+    /// assert!(assets.contains("test.png") && !assets.contains("test.jpg"));
+    /// ```
+    ///
+    pub fn retain(&mut self, mut predicate: impl FnMut(&Path, &[u8]) -> bool) -> &mut Self {
+        self.assets.retain(|path, bytes| predicate(path, bytes));
+        self
+    }
+
+    ///
+    /// Returns a new set of raw assets which contains a copy of the assets for which the given predicate returns `true`.
+    /// Use [RawAssets::retain] to avoid copying the assets.
+    ///
+    pub fn filter(&self, mut predicate: impl FnMut(&Path, &[u8]) -> bool) -> Self {
+        Self {
+            assets: self
+                .assets
+                .iter()
+                .filter(|(path, bytes)| predicate(path, bytes))
+                .map(|(path, bytes)| (path.clone(), bytes.clone()))
+                .collect(),
+            case_insensitive: self.case_insensitive,
+            warnings: self.warnings.clone(),
+        }
+    }
+
     ///
     /// Deserialize the asset with the given path into a type that implements the [Deserialize] trait.
     ///
@@ -116,6 +337,7 @@ impl RawAssets {
     /// # use three_d_asset::io::*;
     /// # use three_d_asset::Texture2D;
     /// let mut assets = load(&["test_data/test.png"]).unwrap();
+    /// # #[cfg(feature = "png")]
     /// let texture: Texture2D = assets.deserialize("test.png").unwrap();
     /// ```
     ///
@@ -124,10 +346,110 @@ impl RawAssets {
     /// ```
     /// # use three_d_asset::io::*;
     /// # use three_d_asset::Texture2D;
+    /// # #[cfg(feature = "png")]
     /// let texture: Texture2D = load(&["test_data/test.png"]).unwrap().deserialize("").unwrap();
     /// ```
     pub fn deserialize<T: Deserialize>(&mut self, path: impl AsRef<Path>) -> Result<T> {
-        T::deserialize(path, self)
+        let path = self.match_path(path.as_ref())?;
+        let format = self.format(&path);
+        T::deserialize(&path, self).map_err(|e| e.with_parse_context(&path, &format))
+    }
+
+    ///
+    /// Deserialize the asset with the given path into a type that implements the [DeserializeWithOptions] trait using the given format specific options.
+    /// The path is matched in the same way as in [RawAssets::deserialize].
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// # use three_d_asset::TriMesh;
+    /// # #[cfg(feature = "stl")]
+    /// # {
+    /// let mut assets = load(&["test_data/cube.stl"]).unwrap();
+    /// let mesh: TriMesh = assets
+    ///     .deserialize_with_options("cube.stl", StlOptions { weld_vertices: true })
+    ///     .unwrap();
+    /// # }
+    /// ```
+    ///
+    pub fn deserialize_with_options<T: DeserializeWithOptions<O>, O>(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: O,
+    ) -> Result<T> {
+        let path = self.match_path(path.as_ref())?;
+        let format = self.format(&path);
+        T::deserialize_with_options(&path, self, options)
+            .map_err(|e| e.with_parse_context(&path, &format))
+    }
+
+    ///
+    /// Deserialize the asset with the given path in the same way as [RawAssets::deserialize] and returns it together with the warnings that were emitted while deserializing it,
+    /// for example about unsupported data that was skipped.
+    /// The returned warnings are removed from the warnings of this set of raw assets, see [RawAssets::warnings].
+    ///
+    /// ```
+    /// # use three_d_asset::io::*;
+    /// # use three_d_asset::Model;
+    /// # #[cfg(feature = "obj")]
+    /// # {
+    /// let mut assets = load(&["test_data/cube.obj"]).unwrap();
+    /// let (model, warnings): (Model, _) = assets.deserialize_with_warnings("cube.obj").unwrap();
+    /// for warning in warnings {
+    ///     println!("{}", warning);
+    /// }
+    /// # }
+    /// ```
+    ///
+    pub fn deserialize_with_warnings<T: Deserialize>(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<(T, Vec<Warning>)> {
+        let start = self.warnings.len();
+        let asset = self.deserialize(path)?;
+        Ok((asset, self.warnings.split_off(start)))
+    }
+
+    ///
+    /// Returns the warnings that have been emitted while deserializing assets from this set of raw assets and which have not been taken yet.
+    /// The warnings are also logged using the `log` crate if the `log` feature is enabled.
+    ///
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    ///
+    /// Removes and returns the warnings that have been emitted while deserializing assets from this set of raw assets, see [RawAssets::warnings].
+    ///
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    ///
+    /// Adds a warning about the asset at the given path and logs it if the `log` feature is enabled.
+    ///
+    #[cfg(any(feature = "obj", feature = "gltf"))]
+    pub(crate) fn warn(&mut self, path: &Path, message: impl Into<String>) {
+        let warning = Warning {
+            path: path.to_path_buf(),
+            message: message.into(),
+        };
+        #[cfg(feature = "log")]
+        log::warn!("{}", warning);
+        self.warnings.push(warning);
+    }
+
+    ///
+    /// Returns the format of the asset at the given path, ie. the MIME type for data URLs and otherwise the file extension.
+    ///
+    fn format(&self, path: &Path) -> String {
+        self.mime_type(path)
+            .map(|mime_type| mime_type.to_owned())
+            .or_else(|| {
+                path.extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_lowercase())
+            })
+            .unwrap_or_else(|| "unknown".to_owned())
     }
 
     ///
@@ -140,21 +462,127 @@ impl RawAssets {
     }
 }
 
+///
+/// Returns the first of the given paths which contains the given path, see [RawAssets::get], optionally ignoring case if no path matches with the case-sensitive comparison.
+///
+pub(super) fn find_matching_path<'a>(
+    paths: impl Iterator<Item = &'a Path> + Clone,
+    path: &Path,
+    case_insensitive: bool,
+) -> Option<&'a Path> {
+    let path = path.to_str().unwrap();
+    find_canonical_path(paths.clone(), &canonical_path(path, false), false).or_else(|| {
+        case_insensitive
+            .then(|| find_canonical_path(paths, &canonical_path(path, true), true))
+            .flatten()
+    })
+}
+
+///
+/// Returns the first of the given paths which contains the given canonical path, see [canonical_path].
+/// A path which ends with the given path at a path component boundary is preferred, so `photo.jpg` matches `a/photo.jpg` before `b/myphoto.jpg`.
+///
+fn find_canonical_path<'a>(
+    paths: impl Iterator<Item = &'a Path>,
+    path: &str,
+    lowercase: bool,
+) -> Option<&'a Path> {
+    let mut found = None;
+    for key in paths {
+        let canonical_key = canonical_path(key.to_str().unwrap(), lowercase);
+        if canonical_key.contains(path) {
+            if canonical_key == path || canonical_key.ends_with(&format!("/{}", path)) {
+                return Some(key);
+            }
+            found.get_or_insert(key);
+        }
+    }
+    found
+}
+
+///
+/// Returns the path with forward slashes as separators and with the `.jpeg` extension replaced by `.jpg`, optionally converted to lowercase.
+///
+fn canonical_path(path: &str, lowercase: bool) -> String {
+    let path = path.replace('\\', "/");
+    let path = if lowercase { path.to_lowercase() } else { path };
+    match path.strip_suffix(".jpeg") {
+        Some(stem) => format!("{}.jpg", stem),
+        None => path,
+    }
+}
+
+impl<P: AsRef<Path>> FromIterator<(P, Vec<u8>)> for RawAssets {
+    fn from_iter<I: IntoIterator<Item = (P, Vec<u8>)>>(iter: I) -> Self {
+        let mut raw_assets = Self::new();
+        for (path, bytes) in iter {
+            raw_assets.insert(path, bytes);
+        }
+        raw_assets
+    }
+}
+
 impl std::ops::Deref for RawAssets {
     type Target = HashMap<PathBuf, Vec<u8>>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.assets
     }
 }
 
 impl std::fmt::Debug for RawAssets {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut d = f.debug_struct("RawAssets");
-        for (key, value) in self.0.iter() {
+        for (key, value) in self.assets.iter() {
             d.field("path", key);
             d.field("byte length", &value.len());
         }
         d.finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn match_jpg_paths() {
+        let mut assets = RawAssets::new();
+        assets
+            .insert("photo.jpg", vec![0])
+            .insert("photograph.jpg", vec![1])
+            .insert("images/cat.jpeg", vec![2])
+            .insert("images/mycat.jpg", vec![3]);
+        assert_eq!(assets.get("photo.jpg").unwrap(), &[0]);
+        assert_eq!(assets.get("photo.jpeg").unwrap(), &[0]);
+        assert_eq!(assets.get("photograph.jpg").unwrap(), &[1]);
+        assert_eq!(assets.get("photograph.jpeg").unwrap(), &[1]);
+        assert_eq!(assets.get("cat.jpg").unwrap(), &[2]);
+        assert_eq!(assets.get("cat.jpeg").unwrap(), &[2]);
+        assert_eq!(assets.get("images\\cat.jpg").unwrap(), &[2]);
+        assert_eq!(assets.get("mycat.jpeg").unwrap(), &[3]);
+        assert!(!assets.contains("dog.jpg"));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    pub fn parse_error_context() {
+        let mut assets = RawAssets::new();
+        assets.insert("textures/broken.png", vec![1, 2, 3]);
+        let error = assets
+            .deserialize::<crate::Texture2D>("broken.png")
+            .unwrap_err();
+        match &error {
+            Error::Parse { path, format, .. } => {
+                assert_eq!(path, "textures/broken.png");
+                assert_eq!(format, "png");
+            }
+            _ => panic!("Wrong error: {:?}", error),
+        }
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(matches!(
+            assets.deserialize::<crate::Texture2D>("missing.png"),
+            Err(Error::NotLoaded(_))
+        ));
+    }
+}
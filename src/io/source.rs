@@ -0,0 +1,221 @@
+//!
+//! Functionality for loading assets from a custom source, for example a virtual file system.
+//!
+
+use crate::{io::RawAssets, Error, Result};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+
+///
+/// A source of assets, for example the file system, a web server or a virtual file system, which is used by [load_async_with_source] to load assets.
+///
+/// ```
+/// # use three_d_asset::io::*;
+/// # use std::{future::Future, path::Path, pin::Pin};
+/// struct EmbeddedSource;
+///
+/// impl AssetSource for EmbeddedSource {
+///     fn load<'a>(
+///         &'a self,
+///         path: &'a Path,
+///     ) -> Pin<Box<dyn Future<Output = three_d_asset::Result<Vec<u8>>> + 'a>> {
+///         Box::pin(async move {
+///             match path.to_str() {
+///                 Some("test.png") => Ok(include_bytes!("../../test_data/test.png").to_vec()),
+///                 _ => Err(three_d_asset::Error::NotLoaded(path.display().to_string())),
+///             }
+///         })
+///     }
+/// }
+/// ```
+///
+pub trait AssetSource {
+    ///
+    /// Loads and returns the bytes of the asset at the given path.
+    ///
+    fn load<'a>(&'a self, path: &'a Path) -> Pin<Box<dyn Future<Output = Result<Vec<u8>>> + 'a>>;
+}
+
+///
+/// An [AssetSource] which loads assets from disk using relative or absolute paths.
+///
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileSystemSource;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AssetSource for FileSystemSource {
+    fn load<'a>(&'a self, path: &'a Path) -> Pin<Box<dyn Future<Output = Result<Vec<u8>>> + 'a>> {
+        Box::pin(async move {
+            std::fs::read(path)
+                .map_err(|e| Error::FailedLoading(path.to_str().unwrap().to_string(), e))
+        })
+    }
+}
+
+///
+/// An [AssetSource] which downloads assets from absolute URLs.
+/// On web, relative URLs are relative to the URL of the current page.
+///
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Debug, Default)]
+pub struct HttpSource {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl AssetSource for HttpSource {
+    fn load<'a>(&'a self, path: &'a Path) -> Pin<Box<dyn Future<Output = Result<Vec<u8>>> + 'a>> {
+        Box::pin(async move {
+            #[cfg(target_arch = "wasm32")]
+            let path = &super::loader::base_path().join(path);
+            let path_name = || path.to_str().unwrap().to_string();
+            let url = reqwest::Url::parse(path.to_str().unwrap())
+                .map_err(|_| Error::FailedParsingUrl(path_name()))?;
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .map_err(|e| Error::FailedLoadingUrl(path_name(), e))?;
+            if !response.status().is_success() {
+                Err(Error::FailedLoadingUrlStatus(
+                    path_name(),
+                    response.status(),
+                ))?;
+            }
+            Ok(response
+                .bytes()
+                .await
+                .map_err(|e| Error::FailedLoadingUrl(path_name(), e))?
+                .to_vec())
+        })
+    }
+}
+
+///
+/// Async loads all of the resources in the given paths through the given [AssetSource] and returns the [RawAssets] resources.
+/// Dependencies, for example the buffers and textures of a `.gltf` file, are also loaded through the given source,
+/// while data URLs are parsed directly (requires the `data-url` feature flag).
+/// The resources are loaded one at a time.
+///
+/// ```
+/// # use three_d_asset::io::*;
+/// # #[cfg(not(target_arch = "wasm32"))]
+/// # async fn load_from_disk() {
+/// let assets = load_async_with_source(&["test_data/Cube_BaseColor.png"], &FileSystemSource)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+///
+pub async fn load_async_with_source(
+    paths: &[impl AsRef<Path>],
+    source: &impl AssetSource,
+) -> Result<RawAssets> {
+    let mut raw_assets = load_single(paths, source).await?;
+    let mut dependencies = super::get_dependencies(&raw_assets);
+    while !dependencies.is_empty() {
+        let deps = load_single(&dependencies, source).await?;
+        dependencies = super::get_dependencies(&deps);
+        raw_assets.extend(deps);
+    }
+    Ok(raw_assets)
+}
+
+async fn load_single(paths: &[impl AsRef<Path>], source: &impl AssetSource) -> Result<RawAssets> {
+    let mut raw_assets = RawAssets::new();
+    for path in paths {
+        let path = path.as_ref();
+        let bytes = if super::loader::is_data_url(path) {
+            super::loader::parse_data_url(path.to_str().unwrap())?
+        } else {
+            source.load(path).await?
+        };
+        raw_assets.insert(path, bytes);
+    }
+    Ok(raw_assets)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct NoopWaker;
+
+    impl std::task::Wake for NoopWaker {
+        fn wake(self: std::sync::Arc<Self>) {}
+    }
+
+    fn block_on<T>(future: impl Future<Output = T>) -> T {
+        let mut future = std::pin::pin!(future);
+        let waker = std::task::Waker::from(std::sync::Arc::new(NoopWaker));
+        let mut context = std::task::Context::from_waker(&waker);
+        loop {
+            if let std::task::Poll::Ready(value) = future.as_mut().poll(&mut context) {
+                return value;
+            }
+        }
+    }
+
+    #[test]
+    pub fn load_from_file_system_source() {
+        let assets = block_on(load_async_with_source(
+            &["test_data/Cube_BaseColor.png"],
+            &FileSystemSource,
+        ))
+        .unwrap();
+        assert_eq!(
+            assets.get("Cube_BaseColor.png").unwrap(),
+            include_bytes!("../../test_data/Cube_BaseColor.png")
+        );
+        assert!(block_on(load_async_with_source(&["missing.png"], &FileSystemSource)).is_err());
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    pub fn load_gltf_from_custom_source() {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        struct MemorySource(HashMap<PathBuf, Vec<u8>>);
+
+        impl AssetSource for MemorySource {
+            fn load<'a>(
+                &'a self,
+                path: &'a Path,
+            ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>>> + 'a>> {
+                Box::pin(async move {
+                    self.0
+                        .get(path)
+                        .cloned()
+                        .ok_or(Error::NotLoaded(path.to_str().unwrap().to_string()))
+                })
+            }
+        }
+
+        let source = MemorySource(HashMap::from([
+            (
+                PathBuf::from("vfs/Cube.gltf"),
+                include_bytes!("../../test_data/Cube.gltf").to_vec(),
+            ),
+            (
+                PathBuf::from("vfs/Cube.bin"),
+                include_bytes!("../../test_data/Cube.bin").to_vec(),
+            ),
+            (
+                PathBuf::from("vfs/Cube_BaseColor.png"),
+                include_bytes!("../../test_data/Cube_BaseColor.png").to_vec(),
+            ),
+            (
+                PathBuf::from("vfs/Cube_MetallicRoughness.png"),
+                include_bytes!("../../test_data/Cube_MetallicRoughness.png").to_vec(),
+            ),
+        ]));
+        let mut assets = block_on(load_async_with_source(&["vfs/Cube.gltf"], &source)).unwrap();
+        assert_eq!(assets.len(), 4);
+        let model: crate::Model = assets.deserialize("Cube.gltf").unwrap();
+        assert_eq!(model.geometries.len(), 1);
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{geometry::*, io::RawAssets, Error, Node, Result, Scene};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+///
+/// Options for deserializing an .stl file (see [DeserializeWithOptions](crate::io::DeserializeWithOptions)).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StlOptions {
+    /// Whether or not to weld identical vertices, ie. vertices with the same position and normal, into one vertex.
+    /// If true, the resulting [TriMesh] uses [Indices::U32], otherwise it uses [Indices::None] and three vertices per triangle.
+    pub weld_vertices: bool,
+}
+
+///
+/// The format used when serializing into an .stl file (see [SerializeWithOptions](crate::io::SerializeWithOptions)).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StlFormat {
+    /// The compact binary format. All solids are written as one, since it is not possible to have more than one solid in a binary .stl file.
+    #[default]
+    Binary,
+    /// The human readable ASCII format.
+    Ascii,
+}
+
+pub fn deserialize_stl(
+    raw_assets: &mut RawAssets,
+    path: &PathBuf,
+    options: StlOptions,
+) -> Result<Scene> {
+    let bytes = raw_assets.remove(path)?;
+    let solids = if is_binary(&bytes) {
+        vec![parse_binary(&bytes)?]
+    } else {
+        parse_ascii(std::str::from_utf8(&bytes).map_err(|_| Error::StlCorruptData)?)?
+    };
+    let children = solids
+        .into_iter()
+        .map(|(name, facets)| Node {
+            name,
+            geometry: Some(Geometry::Triangles(tri_mesh(facets, options.weld_vertices))),
+            ..Default::default()
+        })
+        .collect();
+    Ok(Scene {
+        name: path.to_str().unwrap_or("default").to_owned(),
+        children,
+        ..Default::default()
+    })
+}
+
+struct Facet {
+    normal: Vec3,
+    vertices: [Vec3; 3],
+}
+
+///
+/// A binary .stl file has an 80 byte header followed by the number of triangles and 50 bytes per triangle.
+/// ASCII files usually starts with `solid`, but so does some binary files, so the length is checked instead.
+///
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < 84 {
+        return false;
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    count
+        .checked_mul(50)
+        .and_then(|l| l.checked_add(84))
+        .map(|l| l == bytes.len())
+        .unwrap_or(false)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<(String, Vec<Facet>)> {
+    let name = String::from_utf8_lossy(&bytes[0..80])
+        .trim_end_matches('\0')
+        .to_string();
+    let read_vec3 = |offset: usize| {
+        let f = |i: usize| {
+            let o = offset + i * 4;
+            f32::from_le_bytes([bytes[o], bytes[o + 1], bytes[o + 2], bytes[o + 3]])
+        };
+        vec3(f(0), f(1), f(2))
+    };
+    let facets = (84..bytes.len())
+        .step_by(50)
+        .map(|offset| Facet {
+            normal: read_vec3(offset),
+            vertices: [
+                read_vec3(offset + 12),
+                read_vec3(offset + 24),
+                read_vec3(offset + 36),
+            ],
+        })
+        .collect();
+    Ok((name, facets))
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<(String, Vec<Facet>)>> {
+    let parse_vec3 = |tokens: &mut std::str::SplitWhitespace| -> Result<Vec3> {
+        let mut v = [0.0; 3];
+        for value in v.iter_mut() {
+            *value = tokens
+                .next()
+                .and_then(|t| t.parse::<f32>().ok())
+                .ok_or(Error::StlCorruptData)?;
+        }
+        Ok(vec3(v[0], v[1], v[2]))
+    };
+
+    let mut solids = Vec::new();
+    let mut current: Option<(String, Vec<Facet>)> = None;
+    let mut normal = vec3(0.0, 0.0, 0.0);
+    let mut vertices = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("solid") => {
+                if current.is_some() {
+                    Err(Error::StlCorruptData)?;
+                }
+                current = Some((line["solid".len()..].trim().to_string(), Vec::new()));
+            }
+            Some("facet") => {
+                if tokens.next() != Some("normal") {
+                    Err(Error::StlCorruptData)?;
+                }
+                normal = parse_vec3(&mut tokens)?;
+                vertices.clear();
+            }
+            Some("vertex") => {
+                vertices.push(parse_vec3(&mut tokens)?);
+            }
+            Some("endfacet") => {
+                // Some exporters write polygons with more than three vertices, those are triangulated as a fan.
+                if vertices.len() < 3 {
+                    Err(Error::StlCorruptData)?;
+                }
+                let (_, facets) = current.as_mut().ok_or(Error::StlCorruptData)?;
+                for i in 1..vertices.len() - 1 {
+                    facets.push(Facet {
+                        normal,
+                        vertices: [vertices[0], vertices[i], vertices[i + 1]],
+                    });
+                }
+            }
+            Some("endsolid") => {
+                solids.push(current.take().ok_or(Error::StlCorruptData)?);
+            }
+            Some("outer") | Some("endloop") | None => {}
+            Some(_) => Err(Error::StlCorruptData)?,
+        }
+    }
+    // Be forgiving towards files that are missing the final `endsolid`.
+    solids.extend(current);
+    if solids.is_empty() {
+        Err(Error::StlCorruptData)?;
+    }
+    Ok(solids)
+}
+
+fn tri_mesh(facets: Vec<Facet>, weld_vertices: bool) -> TriMesh {
+    let has_normals = facets.iter().any(|f| f.normal.magnitude2() > 0.0);
+    let normal = |facet: &Facet| {
+        if facet.normal.magnitude2() > 0.0 {
+            facet.normal
+        } else {
+            facet_normal(&facet.vertices)
+        }
+    };
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    if weld_vertices {
+        let mut indices = Vec::new();
+        let mut map: HashMap<[u32; 6], u32> = HashMap::new();
+        for facet in facets.iter() {
+            let n = if has_normals {
+                normal(facet)
+            } else {
+                vec3(0.0, 0.0, 0.0)
+            };
+            for p in facet.vertices {
+                let key = [
+                    p.x.to_bits(),
+                    p.y.to_bits(),
+                    p.z.to_bits(),
+                    n.x.to_bits(),
+                    n.y.to_bits(),
+                    n.z.to_bits(),
+                ];
+                let index = *map.entry(key).or_insert_with(|| {
+                    positions.push(p);
+                    normals.push(n);
+                    positions.len() as u32 - 1
+                });
+                indices.push(index);
+            }
+        }
+        TriMesh {
+            positions: Positions::F32(positions),
+            indices: Indices::U32(indices),
+            normals: if has_normals { Some(normals) } else { None },
+            ..Default::default()
+        }
+    } else {
+        for facet in facets.iter() {
+            let n = normal(facet);
+            for p in facet.vertices {
+                positions.push(p);
+                normals.push(n);
+            }
+        }
+        TriMesh {
+            positions: Positions::F32(positions),
+            indices: Indices::None,
+            normals: if has_normals { Some(normals) } else { None },
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Serialize the given named triangle meshes into one .stl file.
+/// The binary header contains the name of the first mesh.
+///
+pub fn serialize_stl(
+    path: &Path,
+    solids: &[(&str, &TriMesh)],
+    format: StlFormat,
+) -> Result<RawAssets> {
+    let bytes = match format {
+        StlFormat::Binary => {
+            let mut header = solids
+                .first()
+                .map(|(name, _)| name.as_bytes().to_vec())
+                .unwrap_or_default();
+            header.resize(80, 0);
+            let mut bytes = header;
+            let facets = solids
+                .iter()
+                .flat_map(|(_, mesh)| facets(mesh))
+                .collect::<Vec<_>>();
+            bytes.extend((facets.len() as u32).to_le_bytes());
+            for facet in facets {
+                for v in std::iter::once(facet.normal).chain(facet.vertices) {
+                    bytes.extend(v.x.to_le_bytes());
+                    bytes.extend(v.y.to_le_bytes());
+                    bytes.extend(v.z.to_le_bytes());
+                }
+                bytes.extend(0u16.to_le_bytes());
+            }
+            bytes
+        }
+        StlFormat::Ascii => {
+            use std::fmt::Write;
+            let mut text = String::new();
+            for (name, mesh) in solids {
+                writeln!(text, "solid {}", name).unwrap();
+                for facet in facets(mesh) {
+                    let n = facet.normal;
+                    writeln!(text, "  facet normal {:e} {:e} {:e}", n.x, n.y, n.z).unwrap();
+                    writeln!(text, "    outer loop").unwrap();
+                    for p in facet.vertices {
+                        writeln!(text, "      vertex {:e} {:e} {:e}", p.x, p.y, p.z).unwrap();
+                    }
+                    writeln!(text, "    endloop").unwrap();
+                    writeln!(text, "  endfacet").unwrap();
+                }
+                writeln!(text, "endsolid {}", name).unwrap();
+            }
+            text.into_bytes()
+        }
+    };
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, bytes);
+    Ok(raw_assets)
+}
+
+fn facets(mesh: &TriMesh) -> Vec<Facet> {
+    let positions = mesh.positions.to_f32();
+    let mut facets = Vec::with_capacity(mesh.triangle_count());
+    mesh.for_each_triangle(|i0, i1, i2| {
+        let vertices = [positions[i0], positions[i1], positions[i2]];
+        let normal = match mesh.normals {
+            Some(ref normals) if normals[i0] == normals[i1] && normals[i1] == normals[i2] => {
+                normals[i0]
+            }
+            Some(ref normals) => {
+                // The vertex normals cancel out if the vertices are shared by opposite faces, for example the two sides of a thin sheet
+                let n = normals[i0] + normals[i1] + normals[i2];
+                if n.magnitude2() > 0.0 {
+                    n.normalize()
+                } else {
+                    facet_normal(&vertices)
+                }
+            }
+            None => facet_normal(&vertices),
+        };
+        facets.push(Facet { normal, vertices });
+    });
+    facets
+}
+
+///
+/// Returns the normal of the triangle with the given vertices in counterclockwise order or a zero vector if the triangle is degenerate.
+///
+fn facet_normal([p0, p1, p2]: &[Vec3; 3]) -> Vec3 {
+    let n = (p1 - p0).cross(p2 - p0);
+    if n.magnitude2() > 0.0 {
+        n.normalize()
+    } else {
+        n
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::{RawAssets, Serialize, SerializeWithOptions, StlFormat, StlOptions};
+    use crate::{Indices, Model, TriMesh};
+
+    #[test]
+    pub fn deserialize_binary_stl() {
+        let mesh: TriMesh = crate::io::load_and_deserialize("test_data/cube.stl").unwrap();
+        assert_eq!(mesh.triangle_count(), 12);
+        assert_eq!(mesh.vertex_count(), 36);
+        assert!(matches!(mesh.indices, Indices::None));
+        assert_eq!(mesh.normals.as_ref().map(|n| n.len()), Some(36));
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn deserialize_ascii_stl() {
+        let mesh: TriMesh = crate::io::load_and_deserialize("test_data/cube_ascii.stl").unwrap();
+        assert_eq!(mesh.triangle_count(), 12);
+        assert_eq!(mesh.vertex_count(), 36);
+        assert_eq!(mesh.normals.as_ref().map(|n| n.len()), Some(36));
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn deserialize_stl_with_welding() {
+        let mesh: TriMesh = RawAssets::new()
+            .insert(
+                "cube.stl",
+                include_bytes!("../../test_data/cube.stl").to_vec(),
+            )
+            .deserialize_with_options(
+                "cube.stl",
+                StlOptions {
+                    weld_vertices: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(mesh.triangle_count(), 12);
+        // Four vertices per side, since the normals differ between the sides.
+        assert_eq!(mesh.vertex_count(), 24);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn serialize_binary_stl() {
+        let bytes = include_bytes!("../../test_data/cube.stl").to_vec();
+        let model: Model = RawAssets::new()
+            .insert("cube.stl", bytes.clone())
+            .deserialize("cube.stl")
+            .unwrap();
+        let mut raw_assets = model.serialize("cube.stl").unwrap();
+        assert_eq!(raw_assets.remove("cube.stl").unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn serialize_ascii_stl() {
+        let mesh = TriMesh::sphere(8);
+        let mut raw_assets = mesh
+            .serialize_with_options("sphere.stl", StlFormat::Ascii)
+            .unwrap();
+        assert!(raw_assets.get("sphere.stl").unwrap().starts_with(b"solid"));
+        let loaded: TriMesh = raw_assets.deserialize("sphere.stl").unwrap();
+        assert_eq!(loaded.triangle_count(), mesh.triangle_count());
+        assert_eq!(loaded.vertex_count(), 3 * mesh.triangle_count());
+    }
+
+    #[test]
+    pub fn serialize_stl_with_opposite_vertex_normals() {
+        let mesh = TriMesh {
+            positions: crate::Positions::F32(vec![
+                crate::vec3(0.0, 0.0, 0.0),
+                crate::vec3(1.0, 0.0, 0.0),
+                crate::vec3(0.0, 1.0, 0.0),
+            ]),
+            normals: Some(vec![
+                crate::vec3(0.0, 0.0, 1.0),
+                crate::vec3(0.0, 0.0, -1.0),
+                crate::vec3(0.0, 0.0, 0.0),
+            ]),
+            ..Default::default()
+        };
+        let mut raw_assets = mesh.serialize("sheet.stl").unwrap();
+        let loaded: TriMesh = raw_assets.deserialize("sheet.stl").unwrap();
+        assert_eq!(loaded.normals.unwrap(), vec![crate::vec3(0.0, 0.0, 1.0); 3]);
+    }
+}
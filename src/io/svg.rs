@@ -0,0 +1,110 @@
+use crate::{texture::*, Error, Result};
+use resvg::{tiny_skia, usvg};
+
+impl Texture2D {
+    ///
+    /// Rasterizes the given .svg file into a texture with the given size in pixels and [TextureData::RgbaU8] data with straight (not premultiplied) alpha.
+    /// If both the width and height are `None`, the size declared by the `width` and `height` attributes of the SVG is used
+    /// and if only one of them is `None`, it is computed from the other and the aspect ratio of the SVG.
+    /// The aspect ratio of the SVG is always preserved, so if it does not match the given size, the image is centered and the remaining area is transparent,
+    /// as is any area where nothing is drawn.
+    ///
+    /// **Note:** Text and embedded raster images are not rendered.
+    ///
+    pub fn from_svg_bytes(bytes: &[u8], width: Option<u32>, height: Option<u32>) -> Result<Self> {
+        let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())?;
+        let size = tree.size();
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (
+                width,
+                (width as f32 * size.height() / size.width()).round() as u32,
+            ),
+            (None, Some(height)) => (
+                (height as f32 * size.width() / size.height()).round() as u32,
+                height,
+            ),
+            (None, None) => (size.width().round() as u32, size.height().round() as u32),
+        };
+        let mut pixmap = tiny_skia::Pixmap::new(width.max(1), height.max(1))
+            .ok_or_else(|| Error::SvgRasterization(format!("invalid size {}x{}", width, height)))?;
+
+        let scale = (width as f32 / size.width()).min(height as f32 / size.height());
+        let transform = tiny_skia::Transform::from_translate(
+            0.5 * (width as f32 - scale * size.width()),
+            0.5 * (height as f32 - scale * size.height()),
+        )
+        .pre_scale(scale, scale);
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+        Ok(Texture2D {
+            data: TextureData::RgbaU8(
+                pixmap
+                    .pixels()
+                    .iter()
+                    .map(|p| {
+                        let c = p.demultiply();
+                        [c.red(), c.green(), c.blue(), c.alpha()]
+                    })
+                    .collect(),
+            ),
+            width: pixmap.width(),
+            height: pixmap.height(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Texture2D, TextureData};
+
+    const SVG: &[u8] =
+        br##"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="4" viewBox="0 0 2 1">
+        <rect x="0" y="0" width="1" height="1" fill="#ff0000" fill-opacity="0.5"/>
+    </svg>"##;
+
+    fn texels(tex: &Texture2D) -> &Vec<[u8; 4]> {
+        if let TextureData::RgbaU8(data) = &tex.data {
+            data
+        } else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        }
+    }
+
+    #[test]
+    pub fn rasterize_svg() {
+        let tex = Texture2D::from_svg_bytes(SVG, None, None).unwrap();
+        assert_eq!((tex.width, tex.height), (8, 4));
+        let data = texels(&tex);
+        assert_eq!(data[8 + 1], [255, 0, 0, 128]);
+        assert_eq!(data[8 + 6], [0, 0, 0, 0]);
+
+        let tex = Texture2D::from_svg_bytes(SVG, Some(32), None).unwrap();
+        assert_eq!((tex.width, tex.height), (32, 16));
+        let data = texels(&tex);
+        assert_eq!(data[5 * 32 + 15], [255, 0, 0, 128]);
+        assert_eq!(data[5 * 32 + 16], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    pub fn rasterize_svg_with_other_aspect_ratio() {
+        let tex = Texture2D::from_svg_bytes(SVG, Some(16), Some(16)).unwrap();
+        assert_eq!((tex.width, tex.height), (16, 16));
+        let data = texels(&tex);
+        // The 16x8 image is centered vertically.
+        assert_eq!(data[2 * 16 + 2], [0, 0, 0, 0]);
+        assert_eq!(data[6 * 16 + 2], [255, 0, 0, 128]);
+        assert_eq!(data[13 * 16 + 2], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    pub fn deserialize_svg() {
+        let tex: Texture2D = crate::io::RawAssets::new()
+            .insert("icon.svg", SVG.to_vec())
+            .deserialize("")
+            .unwrap();
+        assert_eq!(tex.name, "icon.svg");
+        assert_eq!((tex.width, tex.height), (8, 4));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{io::RawAssets, volume::*, Error, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 ///
 /// Deserialize a loaded .vol file into a [VoxelGrid].
@@ -64,6 +64,36 @@ pub fn deserialize_vol(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<Vox
     })
 }
 
+///
+/// Serialize a [VoxelGrid] into a .vol file with the same header and layout as expected by [deserialize_vol],
+/// so a grid which is loaded from a .vol file and saved again results in the same bytes.
+/// Returns an error if the voxel data is not one of the 8-bit formats [TextureData::RU8], [TextureData::RgU8], [TextureData::RgbU8] or [TextureData::RgbaU8].
+///
+pub fn serialize_vol(voxel_grid: &VoxelGrid, path: &Path) -> Result<RawAssets> {
+    let voxels = &voxel_grid.voxels;
+    // The axes of the file are ordered differently than the axes of the voxel data, see deserialize_vol
+    let (width, height, depth) = (voxels.height, voxels.depth, voxels.width);
+    let size = voxel_grid.size;
+    let mut bytes = Vec::with_capacity(28 + (width * height * depth) as usize * 4);
+    for value in [width, height, depth, 0] {
+        bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    for value in [size.y, size.z, size.x] {
+        bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    let (width, height, depth) = (width as usize, height as usize, depth as usize);
+    match &voxels.data {
+        TextureData::RU8(data) => bytes.extend(unflip(data, width, height, depth)),
+        TextureData::RgU8(data) => bytes.extend(unflip(data, width, height, depth).flatten()),
+        TextureData::RgbU8(data) => bytes.extend(unflip(data, width, height, depth).flatten()),
+        TextureData::RgbaU8(data) => bytes.extend(unflip(data, width, height, depth).flatten()),
+        data => Err(Error::VolUnsupportedData(format!("{:?}", data.kind())))?,
+    }
+    let mut raw_assets = RawAssets::new();
+    raw_assets.insert(path, bytes);
+    Ok(raw_assets)
+}
+
 fn flip<T: Default + Clone>(data: Vec<T>, width: usize, height: usize, depth: usize) -> Vec<T> {
     let mut out_data = vec![T::default(); width * height * depth];
     for x in 0..width {
@@ -77,3 +107,71 @@ fn flip<T: Default + Clone>(data: Vec<T>, width: usize, height: usize, depth: us
     }
     out_data
 }
+
+///
+/// The inverse of [flip], which returns the voxels in the order of the .vol file.
+///
+fn unflip<T: Copy>(
+    data: &[T],
+    width: usize,
+    height: usize,
+    depth: usize,
+) -> impl Iterator<Item = T> + '_ {
+    (0..width).flat_map(move |x| {
+        (0..height)
+            .flat_map(move |y| (0..depth).map(move |z| data[y * width * depth + x * depth + z]))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::{RawAssets, Serialize};
+    use crate::{Texture3D, TextureData, VoxelGrid};
+
+    #[test]
+    pub fn serialize_vol() {
+        let bytes = std::fs::read("test_data/C60Small.vol").unwrap();
+        let voxel_grid: VoxelGrid = RawAssets::new()
+            .insert("C60Small.vol", bytes.clone())
+            .deserialize("C60Small.vol")
+            .unwrap();
+        let mut raw_assets = voxel_grid.serialize("C60Small.vol").unwrap();
+        assert_eq!(raw_assets.remove("C60Small.vol").unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn serialize_vol_rg() {
+        let voxel_grid = VoxelGrid {
+            name: "grid.vol".to_owned(),
+            voxels: Texture3D {
+                data: TextureData::RgU8((0..24).map(|i| [i, 100 + i]).collect()),
+                width: 2,
+                height: 3,
+                depth: 4,
+                ..Default::default()
+            },
+            size: crate::vec3(1.0, 2.0, 3.0),
+        };
+        let mut raw_assets = voxel_grid.serialize("grid.vol").unwrap();
+        let reloaded: VoxelGrid = raw_assets.deserialize("grid.vol").unwrap();
+        assert_eq!(reloaded.size, voxel_grid.size);
+        assert_eq!(
+            (
+                reloaded.voxels.width,
+                reloaded.voxels.height,
+                reloaded.voxels.depth
+            ),
+            (2, 3, 4)
+        );
+        assert_eq!(reloaded.voxels.data, voxel_grid.voxels.data);
+
+        let voxel_grid = VoxelGrid {
+            voxels: Texture3D {
+                data: TextureData::RF32(vec![0.0]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(voxel_grid.serialize("grid.vol").is_err());
+    }
+}
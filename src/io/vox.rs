@@ -0,0 +1,227 @@
+use crate::{io::RawAssets, volume::*, Error, Result};
+use std::path::PathBuf;
+
+///
+/// Deserialize a loaded MagicaVoxel .vox file into a [VoxelGrid] with [TextureData::RgbaU8] voxel data.
+/// Empty voxels are fully transparent and the size of the grid is one unit per voxel.
+///
+/// MagicaVoxel uses a right-handed z-up coordinate system, which is converted into the y-up coordinate system used by this crate,
+/// ie. the MagicaVoxel `(x, y, z)` voxel is placed at `(x, z, size_y - 1 - y)`.
+///
+/// **Note:** If the file contains more than one model, only the first model is returned.
+///
+pub fn deserialize_vox(raw_assets: &mut RawAssets, path: &PathBuf) -> Result<VoxelGrid> {
+    let name = path.to_str().unwrap().to_string();
+    let bytes = raw_assets.remove(path)?;
+    if bytes.len() < 8 || &bytes[0..4] != b"VOX " {
+        Err(Error::VoxCorruptData)?;
+    }
+    let (id, content, children) = chunk(&bytes, 8)?.ok_or(Error::VoxCorruptData)?;
+    if id != b"MAIN" || !content.is_empty() {
+        Err(Error::VoxCorruptData)?;
+    }
+
+    let mut size = None;
+    let mut voxels = None;
+    let mut palette = None;
+    let mut offset = 0;
+    while let Some((id, content, grandchildren)) = chunk(children, offset)? {
+        offset += 12 + content.len() + grandchildren.len();
+        match id {
+            b"SIZE" if size.is_none() => {
+                size = Some([
+                    read_u32(content, 0)?,
+                    read_u32(content, 4)?,
+                    read_u32(content, 8)?,
+                ])
+            }
+            b"XYZI" if voxels.is_none() => {
+                let count = read_u32(content, 0)? as usize;
+                voxels = Some(
+                    content
+                        .get(
+                            4..count
+                                .checked_mul(4)
+                                .and_then(|n| n.checked_add(4))
+                                .ok_or(Error::VoxCorruptData)?,
+                        )
+                        .ok_or(Error::VoxCorruptData)?
+                        .chunks(4)
+                        .map(|v| [v[0], v[1], v[2], v[3]])
+                        .collect::<Vec<_>>(),
+                );
+            }
+            b"RGBA" => {
+                let colors = content.get(..1024).ok_or(Error::VoxCorruptData)?;
+                // Color index i is stored at position i - 1, index 0 is always empty.
+                let mut p = [[0u8; 4]; 256];
+                for i in 1..256 {
+                    p[i].copy_from_slice(&colors[(i - 1) * 4..i * 4]);
+                }
+                palette = Some(p);
+            }
+            _ => {}
+        }
+    }
+
+    let [size_x, size_y, size_z] = size.ok_or(Error::VoxCorruptData)?;
+    // Voxel coordinates are stored as bytes, so MagicaVoxel models are at most 256 voxels along each axis
+    if [size_x, size_y, size_z].iter().any(|&s| s == 0 || s > 256) {
+        Err(Error::VoxCorruptData)?;
+    }
+    let palette = palette.unwrap_or_else(default_palette);
+    let width = size_x as usize;
+    let height = size_z as usize;
+    let depth = size_y as usize;
+    let mut data = vec![[0u8; 4]; width * height * depth];
+    for [x, y, z, i] in voxels.unwrap_or_default() {
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+        if x >= size_x as usize || y >= size_y as usize || z >= size_z as usize {
+            Err(Error::VoxCorruptData)?;
+        }
+        let (x, y, z) = (x, z, depth - 1 - y);
+        data[z * width * height + y * width + x] = palette[i as usize];
+    }
+    Ok(VoxelGrid {
+        voxels: Texture3D {
+            data: TextureData::RgbaU8(data),
+            width: width as u32,
+            height: height as u32,
+            depth: depth as u32,
+            ..Default::default()
+        },
+        size: Vec3::new(width as f32, height as f32, depth as f32),
+        name,
+    })
+}
+
+///
+/// Returns the id, content and children of the chunk starting at the given offset or `None` if there are no more chunks.
+///
+#[allow(clippy::type_complexity)]
+fn chunk(bytes: &[u8], offset: usize) -> Result<Option<(&[u8], &[u8], &[u8])>> {
+    if offset >= bytes.len() {
+        return Ok(None);
+    }
+    let id = bytes.get(offset..offset + 4).ok_or(Error::VoxCorruptData)?;
+    let content_size = read_u32(bytes, offset + 4)? as usize;
+    let children_size = read_u32(bytes, offset + 8)? as usize;
+    let content_start = offset + 12;
+    let children_start = content_start + content_size;
+    let content = bytes
+        .get(content_start..children_start)
+        .ok_or(Error::VoxCorruptData)?;
+    let children = bytes
+        .get(children_start..children_start + children_size)
+        .ok_or(Error::VoxCorruptData)?;
+    Ok(Some((id, content, children)))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    let b = bytes.get(offset..offset + 4).ok_or(Error::VoxCorruptData)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+///
+/// The palette used by MagicaVoxel when the file does not contain an `RGBA` chunk.
+/// It consists of a 6x6x6 color cube, without black, followed by red, green, blue and gray ramps.
+///
+fn default_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0u8; 4]; 256];
+    let steps = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    let mut i = 1;
+    for r in steps {
+        for g in steps {
+            for b in steps {
+                if i < 216 {
+                    palette[i] = [r, g, b, 0xff];
+                    i += 1;
+                }
+            }
+        }
+    }
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    for channel in 0..4 {
+        for v in ramp {
+            palette[i] = match channel {
+                0 => [v, 0, 0, 0xff],
+                1 => [0, v, 0, 0xff],
+                2 => [0, 0, v, 0xff],
+                _ => [v, v, v, 0xff],
+            };
+            i += 1;
+        }
+    }
+    palette
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{TextureData, VoxelGrid};
+
+    #[test]
+    pub fn deserialize_vox() {
+        let voxel_grid: VoxelGrid = crate::io::load_and_deserialize("test_data/test.vox").unwrap();
+        let voxels = &voxel_grid.voxels;
+        assert_eq!((voxels.width, voxels.height, voxels.depth), (2, 4, 3));
+        if let TextureData::RgbaU8(data) = &voxels.data {
+            assert_eq!(data.len(), 24);
+            assert_eq!(data.iter().filter(|c| c[3] > 0).count(), 3);
+            assert_eq!(data[2 * 8], [0, 0, 0, 255]);
+            assert_eq!(data[2 * 8 + 3 * 2 + 1], [7, 13, 29, 255]);
+            assert_eq!(data[1], [14, 26, 58, 255]);
+        } else {
+            panic!("Wrong texture data");
+        }
+    }
+
+    #[test]
+    pub fn deserialize_vox_with_default_palette() {
+        let voxel_grid: VoxelGrid =
+            crate::io::load_and_deserialize("test_data/default_palette.vox").unwrap();
+        if let TextureData::RgbaU8(data) = &voxel_grid.voxels.data {
+            assert_eq!(data[2 * 8], [255, 255, 255, 255]);
+            assert_eq!(data[2 * 8 + 3 * 2 + 1], [255, 255, 204, 255]);
+            assert_eq!(data[1], [255, 255, 153, 255]);
+        } else {
+            panic!("Wrong texture data");
+        }
+        assert_eq!(super::default_palette()[255], [0x11, 0x11, 0x11, 0xff]);
+    }
+
+    #[test]
+    pub fn deserialize_vox_with_corrupt_size() {
+        let vox = |size: [u32; 3], count: u32| {
+            let mut children = b"SIZE".to_vec();
+            for v in [12, 0, size[0], size[1], size[2]] {
+                children.extend(v.to_le_bytes());
+            }
+            children.extend(b"XYZI");
+            for v in [4, 0, count] {
+                children.extend(v.to_le_bytes());
+            }
+            let mut bytes = b"VOX ".to_vec();
+            bytes.extend(150u32.to_le_bytes());
+            bytes.extend(b"MAIN");
+            bytes.extend(0u32.to_le_bytes());
+            bytes.extend((children.len() as u32).to_le_bytes());
+            bytes.extend(children);
+            bytes
+        };
+        let deserialize = |bytes: Vec<u8>| {
+            let path = std::path::PathBuf::from("test.vox");
+            super::deserialize_vox(crate::io::RawAssets::new().insert(&path, bytes), &path)
+        };
+        assert!(deserialize(vox([2, 2, 2], 0)).is_ok());
+        for size in [[u32::MAX; 3], [257, 1, 1], [1, 0, 1]] {
+            assert!(matches!(
+                deserialize(vox(size, 0)),
+                Err(crate::Error::VoxCorruptData)
+            ));
+        }
+        assert!(matches!(
+            deserialize(vox([2, 2, 2], u32::MAX)),
+            Err(crate::Error::VoxCorruptData)
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+//!
+//! Functionality for loading assets from a ZIP archive.
+//!
+
+use crate::{io::RawAssets, Error, Result};
+
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014b50;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+
+///
+/// Loads all of the files in the given ZIP archive and returns the [RawAssets] resources where each file is stored at its path inside the archive.
+/// The files can then be deserialized as usual, for example a `.gltf` file referencing a `.bin` file and textures inside the archive.
+///
+/// Only stored and deflate compressed entries are supported and the entries cannot be encrypted.
+/// Directory entries are ignored.
+///
+pub fn load_from_zip(bytes: &[u8]) -> Result<RawAssets> {
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&i| read_u32(bytes, i) == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .ok_or(Error::ZipCorruptData)?;
+    let entry_count = read_u16(bytes, end + 10).ok_or(Error::ZipCorruptData)? as usize;
+    let mut offset = read_u32(bytes, end + 16).ok_or(Error::ZipCorruptData)? as usize;
+
+    let mut raw_assets = RawAssets::new();
+    for _ in 0..entry_count {
+        if read_u32(bytes, offset) != Some(CENTRAL_DIRECTORY_SIGNATURE) {
+            Err(Error::ZipCorruptData)?;
+        }
+        let field = |index: usize| read_u16(bytes, offset + index).ok_or(Error::ZipCorruptData);
+        let flags = field(8)?;
+        let method = field(10)?;
+        let name_length = field(28)? as usize;
+        let entry_length = 46 + name_length + field(30)? as usize + field(32)? as usize;
+        let crc = read_u32(bytes, offset + 16).ok_or(Error::ZipCorruptData)?;
+        let compressed_size = read_u32(bytes, offset + 20).ok_or(Error::ZipCorruptData)? as usize;
+        let size = read_u32(bytes, offset + 24).ok_or(Error::ZipCorruptData)? as usize;
+        let header = read_u32(bytes, offset + 42).ok_or(Error::ZipCorruptData)? as usize;
+        let name = bytes
+            .get(offset + 46..offset + 46 + name_length)
+            .ok_or(Error::ZipCorruptData)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset += entry_length;
+
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & 1 != 0 {
+            Err(Error::ZipUnsupportedEntry(
+                name.clone(),
+                "encrypted".to_owned(),
+            ))?;
+        }
+        if compressed_size == u32::MAX as usize || size == u32::MAX as usize {
+            Err(Error::ZipUnsupportedEntry(name.clone(), "zip64".to_owned()))?;
+        }
+
+        if read_u32(bytes, header) != Some(LOCAL_HEADER_SIGNATURE) {
+            Err(Error::ZipCorruptData)?;
+        }
+        let start = header
+            + 30
+            + read_u16(bytes, header + 26).ok_or(Error::ZipCorruptData)? as usize
+            + read_u16(bytes, header + 28).ok_or(Error::ZipCorruptData)? as usize;
+        let data = bytes
+            .get(start..start + compressed_size)
+            .ok_or(Error::ZipCorruptData)?;
+        let data = match method {
+            0 => data.to_vec(),
+            8 => {
+                use std::io::Read;
+                // The size in the header is not trusted for the allocation and the decoded data is not allowed to grow beyond it
+                let mut decompressed = Vec::new();
+                flate2::read::DeflateDecoder::new(data)
+                    .take(size as u64 + 1)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|_| Error::ZipCorruptData)?;
+                decompressed
+            }
+            _ => Err(Error::ZipUnsupportedEntry(
+                name.clone(),
+                format!("compression method {}", method),
+            ))?,
+        };
+        let mut checksum = flate2::Crc::new();
+        checksum.update(&data);
+        if data.len() != size || checksum.sum() != crc {
+            Err(Error::ZipCorruptData)?;
+        }
+        raw_assets.insert(name, data);
+    }
+    Ok(raw_assets)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod test {
+    ///
+    /// Creates a ZIP archive with the given entries, where the entries are deflate compressed if the flag is set and otherwise stored.
+    ///
+    fn zip(entries: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        use std::io::Write;
+        let mut bytes = Vec::new();
+        let mut central_directory = Vec::new();
+        for &(name, data, deflate) in entries {
+            let compressed = if deflate {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            } else {
+                data.to_vec()
+            };
+            let mut crc = flate2::Crc::new();
+            crc.update(data);
+            // The fields shared by the local header and the central directory entry from the version needed to the extra field length
+            let mut fields = Vec::new();
+            fields.extend(20u16.to_le_bytes());
+            fields.extend(0u16.to_le_bytes());
+            fields.extend((if deflate { 8u16 } else { 0 }).to_le_bytes());
+            fields.extend([0; 4]);
+            fields.extend(crc.sum().to_le_bytes());
+            fields.extend((compressed.len() as u32).to_le_bytes());
+            fields.extend((data.len() as u32).to_le_bytes());
+            fields.extend((name.len() as u16).to_le_bytes());
+            fields.extend(0u16.to_le_bytes());
+
+            central_directory.extend(super::CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+            central_directory.extend(20u16.to_le_bytes());
+            central_directory.extend(&fields);
+            // The comment length, disk number start, internal and external attributes
+            central_directory.extend([0; 10]);
+            central_directory.extend((bytes.len() as u32).to_le_bytes());
+            central_directory.extend(name.as_bytes());
+
+            bytes.extend(super::LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            bytes.extend(&fields);
+            bytes.extend(name.as_bytes());
+            bytes.extend(compressed);
+        }
+        let offset = bytes.len() as u32;
+        bytes.extend(&central_directory);
+        bytes.extend(super::END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.extend((entries.len() as u16).to_le_bytes());
+        bytes.extend((entries.len() as u16).to_le_bytes());
+        bytes.extend((central_directory.len() as u32).to_le_bytes());
+        bytes.extend(offset.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        bytes
+    }
+
+    fn cube_zip() -> Vec<u8> {
+        zip(&[
+            ("models/", &[], false),
+            (
+                "models/Cube.gltf",
+                include_bytes!("../../test_data/Cube.gltf"),
+                true,
+            ),
+            (
+                "models/Cube.bin",
+                include_bytes!("../../test_data/Cube.bin"),
+                false,
+            ),
+            (
+                "models/Cube_BaseColor.png",
+                include_bytes!("../../test_data/Cube_BaseColor.png"),
+                false,
+            ),
+            (
+                "models/Cube_MetallicRoughness.png",
+                include_bytes!("../../test_data/Cube_MetallicRoughness.png"),
+                true,
+            ),
+        ])
+    }
+
+    #[test]
+    pub fn load_zip() {
+        let raw_assets = super::load_from_zip(&cube_zip()).unwrap();
+        assert_eq!(raw_assets.len(), 4);
+        assert_eq!(
+            raw_assets.get("models/Cube.bin").unwrap(),
+            include_bytes!("../../test_data/Cube.bin")
+        );
+        assert_eq!(
+            raw_assets.get("models/Cube.gltf").unwrap(),
+            include_bytes!("../../test_data/Cube.gltf")
+        );
+    }
+
+    #[cfg(feature = "gltf")]
+    #[test]
+    pub fn deserialize_gltf_from_zip() {
+        let model: crate::Model = super::load_from_zip(&cube_zip())
+            .unwrap()
+            .deserialize("Cube.gltf")
+            .unwrap();
+        assert_eq!(model.geometries.len(), 1);
+        assert!(model.materials[0].albedo_texture.is_some());
+    }
+
+    #[test]
+    pub fn load_corrupt_zip() {
+        assert!(super::load_from_zip(&[0; 16]).is_err());
+        let mut bytes = zip(&[("test.bin", &[1, 2, 3, 4], false)]);
+        bytes[30 + "test.bin".len()] = 5;
+        assert!(matches!(
+            super::load_from_zip(&bytes),
+            Err(crate::Error::ZipCorruptData)
+        ));
+    }
+
+    #[test]
+    pub fn load_zip_bomb() {
+        // The entry decodes to more data than the size in the central directory
+        let mut bytes = zip(&[("bomb.bin", &vec![0; 1 << 20], true)]);
+        let end = bytes.len() - 22;
+        let offset = u32::from_le_bytes(bytes[end + 16..end + 20].try_into().unwrap()) as usize;
+        bytes[offset + 24..offset + 28].copy_from_slice(&16u32.to_le_bytes());
+        assert!(matches!(
+            super::load_from_zip(&bytes),
+            Err(crate::Error::ZipCorruptData)
+        ));
+    }
+}
@@ -16,6 +16,9 @@ pub mod prelude;
 mod camera;
 pub use camera::*;
 
+mod light;
+pub use light::*;
+
 pub mod texture;
 pub use texture::*;
 
@@ -48,6 +51,16 @@ pub struct Scene {
     pub children: Vec<Node>,
     /// A list of materials used in this scene. The materials are referenced by index in the relevant nodes.
     pub materials: Vec<PbrMaterial>,
+    /// A list of cameras defined in this scene, placed in world space using the transformations of the nodes they belong to.
+    /// The cameras are referenced by index in the relevant nodes.
+    /// Note that node animations are not applied to the cameras.
+    pub cameras: Vec<Camera>,
+    /// A list of lights defined in this scene, placed in world space using the transformations of the nodes they belong to.
+    /// The lights are referenced by index in the relevant nodes.
+    /// Note that node animations are not applied to the lights.
+    pub lights: Vec<Light>,
+    /// A list of skins used in this scene. The skins are referenced by index in the relevant nodes.
+    pub skins: Vec<Skin>,
 }
 
 impl Default for Scene {
@@ -56,10 +69,30 @@ impl Default for Scene {
             name: "scene".to_owned(),
             children: Vec::new(),
             materials: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: Vec::new(),
         }
     }
 }
 
+///
+/// A set of [Scene]s loaded from the same file, for example a glTF file which can define several scenes.
+///
+#[derive(Debug, Clone)]
+pub struct Scenes {
+    /// The scenes.
+    pub scenes: Vec<Scene>,
+    /// The index into [Scenes::scenes] of the scene which should be shown by default.
+    pub default_scene: usize,
+}
+
+impl std::convert::From<Scenes> for Scene {
+    fn from(mut scenes: Scenes) -> Self {
+        scenes.scenes.swap_remove(scenes.default_scene)
+    }
+}
+
 ///
 /// A node in a [Scene] graph. Each node may contain a set of children nodes, hence the whole [Scene] representaion has a tree structure.
 ///
@@ -80,6 +113,12 @@ pub struct Node {
     pub geometry: Option<Geometry>,
     /// Optional index into [Scene::materials], indicating which material should be applied to geometry below this node in the tree.
     pub material_index: Option<usize>,
+    /// Optional index into [Scene::skins], indicating which skin should deform the geometry of this node.
+    pub skin_index: Option<usize>,
+    /// Optional index into [Scene::cameras] of the camera placed by this node.
+    pub camera_index: Option<usize>,
+    /// Optional index into [Scene::lights] of the light placed by this node.
+    pub light_index: Option<usize>,
 }
 
 impl Default for Node {
@@ -91,6 +130,9 @@ impl Default for Node {
             animations: Vec::new(),
             geometry: None,
             material_index: None,
+            skin_index: None,
+            camera_index: None,
+            light_index: None,
         }
     }
 }
@@ -107,6 +149,12 @@ pub struct Model {
     pub geometries: Vec<Primitive>,
     /// A list of materials for this model
     pub materials: Vec<PbrMaterial>,
+    /// A list of cameras for this model, see [Scene::cameras].
+    pub cameras: Vec<Camera>,
+    /// A list of lights for this model, see [Scene::lights].
+    pub lights: Vec<Light>,
+    /// A list of skins for this model.
+    pub skins: Vec<Skin>,
 }
 
 ///
@@ -126,6 +174,9 @@ pub struct Primitive {
     pub geometry: Geometry,
     /// Optional index into [Model::materials], indicating which material should be applied to [Primitive::geometry].
     pub material_index: Option<usize>,
+    /// Optional index into [Model::skins], indicating which skin should deform the [Primitive::geometry].
+    /// The joint matrices of a skin transform directly to world space, so [Primitive::transformation] and [Primitive::animations] should not be applied to skinned geometry.
+    pub skin_index: Option<usize>,
 }
 
 impl std::ops::Deref for Primitive {
@@ -150,11 +201,120 @@ impl std::convert::From<Scene> for Model {
         Self {
             name: scene.name,
             materials: scene.materials,
+            cameras: scene.cameras,
+            lights: scene.lights,
+            skins: scene.skins,
             geometries,
         }
     }
 }
 
+impl Model {
+    ///
+    /// Translates the center of the combined bounding box of all geometries to the origin and uniformly scales the model such that the largest side of the bounding box has a length of 1.
+    /// Returns the applied transformation, see [AxisAlignedBoundingBox::normalizing_transformation].
+    /// The bounding box is computed in the rest pose without animations and the transformation is applied to the model by multiplying it onto
+    /// [Primitive::transformation], or the first key frame transformation of each of the [Primitive::animations] if the primitive is animated, and the root joints of the skins.
+    /// The vertex data, cameras and lights are not changed.
+    ///
+    pub fn normalize(&mut self) -> Mat4 {
+        let joint_matrices = self
+            .skins
+            .iter()
+            .map(|skin| skin.joint_matrices())
+            .collect::<Vec<_>>();
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for primitive in self.geometries.iter() {
+            let skinned = match (&primitive.geometry, primitive.skin_index) {
+                (Geometry::Triangles(mesh), Some(skin_index)) => mesh
+                    .joint_indices
+                    .as_ref()
+                    .zip(mesh.joint_weights.as_ref())
+                    .zip(joint_matrices.get(skin_index))
+                    .map(|((indices, weights), matrices)| {
+                        mesh.positions
+                            .to_f32()
+                            .into_iter()
+                            .zip(indices.iter().zip(weights))
+                            .map(|(position, (indices, weights))| {
+                                // Joints which are not in the skin are ignored
+                                (0..4).fold(Vec3::zero(), |sum, k| {
+                                    match matrices.get(indices[k] as usize) {
+                                        Some(matrix) => {
+                                            sum + (matrix * position.extend(1.0)).truncate()
+                                                * weights[k]
+                                        }
+                                        None => sum,
+                                    }
+                                })
+                            })
+                            .collect::<Vec<_>>()
+                    }),
+                _ => None,
+            };
+            if let Some(positions) = skinned {
+                aabb.expand(&positions);
+            } else {
+                let mut geometry_aabb = match &primitive.geometry {
+                    Geometry::Triangles(mesh) => mesh.compute_aabb(),
+                    Geometry::Points(point_cloud) => point_cloud.compute_aabb(),
+                };
+                geometry_aabb.transform(&primitive.transformation);
+                aabb.expand_with_aabb(&geometry_aabb);
+            }
+        }
+
+        let transformation = aabb.normalizing_transformation();
+        for primitive in self.geometries.iter_mut() {
+            if primitive.skin_index.is_some() {
+                continue;
+            }
+            if primitive.animations.is_empty() {
+                primitive.transformation = transformation * primitive.transformation;
+            }
+            for animation in primitive.animations.iter_mut() {
+                if let Some((first, _)) = animation.key_frames.first_mut() {
+                    *first = transformation * *first;
+                }
+            }
+        }
+        for skin in self.skins.iter_mut() {
+            for joint in skin
+                .joints
+                .iter_mut()
+                .filter(|joint| joint.parent.is_none())
+            {
+                joint.base_transformation = transformation * joint.base_transformation;
+            }
+        }
+        transformation
+    }
+}
+
+impl Error {
+    ///
+    /// Wraps this error in an [Error::Parse] error with the path and format of the file which failed to parse.
+    /// Errors which already describe the file, for example an unsupported format or a missing feature, are returned unchanged.
+    ///
+    pub(crate) fn with_parse_context(self, path: &std::path::Path, format: &str) -> Self {
+        let path = path.to_str().unwrap_or_default();
+        // Avoid repeating a potentially large data URL in the error message
+        let path = match path.find(',') {
+            Some(i) if path.starts_with("data:") => format!("{},...", &path[..i]),
+            _ => path.to_owned(),
+        };
+        match self {
+            Self::FailedDeserialize(_) | Self::FeatureMissing(_) => self,
+            Self::Parse { path: ref p, .. } if *p == path => self,
+            _ => Self::Parse {
+                path,
+                format: format.to_owned(),
+                source: Box::new(self),
+            },
+        }
+    }
+}
+
 fn visit(
     node: Node,
     mut animations: Vec<KeyFrameAnimation>,
@@ -184,6 +344,7 @@ fn visit(
             animations: animations.clone(),
             geometry,
             material_index: node.material_index,
+            skin_index: node.skin_index,
         });
     }
     for child in node.children {
@@ -209,11 +370,24 @@ pub enum Error {
     InvalidNumberOfIndices(usize),
     #[error("the max index {0} must be less than the number of vertices {1}")]
     InvalidIndices(usize, usize),
+    #[error("the index {0} cannot be represented by {1} indices")]
+    IndexOverflow(u32, String),
     #[error("the transformation matrix cannot be inverted and is therefore invalid")]
     FailedInvertingTransformationMatrix,
+    #[error("the mesh must have both normals and uv coordinates to compute tangents")]
+    MissingNormalsOrUvs,
     #[cfg(feature = "image")]
     #[error("error while parsing an image file")]
     Image(#[from] image::ImageError),
+    #[cfg(feature = "exr")]
+    #[error("error while parsing an .exr file")]
+    Exr(#[from] exr::error::Error),
+    #[cfg(feature = "exr")]
+    #[error("the {0} channel of the .exr file has {1} samples, but the image has {2} pixels")]
+    ExrChannelMismatch(String, usize, usize),
+    #[cfg(feature = "tiff")]
+    #[error("error while parsing a .tiff file")]
+    Tiff(#[from] tiff::TiffError),
     #[cfg(feature = "obj")]
     #[error("error while parsing an .obj file")]
     Obj(#[from] wavefront_obj::ParseError),
@@ -234,8 +408,55 @@ pub enum Error {
     #[cfg(feature = "gltf")]
     #[error("the .gltf file contain missing buffer data")]
     GltfMissingData,
+    #[cfg(feature = "draco")]
+    #[error("error while decoding a Draco compressed mesh in a .gltf file")]
+    Draco(#[from] draco_oxide_decoder::Err),
+    #[cfg(feature = "stl")]
+    #[error("the .stl file contain corrupt data")]
+    StlCorruptData,
+    #[cfg(feature = "ply")]
+    #[error("the .ply file contain corrupt data")]
+    PlyCorruptData,
+    #[cfg(feature = "dds")]
+    #[error("the .dds file contain corrupt data")]
+    DdsCorruptData,
+    #[cfg(feature = "dds")]
+    #[error("the .dds file contain data in an unsupported format: {0}")]
+    DdsUnsupportedFormat(String),
+    #[cfg(feature = "ktx2")]
+    #[error("the .ktx2 file contain corrupt data")]
+    Ktx2CorruptData,
+    #[cfg(feature = "ktx2")]
+    #[error("the .ktx2 file contain data in an unsupported format: {0}")]
+    Ktx2UnsupportedFormat(String),
+    #[cfg(feature = "qoi")]
+    #[error("the .qoi file contain corrupt data")]
+    QoiCorruptData,
+    #[cfg(feature = "svg")]
+    #[error("error while parsing an .svg file")]
+    Svg(#[from] resvg::usvg::Error),
+    #[cfg(feature = "svg")]
+    #[error("error while rasterizing an .svg file: {0}")]
+    SvgRasterization(String),
+    #[cfg(feature = "basis")]
+    #[error("error while transcoding a .basis file: {0}")]
+    BasisTranscoding(String),
     #[error("the .vol file contain wrong data size")]
     VolCorruptData,
+    #[cfg(feature = "vol")]
+    #[error(
+        ".vol files only support 8-bit data with one to four channels, but the voxel data is {0}"
+    )]
+    VolUnsupportedData(String),
+    #[cfg(feature = "vox")]
+    #[error("the .vox file contain corrupt data")]
+    VoxCorruptData,
+    #[cfg(feature = "zip")]
+    #[error("the .zip archive contain corrupt data")]
+    ZipCorruptData,
+    #[cfg(feature = "zip")]
+    #[error("the .zip archive entry {0} is not supported: {1}")]
+    ZipUnsupportedEntry(String, String),
     #[cfg(not(target_arch = "wasm32"))]
     #[error("error while loading the file {0}: {1}")]
     FailedLoading(String, std::io::Error),
@@ -243,6 +464,9 @@ pub enum Error {
     #[error("error while loading the url {0}: {1}")]
     FailedLoadingUrl(String, reqwest::Error),
     #[cfg(feature = "reqwest")]
+    #[error("the url {0} responded with status {1}")]
+    FailedLoadingUrlStatus(String, reqwest::StatusCode),
+    #[cfg(feature = "reqwest")]
     #[error("error while parsing the url {0}")]
     FailedParsingUrl(String),
     #[cfg(feature = "data-url")]
@@ -250,12 +474,122 @@ pub enum Error {
     FailedParsingDataUrl(String, String),
     #[error("tried to use {0} which was not loaded or otherwise added to the raw assets")]
     NotLoaded(String),
+    #[error("decompression of {0} texture data is not supported")]
+    UnsupportedDecompression(String),
+    #[error("flipping {0} texture data is not supported")]
+    UnsupportedFlip(String),
+    #[error("rotating {0} texture data is not supported")]
+    UnsupportedRotation(String),
+    #[error("the {2}x{3} region at ({0}, {1}) is outside the {4}x{5} texture")]
+    RegionOutOfBounds(u32, u32, u32, u32, u32, u32),
+    #[error("the texture size must be at least 1x1, but the requested size is {0}x{1}")]
+    InvalidTextureSize(u32, u32),
+    #[error("conversion from {0} to {1} texture data is not supported")]
+    UnsupportedConversion(String, String),
+    #[error("a 3D texture needs at least one slice")]
+    MissingTextureSlices,
+    #[error("slice {0} of the 3D texture is a {1} texture, but the first slice is a {2} texture")]
+    TextureSliceMismatch(usize, String, String),
+    #[error("the faces of a cube texture must be square, but the first face is {0}x{1}")]
+    NonSquareCubeFace(u32, u32),
+    #[error("face {0} of the cube texture is a {1} texture, but the first face is a {2} texture")]
+    CubeFaceMismatch(usize, String, String),
+    #[error(
+        "a {0}x{1} texture is neither a horizontal 4x3 nor a vertical 3x4 cross of cube faces"
+    )]
+    InvalidCubeCross(u32, u32),
+    #[error("the textures do not fit into an atlas of size {0}x{0}")]
+    AtlasOverflow(u32),
+    #[error("the name {0} is used for more than one texture in the atlas")]
+    DuplicateAtlasName(String),
+    #[error("{0} texture data has no alpha channel")]
+    MissingAlphaChannel(String),
     #[error("the feature {0} is needed")]
     FeatureMissing(String),
-    #[error("failed to deserialize the file {0}")]
+    #[error("the file {0} has an unsupported format")]
     FailedDeserialize(String),
+    #[error("failed to parse {path} as {format}: {source}")]
+    Parse {
+        path: String,
+        format: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
     #[error("failed to serialize the file {0}")]
     FailedSerialize(String),
     #[error("failed to find {0} in the file {1}")]
     FailedConvertion(String, String),
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn primitive(mesh: TriMesh, transformation: Mat4) -> Primitive {
+        Primitive {
+            name: String::new(),
+            transformation,
+            animations: Vec::new(),
+            geometry: Geometry::Triangles(mesh),
+            material_index: None,
+            skin_index: None,
+        }
+    }
+
+    #[test]
+    pub fn normalize_model() {
+        let translated = primitive(
+            TriMesh::square(),
+            Mat4::from_translation(vec3(4.0, 0.0, 0.0)),
+        );
+        let mut animated = primitive(TriMesh::cube(), Mat4::identity());
+        animated.animations.push(KeyFrameAnimation {
+            name: None,
+            key_frames: vec![(Mat4::identity(), std::sync::Arc::new(KeyFrames::default()))],
+        });
+        let mut square = TriMesh::square();
+        // The second joint of each vertex is not in the skin, but has no weight
+        square.joint_indices = Some(vec![Vector4::new(0, 1, 0, 0); 4]);
+        square.joint_weights = Some(vec![vec4(1.0, 0.0, 0.0, 0.0); 4]);
+        let mut skinned = primitive(square.clone(), Mat4::identity());
+        skinned.skin_index = Some(0);
+        // A skin which does not exist is ignored
+        let mut missing_skin = primitive(square, Mat4::identity());
+        missing_skin.skin_index = Some(5);
+        let joint = Joint {
+            name: String::new(),
+            parent: None,
+            base_transformation: Mat4::from_translation(vec3(0.0, 0.0, -3.0)),
+            translation: Vec3::zero(),
+            rotation: Quat::one(),
+            scale: vec3(1.0, 1.0, 1.0),
+            inverse_bind_matrix: Mat4::identity(),
+        };
+        let mut model = Model {
+            name: String::new(),
+            geometries: vec![translated, animated, skinned, missing_skin],
+            materials: Vec::new(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            skins: vec![Skin {
+                joints: vec![joint],
+                ..Default::default()
+            }],
+        };
+
+        // The bounding box is from (-1, -1, -3) to (5, 1, 1)
+        let transformation = model.normalize();
+        let expected = Mat4::from_scale(1.0 / 6.0) * Mat4::from_translation(vec3(-2.0, 0.0, 1.0));
+        assert_eq!(transformation, expected);
+        assert_eq!(
+            model.geometries[0].transformation,
+            expected * Mat4::from_translation(vec3(4.0, 0.0, 0.0))
+        );
+        assert_eq!(model.geometries[1].transformation, Mat4::identity());
+        assert_eq!(model.geometries[1].animations[0].key_frames[0].0, expected);
+        assert_eq!(model.geometries[2].transformation, Mat4::identity());
+        assert_eq!(
+            model.skins[0].joints[0].base_transformation,
+            expected * Mat4::from_translation(vec3(0.0, 0.0, -3.0))
+        );
+    }
+}
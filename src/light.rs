@@ -0,0 +1,49 @@
+pub use crate::prelude::*;
+
+///
+/// The type of a [Light] (directional, point or spot) including its placement in world space.
+///
+#[derive(Clone, Debug)]
+pub enum LightType {
+    /// A light infinitely far away which emits light in one direction and which is not attenuated.
+    Directional {
+        /// The normalized direction the light is emitted in.
+        direction: Vec3,
+    },
+    /// A light which emits light in all directions from a position.
+    Point {
+        /// The position of the light.
+        position: Vec3,
+        /// Optional distance at which the intensity of the light can be considered to have reached zero.
+        range: Option<f32>,
+    },
+    /// A light which emits light in a cone from a position.
+    Spot {
+        /// The position of the light.
+        position: Vec3,
+        /// The normalized direction of the center of the cone.
+        direction: Vec3,
+        /// Optional distance at which the intensity of the light can be considered to have reached zero.
+        range: Option<f32>,
+        /// The angle from the center of the cone where the falloff begins.
+        inner_cone_angle: Radians,
+        /// The angle from the center of the cone where the falloff ends.
+        outer_cone_angle: Radians,
+    },
+}
+
+///
+/// A light source, for example a punctual light defined in a glTF file.
+///
+#[derive(Clone, Debug)]
+pub struct Light {
+    /// The name. Might not be anything meaningful.
+    pub name: String,
+    /// The color of the light.
+    pub color: Color,
+    /// The intensity of the light, given in the same units as the glTF `KHR_lights_punctual` extension,
+    /// that is luminous intensity in candela (lm/sr) for point and spot lights and illuminance in lux (lm/m²) for directional lights.
+    pub intensity: f32,
+    /// The type of light including its placement.
+    pub light_type: LightType,
+}
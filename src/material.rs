@@ -2,6 +2,7 @@
 //! Contain material asset definitions.
 //!
 
+use crate::prelude::*;
 #[doc(inline)]
 pub use crate::{prelude::Color, texture::texture2d::*};
 
@@ -36,6 +37,64 @@ pub enum NormalDistributionFunction {
     TrowbridgeReitzGGX,
 }
 
+/// Specifies how the alpha value of the albedo color and texture of a material is interpreted.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum AlphaMode {
+    /// The alpha value is ignored and the material is fully opaque.
+    #[default]
+    Opaque,
+    /// The material is fully opaque where the alpha value is greater than or equal to the given cutoff value and fully transparent otherwise.
+    Mask(f32),
+    /// The alpha value is used to blend the material with the background.
+    Blend,
+}
+
+///
+/// A transformation of the uv coordinates used when sampling a texture, for example used for tiling a texture or for selecting a region of a texture atlas.
+/// The default is the identity transformation.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureTransform {
+    /// The offset of the uv coordinates.
+    pub offset: Vec2,
+    /// The rotation in radians of the uv coordinates around the origin.
+    pub rotation: f32,
+    /// The scale of the uv coordinates.
+    pub scale: Vec2,
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self {
+            offset: vec2(0.0, 0.0),
+            rotation: 0.0,
+            scale: vec2(1.0, 1.0),
+        }
+    }
+}
+
+impl TextureTransform {
+    ///
+    /// Returns the transformation as a matrix which should be applied to uv coordinates on the form `(u, v, 1)`,
+    /// ie. first the scale, then the rotation and finally the offset, which is the same as in the glTF `KHR_texture_transform` extension.
+    ///
+    pub fn matrix(&self) -> Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        Mat3::new(
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            self.offset.x,
+            self.offset.y,
+            1.0,
+        ) * Mat3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+            * Mat3::from_nonuniform_scale(self.scale.x, self.scale.y)
+    }
+}
+
 ///
 /// A CPU-side version of a material used for physically based rendering (PBR).
 ///
@@ -47,6 +106,17 @@ pub struct PbrMaterial {
     pub albedo: Color,
     /// Texture with albedo base colors, also called diffuse color. Assumed to be in sRGB with or without an alpha channel.
     pub albedo_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::albedo_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub albedo_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::albedo_texture].
+    pub albedo_texture_transform: TextureTransform,
+    /// Texture with alpha values which are multiplied with the alpha value of the [Self::albedo] and [Self::albedo_texture].
+    /// The alpha values are sampled from the red channel.
+    pub alpha_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::alpha_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub alpha_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::alpha_texture].
+    pub alpha_texture_transform: TextureTransform,
     /// A value in the range `[0..1]` specifying how metallic the material is.
     pub metallic: f32,
     /// A value in the range `[0..1]` specifying how rough the material surface is.
@@ -59,22 +129,74 @@ pub struct PbrMaterial {
     /// The metallic values are sampled from the blue channel and the roughness from the green channel.
     /// Can be combined with occlusion into one texture, see [Self::occlusion_metallic_roughness_texture].
     pub metallic_roughness_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::metallic_roughness_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub metallic_roughness_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::metallic_roughness_texture].
+    pub metallic_roughness_texture_transform: TextureTransform,
     /// A scalar multiplier controlling the amount of occlusion applied from the [Self::occlusion_texture]. A value of 0.0 means no occlusion. A value of 1.0 means full occlusion.
     pub occlusion_strength: f32,
     /// An occlusion map. Higher values indicate areas that should receive full indirect lighting and lower values indicate no indirect lighting.
     /// The occlusion values are sampled from the red channel.
     /// Can be combined with metallic and roughness into one texture, see [Self::occlusion_metallic_roughness_texture].
     pub occlusion_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::occlusion_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub occlusion_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::occlusion_texture].
+    pub occlusion_texture_transform: TextureTransform,
     /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
     pub normal_scale: f32,
     /// A tangent space normal map, also known as bump map.
     pub normal_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::normal_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub normal_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::normal_texture].
+    pub normal_texture_transform: TextureTransform,
     /// Color of light shining from an object.
     pub emissive: Color,
+    /// A scalar multiplier applied to the [Self::emissive] color and the [Self::emissive_texture] to get the final emitted light.
+    /// Is not limited to the range `[0..1]` which makes it possible to specify high dynamic range emission.
+    pub emissive_strength: f32,
     /// Texture with color of light shining from an object.
     pub emissive_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::emissive_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub emissive_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::emissive_texture].
+    pub emissive_texture_transform: TextureTransform,
+    /// A value in the range `[0..1]` specifying the intensity of a clear coat layer on top of the material, for example a lacquer. A value of 0.0 means no clear coat.
+    pub clearcoat: f32,
+    /// Texture containing the clear coat intensity which is multiplied with the [Self::clearcoat] to get the final parameter.
+    /// The clear coat values are sampled from the red channel.
+    pub clearcoat_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::clearcoat_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub clearcoat_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::clearcoat_texture].
+    pub clearcoat_texture_transform: TextureTransform,
+    /// A value in the range `[0..1]` specifying how rough the clear coat layer is.
+    pub clearcoat_roughness: f32,
+    /// Texture containing the clear coat roughness which is multiplied with the [Self::clearcoat_roughness] to get the final parameter.
+    /// The roughness values are sampled from the green channel.
+    pub clearcoat_roughness_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::clearcoat_roughness_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub clearcoat_roughness_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::clearcoat_roughness_texture].
+    pub clearcoat_roughness_texture_transform: TextureTransform,
+    /// A scalar multiplier applied to each normal vector of the [Self::clearcoat_normal_texture].
+    pub clearcoat_normal_scale: f32,
+    /// A tangent space normal map for the clear coat layer.
+    pub clearcoat_normal_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::clearcoat_normal_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub clearcoat_normal_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::clearcoat_normal_texture].
+    pub clearcoat_normal_texture_transform: TextureTransform,
     /// Alpha cutout value for transparency in deferred rendering pipeline.
+    #[deprecated(note = "use `alpha_mode` with `AlphaMode::Mask` instead")]
     pub alpha_cutout: Option<f32>,
+    /// Specifies how the alpha value of the [Self::albedo] and [Self::albedo_texture] is interpreted.
+    pub alpha_mode: AlphaMode,
+    /// Whether or not the back faces of the geometry should be rendered, in which case the normals are reversed when rendering the back faces.
+    pub is_double_sided: bool,
+    /// Whether or not the material should be rendered without lighting, in which case only the [Self::albedo] and [Self::albedo_texture] are used.
+    pub is_unlit: bool,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
     /// The index of refraction for this material    
@@ -83,28 +205,117 @@ pub struct PbrMaterial {
     pub transmission: f32,
     /// Texture containing the transmission parameter which are multiplied with the [Self::transmission] to get the final parameter.
     pub transmission_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::transmission_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub transmission_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::transmission_texture].
+    pub transmission_texture_transform: TextureTransform,
+    /// The thickness of the volume beneath the surface in the coordinate space of the mesh.
+    /// A value of 0.0 means that the material is thin-walled, otherwise the material is the boundary of a volume.
+    pub thickness: f32,
+    /// Texture containing the thickness which is multiplied with the [Self::thickness] to get the final thickness.
+    /// The thickness values are sampled from the green channel.
+    pub thickness_texture: Option<Texture2D>,
+    /// The index of the uv set used when sampling the [Self::thickness_texture], see [TriMesh::uv_set](crate::TriMesh::uv_set).
+    pub thickness_uv_set: usize,
+    /// The transformation of the uv coordinates used when sampling the [Self::thickness_texture].
+    pub thickness_texture_transform: TextureTransform,
+    /// The color that white light turns into due to absorption when travelling the [Self::attenuation_distance] inside the volume.
+    pub attenuation_color: Color,
+    /// The average distance in world space that light travels inside the volume before interacting with a particle.
+    /// Is infinite if the light is not attenuated.
+    pub attenuation_distance: f32,
+}
+
+impl PbrMaterial {
+    ///
+    /// Sets the [Texture2D::color_space] of all textures according to how they are used,
+    /// ie. the albedo and emissive textures are sRGB encoded while all other textures contain linear data.
+    ///
+    pub fn assign_texture_color_spaces(&mut self) {
+        for texture in [&mut self.albedo_texture, &mut self.emissive_texture]
+            .into_iter()
+            .flatten()
+        {
+            texture.color_space = ColorSpace::Srgb;
+        }
+        for texture in [
+            &mut self.alpha_texture,
+            &mut self.occlusion_metallic_roughness_texture,
+            &mut self.metallic_roughness_texture,
+            &mut self.occlusion_texture,
+            &mut self.normal_texture,
+            &mut self.transmission_texture,
+            &mut self.thickness_texture,
+            &mut self.clearcoat_texture,
+            &mut self.clearcoat_roughness_texture,
+            &mut self.clearcoat_normal_texture,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            texture.color_space = ColorSpace::Linear;
+        }
+    }
 }
 
 impl Default for PbrMaterial {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             name: "default".to_string(),
             albedo: Color::WHITE,
             albedo_texture: None,
+            albedo_uv_set: 0,
+            albedo_texture_transform: TextureTransform::default(),
+            alpha_texture: None,
+            alpha_uv_set: 0,
+            alpha_texture_transform: TextureTransform::default(),
             occlusion_metallic_roughness_texture: None,
             metallic_roughness_texture: None,
+            metallic_roughness_uv_set: 0,
+            metallic_roughness_texture_transform: TextureTransform::default(),
             occlusion_texture: None,
+            occlusion_uv_set: 0,
+            occlusion_texture_transform: TextureTransform::default(),
             metallic: 0.0,
             roughness: 1.0,
             occlusion_strength: 1.0,
             normal_texture: None,
+            normal_uv_set: 0,
+            normal_texture_transform: TextureTransform::default(),
             normal_scale: 1.0,
             emissive: Color::BLACK,
+            emissive_strength: 1.0,
             emissive_texture: None,
+            emissive_uv_set: 0,
+            emissive_texture_transform: TextureTransform::default(),
             index_of_refraction: 1.5,
             transmission: 0.0,
             transmission_texture: None,
+            transmission_uv_set: 0,
+            transmission_texture_transform: TextureTransform::default(),
+            thickness: 0.0,
+            thickness_texture: None,
+            thickness_uv_set: 0,
+            thickness_texture_transform: TextureTransform::default(),
+            attenuation_color: Color::WHITE,
+            attenuation_distance: f32::INFINITY,
+            clearcoat: 0.0,
+            clearcoat_texture: None,
+            clearcoat_uv_set: 0,
+            clearcoat_texture_transform: TextureTransform::default(),
+            clearcoat_roughness: 0.0,
+            clearcoat_roughness_texture: None,
+            clearcoat_roughness_uv_set: 0,
+            clearcoat_roughness_texture_transform: TextureTransform::default(),
+            clearcoat_normal_scale: 1.0,
+            clearcoat_normal_texture: None,
+            clearcoat_normal_uv_set: 0,
+            clearcoat_normal_texture_transform: TextureTransform::default(),
             alpha_cutout: None,
+            alpha_mode: AlphaMode::Opaque,
+            is_double_sided: false,
+            is_unlit: false,
             lighting_model: LightingModel::Blinn,
         }
     }
@@ -12,22 +12,14 @@ pub struct AxisAlignedBoundingBox {
 impl AxisAlignedBoundingBox {
     /// An empty bounding box.
     pub const EMPTY: Self = Self {
-        min: Vec3::new(std::f32::INFINITY, std::f32::INFINITY, std::f32::INFINITY),
-        max: Vec3::new(
-            std::f32::NEG_INFINITY,
-            std::f32::NEG_INFINITY,
-            std::f32::NEG_INFINITY,
-        ),
+        min: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        max: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
     };
 
     /// An infinitely large bounding box.
     pub const INFINITE: Self = Self {
-        min: Vec3::new(
-            std::f32::NEG_INFINITY,
-            std::f32::NEG_INFINITY,
-            std::f32::NEG_INFINITY,
-        ),
-        max: Vec3::new(std::f32::INFINITY, std::f32::INFINITY, std::f32::INFINITY),
+        min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
     };
 
     ///
@@ -164,6 +156,20 @@ impl AxisAlignedBoundingBox {
         self.max = aabb.max;
     }
 
+    ///
+    /// Returns the transformation which translates the center of this bounding box to the origin and uniformly scales it such that the largest side has a length of 1.
+    /// Returns the identity if the bounding box is empty or infinite, and does not scale if the bounding box has zero size.
+    ///
+    pub fn normalizing_transformation(&self) -> Mat4 {
+        if self.is_empty() || self.is_infinite() {
+            return Mat4::identity();
+        }
+        let size = self.size();
+        let largest = size.x.max(size.y).max(size.z);
+        let scale = if largest > 0.0 { 1.0 / largest } else { 1.0 };
+        Mat4::from_scale(scale) * Mat4::from_translation(-self.center())
+    }
+
     ///
     /// The distance from position to the point in this bounding box that is closest to position.
     ///
@@ -206,3 +212,42 @@ impl AxisAlignedBoundingBox {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn normalizing_transformation() {
+        let aabb =
+            AxisAlignedBoundingBox::new_with_positions(&[vec3(1.0, 2.0, 3.0), vec3(3.0, 6.0, 4.0)]);
+        let transformation = aabb.normalizing_transformation();
+        assert_eq!(
+            transformation,
+            Mat4::new(
+                0.25, 0.0, 0.0, 0.0, 0.0, 0.25, 0.0, 0.0, 0.0, 0.0, 0.25, 0.0, -0.5, -1.0, -0.875,
+                1.0
+            )
+        );
+        let mut normalized = aabb;
+        normalized.transform(&transformation);
+        assert_eq!(normalized.min(), vec3(-0.25, -0.5, -0.125));
+        assert_eq!(normalized.max(), vec3(0.25, 0.5, 0.125));
+
+        // A single point is only translated
+        let point = AxisAlignedBoundingBox::new_with_positions(&[vec3(1.0, 2.0, 3.0)]);
+        assert_eq!(
+            point.normalizing_transformation(),
+            Mat4::from_translation(vec3(-1.0, -2.0, -3.0))
+        );
+
+        assert_eq!(
+            AxisAlignedBoundingBox::EMPTY.normalizing_transformation(),
+            Mat4::identity()
+        );
+        assert_eq!(
+            AxisAlignedBoundingBox::INFINITE.normalizing_transformation(),
+            Mat4::identity()
+        );
+    }
+}
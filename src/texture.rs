@@ -8,25 +8,26 @@ pub use texture2d::*;
 pub(crate) mod texture3d;
 pub use texture3d::*;
 
+pub(crate) mod texture_cube;
+pub use texture_cube::*;
+
+pub(crate) mod atlas;
+pub use atlas::*;
+
 pub use crate::prelude::f16;
 
 ///
 /// Possible modes of interpolation which determines the texture output between texture pixels.
 ///
 #[allow(missing_docs)]
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 pub enum Interpolation {
     Nearest,
+    #[default]
     Linear,
     CubicSpline,
 }
 
-impl Default for Interpolation {
-    fn default() -> Self {
-        Self::Linear
-    }
-}
-
 ///
 /// Possible wrapping modes for a texture which determines how the texture is applied outside of the
 /// [0..1] uv coordinate range.
@@ -40,7 +41,19 @@ pub enum Wrapping {
 }
 
 ///
-/// The pixel/texel data for a [Texture2D] or [Texture3D].
+/// The color space of the color channels of texture data, ie. all channels except alpha.
+///
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum ColorSpace {
+    /// The values are encoded with the sRGB transfer function which is the case for most color images, for example albedo and emissive textures.
+    #[default]
+    Srgb,
+    /// The values are linear which is the case for non-color data, for example normal, metallic, roughness and occlusion textures, as well as most floating point data.
+    Linear,
+}
+
+///
+/// The pixel/texel data for a [Texture2D], [Texture3D] or [TextureCube].
 ///
 /// If 2D data, the data array should start with the top left texel and then one row at a time.
 /// The indices `(row, column)` into the 2D data would look like
@@ -64,6 +77,9 @@ pub enum Wrapping {
 /// ]
 /// ```
 ///
+/// The block compressed variants instead contain blocks of 4x4 texels, ordered in the same way as the texels above.
+/// If the width or height is not a multiple of 4, the last blocks in each row/column are only partially used.
+///
 #[derive(Clone, PartialEq)]
 pub enum TextureData {
     /// One byte in the red channel.
@@ -75,6 +91,15 @@ pub enum TextureData {
     /// One byte in the red, green, blue and alpha channel.
     RgbaU8(Vec<[u8; 4]>),
 
+    /// 16-bit unsigned integer in the red channel.
+    RU16(Vec<u16>),
+    /// 16-bit unsigned integer in the red and green channel.
+    RgU16(Vec<[u16; 2]>),
+    /// 16-bit unsigned integer in the red, green and blue channel.
+    RgbU16(Vec<[u16; 3]>),
+    /// 16-bit unsigned integer in the red, green, blue and alpha channel.
+    RgbaU16(Vec<[u16; 4]>),
+
     /// 16-bit float in the red channel.
     RF16(Vec<f16>),
     /// 16-bit float in the red and green channel.
@@ -92,6 +117,225 @@ pub enum TextureData {
     RgbF32(Vec<[f32; 3]>),
     /// 32-bit float in the red, green, blue and alpha channel.
     RgbaF32(Vec<[f32; 4]>),
+
+    /// BC1 (DXT1) compressed blocks of 4x4 texels with one byte in the red, green, blue and alpha channel when decompressed.
+    Bc1RgbaU8(Vec<[u8; 8]>),
+    /// BC3 (DXT5) compressed blocks of 4x4 texels with one byte in the red, green, blue and alpha channel when decompressed.
+    Bc3RgbaU8(Vec<[u8; 16]>),
+    /// BC5 compressed blocks of 4x4 texels with one byte in the red and green channel when decompressed.
+    Bc5RgU8(Vec<[u8; 16]>),
+    /// BC7 compressed blocks of 4x4 texels with one byte in the red, green, blue and alpha channel when decompressed.
+    Bc7RgbaU8(Vec<[u8; 16]>),
+    /// ETC2 (with EAC alpha) compressed blocks of 4x4 texels with one byte in the red, green, blue and alpha channel when decompressed.
+    Etc2RgbaU8(Vec<[u8; 16]>),
+    /// ASTC compressed blocks of 4x4 texels with one byte in the red, green, blue and alpha channel when decompressed.
+    Astc4x4RgbaU8(Vec<[u8; 16]>),
+}
+
+///
+/// The format of [TextureData] without the data itself, ie. one variant for each variant of [TextureData], see [TextureData::kind].
+///
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum TextureDataKind {
+    RU8,
+    RgU8,
+    RgbU8,
+    RgbaU8,
+    RU16,
+    RgU16,
+    RgbU16,
+    RgbaU16,
+    RF16,
+    RgF16,
+    RgbF16,
+    RgbaF16,
+    RF32,
+    RgF32,
+    RgbF32,
+    RgbaF32,
+    Bc1RgbaU8,
+    Bc3RgbaU8,
+    Bc5RgU8,
+    Bc7RgbaU8,
+    Etc2RgbaU8,
+    Astc4x4RgbaU8,
+}
+
+impl TextureDataKind {
+    ///
+    /// Returns the number of channels, which for the block compressed formats is the number of channels when decompressed.
+    ///
+    pub fn channel_count(&self) -> usize {
+        match self {
+            Self::RU8 | Self::RU16 | Self::RF16 | Self::RF32 => 1,
+            Self::RgU8 | Self::RgU16 | Self::RgF16 | Self::RgF32 | Self::Bc5RgU8 => 2,
+            Self::RgbU8 | Self::RgbU16 | Self::RgbF16 | Self::RgbF32 => 3,
+            _ => 4,
+        }
+    }
+
+    ///
+    /// Returns whether or not the format is block compressed.
+    ///
+    pub fn is_compressed(&self) -> bool {
+        matches!(
+            self,
+            Self::Bc1RgbaU8
+                | Self::Bc3RgbaU8
+                | Self::Bc5RgU8
+                | Self::Bc7RgbaU8
+                | Self::Etc2RgbaU8
+                | Self::Astc4x4RgbaU8
+        )
+    }
+}
+
+impl TextureData {
+    ///
+    /// Returns the format of the data.
+    ///
+    pub fn kind(&self) -> TextureDataKind {
+        match self {
+            Self::RU8(_) => TextureDataKind::RU8,
+            Self::RgU8(_) => TextureDataKind::RgU8,
+            Self::RgbU8(_) => TextureDataKind::RgbU8,
+            Self::RgbaU8(_) => TextureDataKind::RgbaU8,
+            Self::RU16(_) => TextureDataKind::RU16,
+            Self::RgU16(_) => TextureDataKind::RgU16,
+            Self::RgbU16(_) => TextureDataKind::RgbU16,
+            Self::RgbaU16(_) => TextureDataKind::RgbaU16,
+            Self::RF16(_) => TextureDataKind::RF16,
+            Self::RgF16(_) => TextureDataKind::RgF16,
+            Self::RgbF16(_) => TextureDataKind::RgbF16,
+            Self::RgbaF16(_) => TextureDataKind::RgbaF16,
+            Self::RF32(_) => TextureDataKind::RF32,
+            Self::RgF32(_) => TextureDataKind::RgF32,
+            Self::RgbF32(_) => TextureDataKind::RgbF32,
+            Self::RgbaF32(_) => TextureDataKind::RgbaF32,
+            Self::Bc1RgbaU8(_) => TextureDataKind::Bc1RgbaU8,
+            Self::Bc3RgbaU8(_) => TextureDataKind::Bc3RgbaU8,
+            Self::Bc5RgU8(_) => TextureDataKind::Bc5RgU8,
+            Self::Bc7RgbaU8(_) => TextureDataKind::Bc7RgbaU8,
+            Self::Etc2RgbaU8(_) => TextureDataKind::Etc2RgbaU8,
+            Self::Astc4x4RgbaU8(_) => TextureDataKind::Astc4x4RgbaU8,
+        }
+    }
+
+    ///
+    /// Returns whether or not the data is block compressed.
+    ///
+    pub fn is_compressed(&self) -> bool {
+        matches!(
+            self,
+            Self::Bc1RgbaU8(_)
+                | Self::Bc3RgbaU8(_)
+                | Self::Bc5RgU8(_)
+                | Self::Bc7RgbaU8(_)
+                | Self::Etc2RgbaU8(_)
+                | Self::Astc4x4RgbaU8(_)
+        )
+    }
+
+    ///
+    /// Returns the uncompressed version of this data with the given size in texels, ie. [TextureData::RgbaU8] for BC1, BC3 and BC7 and
+    /// [TextureData::RgU8] for BC5. Returns a copy of the data if it is not compressed and an error if decompression of the format is not supported (ETC2 and ASTC).
+    /// Decompression of BC1, BC3, BC5 and BC7 data requires one of the `dds`, `ktx2` or `basis` features and returns an error if there are fewer blocks than needed to cover the given size.
+    ///
+    #[cfg_attr(
+        not(any(feature = "dds", feature = "ktx2", feature = "basis")),
+        allow(unused_variables)
+    )]
+    pub fn decompress(&self, width: u32, height: u32) -> crate::Result<Self> {
+        #[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+        let rgba = |texels: Vec<u8>| {
+            Self::RgbaU8(texels.chunks(4).map(|c| [c[0], c[1], c[2], c[3]]).collect())
+        };
+        Ok(match self {
+            #[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+            Self::Bc1RgbaU8(blocks) => rgba(decompress_blocks(
+                "BC1",
+                blocks,
+                (width, height),
+                4,
+                bcdec_rs::bc1,
+            )?),
+            #[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+            Self::Bc3RgbaU8(blocks) => rgba(decompress_blocks(
+                "BC3",
+                blocks,
+                (width, height),
+                4,
+                bcdec_rs::bc3,
+            )?),
+            #[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+            Self::Bc5RgU8(blocks) => Self::RgU8(
+                decompress_blocks("BC5", blocks, (width, height), 2, |block, out, pitch| {
+                    bcdec_rs::bc5(block, out, pitch, false)
+                })?
+                .chunks(2)
+                .map(|c| [c[0], c[1]])
+                .collect(),
+            ),
+            #[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+            Self::Bc7RgbaU8(blocks) => rgba(decompress_blocks(
+                "BC7",
+                blocks,
+                (width, height),
+                4,
+                bcdec_rs::bc7,
+            )?),
+            #[cfg(not(any(feature = "dds", feature = "ktx2", feature = "basis")))]
+            Self::Bc1RgbaU8(_) => Err(crate::Error::UnsupportedDecompression("BC1".to_owned()))?,
+            #[cfg(not(any(feature = "dds", feature = "ktx2", feature = "basis")))]
+            Self::Bc3RgbaU8(_) => Err(crate::Error::UnsupportedDecompression("BC3".to_owned()))?,
+            #[cfg(not(any(feature = "dds", feature = "ktx2", feature = "basis")))]
+            Self::Bc5RgU8(_) => Err(crate::Error::UnsupportedDecompression("BC5".to_owned()))?,
+            #[cfg(not(any(feature = "dds", feature = "ktx2", feature = "basis")))]
+            Self::Bc7RgbaU8(_) => Err(crate::Error::UnsupportedDecompression("BC7".to_owned()))?,
+            Self::Etc2RgbaU8(_) => Err(crate::Error::UnsupportedDecompression("ETC2".to_owned()))?,
+            Self::Astc4x4RgbaU8(_) => {
+                Err(crate::Error::UnsupportedDecompression("ASTC".to_owned()))?
+            }
+            _ => self.clone(),
+        })
+    }
+}
+
+///
+/// Decodes the given 4x4 blocks with the given block decoder into tightly packed texels with the given number of channels.
+/// Returns an error if there are fewer blocks than needed to cover the given size.
+///
+#[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+fn decompress_blocks<const N: usize>(
+    format: &str,
+    blocks: &[[u8; N]],
+    (width, height): (u32, u32),
+    channels: usize,
+    decode_block: impl Fn(&[u8], &mut [u8], usize),
+) -> crate::Result<Vec<u8>> {
+    let (width, height) = (width as usize, height as usize);
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    if blocks.len() < blocks_x * blocks_y {
+        Err(crate::Error::InvalidBufferLength(
+            format!("{} block", format),
+            blocks_x * blocks_y,
+            blocks.len(),
+        ))?;
+    }
+    let pitch = blocks_x * 4 * channels;
+    let mut texels = vec![0u8; pitch * blocks_y * 4];
+    for (i, block) in blocks.iter().take(blocks_x * blocks_y).enumerate() {
+        let (bx, by) = (i % blocks_x, i / blocks_x);
+        decode_block(
+            block,
+            &mut texels[by * 4 * pitch + bx * 4 * channels..],
+            pitch,
+        );
+    }
+    Ok((0..height)
+        .flat_map(|y| texels[y * pitch..y * pitch + width * channels].to_vec())
+        .collect())
 }
 
 impl std::fmt::Debug for TextureData {
@@ -101,6 +345,10 @@ impl std::fmt::Debug for TextureData {
             Self::RgU8(values) => write!(f, "RG u8 ({:?})", values.len()),
             Self::RgbU8(values) => write!(f, "RGB u8 ({:?})", values.len()),
             Self::RgbaU8(values) => write!(f, "RGBA u8 ({:?})", values.len()),
+            Self::RU16(values) => write!(f, "R u16 ({:?})", values.len()),
+            Self::RgU16(values) => write!(f, "RG u16 ({:?})", values.len()),
+            Self::RgbU16(values) => write!(f, "RGB u16 ({:?})", values.len()),
+            Self::RgbaU16(values) => write!(f, "RGBA u16 ({:?})", values.len()),
             Self::RF16(values) => write!(f, "R f16 ({:?})", values.len()),
             Self::RgF16(values) => write!(f, "RG f16 ({:?})", values.len()),
             Self::RgbF16(values) => write!(f, "RGB f16 ({:?})", values.len()),
@@ -109,6 +357,14 @@ impl std::fmt::Debug for TextureData {
             Self::RgF32(values) => write!(f, "RG f32 ({:?})", values.len()),
             Self::RgbF32(values) => write!(f, "RGB f32 ({:?})", values.len()),
             Self::RgbaF32(values) => write!(f, "RGBA f32 ({:?})", values.len()),
+            Self::Bc1RgbaU8(blocks) => write!(f, "BC1 RGBA u8 ({:?} blocks)", blocks.len()),
+            Self::Bc3RgbaU8(blocks) => write!(f, "BC3 RGBA u8 ({:?} blocks)", blocks.len()),
+            Self::Bc5RgU8(blocks) => write!(f, "BC5 RG u8 ({:?} blocks)", blocks.len()),
+            Self::Bc7RgbaU8(blocks) => write!(f, "BC7 RGBA u8 ({:?} blocks)", blocks.len()),
+            Self::Etc2RgbaU8(blocks) => write!(f, "ETC2 RGBA u8 ({:?} blocks)", blocks.len()),
+            Self::Astc4x4RgbaU8(blocks) => {
+                write!(f, "ASTC 4x4 RGBA u8 ({:?} blocks)", blocks.len())
+            }
         }
     }
 }
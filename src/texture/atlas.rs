@@ -0,0 +1,343 @@
+use crate::prelude::*;
+use crate::texture::texture2d::{texels_from_f32, texels_to_f32};
+#[doc(inline)]
+pub use crate::texture::{Texture2D, TextureDataKind};
+use std::collections::{HashMap, HashSet};
+
+///
+/// The placement of a texture in an [Atlas], both in texels and in uv coordinates of the atlas texture.
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AtlasRect {
+    /// The x coordinate in texels of the left edge of the texture in the atlas.
+    pub x: u32,
+    /// The y coordinate in texels of the top edge of the texture in the atlas.
+    pub y: u32,
+    /// The width in texels of the texture.
+    pub width: u32,
+    /// The height in texels of the texture.
+    pub height: u32,
+    /// The uv coordinates of the top left corner of the texture in the atlas.
+    pub uv_min: Vec2,
+    /// The uv coordinates of the bottom right corner of the texture in the atlas.
+    pub uv_max: Vec2,
+}
+
+///
+/// Combines many small textures into a single atlas texture, see [Atlas::pack].
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Atlas {
+    /// The number of texels between two textures in the atlas, which avoids bleeding between neighbouring textures when sampling with linear interpolation.
+    pub padding: u32,
+}
+
+impl Default for Atlas {
+    fn default() -> Self {
+        Self { padding: 1 }
+    }
+}
+
+impl Atlas {
+    ///
+    /// Packs the given named textures into one atlas texture with a width and height of at most `max_size`, using a skyline bin-packing algorithm.
+    /// Returns the atlas texture together with the placement of each of the textures in the atlas.
+    /// The textures are converted to a common format which has the largest number of channels and the highest precision among the textures, see [Texture2D::convert].
+    /// The padding and any unused area of the atlas is filled with zeros and mip levels are not generated.
+    /// Returns an error if the textures do not fit within `max_size` or if more than one texture has the same name.
+    ///
+    pub fn pack(
+        &self,
+        textures: &[(&str, Texture2D)],
+        max_size: u32,
+    ) -> crate::Result<(Texture2D, HashMap<String, AtlasRect>)> {
+        let mut names = HashSet::new();
+        if let Some((name, _)) = textures.iter().find(|(name, _)| !names.insert(*name)) {
+            Err(crate::Error::DuplicateAtlasName(name.to_string()))?;
+        }
+        let padding = self.padding;
+        let sizes = textures
+            .iter()
+            .map(|(_, t)| (t.width + padding, t.height + padding))
+            .collect::<Vec<_>>();
+        let mut order = (0..textures.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse((sizes[i].1, sizes[i].0)));
+
+        // Try increasing widths to get an atlas which is close to square
+        let min_width = sizes
+            .iter()
+            .map(|s| s.0 - padding)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let mut width = min_width.next_power_of_two().min(max_size);
+        let (width, height, positions) = loop {
+            // The padding is only needed between the textures, so the bin is extended by the padding
+            let packed = pack_skyline(&sizes, &order, width + padding, max_size + padding);
+            match packed {
+                Some((height, positions))
+                    if height.saturating_sub(padding) <= width || width == max_size =>
+                {
+                    break (width, height.saturating_sub(padding).max(1), positions);
+                }
+                _ if width < max_size => width = (width * 2).min(max_size),
+                _ => Err(crate::Error::AtlasOverflow(max_size))?,
+            }
+        };
+
+        let kind = common_kind(textures.iter().map(|(_, t)| t.data.kind()));
+        let channels = kind.channel_count();
+        let mut values = vec![0.0; width as usize * height as usize * channels];
+        let mut rects = HashMap::with_capacity(textures.len());
+        for ((name, texture), &(x, y)) in textures.iter().zip(&positions) {
+            let mut texture = texture.clone();
+            texture.convert(kind)?;
+            let (_, source) = texels_to_f32(&texture.data, false);
+            let row_length = texture.width as usize * channels;
+            for (row, texels) in source.chunks(row_length.max(1)).enumerate() {
+                let start = ((y as usize + row) * width as usize + x as usize) * channels;
+                values[start..start + row_length].copy_from_slice(texels);
+            }
+            rects.insert(
+                name.to_string(),
+                AtlasRect {
+                    x,
+                    y,
+                    width: texture.width,
+                    height: texture.height,
+                    uv_min: vec2(x as f32 / width as f32, y as f32 / height as f32),
+                    uv_max: vec2(
+                        (x + texture.width) as f32 / width as f32,
+                        (y + texture.height) as f32 / height as f32,
+                    ),
+                },
+            );
+        }
+        let first = textures.first().map(|(_, t)| t);
+        Ok((
+            Texture2D {
+                name: "atlas".to_owned(),
+                data: texels_from_f32(kind, channels, &values, false),
+                width,
+                height,
+                color_space: first.map(|t| t.color_space).unwrap_or_default(),
+                premultiplied_alpha: first.map(|t| t.premultiplied_alpha).unwrap_or(false),
+                ..Default::default()
+            },
+            rects,
+        ))
+    }
+}
+
+///
+/// Packs the rectangles with the given sizes in the given order into a bin with the given width using the bottom-left skyline heuristic.
+/// Returns the used height of the bin and the position of each rectangle or `None` if the rectangles do not fit within the given height.
+///
+fn pack_skyline(
+    sizes: &[(u32, u32)],
+    order: &[usize],
+    width: u32,
+    max_height: u32,
+) -> Option<(u32, Vec<(u32, u32)>)> {
+    // Each segment of the skyline is the x coordinate where it starts, its y coordinate and its width
+    let mut skyline = vec![(0u32, 0u32, width)];
+    let mut positions = vec![(0, 0); sizes.len()];
+    let mut height = 0;
+    for &i in order {
+        let (w, h) = sizes[i];
+        let mut best: Option<(u32, u32, usize)> = None;
+        for (start, &(x, _, _)) in skyline.iter().enumerate() {
+            if x + w > width {
+                break;
+            }
+            let y = skyline[start..]
+                .iter()
+                .take_while(|s| s.0 < x + w)
+                .map(|s| s.1)
+                .max()
+                .unwrap_or(0);
+            if y + h <= max_height && !matches!(best, Some((_, best_y, _)) if best_y <= y) {
+                best = Some((x, y, start));
+            }
+        }
+        let (x, y, start) = best?;
+        positions[i] = (x, y);
+        height = height.max(y + h);
+
+        // Replace the covered part of the skyline with the new segment
+        let end = x + w;
+        let mut segments = Vec::with_capacity(skyline.len() + 1);
+        segments.extend_from_slice(&skyline[..start]);
+        segments.push((x, y + h, w));
+        for &(sx, sy, sw) in &skyline[start..] {
+            if sx + sw > end {
+                let cut = end.max(sx);
+                segments.push((cut, sy, sx + sw - cut));
+            }
+        }
+        skyline.clear();
+        for segment in segments {
+            match skyline.last_mut() {
+                Some(last) if last.1 == segment.1 => last.2 += segment.2,
+                _ => skyline.push(segment),
+            }
+        }
+    }
+    Some((height, positions))
+}
+
+///
+/// Returns the uncompressed format with the largest number of channels and the highest precision among the given formats.
+///
+fn common_kind(kinds: impl Iterator<Item = TextureDataKind>) -> TextureDataKind {
+    use TextureDataKind::*;
+    let mut channels = 1;
+    let (mut is_u16, mut is_f16, mut is_f32) = (false, false, false);
+    for kind in kinds {
+        channels = channels.max(kind.channel_count());
+        match kind {
+            RU16 | RgU16 | RgbU16 | RgbaU16 => is_u16 = true,
+            RF16 | RgF16 | RgbF16 | RgbaF16 => is_f16 = true,
+            RF32 | RgF32 | RgbF32 | RgbaF32 => is_f32 = true,
+            _ => {}
+        }
+    }
+    let kinds = if is_f32 || (is_u16 && is_f16) {
+        [RF32, RgF32, RgbF32, RgbaF32]
+    } else if is_f16 {
+        [RF16, RgF16, RgbF16, RgbaF16]
+    } else if is_u16 {
+        [RU16, RgU16, RgbU16, RgbaU16]
+    } else {
+        [RU8, RgU8, RgbU8, RgbaU8]
+    };
+    kinds[channels - 1]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TextureData;
+
+    fn texture(width: u32, height: u32, data: TextureData) -> Texture2D {
+        Texture2D {
+            data,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    pub fn pack() {
+        let textures = [
+            ("a", texture(4, 4, TextureData::RU8(vec![1; 16]))),
+            (
+                "b",
+                texture(2, 2, TextureData::RgbaU8(vec![[2, 3, 4, 5]; 4])),
+            ),
+            ("c", texture(3, 2, TextureData::RU8(vec![6; 6]))),
+            ("d", texture(1, 5, TextureData::RU8(vec![7; 5]))),
+        ];
+        let (atlas, rects) = Atlas::default().pack(&textures, 16).unwrap();
+        assert_eq!(atlas.name, "atlas");
+        assert!(atlas.width <= 16 && atlas.height <= 16);
+        let TextureData::RgbaU8(texels) = &atlas.data else {
+            panic!("Wrong texture data: {:?}", atlas.data)
+        };
+        assert_eq!(texels.len(), (atlas.width * atlas.height) as usize);
+        assert_eq!(rects.len(), textures.len());
+
+        let mut covered = vec![None; texels.len()];
+        for (name, texture) in textures.iter() {
+            let rect = rects[*name];
+            assert_eq!((rect.width, rect.height), (texture.width, texture.height));
+            assert!(rect.x + rect.width <= atlas.width);
+            assert!(rect.y + rect.height <= atlas.height);
+            assert_eq!(
+                rect.uv_min,
+                vec2(
+                    rect.x as f32 / atlas.width as f32,
+                    rect.y as f32 / atlas.height as f32
+                )
+            );
+            assert_eq!(
+                rect.uv_max,
+                vec2(
+                    (rect.x + rect.width) as f32 / atlas.width as f32,
+                    (rect.y + rect.height) as f32 / atlas.height as f32
+                )
+            );
+            let mut converted = texture.clone();
+            converted.convert(TextureDataKind::RgbaU8).unwrap();
+            let TextureData::RgbaU8(expected) = converted.data else {
+                unreachable!()
+            };
+            // The texture including its padding to the right and below does not overlap other textures
+            for y in rect.y..(rect.y + rect.height + 1).min(atlas.height) {
+                for x in rect.x..(rect.x + rect.width + 1).min(atlas.width) {
+                    let index = (y * atlas.width + x) as usize;
+                    assert_eq!(covered[index], None, "{} overlaps at {}, {}", name, x, y);
+                    covered[index] = Some(*name);
+                    if x < rect.x + rect.width && y < rect.y + rect.height {
+                        let source = ((y - rect.y) * rect.width + x - rect.x) as usize;
+                        assert_eq!(texels[index], expected[source]);
+                    } else {
+                        assert_eq!(texels[index], [0; 4]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn pack_without_padding() {
+        let textures = [
+            ("a", texture(2, 2, TextureData::RU8(vec![1; 4]))),
+            ("b", texture(2, 2, TextureData::RU8(vec![2; 4]))),
+            ("c", texture(2, 2, TextureData::RU8(vec![3; 4]))),
+            ("d", texture(2, 2, TextureData::RU8(vec![4; 4]))),
+        ];
+        let (atlas, rects) = Atlas { padding: 0 }.pack(&textures, 4).unwrap();
+        assert_eq!((atlas.width, atlas.height), (4, 4));
+        let mut corners = rects.values().map(|r| (r.x, r.y)).collect::<Vec<_>>();
+        corners.sort();
+        assert_eq!(corners, vec![(0, 0), (0, 2), (2, 0), (2, 2)]);
+        assert_eq!(rects["a"].uv_max - rects["a"].uv_min, vec2(0.5, 0.5));
+        let TextureData::RU8(texels) = &atlas.data else {
+            panic!("Wrong texture data: {:?}", atlas.data)
+        };
+        assert!(texels.iter().all(|&t| t > 0));
+    }
+
+    #[test]
+    pub fn pack_overflow() {
+        let textures = [("a", texture(8, 8, TextureData::RU8(vec![1; 64])))];
+        assert!(matches!(
+            Atlas::default().pack(&textures, 4),
+            Err(crate::Error::AtlasOverflow(4))
+        ));
+        // The padding between the textures is also needed
+        let textures = [
+            ("a", texture(2, 4, TextureData::RU8(vec![1; 8]))),
+            ("b", texture(2, 4, TextureData::RU8(vec![2; 8]))),
+        ];
+        assert!(Atlas { padding: 0 }.pack(&textures, 4).is_ok());
+        assert!(matches!(
+            Atlas::default().pack(&textures, 4),
+            Err(crate::Error::AtlasOverflow(4))
+        ));
+    }
+
+    #[test]
+    pub fn pack_duplicate_names() {
+        let textures = [
+            ("a", texture(1, 1, TextureData::RU8(vec![1]))),
+            ("a", texture(1, 1, TextureData::RU8(vec![2]))),
+        ];
+        assert!(matches!(
+            Atlas::default().pack(&textures, 4),
+            Err(crate::Error::DuplicateAtlasName(name)) if name == "a"
+        ));
+    }
+}
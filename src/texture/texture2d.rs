@@ -1,5 +1,6 @@
+use crate::prelude::*;
 #[doc(inline)]
-pub use crate::texture::{Interpolation, TextureData, Wrapping};
+pub use crate::texture::{ColorSpace, Interpolation, TextureData, TextureDataKind, Wrapping};
 
 ///
 /// A CPU-side version of a 2D texture.
@@ -14,6 +15,10 @@ pub struct Texture2D {
     pub width: u32,
     /// The height of the image
     pub height: u32,
+    /// The color space of the color channels in [Texture2D::data] and [Texture2D::mip_levels], see [Texture2D::to_linear] and [Texture2D::to_srgb].
+    pub color_space: ColorSpace,
+    /// Whether or not the color channels in [Texture2D::data] and [Texture2D::mip_levels] are multiplied by the alpha channel, see [Texture2D::premultiply_alpha] and [Texture2D::unpremultiply_alpha].
+    pub premultiplied_alpha: bool,
     /// The way the pixel data is interpolated when the texture is far away
     pub min_filter: Interpolation,
     /// The way the pixel data is interpolated when the texture is close
@@ -25,6 +30,9 @@ pub struct Texture2D {
     pub wrap_s: Wrapping,
     /// Determines how the texture is sampled outside the [0..1] t coordinate range (the second value of the uv coordinates).
     pub wrap_t: Wrapping,
+    /// Precomputed mip levels following the base level in [Texture2D::data], each with half the width and height (rounded down, but at least 1) of the previous level.
+    /// If empty, the mip levels have to be generated from the base level, see [Texture2D::mip_map_filter].
+    pub mip_levels: Vec<TextureData>,
 }
 
 impl Default for Texture2D {
@@ -34,11 +42,2286 @@ impl Default for Texture2D {
             data: TextureData::RgbaU8(vec![[0, 0, 0, 0]]),
             width: 1,
             height: 1,
+            color_space: ColorSpace::Srgb,
+            premultiplied_alpha: false,
             min_filter: Interpolation::Linear,
             mag_filter: Interpolation::Linear,
             mip_map_filter: Some(Interpolation::Linear),
             wrap_s: Wrapping::Repeat,
             wrap_t: Wrapping::Repeat,
+            mip_levels: Vec::new(),
         }
     }
 }
+
+impl Texture2D {
+    ///
+    /// Decompresses block compressed texture data, including all mip levels, see [TextureData::decompress].
+    /// Does nothing if the data is not compressed.
+    ///
+    pub fn decompress(&mut self) -> crate::Result<()> {
+        let (width, height) = (self.width, self.height);
+        self.data = self.data.decompress(width, height)?;
+        for (i, level) in self.mip_levels.iter_mut().enumerate() {
+            let shift = i as u32 + 1;
+            *level = level.decompress((width >> shift).max(1), (height >> shift).max(1))?;
+        }
+        Ok(())
+    }
+}
+
+impl Texture2D {
+    ///
+    /// Generates the full chain of mip levels down to a single texel, with the base level at index 0, see [Texture2D::mip_levels].
+    /// Each level is downsampled from the previous level using the given filter, ie. [Interpolation::Nearest] picks a single texel,
+    /// [Interpolation::Linear] averages 2x2 texels (box filter) and [Interpolation::CubicSpline] uses a 4x4 triangle filter.
+    /// All levels are uncompressed and stored in the same [TextureData] variant, so block compressed data is decompressed first.
+    ///
+    /// If `is_srgb` is true, the color channels of 8 and 16 bit data are converted to linear space before downsampling and back to sRGB afterwards.
+    /// The alpha channel, ie. the last channel of two and four channel data, and floating point data are always assumed to be linear.
+    ///
+    pub fn generate_mipmaps(
+        &self,
+        filter: Interpolation,
+        is_srgb: bool,
+    ) -> crate::Result<Vec<TextureData>> {
+        let data = self.data.decompress(self.width, self.height)?;
+        let (channels, mut values) = texels_to_f32(&data, is_srgb);
+        let (mut width, mut height) = (self.width as usize, self.height as usize);
+        let mut levels = vec![data];
+        while width > 1 || height > 1 {
+            let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+            let taps_x: Vec<_> = (0..next_width).map(|x| taps(x, width, filter)).collect();
+            let taps_y: Vec<_> = (0..next_height).map(|y| taps(y, height, filter)).collect();
+            let mut next = vec![0.0; next_width * next_height * channels];
+            for (y, taps_y) in taps_y.iter().enumerate() {
+                for (x, taps_x) in taps_x.iter().enumerate() {
+                    let target = (y * next_width + x) * channels;
+                    for &(sy, wy) in taps_y {
+                        for &(sx, wx) in taps_x {
+                            let source = (sy * width + sx) * channels;
+                            for c in 0..channels {
+                                next[target + c] += wx * wy * values[source + c];
+                            }
+                        }
+                    }
+                }
+            }
+            levels.push(texels_from_f32(levels[0].kind(), channels, &next, is_srgb));
+            (values, width, height) = (next, next_width, next_height);
+        }
+        Ok(levels)
+    }
+
+    ///
+    /// Resizes the texture in place to the given width and height using the given filter. Returns an error if the width or height is 0.
+    /// The aspect ratio is not preserved, unless the given width and height has the same ratio as the current width and height.
+    /// Block compressed data is decompressed first and any precomputed [Texture2D::mip_levels] are removed since they no longer match.
+    ///
+    /// If `is_srgb` is true, the color channels of 8 and 16 bit data are converted to linear space before resampling and back to sRGB afterwards.
+    /// The alpha channel, ie. the last channel of two and four channel data, and floating point data are always assumed to be linear.
+    ///
+    pub fn resize(
+        &mut self,
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+        is_srgb: bool,
+    ) -> crate::Result<()> {
+        if width == 0 || height == 0 {
+            Err(crate::Error::InvalidTextureSize(width, height))?;
+        }
+        let data = self.data.decompress(self.width, self.height)?;
+        let (channels, values) = texels_to_f32(&data, is_srgb);
+        let (old_width, old_height) = (self.width as usize, self.height as usize);
+        let (new_width, new_height) = (width as usize, height as usize);
+
+        let taps_x = resample_taps(old_width, new_width, filter);
+        let mut horizontal = vec![0.0; new_width * old_height * channels];
+        for y in 0..old_height {
+            for (x, taps) in taps_x.iter().enumerate() {
+                let target = (y * new_width + x) * channels;
+                for &(sx, w) in taps {
+                    let source = (y * old_width + sx) * channels;
+                    for c in 0..channels {
+                        horizontal[target + c] += w * values[source + c];
+                    }
+                }
+            }
+        }
+        let taps_y = resample_taps(old_height, new_height, filter);
+        let mut resized = vec![0.0; new_width * new_height * channels];
+        for (y, taps) in taps_y.iter().enumerate() {
+            for x in 0..new_width {
+                let target = (y * new_width + x) * channels;
+                for &(sy, w) in taps {
+                    let source = (sy * new_width + x) * channels;
+                    for c in 0..channels {
+                        resized[target + c] += w * horizontal[source + c];
+                    }
+                }
+            }
+        }
+
+        self.data = texels_from_f32(data.kind(), channels, &resized, is_srgb);
+        self.width = width;
+        self.height = height;
+        self.mip_levels.clear();
+        Ok(())
+    }
+
+    ///
+    /// Flips the texture upside down by reversing the order of the rows of texels, including all mip levels.
+    /// Flipping twice results in the original texture.
+    /// Returns an error if the data is block compressed.
+    ///
+    pub fn flip_vertical(&mut self) -> crate::Result<()> {
+        self.flip(true)
+    }
+
+    ///
+    /// Mirrors the texture by reversing the order of the texels in each row, including all mip levels.
+    /// Flipping twice results in the original texture.
+    /// Returns an error if the data is block compressed.
+    ///
+    pub fn flip_horizontal(&mut self) -> crate::Result<()> {
+        self.flip(false)
+    }
+
+    ///
+    /// Rotates the texture clockwise by the given multiple of 90 degrees, including all mip levels, by moving the texels without any resampling.
+    /// The width and height are swapped when rotating by 90 or 270 degrees, so rotating four times by 90 degrees results in the original texture.
+    /// Returns an error if the data is block compressed.
+    ///
+    pub fn rotate(&mut self, rotation: TextureRotation) -> crate::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        self.data = rotate_data(&self.data, width, height, rotation)?;
+        for (i, level) in self.mip_levels.iter_mut().enumerate() {
+            let shift = i + 1;
+            *level = rotate_data(
+                level,
+                (width >> shift).max(1),
+                (height >> shift).max(1),
+                rotation,
+            )?;
+        }
+        if rotation != TextureRotation::Cw180 {
+            std::mem::swap(&mut self.width, &mut self.height);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns a new texture containing the given rectangle of this texture, where `x` and `y` is the position of the top left corner in texels.
+    /// The name, filters and wrapping modes are copied to the new texture, while the mip levels are not.
+    /// Block compressed data is decompressed first.
+    /// Returns an error if the rectangle is empty or not fully inside the texture.
+    ///
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> crate::Result<Texture2D> {
+        if width == 0
+            || height == 0
+            || x as u64 + width as u64 > self.width as u64
+            || y as u64 + height as u64 > self.height as u64
+        {
+            Err(crate::Error::RegionOutOfBounds(
+                x,
+                y,
+                width,
+                height,
+                self.width,
+                self.height,
+            ))?;
+        }
+        fn crop<T: Copy>(
+            values: &[T],
+            stride: usize,
+            (x, y, width, height): (usize, usize, usize, usize),
+        ) -> Vec<T> {
+            let mut cropped = Vec::with_capacity(width * height);
+            for row in values.chunks(stride).skip(y).take(height) {
+                cropped.extend_from_slice(&row[x..x + width]);
+            }
+            cropped
+        }
+        let stride = self.width as usize;
+        let region = (x as usize, y as usize, width as usize, height as usize);
+        let data = match self.data.decompress(self.width, self.height)? {
+            TextureData::RU8(values) => TextureData::RU8(crop(&values, stride, region)),
+            TextureData::RgU8(values) => TextureData::RgU8(crop(&values, stride, region)),
+            TextureData::RgbU8(values) => TextureData::RgbU8(crop(&values, stride, region)),
+            TextureData::RgbaU8(values) => TextureData::RgbaU8(crop(&values, stride, region)),
+            TextureData::RU16(values) => TextureData::RU16(crop(&values, stride, region)),
+            TextureData::RgU16(values) => TextureData::RgU16(crop(&values, stride, region)),
+            TextureData::RgbU16(values) => TextureData::RgbU16(crop(&values, stride, region)),
+            TextureData::RgbaU16(values) => TextureData::RgbaU16(crop(&values, stride, region)),
+            TextureData::RF16(values) => TextureData::RF16(crop(&values, stride, region)),
+            TextureData::RgF16(values) => TextureData::RgF16(crop(&values, stride, region)),
+            TextureData::RgbF16(values) => TextureData::RgbF16(crop(&values, stride, region)),
+            TextureData::RgbaF16(values) => TextureData::RgbaF16(crop(&values, stride, region)),
+            TextureData::RF32(values) => TextureData::RF32(crop(&values, stride, region)),
+            TextureData::RgF32(values) => TextureData::RgF32(crop(&values, stride, region)),
+            TextureData::RgbF32(values) => TextureData::RgbF32(crop(&values, stride, region)),
+            TextureData::RgbaF32(values) => TextureData::RgbaF32(crop(&values, stride, region)),
+            _ => unreachable!("the texture data is decompressed"),
+        };
+        Ok(Texture2D {
+            name: self.name.clone(),
+            data,
+            width,
+            height,
+            color_space: self.color_space,
+            premultiplied_alpha: self.premultiplied_alpha,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+            mip_levels: Vec::new(),
+        })
+    }
+
+    ///
+    /// Generates a tangent-space normal map from this texture, treating the red channel of one and two channel data and the luminance of three and four channel data as height.
+    /// The gradients are computed using a Sobel filter where texels outside the texture are clamped to the edge.
+    /// The strength scales the slope, such that a strength of one means that a height difference of one between two neighbouring texels corresponds to a 45 degree slope.
+    /// The resulting [TextureData::RgbU8] normals are encoded in the range `[0..1]` with the green channel pointing up in the image, and the mip levels are not generated.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn normal_map_from_height(&self, strength: f32) -> crate::Result<Texture2D> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (channels, values) =
+            texels_to_f32(&self.data.decompress(self.width, self.height)?, false);
+        let heights = values
+            .chunks(channels)
+            .map(|t| {
+                if channels < 3 {
+                    t[0]
+                } else {
+                    0.2126 * t[0] + 0.7152 * t[1] + 0.0722 * t[2]
+                }
+            })
+            .collect::<Vec<_>>();
+        let sample = |x: usize, y: usize, dx: isize, dy: isize| {
+            let x = x.saturating_add_signed(dx).min(width - 1);
+            let y = y.saturating_add_signed(dy).min(height - 1);
+            heights[y * width + x]
+        };
+        let mut normals = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                // The differences are taken before summing, so a constant height results in exactly zero gradients
+                let gradient_x = ((sample(x, y, 1, -1) - sample(x, y, -1, -1))
+                    + 2.0 * (sample(x, y, 1, 0) - sample(x, y, -1, 0))
+                    + (sample(x, y, 1, 1) - sample(x, y, -1, 1)))
+                    / 8.0;
+                let gradient_y = ((sample(x, y, -1, 1) - sample(x, y, -1, -1))
+                    + 2.0 * (sample(x, y, 0, 1) - sample(x, y, 0, -1))
+                    + (sample(x, y, 1, 1) - sample(x, y, 1, -1)))
+                    / 8.0;
+                // The image y axis points down while the tangent-space y axis points up
+                let normal = vec3(-gradient_x * strength, gradient_y * strength, 1.0).normalize();
+                normals.push(
+                    [normal.x, normal.y, normal.z].map(|v| ((v * 0.5 + 0.5) * 255.0).round() as u8),
+                );
+            }
+        }
+        Ok(Texture2D {
+            name: self.name.clone(),
+            data: TextureData::RgbU8(normals),
+            width: self.width,
+            height: self.height,
+            color_space: ColorSpace::Linear,
+            premultiplied_alpha: false,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+            mip_levels: Vec::new(),
+        })
+    }
+
+    ///
+    /// Packs separate occlusion, roughness and metallic textures into the red, green and blue channel of one [TextureData::RgbU8] texture in linear space,
+    /// which is the layout expected by glTF, see [PbrMaterial::occlusion_metallic_roughness_texture](crate::PbrMaterial::occlusion_metallic_roughness_texture).
+    /// The red channel of each of the given textures is used and resized to the given width and height using [ResizeFilter::Triangle] if needed.
+    /// Missing textures are filled with full occlusion (no shadowing), full roughness and zero metallic respectively.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn pack_orm(
+        occlusion: Option<&Texture2D>,
+        roughness: Option<&Texture2D>,
+        metallic: Option<&Texture2D>,
+        width: u32,
+        height: u32,
+    ) -> crate::Result<Texture2D> {
+        let channel = |texture: Option<&Texture2D>, default: u8| -> crate::Result<Vec<u8>> {
+            let Some(texture) = texture else {
+                return Ok(vec![default; width as usize * height as usize]);
+            };
+            let data = if texture.width != width || texture.height != height {
+                let mut texture = texture.clone();
+                texture.resize(width, height, ResizeFilter::Triangle, false)?;
+                texture.data
+            } else {
+                texture.data.decompress(width, height)?
+            };
+            let (channels, values) = texels_to_f32(&data, false);
+            Ok(values
+                .iter()
+                .step_by(channels)
+                .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+                .collect())
+        };
+        let occlusion = channel(occlusion, 255)?;
+        let roughness = channel(roughness, 255)?;
+        let metallic = channel(metallic, 0)?;
+        Ok(Texture2D {
+            name: "orm".to_owned(),
+            data: TextureData::RgbU8(
+                occlusion
+                    .into_iter()
+                    .zip(roughness)
+                    .zip(metallic)
+                    .map(|((o, r), m)| [o, r, m])
+                    .collect(),
+            ),
+            width,
+            height,
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        })
+    }
+
+    ///
+    /// Samples the texture at the given uv coordinates using bilinear interpolation between the four closest texels, where the coordinates outside the `[0..1]` range are handled according to [Texture2D::wrap_s] and [Texture2D::wrap_t].
+    /// Returns the red, green, blue and alpha values where integer values are normalized to the range `[0..1]` while missing red, green and blue channels are zero and a missing alpha channel is one.
+    /// The values are not converted from sRGB to linear space and the mip levels are not used.
+    /// An empty texture, ie. with zero width or height, samples as transparent black.
+    /// Panics if the data is block compressed, see [Texture2D::decompress].
+    ///
+    pub fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let [a, b, c, d] = [(x0, y0), (x0 + 1, y0), (x0, y0 + 1), (x0 + 1, y0 + 1)]
+            .map(|(x, y)| self.wrapped_texel(x, y));
+        std::array::from_fn(|i| {
+            let top = a[i] + (b[i] - a[i]) * tx;
+            let bottom = c[i] + (d[i] - c[i]) * tx;
+            top + (bottom - top) * ty
+        })
+    }
+
+    ///
+    /// Samples the texture at the given uv coordinates using the closest texel, otherwise the same as [Texture2D::sample].
+    /// Panics if the data is block compressed, see [Texture2D::decompress].
+    ///
+    pub fn sample_nearest(&self, u: f32, v: f32) -> [f32; 4] {
+        let x = (u * self.width as f32).floor() as i64;
+        let y = (v * self.height as f32).floor() as i64;
+        self.wrapped_texel(x, y)
+    }
+
+    fn wrapped_texel(&self, x: i64, y: i64) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+        let wrap = |i: i64, size: u32, wrapping: Wrapping| {
+            let size = size as i64;
+            (match wrapping {
+                Wrapping::Repeat => i.rem_euclid(size),
+                Wrapping::ClampToEdge => i.clamp(0, size - 1),
+                Wrapping::MirroredRepeat => {
+                    let i = i.rem_euclid(2 * size);
+                    if i < size {
+                        i
+                    } else {
+                        2 * size - 1 - i
+                    }
+                }
+            }) as usize
+        };
+        let index = wrap(y, self.height, self.wrap_t) * self.width as usize
+            + wrap(x, self.width, self.wrap_s);
+        fn texel<T: Copy, const N: usize>(
+            values: &[[T; N]],
+            index: usize,
+            f: impl Fn(T) -> f32,
+        ) -> [f32; 4] {
+            let mut texel = [0.0, 0.0, 0.0, 1.0];
+            for (c, value) in values[index].into_iter().enumerate() {
+                texel[c] = f(value);
+            }
+            texel
+        }
+        let u8 = |v: u8| v as f32 / 255.0;
+        let u16 = |v: u16| v as f32 / 65535.0;
+        let f16 = |v: f16| v.to_f32();
+        let f32 = |v: f32| v;
+        match &self.data {
+            TextureData::RU8(values) => [u8(values[index]), 0.0, 0.0, 1.0],
+            TextureData::RgU8(values) => texel(values, index, u8),
+            TextureData::RgbU8(values) => texel(values, index, u8),
+            TextureData::RgbaU8(values) => texel(values, index, u8),
+            TextureData::RU16(values) => [u16(values[index]), 0.0, 0.0, 1.0],
+            TextureData::RgU16(values) => texel(values, index, u16),
+            TextureData::RgbU16(values) => texel(values, index, u16),
+            TextureData::RgbaU16(values) => texel(values, index, u16),
+            TextureData::RF16(values) => [f16(values[index]), 0.0, 0.0, 1.0],
+            TextureData::RgF16(values) => texel(values, index, f16),
+            TextureData::RgbF16(values) => texel(values, index, f16),
+            TextureData::RgbaF16(values) => texel(values, index, f16),
+            TextureData::RF32(values) => [values[index], 0.0, 0.0, 1.0],
+            TextureData::RgF32(values) => texel(values, index, f32),
+            TextureData::RgbF32(values) => texel(values, index, f32),
+            TextureData::RgbaF32(values) => texel(values, index, f32),
+            _ => panic!(
+                "cannot sample {:?} texture data, it has to be decompressed first",
+                self.data.kind()
+            ),
+        }
+    }
+
+    ///
+    /// Returns the average color of all texels in the base level.
+    /// The average is computed in linear space, so if [Texture2D::color_space] is [ColorSpace::Srgb], the color channels are converted to linear space before averaging and the result is converted back to sRGB.
+    /// One and two channel data is treated as luminance and luminance with alpha respectively, and a missing alpha channel is fully opaque.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn average_color(&self) -> crate::Result<Color> {
+        let texels = self.linear_texels()?;
+        let mut sum = [0.0f64; 4];
+        for texel in texels.iter() {
+            for c in 0..4 {
+                sum[c] += texel[c] as f64;
+            }
+        }
+        let count = texels.len().max(1) as f64;
+        Ok(self.color_from_linear(sum.map(|v| (v / count) as f32)))
+    }
+
+    ///
+    /// Returns the most common color of the base level, ignoring fully transparent texels.
+    /// The colors are grouped in a histogram with 16 levels for each of the red, green and blue channels and the result is the average color, see [Texture2D::average_color], of the texels in the largest group.
+    /// Returns the average color of all texels if all texels are fully transparent.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn dominant_color(&self) -> crate::Result<Color> {
+        let texels = self.linear_texels()?;
+        let bins = texels
+            .iter()
+            .map(|texel| {
+                (texel[3] > 0.0).then(|| {
+                    let color = self.color_from_linear(*texel);
+                    (color.r as usize >> 4) << 8
+                        | (color.g as usize >> 4) << 4
+                        | color.b as usize >> 4
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut histogram = vec![0usize; 4096];
+        for bin in bins.iter().flatten() {
+            histogram[*bin] += 1;
+        }
+        let Some(largest) = (0..histogram.len())
+            .filter(|&i| histogram[i] > 0)
+            .max_by_key(|&i| (histogram[i], std::cmp::Reverse(i)))
+        else {
+            return self.average_color();
+        };
+        let mut sum = [0.0f64; 4];
+        for (texel, _) in texels
+            .iter()
+            .zip(&bins)
+            .filter(|(_, bin)| **bin == Some(largest))
+        {
+            for c in 0..4 {
+                sum[c] += texel[c] as f64;
+            }
+        }
+        let count = histogram[largest] as f64;
+        Ok(self.color_from_linear(sum.map(|v| (v / count) as f32)))
+    }
+
+    ///
+    /// Returns a histogram with the given number of bins for each of the red, green, blue and alpha channels of the base level, where each bin contains the number of texels with a value in that bin.
+    /// Integer values are normalized to the range `[0..1]` and floating point values are clamped to that range before they are divided into bins of equal size.
+    /// The values are not converted from sRGB to linear space and the histograms of channels which are not present in the data, for example the alpha channel of [TextureData::RgbU8], are all zeros.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn histogram(&self, bins: usize) -> crate::Result<[Vec<u32>; 4]> {
+        let mut histogram = std::array::from_fn(|_| vec![0; bins]);
+        if bins == 0 {
+            return Ok(histogram);
+        }
+        let (channels, values) =
+            texels_to_f32(&self.data.decompress(self.width, self.height)?, false);
+        for texel in values.chunks(channels) {
+            for (c, value) in texel.iter().enumerate() {
+                let bin = (value.clamp(0.0, 1.0) * bins as f32) as usize;
+                histogram[c][bin.min(bins - 1)] += 1;
+            }
+        }
+        Ok(histogram)
+    }
+
+    ///
+    /// Returns a single channel copy of the texture containing the luminance computed with the Rec. 709 weights in linear space,
+    /// so if [Texture2D::color_space] is [ColorSpace::Srgb], integer data is converted to linear space before computing the luminance and the result is converted back to sRGB.
+    /// The data has the same precision as this texture, ie. [TextureData::RU8], [TextureData::RU16], [TextureData::RF16] or [TextureData::RF32].
+    /// If `keep_alpha` is true and the texture has an alpha channel, the alpha channel is kept as the second channel, for example resulting in [TextureData::RgU8].
+    /// One and two channel data is treated as luminance and luminance with alpha respectively and the mip levels are not copied.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn to_grayscale(&self, keep_alpha: bool) -> crate::Result<Texture2D> {
+        use TextureDataKind::*;
+        let data = self.data.decompress(self.width, self.height)?;
+        let is_srgb = self.color_space == ColorSpace::Srgb;
+        let (channels, values) = texels_to_f32(&data, is_srgb);
+        let has_alpha = keep_alpha && (channels == 2 || channels == 4);
+        let target_channels = if has_alpha { 2 } else { 1 };
+        let mut luminance = Vec::with_capacity(values.len() / channels * target_channels);
+        for texel in values.chunks(channels) {
+            luminance.push(if channels < 3 {
+                texel[0]
+            } else {
+                0.2126 * texel[0] + 0.7152 * texel[1] + 0.0722 * texel[2]
+            });
+            if has_alpha {
+                luminance.push(texel[channels - 1]);
+            }
+        }
+        let kinds = match data.kind() {
+            RU16 | RgU16 | RgbU16 | RgbaU16 => [RU16, RgU16],
+            RF16 | RgF16 | RgbF16 | RgbaF16 => [RF16, RgF16],
+            RF32 | RgF32 | RgbF32 | RgbaF32 => [RF32, RgF32],
+            _ => [RU8, RgU8],
+        };
+        Ok(Texture2D {
+            name: self.name.clone(),
+            data: texels_from_f32(
+                kinds[target_channels - 1],
+                target_channels,
+                &luminance,
+                is_srgb,
+            ),
+            width: self.width,
+            height: self.height,
+            color_space: self.color_space,
+            premultiplied_alpha: self.premultiplied_alpha && has_alpha,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+            mip_levels: Vec::new(),
+        })
+    }
+
+    ///
+    /// Returns a low dynamic range copy of the texture, where the color channels are mapped from linear high dynamic range values to the range `[0..1]` using the given operator and then sRGB encoded,
+    /// so the data is [TextureData::RgbU8], or [TextureData::RgbaU8] if the texture has an alpha channel, and the color space is [ColorSpace::Srgb].
+    /// One and two channel data is treated as luminance and luminance with alpha respectively and integer data in the [ColorSpace::Srgb] color space is converted to linear space before tone mapping.
+    /// The alpha channel is copied unchanged and the mip levels are not copied.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn tonemap(&self, operator: ToneMap) -> crate::Result<Texture2D> {
+        let (channels, values) = texels_to_f32(
+            &self.data.decompress(self.width, self.height)?,
+            self.color_space == ColorSpace::Srgb,
+        );
+        let has_alpha = channels == 2 || channels == 4;
+        let target_channels = if has_alpha { 4 } else { 3 };
+        let mut mapped = Vec::with_capacity(values.len() / channels * target_channels);
+        for texel in values.chunks(channels) {
+            let color = if channels < 3 {
+                [texel[0]; 3]
+            } else {
+                [texel[0], texel[1], texel[2]]
+            };
+            mapped.extend(color.map(|value| operator.apply(value)));
+            if has_alpha {
+                mapped.push(texel[channels - 1]);
+            }
+        }
+        let kind = if has_alpha {
+            TextureDataKind::RgbaU8
+        } else {
+            TextureDataKind::RgbU8
+        };
+        Ok(Texture2D {
+            name: self.name.clone(),
+            data: texels_from_f32(kind, target_channels, &mapped, true),
+            width: self.width,
+            height: self.height,
+            color_space: ColorSpace::Srgb,
+            premultiplied_alpha: self.premultiplied_alpha,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+            mip_levels: Vec::new(),
+        })
+    }
+
+    fn linear_texels(&self) -> crate::Result<Vec<[f32; 4]>> {
+        let (channels, values) =
+            texels_to_f32(&self.data.decompress(self.width, self.height)?, false);
+        Ok(values
+            .chunks(channels)
+            .map(|t| {
+                let mut texel = match channels {
+                    1 => [t[0], t[0], t[0], 1.0],
+                    2 => [t[0], t[0], t[0], t[1]],
+                    3 => [t[0], t[1], t[2], 1.0],
+                    _ => [t[0], t[1], t[2], t[3]],
+                };
+                if self.color_space == ColorSpace::Srgb {
+                    for value in texel[..3].iter_mut() {
+                        *value = srgb_to_linear(*value);
+                    }
+                }
+                texel
+            })
+            .collect())
+    }
+
+    fn color_from_linear(&self, mut texel: [f32; 4]) -> Color {
+        if self.color_space == ColorSpace::Srgb {
+            for value in texel[..3].iter_mut() {
+                *value = linear_to_srgb(*value);
+            }
+        }
+        let [r, g, b, a] = texel.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+        Color::new(r, g, b, a)
+    }
+
+    ///
+    /// Converts the data, including all mip levels, into the given format.
+    /// Integer values are normalized to the range `[0..1]` when converted to floating point values and floating point values are clamped to `[0..1]` when converted to integer values.
+    /// Two channel data is treated as luminance and alpha, so the channels are converted as follows:
+    /// - An added alpha channel is set to fully opaque.
+    /// - An added green and blue channel is set to the same value as the red (luminance) channel.
+    /// - Reducing red, green and blue to one channel results in the luminance computed with the Rec. 709 weights.
+    /// - A removed alpha channel is discarded.
+    ///
+    /// Block compressed data is decompressed first. Returns an error if the target format is block compressed.
+    ///
+    pub fn convert(&mut self, kind: TextureDataKind) -> crate::Result<()> {
+        let (width, height) = (self.width, self.height);
+        self.data = convert_data(&self.data, width, height, kind)?;
+        for (i, level) in self.mip_levels.iter_mut().enumerate() {
+            let shift = i as u32 + 1;
+            *level = convert_data(
+                level,
+                (width >> shift).max(1),
+                (height >> shift).max(1),
+                kind,
+            )?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Rearranges the channels of the data, including all mip levels, where each of the four channels in the result is copied from the given channel of the original data.
+    /// The result always has a red, green, blue and alpha channel with the same precision as the original data, for example [TextureData::RgbU8] becomes [TextureData::RgbaU8].
+    /// Missing red, green and blue channels in the original data read as zero while a missing alpha channel reads as one.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn swizzle(&mut self, mapping: [Channel; 4]) -> crate::Result<()> {
+        let (width, height) = (self.width, self.height);
+        self.data = swizzle_data(&self.data.decompress(width, height)?, mapping);
+        for (i, level) in self.mip_levels.iter_mut().enumerate() {
+            let shift = i as u32 + 1;
+            let level_data = level.decompress((width >> shift).max(1), (height >> shift).max(1))?;
+            *level = swizzle_data(&level_data, mapping);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Converts the color channels, including all mip levels, from sRGB to linear space if [Texture2D::color_space] is [ColorSpace::Srgb] and does nothing otherwise.
+    /// Note that 8 bit data loses precision in the dark range when converted to linear space.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn to_linear(&mut self) -> crate::Result<()> {
+        self.transfer(ColorSpace::Linear, srgb_to_linear)
+    }
+
+    ///
+    /// Converts the color channels, including all mip levels, from linear to sRGB space if [Texture2D::color_space] is [ColorSpace::Linear] and does nothing otherwise.
+    /// Block compressed data is decompressed first.
+    ///
+    pub fn to_srgb(&mut self) -> crate::Result<()> {
+        self.transfer(ColorSpace::Srgb, linear_to_srgb)
+    }
+
+    fn transfer(&mut self, color_space: ColorSpace, function: fn(f32) -> f32) -> crate::Result<()> {
+        if self.color_space == color_space {
+            return Ok(());
+        }
+        let transfer =
+            |data: &TextureData, width: u32, height: u32| -> crate::Result<TextureData> {
+                let data = data.decompress(width, height)?;
+                let (channels, mut values) = texels_to_f32(&data, false);
+                let has_alpha = channels == 2 || channels == 4;
+                for (i, value) in values.iter_mut().enumerate() {
+                    if !has_alpha || i % channels != channels - 1 {
+                        *value = function(*value);
+                    }
+                }
+                Ok(texels_from_f32(data.kind(), channels, &values, false))
+            };
+        let (width, height) = (self.width, self.height);
+        self.data = transfer(&self.data, width, height)?;
+        for (i, level) in self.mip_levels.iter_mut().enumerate() {
+            let shift = i as u32 + 1;
+            *level = transfer(level, (width >> shift).max(1), (height >> shift).max(1))?;
+        }
+        self.color_space = color_space;
+        Ok(())
+    }
+
+    ///
+    /// Multiplies the color channels by the alpha channel, including all mip levels, if [Texture2D::premultiplied_alpha] is false and does nothing otherwise.
+    /// Premultiplied alpha avoids dark halos at transparent edges when blending and generating mip levels.
+    /// Block compressed data is decompressed first.
+    /// Returns an error if the data does not have a red, green, blue and alpha channel.
+    ///
+    pub fn premultiply_alpha(&mut self) -> crate::Result<()> {
+        self.multiply_alpha(true)
+    }
+
+    ///
+    /// Divides the color channels by the alpha channel, including all mip levels, if [Texture2D::premultiplied_alpha] is true and does nothing otherwise.
+    /// Texels with zero alpha get zero color channels.
+    /// Block compressed data is decompressed first.
+    /// Returns an error if the data does not have a red, green, blue and alpha channel.
+    ///
+    pub fn unpremultiply_alpha(&mut self) -> crate::Result<()> {
+        self.multiply_alpha(false)
+    }
+
+    fn multiply_alpha(&mut self, premultiply: bool) -> crate::Result<()> {
+        if self.premultiplied_alpha == premultiply {
+            return Ok(());
+        }
+        let (width, height) = (self.width, self.height);
+        let mut data = self.data.decompress(width, height)?;
+        multiply_alpha_data(&mut data, premultiply)?;
+        let mut mip_levels = Vec::with_capacity(self.mip_levels.len());
+        for (i, level) in self.mip_levels.iter().enumerate() {
+            let shift = i as u32 + 1;
+            let mut level = level.decompress((width >> shift).max(1), (height >> shift).max(1))?;
+            multiply_alpha_data(&mut level, premultiply)?;
+            mip_levels.push(level);
+        }
+        self.data = data;
+        self.mip_levels = mip_levels;
+        self.premultiplied_alpha = premultiply;
+        Ok(())
+    }
+
+    fn flip(&mut self, vertical: bool) -> crate::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        flip_data(&mut self.data, width, height, vertical)?;
+        for (i, level) in self.mip_levels.iter_mut().enumerate() {
+            let shift = i + 1;
+            flip_data(
+                level,
+                (width >> shift).max(1),
+                (height >> shift).max(1),
+                vertical,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn convert_data(
+    data: &TextureData,
+    width: u32,
+    height: u32,
+    kind: TextureDataKind,
+) -> crate::Result<TextureData> {
+    if data.kind() == kind {
+        return Ok(data.clone());
+    }
+    if kind.is_compressed() {
+        Err(crate::Error::UnsupportedConversion(
+            format!("{:?}", data.kind()),
+            format!("{:?}", kind),
+        ))?;
+    }
+    let (channels, values) = texels_to_f32(&data.decompress(width, height)?, false);
+    let target_channels = kind.channel_count();
+    let mut converted = Vec::with_capacity(values.len() / channels * target_channels);
+    for texel in values.chunks(channels) {
+        let luminance = |t: &[f32]| 0.2126 * t[0] + 0.7152 * t[1] + 0.0722 * t[2];
+        match (channels, target_channels) {
+            (1, 2) => converted.extend([texel[0], 1.0]),
+            (1, 3) => converted.extend([texel[0]; 3]),
+            (1, 4) => converted.extend([texel[0], texel[0], texel[0], 1.0]),
+            (2, 3) => converted.extend([texel[0]; 3]),
+            (2, 4) => converted.extend([texel[0], texel[0], texel[0], texel[1]]),
+            (3, 1) | (4, 1) => converted.push(luminance(texel)),
+            (3, 2) => converted.extend([luminance(texel), 1.0]),
+            (4, 2) => converted.extend([luminance(texel), texel[3]]),
+            (3, 4) => converted.extend([texel[0], texel[1], texel[2], 1.0]),
+            // The same number of channels or removing the alpha channel of two or four channels
+            _ => converted.extend_from_slice(&texel[..target_channels]),
+        }
+    }
+    Ok(texels_from_f32(kind, target_channels, &converted, false))
+}
+
+fn multiply_alpha_data(data: &mut TextureData, premultiply: bool) -> crate::Result<()> {
+    fn integer(values: &mut [[u16; 4]], max: u32, premultiply: bool) {
+        for texel in values.iter_mut() {
+            let alpha = texel[3] as u32;
+            for value in texel[..3].iter_mut() {
+                *value = if premultiply {
+                    (*value as u32 * alpha + max / 2) / max
+                } else {
+                    (*value as u32 * max + alpha / 2)
+                        .checked_div(alpha)
+                        .map_or(0, |v| v.min(max))
+                } as u16;
+            }
+        }
+    }
+    fn float(values: &mut [[f32; 4]], premultiply: bool) {
+        for texel in values.iter_mut() {
+            let alpha = texel[3];
+            for value in texel[..3].iter_mut() {
+                *value = if premultiply {
+                    *value * alpha
+                } else if alpha == 0.0 {
+                    0.0
+                } else {
+                    *value / alpha
+                };
+            }
+        }
+    }
+    match data {
+        TextureData::RgbaU8(values) => {
+            let mut wide = values
+                .iter()
+                .map(|t| t.map(|v| v as u16))
+                .collect::<Vec<_>>();
+            integer(&mut wide, u8::MAX as u32, premultiply);
+            for (texel, wide) in values.iter_mut().zip(wide) {
+                *texel = wide.map(|v| v as u8);
+            }
+        }
+        TextureData::RgbaU16(values) => integer(values, u16::MAX as u32, premultiply),
+        TextureData::RgbaF16(values) => {
+            let mut wide = values
+                .iter()
+                .map(|t| t.map(|v| v.to_f32()))
+                .collect::<Vec<_>>();
+            float(&mut wide, premultiply);
+            for (texel, wide) in values.iter_mut().zip(wide) {
+                *texel = wide.map(f16::from_f32);
+            }
+        }
+        TextureData::RgbaF32(values) => float(values, premultiply),
+        _ => Err(crate::Error::MissingAlphaChannel(format!(
+            "{:?}",
+            data.kind()
+        )))?,
+    }
+    Ok(())
+}
+
+///
+/// A source channel when swizzling a texture, see [Texture2D::swizzle].
+///
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Channel {
+    /// The red channel.
+    R,
+    /// The green channel.
+    G,
+    /// The blue channel.
+    B,
+    /// The alpha channel.
+    A,
+    /// The constant zero.
+    Zero,
+    /// The constant one, ie. the maximum value for integer data.
+    One,
+}
+
+fn swizzle_data(data: &TextureData, mapping: [Channel; 4]) -> TextureData {
+    fn swizzle<T: Copy, const N: usize>(
+        texels: &[[T; N]],
+        mapping: [Channel; 4],
+        zero: T,
+        one: T,
+    ) -> Vec<[T; 4]> {
+        texels
+            .iter()
+            .map(|texel| {
+                mapping.map(|channel| match channel {
+                    Channel::R => texel[0],
+                    Channel::G if N > 1 => texel[1],
+                    Channel::B if N > 2 => texel[2],
+                    Channel::A if N == 4 => texel[3],
+                    Channel::A | Channel::One => one,
+                    _ => zero,
+                })
+            })
+            .collect()
+    }
+    let (zero16, one16) = (f16::from_f32(0.0), f16::from_f32(1.0));
+    match data {
+        TextureData::RU8(v) => {
+            let texels: Vec<[u8; 1]> = v.iter().map(|v| [*v]).collect();
+            TextureData::RgbaU8(swizzle(&texels, mapping, 0, u8::MAX))
+        }
+        TextureData::RgU8(v) => TextureData::RgbaU8(swizzle(v, mapping, 0, u8::MAX)),
+        TextureData::RgbU8(v) => TextureData::RgbaU8(swizzle(v, mapping, 0, u8::MAX)),
+        TextureData::RgbaU8(v) => TextureData::RgbaU8(swizzle(v, mapping, 0, u8::MAX)),
+        TextureData::RU16(v) => {
+            let texels: Vec<[u16; 1]> = v.iter().map(|v| [*v]).collect();
+            TextureData::RgbaU16(swizzle(&texels, mapping, 0, u16::MAX))
+        }
+        TextureData::RgU16(v) => TextureData::RgbaU16(swizzle(v, mapping, 0, u16::MAX)),
+        TextureData::RgbU16(v) => TextureData::RgbaU16(swizzle(v, mapping, 0, u16::MAX)),
+        TextureData::RgbaU16(v) => TextureData::RgbaU16(swizzle(v, mapping, 0, u16::MAX)),
+        TextureData::RF16(v) => {
+            let texels: Vec<[f16; 1]> = v.iter().map(|v| [*v]).collect();
+            TextureData::RgbaF16(swizzle(&texels, mapping, zero16, one16))
+        }
+        TextureData::RgF16(v) => TextureData::RgbaF16(swizzle(v, mapping, zero16, one16)),
+        TextureData::RgbF16(v) => TextureData::RgbaF16(swizzle(v, mapping, zero16, one16)),
+        TextureData::RgbaF16(v) => TextureData::RgbaF16(swizzle(v, mapping, zero16, one16)),
+        TextureData::RF32(v) => {
+            let texels: Vec<[f32; 1]> = v.iter().map(|v| [*v]).collect();
+            TextureData::RgbaF32(swizzle(&texels, mapping, 0.0, 1.0))
+        }
+        TextureData::RgF32(v) => TextureData::RgbaF32(swizzle(v, mapping, 0.0, 1.0)),
+        TextureData::RgbF32(v) => TextureData::RgbaF32(swizzle(v, mapping, 0.0, 1.0)),
+        TextureData::RgbaF32(v) => TextureData::RgbaF32(swizzle(v, mapping, 0.0, 1.0)),
+        _ => unreachable!("the texture data is decompressed"),
+    }
+}
+
+fn flip_data(
+    data: &mut TextureData,
+    width: usize,
+    height: usize,
+    vertical: bool,
+) -> crate::Result<()> {
+    fn flip<T>(values: &mut [T], width: usize, height: usize, vertical: bool) {
+        if vertical {
+            for y in 0..height / 2 {
+                let (top, bottom) = values.split_at_mut((height - 1 - y) * width);
+                top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+            }
+        } else {
+            for row in values.chunks_mut(width) {
+                row.reverse();
+            }
+        }
+    }
+    match data {
+        TextureData::RU8(values) => flip(values, width, height, vertical),
+        TextureData::RgU8(values) => flip(values, width, height, vertical),
+        TextureData::RgbU8(values) => flip(values, width, height, vertical),
+        TextureData::RgbaU8(values) => flip(values, width, height, vertical),
+        TextureData::RU16(values) => flip(values, width, height, vertical),
+        TextureData::RgU16(values) => flip(values, width, height, vertical),
+        TextureData::RgbU16(values) => flip(values, width, height, vertical),
+        TextureData::RgbaU16(values) => flip(values, width, height, vertical),
+        TextureData::RF16(values) => flip(values, width, height, vertical),
+        TextureData::RgF16(values) => flip(values, width, height, vertical),
+        TextureData::RgbF16(values) => flip(values, width, height, vertical),
+        TextureData::RgbaF16(values) => flip(values, width, height, vertical),
+        TextureData::RF32(values) => flip(values, width, height, vertical),
+        TextureData::RgF32(values) => flip(values, width, height, vertical),
+        TextureData::RgbF32(values) => flip(values, width, height, vertical),
+        TextureData::RgbaF32(values) => flip(values, width, height, vertical),
+        TextureData::Bc1RgbaU8(_) => Err(crate::Error::UnsupportedFlip("BC1".to_owned()))?,
+        TextureData::Bc3RgbaU8(_) => Err(crate::Error::UnsupportedFlip("BC3".to_owned()))?,
+        TextureData::Bc5RgU8(_) => Err(crate::Error::UnsupportedFlip("BC5".to_owned()))?,
+        TextureData::Bc7RgbaU8(_) => Err(crate::Error::UnsupportedFlip("BC7".to_owned()))?,
+        TextureData::Etc2RgbaU8(_) => Err(crate::Error::UnsupportedFlip("ETC2".to_owned()))?,
+        TextureData::Astc4x4RgbaU8(_) => Err(crate::Error::UnsupportedFlip("ASTC".to_owned()))?,
+    }
+    Ok(())
+}
+
+///
+/// The operator used to map high dynamic range values to the range `[0..1]`, see [Texture2D::tonemap].
+///
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ToneMap {
+    /// The Reinhard operator `x / (1 + x)`, which compresses bright values smoothly but desaturates the highlights.
+    Reinhard,
+    /// The filmic curve of the Academy Color Encoding System (ACES) using the approximation by Krzysztof Narkowicz, which gives a higher contrast than [ToneMap::Reinhard].
+    AcesFilmic,
+    /// The exponential operator `1 - exp(-exposure * x)` with the given exposure, where a higher exposure gives a brighter result.
+    Exposure(f32),
+}
+
+impl ToneMap {
+    fn apply(&self, value: f32) -> f32 {
+        let value = value.max(0.0);
+        match *self {
+            Self::Reinhard => value / (1.0 + value),
+            Self::AcesFilmic => {
+                (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)
+            }
+            Self::Exposure(exposure) => 1.0 - (-exposure * value).exp(),
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+///
+/// A clockwise rotation by a multiple of 90 degrees, see [Texture2D::rotate].
+///
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum TextureRotation {
+    /// Rotates by 90 degrees clockwise, so the top row becomes the right column.
+    Cw90,
+    /// Rotates by 180 degrees.
+    Cw180,
+    /// Rotates by 270 degrees clockwise, ie. 90 degrees counterclockwise, so the top row becomes the left column.
+    Cw270,
+}
+
+fn rotate_data(
+    data: &TextureData,
+    width: usize,
+    height: usize,
+    rotation: TextureRotation,
+) -> crate::Result<TextureData> {
+    fn rotate<T: Copy>(
+        values: &[T],
+        width: usize,
+        height: usize,
+        rotation: TextureRotation,
+    ) -> Vec<T> {
+        match rotation {
+            TextureRotation::Cw90 => (0..width)
+                .flat_map(|y| (0..height).map(move |x| values[(height - 1 - x) * width + y]))
+                .collect(),
+            TextureRotation::Cw180 => values.iter().rev().copied().collect(),
+            TextureRotation::Cw270 => (0..width)
+                .flat_map(|y| (0..height).map(move |x| values[x * width + width - 1 - y]))
+                .collect(),
+        }
+    }
+    Ok(match data {
+        TextureData::RU8(values) => TextureData::RU8(rotate(values, width, height, rotation)),
+        TextureData::RgU8(values) => TextureData::RgU8(rotate(values, width, height, rotation)),
+        TextureData::RgbU8(values) => TextureData::RgbU8(rotate(values, width, height, rotation)),
+        TextureData::RgbaU8(values) => TextureData::RgbaU8(rotate(values, width, height, rotation)),
+        TextureData::RU16(values) => TextureData::RU16(rotate(values, width, height, rotation)),
+        TextureData::RgU16(values) => TextureData::RgU16(rotate(values, width, height, rotation)),
+        TextureData::RgbU16(values) => TextureData::RgbU16(rotate(values, width, height, rotation)),
+        TextureData::RgbaU16(values) => {
+            TextureData::RgbaU16(rotate(values, width, height, rotation))
+        }
+        TextureData::RF16(values) => TextureData::RF16(rotate(values, width, height, rotation)),
+        TextureData::RgF16(values) => TextureData::RgF16(rotate(values, width, height, rotation)),
+        TextureData::RgbF16(values) => TextureData::RgbF16(rotate(values, width, height, rotation)),
+        TextureData::RgbaF16(values) => {
+            TextureData::RgbaF16(rotate(values, width, height, rotation))
+        }
+        TextureData::RF32(values) => TextureData::RF32(rotate(values, width, height, rotation)),
+        TextureData::RgF32(values) => TextureData::RgF32(rotate(values, width, height, rotation)),
+        TextureData::RgbF32(values) => TextureData::RgbF32(rotate(values, width, height, rotation)),
+        TextureData::RgbaF32(values) => {
+            TextureData::RgbaF32(rotate(values, width, height, rotation))
+        }
+        TextureData::Bc1RgbaU8(_) => Err(crate::Error::UnsupportedRotation("BC1".to_owned()))?,
+        TextureData::Bc3RgbaU8(_) => Err(crate::Error::UnsupportedRotation("BC3".to_owned()))?,
+        TextureData::Bc5RgU8(_) => Err(crate::Error::UnsupportedRotation("BC5".to_owned()))?,
+        TextureData::Bc7RgbaU8(_) => Err(crate::Error::UnsupportedRotation("BC7".to_owned()))?,
+        TextureData::Etc2RgbaU8(_) => Err(crate::Error::UnsupportedRotation("ETC2".to_owned()))?,
+        TextureData::Astc4x4RgbaU8(_) => Err(crate::Error::UnsupportedRotation("ASTC".to_owned()))?,
+    })
+}
+
+///
+/// The filter used when resizing a texture, see [Texture2D::resize].
+///
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum ResizeFilter {
+    /// Uses the value of the nearest texel.
+    Nearest,
+    /// Linear interpolation between the closest texels when upscaling and averaging when downscaling.
+    #[default]
+    Triangle,
+    /// Cubic interpolation which is sharper than [ResizeFilter::Triangle].
+    CatmullRom,
+    /// Windowed sinc interpolation which is the sharpest and slowest, but might produce ringing artifacts near edges.
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    fn support(&self) -> f32 {
+        match self {
+            Self::Nearest => 0.5,
+            Self::Triangle => 1.0,
+            Self::CatmullRom => 2.0,
+            Self::Lanczos3 => 3.0,
+        }
+    }
+
+    fn kernel(&self, x: f32) -> f32 {
+        let x = x.abs();
+        let sinc = |x: f32| {
+            if x == 0.0 {
+                1.0
+            } else {
+                let x = x * std::f32::consts::PI;
+                x.sin() / x
+            }
+        };
+        match self {
+            Self::Nearest => 1.0,
+            Self::Triangle => (1.0 - x).max(0.0),
+            Self::CatmullRom => {
+                if x < 1.0 {
+                    1.5 * x * x * x - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Lanczos3 => {
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+///
+/// Returns the source texels along one axis and their normalized weights for each texel in the resized texture.
+/// When downscaling, the filter is widened to cover all source texels that contribute to a target texel.
+///
+fn resample_taps(old_size: usize, new_size: usize, filter: ResizeFilter) -> Vec<Vec<(usize, f32)>> {
+    let ratio = old_size as f32 / new_size as f32;
+    let scale = ratio.max(1.0);
+    let support = filter.support() * scale;
+    (0..new_size)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * ratio;
+            if filter == ResizeFilter::Nearest {
+                return vec![((center as usize).min(old_size - 1), 1.0)];
+            }
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(old_size);
+            let mut taps: Vec<_> = (start..end)
+                .map(|j| (j, filter.kernel((j as f32 + 0.5 - center) / scale)))
+                .filter(|(_, w)| *w != 0.0)
+                .collect();
+            let sum: f32 = taps.iter().map(|(_, w)| w).sum();
+            if sum == 0.0 {
+                return vec![((center as usize).min(old_size - 1), 1.0)];
+            }
+            for (_, w) in taps.iter_mut() {
+                *w /= sum;
+            }
+            taps
+        })
+        .collect()
+}
+
+///
+/// Returns the source texels along one axis and their weights used to compute the given texel of the next mip level.
+///
+fn taps(i: usize, size: usize, filter: Interpolation) -> Vec<(usize, f32)> {
+    if size == 1 {
+        return vec![(0, 1.0)];
+    }
+    let clamp = |offset: isize| (2 * i as isize + offset).clamp(0, size as isize - 1) as usize;
+    match filter {
+        Interpolation::Nearest => vec![(clamp(0), 1.0)],
+        Interpolation::Linear => vec![(clamp(0), 0.5), (clamp(1), 0.5)],
+        Interpolation::CubicSpline => vec![
+            (clamp(-1), 0.125),
+            (clamp(0), 0.375),
+            (clamp(1), 0.375),
+            (clamp(2), 0.125),
+        ],
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+///
+/// Returns whether the given channel contains color, as opposed to alpha, and is sRGB encoded.
+///
+fn is_srgb_channel(kind: TextureDataKind, channel: usize, channels: usize, is_srgb: bool) -> bool {
+    let is_integer = matches!(
+        kind,
+        TextureDataKind::RU8
+            | TextureDataKind::RgU8
+            | TextureDataKind::RgbU8
+            | TextureDataKind::RgbaU8
+            | TextureDataKind::RU16
+            | TextureDataKind::RgU16
+            | TextureDataKind::RgbU16
+            | TextureDataKind::RgbaU16
+    );
+    let has_alpha = channels == 2 || channels == 4;
+    is_srgb && is_integer && !(has_alpha && channel == channels - 1)
+}
+
+///
+/// Converts uncompressed texture data into the number of channels and the interleaved channel values in the range `[0..1]` for integer data.
+///
+pub(super) fn texels_to_f32(data: &TextureData, is_srgb: bool) -> (usize, Vec<f32>) {
+    let u8 = |v: &u8| *v as f32 / 255.0;
+    let u16 = |v: &u16| *v as f32 / 65535.0;
+    let f16 = |v: &f16| v.to_f32();
+    let (channels, mut values): (usize, Vec<f32>) = match data {
+        TextureData::RU8(v) => (1, v.iter().map(u8).collect()),
+        TextureData::RgU8(v) => (2, v.iter().flatten().map(u8).collect()),
+        TextureData::RgbU8(v) => (3, v.iter().flatten().map(u8).collect()),
+        TextureData::RgbaU8(v) => (4, v.iter().flatten().map(u8).collect()),
+        TextureData::RU16(v) => (1, v.iter().map(u16).collect()),
+        TextureData::RgU16(v) => (2, v.iter().flatten().map(u16).collect()),
+        TextureData::RgbU16(v) => (3, v.iter().flatten().map(u16).collect()),
+        TextureData::RgbaU16(v) => (4, v.iter().flatten().map(u16).collect()),
+        TextureData::RF16(v) => (1, v.iter().map(f16).collect()),
+        TextureData::RgF16(v) => (2, v.iter().flatten().map(f16).collect()),
+        TextureData::RgbF16(v) => (3, v.iter().flatten().map(f16).collect()),
+        TextureData::RgbaF16(v) => (4, v.iter().flatten().map(f16).collect()),
+        TextureData::RF32(v) => (1, v.clone()),
+        TextureData::RgF32(v) => (2, v.iter().flatten().copied().collect()),
+        TextureData::RgbF32(v) => (3, v.iter().flatten().copied().collect()),
+        TextureData::RgbaF32(v) => (4, v.iter().flatten().copied().collect()),
+        _ => unreachable!("the texture data is decompressed"),
+    };
+    for (i, value) in values.iter_mut().enumerate() {
+        if is_srgb_channel(data.kind(), i % channels, channels, is_srgb) {
+            *value = srgb_to_linear(*value);
+        }
+    }
+    (channels, values)
+}
+
+///
+/// The inverse of [texels_to_f32], where the given kind determines the resulting [TextureData] variant.
+///
+pub(super) fn texels_from_f32(
+    kind: TextureDataKind,
+    channels: usize,
+    values: &[f32],
+    is_srgb: bool,
+) -> TextureData {
+    fn texels<T: Copy, const N: usize>(values: &[T]) -> Vec<[T; N]> {
+        values
+            .chunks(N)
+            .map(|c| std::array::from_fn(|i| c[i]))
+            .collect()
+    }
+    let values: Vec<f32> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            if is_srgb_channel(kind, i % channels, channels, is_srgb) {
+                linear_to_srgb(*v)
+            } else {
+                *v
+            }
+        })
+        .collect();
+    let u8s = || -> Vec<u8> {
+        values
+            .iter()
+            .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect()
+    };
+    let u16s = || -> Vec<u16> {
+        values
+            .iter()
+            .map(|v| (v.clamp(0.0, 1.0) * 65535.0).round() as u16)
+            .collect()
+    };
+    let f16s = || -> Vec<f16> { values.iter().map(|v| f16::from_f32(*v)).collect() };
+    match kind {
+        TextureDataKind::RU8 => TextureData::RU8(u8s()),
+        TextureDataKind::RgU8 => TextureData::RgU8(texels(&u8s())),
+        TextureDataKind::RgbU8 => TextureData::RgbU8(texels(&u8s())),
+        TextureDataKind::RgbaU8 => TextureData::RgbaU8(texels(&u8s())),
+        TextureDataKind::RU16 => TextureData::RU16(u16s()),
+        TextureDataKind::RgU16 => TextureData::RgU16(texels(&u16s())),
+        TextureDataKind::RgbU16 => TextureData::RgbU16(texels(&u16s())),
+        TextureDataKind::RgbaU16 => TextureData::RgbaU16(texels(&u16s())),
+        TextureDataKind::RF16 => TextureData::RF16(f16s()),
+        TextureDataKind::RgF16 => TextureData::RgF16(texels(&f16s())),
+        TextureDataKind::RgbF16 => TextureData::RgbF16(texels(&f16s())),
+        TextureDataKind::RgbaF16 => TextureData::RgbaF16(texels(&f16s())),
+        TextureDataKind::RF32 => TextureData::RF32(values),
+        TextureDataKind::RgF32 => TextureData::RgF32(texels(&values)),
+        TextureDataKind::RgbF32 => TextureData::RgbF32(texels(&values)),
+        TextureDataKind::RgbaF32 => TextureData::RgbaF32(texels(&values)),
+        _ => unreachable!("the kind is not compressed"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn generate_mipmaps() {
+        let tex = Texture2D {
+            data: TextureData::RF32(
+                (0..3)
+                    .flat_map(|y| (0..5).map(move |x| (x + 10 * y) as f32))
+                    .collect(),
+            ),
+            width: 5,
+            height: 3,
+            ..Default::default()
+        };
+        let levels = tex.generate_mipmaps(Interpolation::Linear, false).unwrap();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[0], tex.data);
+        assert_eq!(levels[1], TextureData::RF32(vec![5.5, 7.5]));
+        assert_eq!(levels[2], TextureData::RF32(vec![6.5]));
+
+        let levels = tex.generate_mipmaps(Interpolation::Nearest, false).unwrap();
+        assert_eq!(levels[1], TextureData::RF32(vec![0.0, 2.0]));
+        assert_eq!(levels[2], TextureData::RF32(vec![0.0]));
+    }
+
+    #[test]
+    pub fn generate_mipmaps_srgb() {
+        let tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[0, 0, 0, 0], [255, 255, 255, 255]]),
+            width: 2,
+            height: 1,
+            ..Default::default()
+        };
+        let levels = tex.generate_mipmaps(Interpolation::Linear, true).unwrap();
+        assert_eq!(levels.len(), 2);
+        // The color is averaged in linear space, the alpha is not sRGB encoded
+        assert_eq!(levels[1], TextureData::RgbaU8(vec![[188, 188, 188, 128]]));
+        let levels = tex.generate_mipmaps(Interpolation::Linear, false).unwrap();
+        assert_eq!(levels[1], TextureData::RgbaU8(vec![[128, 128, 128, 128]]));
+    }
+
+    #[test]
+    pub fn resize_triangle_downscale() {
+        let mut tex = Texture2D {
+            data: TextureData::RU8(
+                (0..16)
+                    .map(|i| if (i % 4 + i / 4) % 2 == 0 { 255 } else { 0 })
+                    .collect(),
+            ),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        tex.mip_levels = vec![TextureData::RU8(vec![0; 4])];
+        tex.resize(2, 2, ResizeFilter::Triangle, false).unwrap();
+        assert_eq!((tex.width, tex.height), (2, 2));
+        // The filter is cut off at the border, so each target texel averages 3x3 source texels with the weights 3/7, 3/7 and 1/7 along each axis.
+        assert_eq!(tex.data, TextureData::RU8(vec![130, 125, 125, 130]));
+        assert!(tex.mip_levels.is_empty());
+    }
+
+    #[test]
+    pub fn resize_nearest_upscale() {
+        let data = vec![
+            [1, 2, 3, 4],
+            [5, 6, 7, 8],
+            [9, 10, 11, 12],
+            [13, 14, 15, 16],
+        ];
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(data.clone()),
+            width: 2,
+            height: 2,
+            ..Default::default()
+        };
+        tex.resize(4, 4, ResizeFilter::Nearest, false).unwrap();
+        let expected = (0..16)
+            .map(|i| data[(i / 8) * 2 + (i % 4) / 2])
+            .collect::<Vec<_>>();
+        assert_eq!(tex.data, TextureData::RgbaU8(expected));
+    }
+
+    #[test]
+    pub fn resize_srgb() {
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[0, 64, 255, 0], [255, 64, 0, 255]]),
+            width: 2,
+            height: 1,
+            ..Default::default()
+        };
+        let original = tex.clone();
+        tex.resize(2, 1, ResizeFilter::Triangle, true).unwrap();
+        assert_eq!(tex.data, original.data);
+
+        tex.resize(1, 1, ResizeFilter::Triangle, true).unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[188, 64, 188, 128]]));
+    }
+
+    #[test]
+    pub fn resize_to_zero() {
+        let mut tex = Texture2D::default();
+        assert!(matches!(
+            tex.resize(0, 1, ResizeFilter::Triangle, false),
+            Err(crate::Error::InvalidTextureSize(0, 1))
+        ));
+        assert_eq!(tex, Texture2D::default());
+    }
+
+    #[test]
+    pub fn flip() {
+        let tex = Texture2D {
+            data: TextureData::RU8(vec![0, 1, 2, 3, 4, 5]),
+            width: 3,
+            height: 2,
+            ..Default::default()
+        };
+        let mut flipped = tex.clone();
+        flipped.flip_vertical().unwrap();
+        assert_eq!(flipped.data, TextureData::RU8(vec![3, 4, 5, 0, 1, 2]));
+        flipped.flip_vertical().unwrap();
+        assert_eq!(flipped, tex);
+
+        let mut flipped = tex.clone();
+        flipped.flip_horizontal().unwrap();
+        assert_eq!(flipped.data, TextureData::RU8(vec![2, 1, 0, 5, 4, 3]));
+        flipped.flip_horizontal().unwrap();
+        assert_eq!(flipped, tex);
+
+        // The middle row of an odd height stays in place
+        let mut flipped = Texture2D {
+            data: TextureData::RgF32((0..9).map(|i| [i as f32, 1.0]).collect()),
+            width: 3,
+            height: 3,
+            ..Default::default()
+        };
+        flipped.flip_vertical().unwrap();
+        assert_eq!(
+            flipped.data,
+            TextureData::RgF32(
+                [6, 7, 8, 3, 4, 5, 0, 1, 2]
+                    .map(|i| [i as f32, 1.0])
+                    .to_vec()
+            )
+        );
+    }
+
+    #[test]
+    pub fn flip_mip_levels() {
+        let tex = Texture2D {
+            data: TextureData::RU8((0..16).collect()),
+            width: 4,
+            height: 4,
+            mip_levels: vec![
+                TextureData::RU8(vec![16, 17, 18, 19]),
+                TextureData::RU8(vec![20]),
+            ],
+            ..Default::default()
+        };
+        let mut flipped = tex.clone();
+        flipped.flip_vertical().unwrap();
+        assert_eq!(
+            flipped.mip_levels,
+            vec![
+                TextureData::RU8(vec![18, 19, 16, 17]),
+                TextureData::RU8(vec![20])
+            ]
+        );
+        let mut flipped = tex.clone();
+        flipped.flip_horizontal().unwrap();
+        assert_eq!(
+            flipped.mip_levels,
+            vec![
+                TextureData::RU8(vec![17, 16, 19, 18]),
+                TextureData::RU8(vec![20])
+            ]
+        );
+
+        let mut compressed = Texture2D {
+            data: TextureData::Bc1RgbaU8(vec![[0; 8]]),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        assert!(compressed.flip_vertical().is_err());
+        assert!(compressed.flip_horizontal().is_err());
+    }
+
+    #[test]
+    pub fn crop() {
+        let tex = Texture2D {
+            name: "tex".to_owned(),
+            data: TextureData::RU8((0..12).collect()),
+            width: 4,
+            height: 3,
+            wrap_s: Wrapping::ClampToEdge,
+            mip_levels: vec![TextureData::RU8(vec![0; 2])],
+            ..Default::default()
+        };
+        let cropped = tex.crop(1, 1, 2, 2).unwrap();
+        assert_eq!((cropped.width, cropped.height), (2, 2));
+        assert_eq!(cropped.data, TextureData::RU8(vec![5, 6, 9, 10]));
+        assert_eq!(cropped.name, "tex");
+        assert_eq!(cropped.wrap_s, Wrapping::ClampToEdge);
+        assert!(cropped.mip_levels.is_empty());
+        assert_eq!(tex.crop(0, 0, 4, 3).unwrap().data, tex.data);
+        assert_eq!(
+            tex.crop(3, 0, 1, 3).unwrap().data,
+            TextureData::RU8(vec![3, 7, 11])
+        );
+
+        for (x, y, width, height) in [
+            (0, 0, 0, 1),
+            (0, 0, 1, 0),
+            (1, 0, 4, 1),
+            (0, 3, 1, 1),
+            (u32::MAX, 0, 2, 1),
+        ] {
+            assert!(matches!(
+                tex.crop(x, y, width, height),
+                Err(crate::Error::RegionOutOfBounds(..))
+            ));
+        }
+    }
+
+    #[cfg(any(feature = "dds", feature = "ktx2", feature = "basis"))]
+    #[test]
+    pub fn crop_compressed() {
+        let tex = Texture2D {
+            data: TextureData::Bc1RgbaU8(vec![[0x00, 0xf8, 0x1f, 0x00, 0, 0, 0, 0]]),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        let cropped = tex.crop(1, 1, 2, 3).unwrap();
+        assert_eq!(cropped.data, TextureData::RgbaU8(vec![[255, 0, 0, 255]; 6]));
+    }
+
+    #[test]
+    pub fn convert() {
+        use TextureData::*;
+        let (r, rg) = (RF32(vec![0.5]), RgF32(vec![[0.5, 0.25]]));
+        let (rgb, rgba) = (
+            RgbF32(vec![[0.0, 1.0, 0.0]]),
+            RgbaF32(vec![[0.0, 0.0, 1.0, 0.5]]),
+        );
+        let cases = [
+            (&r, RgF32(vec![[0.5, 1.0]])),
+            (&r, RgbF32(vec![[0.5; 3]])),
+            (&r, RgbaF32(vec![[0.5, 0.5, 0.5, 1.0]])),
+            (&rg, RF32(vec![0.5])),
+            (&rg, RgbF32(vec![[0.5; 3]])),
+            (&rg, RgbaF32(vec![[0.5, 0.5, 0.5, 0.25]])),
+            (&rgb, RF32(vec![0.7152])),
+            (&rgb, RgF32(vec![[0.7152, 1.0]])),
+            (&rgb, RgbaF32(vec![[0.0, 1.0, 0.0, 1.0]])),
+            (&rgba, RF32(vec![0.0722])),
+            (&rgba, RgF32(vec![[0.0722, 0.5]])),
+            (&rgba, RgbF32(vec![[0.0, 0.0, 1.0]])),
+            (&RgbaU8(vec![[255, 0, 0, 128]]), RF32(vec![0.2126])),
+            (&RF32(vec![1.5]), RU8(vec![255])),
+            (
+                &RgbU8(vec![[255, 128, 0]]),
+                RgbaU16(vec![[65535, 32896, 0, 65535]]),
+            ),
+        ];
+        for (data, expected) in cases {
+            let mut tex = Texture2D {
+                data: data.clone(),
+                ..Default::default()
+            };
+            tex.convert(expected.kind()).unwrap();
+            assert_eq!(
+                tex.data,
+                expected,
+                "{:?} to {:?}",
+                data.kind(),
+                expected.kind()
+            );
+        }
+    }
+
+    #[test]
+    pub fn convert_mip_levels() {
+        let mut tex = Texture2D {
+            data: TextureData::RU8(vec![0, 64, 128, 255]),
+            width: 2,
+            height: 2,
+            mip_levels: vec![TextureData::RU8(vec![112])],
+            ..Default::default()
+        };
+        tex.convert(TextureDataKind::RgbaU8).unwrap();
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaU8(vec![
+                [0, 0, 0, 255],
+                [64, 64, 64, 255],
+                [128, 128, 128, 255],
+                [255, 255, 255, 255]
+            ])
+        );
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[112, 112, 112, 255]])]
+        );
+        assert!(matches!(
+            tex.convert(TextureDataKind::Bc1RgbaU8),
+            Err(crate::Error::UnsupportedConversion(_, _))
+        ));
+    }
+
+    #[test]
+    pub fn swizzle() {
+        use Channel::*;
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[1, 2, 3, 4]]),
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        tex.swizzle([B, G, R, A]).unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[3, 2, 1, 4]]));
+
+        // A missing alpha channel reads as one and missing color channels read as zero
+        tex.data = TextureData::RgbU8(vec![[1, 2, 3]]);
+        tex.swizzle([R, G, B, A]).unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[1, 2, 3, 255]]));
+        tex.data = TextureData::RU8(vec![5]);
+        tex.swizzle([G, B, R, R]).unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[0, 0, 5, 5]]));
+
+        tex.data = TextureData::RU16(vec![7]);
+        tex.swizzle([R, Zero, One, A]).unwrap();
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaU16(vec![[7, 0, u16::MAX, u16::MAX]])
+        );
+
+        let half = |v: f32| f16::from_f32(v);
+        tex.data = TextureData::RgF16(vec![[half(0.5), half(0.25)]]);
+        tex.swizzle([A, R, Zero, One]).unwrap();
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaF16(vec![[half(1.0), half(0.5), half(0.0), half(1.0)]])
+        );
+    }
+
+    #[test]
+    pub fn swizzle_mip_levels() {
+        let mut tex = Texture2D {
+            data: TextureData::RgU8(vec![[1, 2]; 4]),
+            width: 2,
+            height: 2,
+            mip_levels: vec![TextureData::RgU8(vec![[3, 4]])],
+            ..Default::default()
+        };
+        tex.swizzle([Channel::G, Channel::G, Channel::G, Channel::R])
+            .unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[2, 2, 2, 1]; 4]));
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[4, 4, 4, 3]])]
+        );
+    }
+
+    #[test]
+    pub fn to_linear_and_srgb() {
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[188, 0, 255, 100]]),
+            width: 1,
+            height: 1,
+            color_space: ColorSpace::Srgb,
+            mip_levels: vec![TextureData::RgbaU8(vec![[188, 188, 188, 188]])],
+            ..Default::default()
+        };
+        // The sRGB value 188 is about 0.5 in linear space, while the alpha channel is unchanged
+        tex.to_linear().unwrap();
+        assert_eq!(tex.color_space, ColorSpace::Linear);
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[128, 0, 255, 100]]));
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[128, 128, 128, 188]])]
+        );
+        // Converting to linear space again does nothing
+        tex.to_linear().unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[128, 0, 255, 100]]));
+
+        tex.to_srgb().unwrap();
+        assert_eq!(tex.color_space, ColorSpace::Srgb);
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[188, 0, 255, 100]]));
+        tex.to_srgb().unwrap();
+        assert_eq!(tex.data, TextureData::RgbaU8(vec![[188, 0, 255, 100]]));
+
+        let mut tex = Texture2D {
+            data: TextureData::RgF32(vec![[0.5, 0.5]]),
+            width: 1,
+            height: 1,
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        };
+        tex.to_srgb().unwrap();
+        let TextureData::RgF32(values) = &tex.data else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        };
+        assert!((values[0][0] - 0.7354).abs() < 0.0001);
+        assert_eq!(values[0][1], 0.5);
+        tex.to_linear().unwrap();
+        let TextureData::RgF32(values) = &tex.data else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        };
+        assert!((values[0][0] - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    pub fn premultiply_alpha_u8() {
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(vec![
+                [255, 128, 0, 128],
+                [200, 100, 50, 0],
+                [10, 20, 30, 255],
+            ]),
+            width: 3,
+            height: 1,
+            mip_levels: vec![TextureData::RgbaU8(vec![[255, 255, 255, 64]])],
+            ..Default::default()
+        };
+        tex.premultiply_alpha().unwrap();
+        assert!(tex.premultiplied_alpha);
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaU8(vec![[128, 64, 0, 128], [0, 0, 0, 0], [10, 20, 30, 255]])
+        );
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[64, 64, 64, 64]])]
+        );
+
+        // Does nothing when the alpha is already premultiplied
+        tex.premultiply_alpha().unwrap();
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[64, 64, 64, 64]])]
+        );
+
+        // The color of fully transparent texels is lost
+        tex.unpremultiply_alpha().unwrap();
+        assert!(!tex.premultiplied_alpha);
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaU8(vec![[255, 128, 0, 128], [0, 0, 0, 0], [10, 20, 30, 255]])
+        );
+        assert_eq!(
+            tex.mip_levels,
+            vec![TextureData::RgbaU8(vec![[255, 255, 255, 64]])]
+        );
+    }
+
+    #[test]
+    pub fn premultiply_alpha_round_trip() {
+        let data = (0..=255u8)
+            .map(|v| [v, 255 - v, v / 2, 128])
+            .collect::<Vec<_>>();
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(data.clone()),
+            width: 256,
+            height: 1,
+            ..Default::default()
+        };
+        tex.premultiply_alpha().unwrap();
+        tex.unpremultiply_alpha().unwrap();
+        let TextureData::RgbaU8(result) = tex.data else {
+            panic!("Wrong texture data: {:?}", tex.data)
+        };
+        for (texel, original) in result.iter().zip(data) {
+            assert_eq!(texel[3], original[3]);
+            for c in 0..3 {
+                assert!(
+                    texel[c].abs_diff(original[c]) <= 1,
+                    "{:?} {:?}",
+                    texel,
+                    original
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn premultiply_alpha_float() {
+        let mut tex = Texture2D {
+            data: TextureData::RgbaF32(vec![[1.0, 0.5, 0.25, 0.5], [1.0, 1.0, 1.0, 0.0]]),
+            width: 2,
+            height: 1,
+            ..Default::default()
+        };
+        tex.premultiply_alpha().unwrap();
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaF32(vec![[0.5, 0.25, 0.125, 0.5], [0.0, 0.0, 0.0, 0.0]])
+        );
+        tex.unpremultiply_alpha().unwrap();
+        assert_eq!(
+            tex.data,
+            TextureData::RgbaF32(vec![[1.0, 0.5, 0.25, 0.5], [0.0, 0.0, 0.0, 0.0]])
+        );
+
+        let mut tex = Texture2D {
+            data: TextureData::RgbU8(vec![[1, 2, 3]]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            tex.premultiply_alpha(),
+            Err(crate::Error::MissingAlphaChannel(_))
+        ));
+    }
+
+    #[test]
+    pub fn normal_map_from_flat_height() {
+        let tex = Texture2D {
+            data: TextureData::RU8(vec![100; 9]),
+            width: 3,
+            height: 3,
+            ..Default::default()
+        };
+        let normal_map = tex.normal_map_from_height(10.0).unwrap();
+        assert_eq!(normal_map.color_space, ColorSpace::Linear);
+        assert_eq!(
+            normal_map.data,
+            TextureData::RgbU8(vec![[128, 128, 255]; 9])
+        );
+    }
+
+    #[test]
+    pub fn normal_map_from_ramp() {
+        // The height increases downwards in the image, ie. towards negative v, so the normals tilt upwards in the image
+        let tex = Texture2D {
+            data: TextureData::RF32((0..16).map(|i| (i / 4) as f32 * 0.25).collect()),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        let TextureData::RgbU8(normals) = tex.normal_map_from_height(1.0).unwrap().data else {
+            panic!("expected RgbU8 data")
+        };
+        assert_eq!(normals[5], [128, 158, 251]);
+        assert!(normals.iter().all(|n| n[0] == 128 && n[1] > 128));
+
+        // The height increases to the right, so the normals tilt to the left
+        let tex = Texture2D {
+            data: TextureData::RF32((0..16).map(|i| (i % 4) as f32 * 0.25).collect()),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        let TextureData::RgbU8(normals) = tex.normal_map_from_height(1.0).unwrap().data else {
+            panic!("expected RgbU8 data")
+        };
+        assert_eq!(normals[5], [97, 128, 251]);
+    }
+
+    #[test]
+    pub fn pack_orm() {
+        let occlusion = Texture2D {
+            data: TextureData::RU8(vec![10, 20]),
+            width: 2,
+            height: 1,
+            ..Default::default()
+        };
+        // Only the red channel is used
+        let roughness = Texture2D {
+            data: TextureData::RgbaU8(vec![[30, 1, 2, 3], [40, 4, 5, 6]]),
+            width: 2,
+            height: 1,
+            ..Default::default()
+        };
+        let orm = Texture2D::pack_orm(Some(&occlusion), Some(&roughness), None, 2, 1).unwrap();
+        assert_eq!(orm.name, "orm");
+        assert_eq!(orm.color_space, ColorSpace::Linear);
+        assert_eq!((orm.width, orm.height), (2, 1));
+        assert_eq!(orm.data, TextureData::RgbU8(vec![[10, 30, 0], [20, 40, 0]]));
+
+        // Missing textures mean no occlusion, full roughness and no metallic
+        let orm = Texture2D::pack_orm(None, None, None, 2, 2).unwrap();
+        assert_eq!(orm.data, TextureData::RgbU8(vec![[255, 255, 0]; 4]));
+
+        // Textures with a different size are resized
+        let metallic = Texture2D {
+            data: TextureData::RF32(vec![0.8]),
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        let orm = Texture2D::pack_orm(None, Some(&roughness), Some(&metallic), 4, 2).unwrap();
+        assert_eq!((orm.width, orm.height), (4, 2));
+        let TextureData::RgbU8(texels) = orm.data else {
+            panic!("Wrong texture data: {:?}", orm.data)
+        };
+        assert_eq!(texels.len(), 8);
+        assert!(texels.iter().all(|t| t[0] == 255 && t[2] == 204));
+        assert_eq!(texels[0][1], 30);
+        assert_eq!(texels[3][1], 40);
+    }
+
+    #[test]
+    pub fn sample_bilinear() {
+        let tex = Texture2D {
+            data: TextureData::RF32(vec![0.0, 1.0, 2.0, 3.0]),
+            width: 2,
+            height: 2,
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+            ..Default::default()
+        };
+        assert_eq!(tex.sample(0.25, 0.25), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(tex.sample(0.75, 0.75), [3.0, 0.0, 0.0, 1.0]);
+        assert_eq!(tex.sample(0.5, 0.5), [1.5, 0.0, 0.0, 1.0]);
+        // A quarter of the way from the first to the second column and three quarters of the way from the first to the second row
+        assert_eq!(tex.sample(0.375, 0.625), [1.75, 0.0, 0.0, 1.0]);
+        assert_eq!(tex.sample(-1.0, 2.0), [2.0, 0.0, 0.0, 1.0]);
+
+        let tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[0, 0, 0, 0], [255, 255, 255, 255]]),
+            width: 2,
+            height: 1,
+            ..Default::default()
+        };
+        // The left half of the first texel is interpolated with the last texel when repeating
+        assert_eq!(tex.sample(0.0, 0.5), [0.5; 4]);
+        assert_eq!(tex.sample(0.5, 0.5), [0.5; 4]);
+        assert_eq!(tex.sample(0.75, 0.5), [1.0; 4]);
+    }
+
+    #[test]
+    pub fn sample_wrapping() {
+        let mut tex = Texture2D {
+            data: TextureData::RF32(vec![0.0, 1.0, 2.0]),
+            width: 3,
+            height: 1,
+            ..Default::default()
+        };
+        for (wrapping, expected) in [
+            (
+                Wrapping::Repeat,
+                [2.0, 0.0, 1.0, 2.0, 0.0, 1.0, 2.0, 0.0, 1.0, 2.0, 0.0],
+            ),
+            (
+                Wrapping::ClampToEdge,
+                [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 2.0, 2.0, 2.0, 2.0],
+            ),
+            (
+                Wrapping::MirroredRepeat,
+                [2.0, 2.0, 1.0, 0.0, 0.0, 1.0, 2.0, 2.0, 1.0, 0.0, 0.0],
+            ),
+        ] {
+            tex.wrap_s = wrapping;
+            let values = (-4..=6)
+                .map(|x| tex.sample_nearest((x as f32 + 0.5) / 3.0, 0.5)[0])
+                .collect::<Vec<_>>();
+            assert_eq!(values, expected, "{:?}", wrapping);
+        }
+    }
+
+    #[test]
+    pub fn sample_empty() {
+        let tex = Texture2D {
+            data: TextureData::RgbaU8(Vec::new()),
+            width: 0,
+            height: 0,
+            ..Default::default()
+        };
+        assert_eq!(tex.sample(0.5, 0.5), [0.0; 4]);
+        assert_eq!(tex.sample_nearest(-1.0, 2.0), [0.0; 4]);
+    }
+
+    #[test]
+    pub fn average_color() {
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[0, 0, 0, 255], [255, 255, 255, 255]]),
+            width: 2,
+            height: 1,
+            color_space: ColorSpace::Srgb,
+            ..Default::default()
+        };
+        // Black and white average to 0.5 in linear space, which is 188 in sRGB
+        assert_eq!(tex.average_color().unwrap(), Color::new(188, 188, 188, 255));
+        tex.color_space = ColorSpace::Linear;
+        assert_eq!(tex.average_color().unwrap(), Color::new(128, 128, 128, 255));
+
+        // One channel data is luminance with an opaque alpha channel
+        let tex = Texture2D {
+            data: TextureData::RF32(vec![0.2, 0.4]),
+            width: 2,
+            height: 1,
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        };
+        assert_eq!(tex.average_color().unwrap(), Color::new(77, 77, 77, 255));
+    }
+
+    #[test]
+    pub fn dominant_color() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let transparent = [0, 255, 0, 0];
+        let mut tex = Texture2D {
+            data: TextureData::RgbaU8(vec![
+                red,
+                blue,
+                [250, 0, 0, 255],
+                transparent,
+                red,
+                blue,
+                transparent,
+                transparent,
+            ]),
+            width: 4,
+            height: 2,
+            color_space: ColorSpace::Srgb,
+            ..Default::default()
+        };
+        // The three red texels are in the same bin and the transparent texels are ignored
+        assert_eq!(tex.dominant_color().unwrap(), Color::new(253, 0, 0, 255));
+
+        // The average color is returned if all texels are transparent
+        tex.data = TextureData::RgbaU8(vec![[0, 0, 0, 0], [255, 255, 255, 0]]);
+        tex.width = 2;
+        tex.height = 1;
+        assert_eq!(tex.dominant_color().unwrap(), Color::new(188, 188, 188, 0));
+    }
+
+    #[test]
+    pub fn rotate() {
+        let tex = Texture2D {
+            data: TextureData::RU8(vec![0, 1, 2, 3, 4, 5]),
+            width: 3,
+            height: 2,
+            mip_levels: vec![TextureData::RU8(vec![6])],
+            ..Default::default()
+        };
+
+        let mut rotated = tex.clone();
+        rotated.rotate(TextureRotation::Cw90).unwrap();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        // The top row becomes the right column
+        assert_eq!(rotated.data, TextureData::RU8(vec![3, 0, 4, 1, 5, 2]));
+        assert_eq!(rotated.mip_levels, tex.mip_levels);
+
+        let mut rotated = tex.clone();
+        rotated.rotate(TextureRotation::Cw180).unwrap();
+        assert_eq!((rotated.width, rotated.height), (3, 2));
+        assert_eq!(rotated.data, TextureData::RU8(vec![5, 4, 3, 2, 1, 0]));
+
+        let mut rotated = tex.clone();
+        rotated.rotate(TextureRotation::Cw270).unwrap();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        // The top row becomes the left column
+        assert_eq!(rotated.data, TextureData::RU8(vec![2, 5, 1, 4, 0, 3]));
+    }
+
+    #[test]
+    pub fn rotate_mip_levels() {
+        let tex = Texture2D {
+            data: TextureData::RgbaU8((0..8).map(|i| [i; 4]).collect()),
+            width: 4,
+            height: 2,
+            mip_levels: vec![
+                TextureData::RgbaU8(vec![[8; 4], [9; 4]]),
+                TextureData::RgbaU8(vec![[10; 4]]),
+            ],
+            ..Default::default()
+        };
+
+        let mut rotated = tex.clone();
+        rotated.rotate(TextureRotation::Cw90).unwrap();
+        assert_eq!(
+            rotated.mip_levels[0],
+            TextureData::RgbaU8(vec![[8; 4], [9; 4]])
+        );
+        rotated.rotate(TextureRotation::Cw90).unwrap();
+        assert_eq!((rotated.width, rotated.height), (4, 2));
+        assert_eq!(
+            rotated.mip_levels[0],
+            TextureData::RgbaU8(vec![[9; 4], [8; 4]])
+        );
+        rotated.rotate(TextureRotation::Cw90).unwrap();
+        rotated.rotate(TextureRotation::Cw90).unwrap();
+        assert_eq!((rotated.width, rotated.height), (4, 2));
+        assert_eq!(rotated.data, tex.data);
+        assert_eq!(rotated.mip_levels, tex.mip_levels);
+
+        let mut rotated = tex.clone();
+        rotated.rotate(TextureRotation::Cw270).unwrap();
+        assert_eq!(
+            rotated.mip_levels[0],
+            TextureData::RgbaU8(vec![[9; 4], [8; 4]])
+        );
+
+        let mut compressed = Texture2D {
+            data: TextureData::Bc1RgbaU8(vec![[0; 8]]),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        assert!(compressed.rotate(TextureRotation::Cw90).is_err());
+    }
+
+    #[test]
+    pub fn histogram() {
+        let tex = Texture2D {
+            data: TextureData::RgbU8(vec![[0, 128, 255], [255, 255, 0], [64, 0, 255]]),
+            width: 3,
+            height: 1,
+            ..Default::default()
+        };
+        let [r, g, b, a] = tex.histogram(4).unwrap();
+        assert_eq!(r, vec![1, 1, 0, 1]);
+        assert_eq!(g, vec![1, 0, 1, 1]);
+        // The maximum value is counted in the last bin
+        assert_eq!(b, vec![1, 0, 0, 2]);
+        // There is no alpha channel
+        assert_eq!(a, vec![0; 4]);
+
+        let tex = Texture2D {
+            data: TextureData::RF32(vec![1.0, -1.0, 2.0, 0.5]),
+            width: 4,
+            height: 1,
+            ..Default::default()
+        };
+        let [r, g, _, _] = tex.histogram(2).unwrap();
+        // Values outside [0..1] are clamped
+        assert_eq!(r, vec![1, 3]);
+        assert_eq!(g, vec![0, 0]);
+        assert_eq!(
+            tex.histogram(0).unwrap(),
+            std::array::from_fn(|_| Vec::<u32>::new())
+        );
+    }
+
+    #[test]
+    pub fn tonemap() {
+        let tex = Texture2D {
+            data: TextureData::RgbaF32(vec![[1.0, 0.0, 1.0e9, 0.25], [-1.0, 3.0, 0.5, 2.0]]),
+            width: 2,
+            height: 1,
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        };
+        let mapped = tex.tonemap(ToneMap::Reinhard).unwrap();
+        assert_eq!(mapped.color_space, ColorSpace::Srgb);
+        // Reinhard maps 1.0 to 0.5 before sRGB encoding, while the alpha channel is copied unchanged
+        assert_eq!(
+            mapped.data,
+            TextureData::RgbaU8(vec![[188, 0, 255, 64], [0, 225, 156, 255]])
+        );
+
+        let tex = Texture2D {
+            data: TextureData::RF32(vec![std::f32::consts::LN_2]),
+            width: 1,
+            height: 1,
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        };
+        let mapped = tex.tonemap(ToneMap::Exposure(1.0)).unwrap();
+        assert_eq!(mapped.data, TextureData::RgbU8(vec![[188, 188, 188]]));
+
+        // sRGB encoded input is converted to linear space first, so a linear value of 0.5 is mapped to 1/3
+        let tex = Texture2D {
+            data: TextureData::RgU8(vec![[188, 100]]),
+            width: 1,
+            height: 1,
+            color_space: ColorSpace::Srgb,
+            ..Default::default()
+        };
+        let mapped = tex.tonemap(ToneMap::Reinhard).unwrap();
+        assert_eq!(mapped.data, TextureData::RgbaU8(vec![[156, 156, 156, 100]]));
+    }
+
+    #[test]
+    pub fn to_grayscale() {
+        let tex = Texture2D {
+            data: TextureData::RgbF32(vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+            width: 3,
+            height: 1,
+            color_space: ColorSpace::Linear,
+            ..Default::default()
+        };
+        let gray = tex.to_grayscale(true).unwrap();
+        // The Rec. 709 weights
+        assert_eq!(gray.data, TextureData::RF32(vec![0.2126, 0.7152, 0.0722]));
+
+        let tex = Texture2D {
+            data: TextureData::RgbaU8(vec![[255, 255, 255, 10], [188, 188, 188, 20]]),
+            width: 2,
+            height: 1,
+            color_space: ColorSpace::Srgb,
+            mip_levels: vec![TextureData::RgbaU8(vec![[0; 4]])],
+            ..Default::default()
+        };
+        // Gray values are unchanged by the conversion to linear space and back
+        let gray = tex.to_grayscale(true).unwrap();
+        assert_eq!(gray.data, TextureData::RgU8(vec![[255, 10], [188, 20]]));
+        assert_eq!(gray.color_space, ColorSpace::Srgb);
+        assert!(gray.mip_levels.is_empty());
+        let gray = tex.to_grayscale(false).unwrap();
+        assert_eq!(gray.data, TextureData::RU8(vec![255, 188]));
+
+        // The luminance of an sRGB color is computed in linear space
+        let tex = Texture2D {
+            data: TextureData::RgbU8(vec![[255, 0, 0]]),
+            width: 1,
+            height: 1,
+            color_space: ColorSpace::Srgb,
+            ..Default::default()
+        };
+        let gray = tex.to_grayscale(true).unwrap();
+        assert_eq!(gray.data, TextureData::RU8(vec![127]));
+
+        let tex = Texture2D {
+            data: TextureData::RgbaU16(vec![[0, 0, 0, 7]]),
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            tex.to_grayscale(true).unwrap().data,
+            TextureData::RgU16(vec![[0, 7]])
+        );
+        assert_eq!(
+            tex.to_grayscale(false).unwrap().data,
+            TextureData::RU16(vec![0])
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::texture::Texture2D;
 #[doc(inline)]
 pub use crate::texture::{Interpolation, TextureData, Wrapping};
 
@@ -48,3 +49,100 @@ impl Default for Texture3D {
         }
     }
 }
+
+impl Texture3D {
+    ///
+    /// Constructs a 3D texture by stacking the given 2D textures along the depth, such that the first slice is at depth zero.
+    /// The name, filtering and wrapping in the s and t directions are copied from the first slice and mip levels of the slices are ignored.
+    /// Returns an error if there are no slices or if the slices do not all have the same width, height and data format.
+    ///
+    pub fn from_slices(slices: &[Texture2D]) -> crate::Result<Self> {
+        let first = slices.first().ok_or(crate::Error::MissingTextureSlices)?;
+        let describe =
+            |slice: &Texture2D| format!("{}x{} {:?}", slice.width, slice.height, slice.data.kind());
+        let mut data = first.data.clone();
+        for (i, slice) in slices.iter().enumerate().skip(1) {
+            if slice.width != first.width
+                || slice.height != first.height
+                || slice.data.kind() != first.data.kind()
+            {
+                Err(crate::Error::TextureSliceMismatch(
+                    i,
+                    describe(slice),
+                    describe(first),
+                ))?;
+            }
+            append_data(&mut data, &slice.data);
+        }
+        Ok(Self {
+            name: first.name.clone(),
+            data,
+            width: first.width,
+            height: first.height,
+            depth: slices.len() as u32,
+            min_filter: first.min_filter,
+            mag_filter: first.mag_filter,
+            mip_map_filter: first.mip_map_filter,
+            wrap_s: first.wrap_s,
+            wrap_t: first.wrap_t,
+            ..Default::default()
+        })
+    }
+}
+
+///
+/// Appends the values of `other` to `data`, which must have the same format.
+///
+fn append_data(data: &mut TextureData, other: &TextureData) {
+    match (data, other) {
+        (TextureData::RU8(values), TextureData::RU8(other)) => values.extend_from_slice(other),
+        (TextureData::RgU8(values), TextureData::RgU8(other)) => values.extend_from_slice(other),
+        (TextureData::RgbU8(values), TextureData::RgbU8(other)) => values.extend_from_slice(other),
+        (TextureData::RgbaU8(values), TextureData::RgbaU8(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::RU16(values), TextureData::RU16(other)) => values.extend_from_slice(other),
+        (TextureData::RgU16(values), TextureData::RgU16(other)) => values.extend_from_slice(other),
+        (TextureData::RgbU16(values), TextureData::RgbU16(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::RgbaU16(values), TextureData::RgbaU16(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::RF16(values), TextureData::RF16(other)) => values.extend_from_slice(other),
+        (TextureData::RgF16(values), TextureData::RgF16(other)) => values.extend_from_slice(other),
+        (TextureData::RgbF16(values), TextureData::RgbF16(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::RgbaF16(values), TextureData::RgbaF16(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::RF32(values), TextureData::RF32(other)) => values.extend_from_slice(other),
+        (TextureData::RgF32(values), TextureData::RgF32(other)) => values.extend_from_slice(other),
+        (TextureData::RgbF32(values), TextureData::RgbF32(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::RgbaF32(values), TextureData::RgbaF32(other)) => {
+            values.extend_from_slice(other)
+        }
+        (TextureData::Bc1RgbaU8(blocks), TextureData::Bc1RgbaU8(other)) => {
+            blocks.extend_from_slice(other)
+        }
+        (TextureData::Bc3RgbaU8(blocks), TextureData::Bc3RgbaU8(other)) => {
+            blocks.extend_from_slice(other)
+        }
+        (TextureData::Bc5RgU8(blocks), TextureData::Bc5RgU8(other)) => {
+            blocks.extend_from_slice(other)
+        }
+        (TextureData::Bc7RgbaU8(blocks), TextureData::Bc7RgbaU8(other)) => {
+            blocks.extend_from_slice(other)
+        }
+        (TextureData::Etc2RgbaU8(blocks), TextureData::Etc2RgbaU8(other)) => {
+            blocks.extend_from_slice(other)
+        }
+        (TextureData::Astc4x4RgbaU8(blocks), TextureData::Astc4x4RgbaU8(other)) => {
+            blocks.extend_from_slice(other)
+        }
+        _ => unreachable!("the slices are validated to have the same format"),
+    }
+}
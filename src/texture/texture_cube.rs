@@ -0,0 +1,508 @@
+use crate::prelude::*;
+use crate::texture::texture2d::texels_from_f32;
+use crate::texture::Texture2D;
+#[doc(inline)]
+pub use crate::texture::{Interpolation, TextureData, Wrapping};
+
+///
+/// A CPU-side version of a cube texture, ie. six square 2D textures forming the faces of a cube.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureCube {
+    /// Name of this texture.
+    pub name: String,
+    /// The pixel data for the six faces in the order positive x, negative x, positive y, negative y, positive z and negative z.
+    pub faces: [TextureData; 6],
+    /// The width of each face
+    pub width: u32,
+    /// The height of each face
+    pub height: u32,
+    /// The way the pixel data is interpolated when the texture is far away
+    pub min_filter: Interpolation,
+    /// The way the pixel data is interpolated when the texture is close
+    pub mag_filter: Interpolation,
+    /// Specifies whether mipmaps should be created for this texture and what type of interpolation to use between the two closest mipmaps.
+    /// Note, however, that the mipmaps only will be created if the width and height of the texture are power of two.
+    pub mip_map_filter: Option<Interpolation>,
+    /// Determines how the texture is sampled outside the [0..1] s coordinate range.
+    pub wrap_s: Wrapping,
+    /// Determines how the texture is sampled outside the [0..1] t coordinate range.
+    pub wrap_t: Wrapping,
+    /// Determines how the texture is sampled outside the [0..1] r coordinate range.
+    pub wrap_r: Wrapping,
+    /// Precomputed mip levels following the base level in [TextureCube::faces], each with half the width and height (rounded down, but at least 1) of the previous level.
+    /// If empty, the mip levels have to be generated from the base level, see [TextureCube::mip_map_filter].
+    pub mip_levels: Vec<[TextureData; 6]>,
+}
+
+impl Default for TextureCube {
+    fn default() -> Self {
+        Self {
+            name: "default".to_owned(),
+            faces: std::array::from_fn(|_| TextureData::RgbaU8(vec![[0, 0, 0, 0]])),
+            width: 1,
+            height: 1,
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: Some(Interpolation::Linear),
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+            wrap_r: Wrapping::ClampToEdge,
+            mip_levels: Vec::new(),
+        }
+    }
+}
+
+impl TextureCube {
+    ///
+    /// Constructs a cube texture from the given faces in the order +X, -X, +Y, -Y, +Z, -Z, where the first row of each face is the top of the face,
+    /// following the OpenGL convention for cube maps.
+    /// The name and filtering are copied from the first face and the mip levels of the faces are ignored.
+    /// Returns an error if the faces are not square or do not all have the same size and data format.
+    ///
+    pub fn from_faces(faces: [Texture2D; 6]) -> crate::Result<Self> {
+        let first = &faces[0];
+        if first.width != first.height {
+            Err(crate::Error::NonSquareCubeFace(first.width, first.height))?;
+        }
+        let describe =
+            |face: &Texture2D| format!("{}x{} {:?}", face.width, face.height, face.data.kind());
+        for (i, face) in faces.iter().enumerate().skip(1) {
+            if face.width != first.width
+                || face.height != first.height
+                || face.data.kind() != first.data.kind()
+            {
+                Err(crate::Error::CubeFaceMismatch(
+                    i,
+                    describe(face),
+                    describe(first),
+                ))?;
+            }
+        }
+        let [px, nx, py, ny, pz, nz] = faces;
+        Ok(Self {
+            name: px.name.clone(),
+            width: px.width,
+            height: px.height,
+            min_filter: px.min_filter,
+            mag_filter: px.mag_filter,
+            mip_map_filter: px.mip_map_filter,
+            faces: [px, nx, py, ny, pz, nz].map(|face| face.data),
+            ..Default::default()
+        })
+    }
+
+    ///
+    /// Constructs a cube texture from a texture containing the six faces laid out in a cross, which is either a horizontal cross with a width and height ratio of 4:3
+    /// ```text
+    ///     +Y
+    /// -X  +Z  +X  -Z
+    ///     -Y
+    /// ```
+    /// or a vertical cross with a width and height ratio of 3:4, where the -Z face is rotated 180 degrees.
+    /// ```text
+    ///     +Y
+    /// -X  +Z  +X
+    ///     -Y
+    ///     -Z
+    /// ```
+    /// Returns an error if the size of the texture does not match one of the layouts.
+    ///
+    pub fn from_cross(texture: &Texture2D) -> crate::Result<Self> {
+        let (width, height) = (texture.width, texture.height);
+        let (w, h) = (width as u64, height as u64);
+        let (size, horizontal) = if w * 3 == h * 4 && width % 4 == 0 {
+            (width / 4, true)
+        } else if w * 4 == h * 3 && width % 3 == 0 {
+            (width / 3, false)
+        } else {
+            Err(crate::Error::InvalidCubeCross(width, height))?
+        };
+        if size == 0 {
+            Err(crate::Error::InvalidCubeCross(width, height))?;
+        }
+        let face = |x: u32, y: u32| texture.crop(x * size, y * size, size, size);
+        let negative_z = if horizontal {
+            face(3, 1)?
+        } else {
+            let mut face = face(1, 3)?;
+            face.flip_vertical()?;
+            face.flip_horizontal()?;
+            face
+        };
+        Self::from_faces([
+            face(2, 1)?,
+            face(0, 1)?,
+            face(1, 0)?,
+            face(1, 2)?,
+            face(1, 1)?,
+            negative_z,
+        ])
+    }
+
+    ///
+    /// Constructs a cube texture with faces of the given size by reprojecting an equirectangular (latitude-longitude) environment map using bilinear interpolation,
+    /// see [equirectangular_to_cubemap] for the orientation convention.
+    ///
+    pub fn from_equirectangular(texture: &Texture2D, face_size: u32) -> crate::Result<Self> {
+        equirectangular_to_cubemap(texture, face_size, Interpolation::Linear)
+    }
+
+    ///
+    /// Reprojects the faces of this cube texture into an equirectangular (latitude-longitude) environment map with the given size,
+    /// using bilinear interpolation within each face.
+    /// The orientation is the same as in [equirectangular_to_cubemap], so the two functions are the inverse of each other, apart from resampling.
+    /// The result has the same data format as the faces, except that block compressed data is decompressed,
+    /// and the values are interpolated as they are stored, so float (HDR) data is interpolated in linear space and keeps its precision.
+    ///
+    pub fn to_equirectangular(&self, width: u32, height: u32) -> crate::Result<Texture2D> {
+        let faces = self
+            .faces
+            .iter()
+            .map(|data| {
+                let mut face = Texture2D {
+                    data: data.clone(),
+                    width: self.width,
+                    height: self.height,
+                    wrap_s: Wrapping::ClampToEdge,
+                    wrap_t: Wrapping::ClampToEdge,
+                    ..Default::default()
+                };
+                face.decompress()?;
+                Ok(face)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        let kind = faces[0].data.kind();
+        let channels = kind.channel_count();
+        let (width, height) = (width.max(1), height.max(1));
+        let mut values = Vec::with_capacity((width * height) as usize * channels);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                let (face, s, t) = cube_face_coordinates(equirectangular_direction(u, v));
+                let texel = faces[face].sample(0.5 * (s + 1.0), 0.5 * (t + 1.0));
+                values.extend_from_slice(&texel[..channels]);
+            }
+        }
+        Ok(Texture2D {
+            name: self.name.clone(),
+            data: texels_from_f32(kind, channels, &values, false),
+            width,
+            height,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::ClampToEdge,
+            ..Default::default()
+        })
+    }
+}
+
+///
+/// Constructs a cube texture with faces of the given size by reprojecting an equirectangular (latitude-longitude) environment map.
+///
+/// The top row of the equirectangular texture is the +Y direction and the bottom row is the -Y direction.
+/// Horizontally, u = 0.5 (the center column) is the +X direction, u = 0.75 is the +Z direction and u = 0 and u = 1 (the left and right edges) are the -X direction,
+/// ie. the direction `(x, y, z)` maps to `u = 0.5 + atan2(z, x) / 2π` and `v = 0.5 - asin(y) / π`.
+/// The faces follow the OpenGL cube map convention, see [TextureCube::from_faces], so the first row of the +X face points towards +Y and its first column towards +Z.
+///
+/// The texture is sampled with the given interpolation, where [Interpolation::Nearest] picks the closest texel and [Interpolation::Linear] and [Interpolation::CubicSpline] use bilinear interpolation, see [Texture2D::sample].
+/// The faces have the same data format as the texture, except that block compressed data is decompressed,
+/// and the values are interpolated as they are stored, so float (HDR) data is interpolated in linear space and keeps its precision.
+///
+pub fn equirectangular_to_cubemap(
+    texture: &Texture2D,
+    face_size: u32,
+    sampling: Interpolation,
+) -> crate::Result<TextureCube> {
+    let mut texture = texture.clone();
+    texture.decompress()?;
+    texture.wrap_s = Wrapping::Repeat;
+    texture.wrap_t = Wrapping::ClampToEdge;
+    let kind = texture.data.kind();
+    let channels = kind.channel_count();
+    let size = face_size.max(1);
+    let face = |face: usize| {
+        let mut values = Vec::with_capacity((size * size) as usize * channels);
+        for y in 0..size {
+            for x in 0..size {
+                let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+                let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+                let (u, v) = equirectangular_coordinates(cube_face_direction(face, s, t));
+                let texel = match sampling {
+                    Interpolation::Nearest => texture.sample_nearest(u, v),
+                    _ => texture.sample(u, v),
+                };
+                values.extend_from_slice(&texel[..channels]);
+            }
+        }
+        Texture2D {
+            name: texture.name.clone(),
+            data: texels_from_f32(kind, channels, &values, false),
+            width: size,
+            height: size,
+            min_filter: texture.min_filter,
+            mag_filter: texture.mag_filter,
+            mip_map_filter: texture.mip_map_filter,
+            ..Default::default()
+        }
+    };
+    TextureCube::from_faces(std::array::from_fn(face))
+}
+
+///
+/// Returns the direction from the center of the cube through the point with the coordinates `s` and `t` in the range `[-1..1]` on the given face,
+/// where `s` increases along a row and `t` increases down the rows of the face.
+///
+fn cube_face_direction(face: usize, s: f32, t: f32) -> Vec3 {
+    match face {
+        0 => vec3(1.0, -t, -s),
+        1 => vec3(-1.0, -t, s),
+        2 => vec3(s, 1.0, t),
+        3 => vec3(s, -1.0, -t),
+        4 => vec3(s, -t, 1.0),
+        _ => vec3(-s, -t, -1.0),
+    }
+}
+
+///
+/// Returns the face and the coordinates on that face which the given direction points to, ie. the inverse of [cube_face_direction].
+///
+fn cube_face_coordinates(d: Vec3) -> (usize, f32, f32) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    if ax >= ay && ax >= az {
+        if d.x > 0.0 {
+            (0, -d.z / ax, -d.y / ax)
+        } else {
+            (1, d.z / ax, -d.y / ax)
+        }
+    } else if ay >= az {
+        if d.y > 0.0 {
+            (2, d.x / ay, d.z / ay)
+        } else {
+            (3, d.x / ay, -d.z / ay)
+        }
+    } else if d.z > 0.0 {
+        (4, d.x / az, -d.y / az)
+    } else {
+        (5, -d.x / az, -d.y / az)
+    }
+}
+
+///
+/// Returns the uv coordinates in an equirectangular texture of the given direction.
+///
+fn equirectangular_coordinates(d: Vec3) -> (f32, f32) {
+    let d = d.normalize();
+    let u = 0.5 + d.z.atan2(d.x) / (2.0 * std::f32::consts::PI);
+    let v = 0.5 - d.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI;
+    (u, v)
+}
+
+///
+/// Returns the direction of the given uv coordinates in an equirectangular texture, ie. the inverse of [equirectangular_coordinates].
+///
+fn equirectangular_direction(u: f32, v: f32) -> Vec3 {
+    let longitude = (u - 0.5) * 2.0 * std::f32::consts::PI;
+    let latitude = (0.5 - v) * std::f32::consts::PI;
+    vec3(
+        latitude.cos() * longitude.cos(),
+        latitude.sin(),
+        latitude.cos() * longitude.sin(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    ///
+    /// Creates a texture with the given number of columns and rows of 2x2 cells, where the texels of each cell are ten times the index of the cell plus the index of the texel within the cell.
+    ///
+    fn cross(columns: u32, rows: u32) -> Texture2D {
+        let (width, height) = (columns * 2, rows * 2);
+        Texture2D {
+            data: TextureData::RU8(
+                (0..height)
+                    .flat_map(|y| {
+                        (0..width).map(move |x| {
+                            ((y / 2 * columns + x / 2) * 10 + (y % 2) * 2 + x % 2) as u8
+                        })
+                    })
+                    .collect(),
+            ),
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    fn cell(index: u8) -> TextureData {
+        TextureData::RU8((0..4).map(|i| index * 10 + i).collect())
+    }
+
+    #[test]
+    pub fn from_horizontal_cross() {
+        let cube = TextureCube::from_cross(&cross(4, 3)).unwrap();
+        assert_eq!((cube.width, cube.height), (2, 2));
+        assert_eq!(
+            cube.faces,
+            [cell(6), cell(4), cell(1), cell(9), cell(5), cell(7)]
+        );
+    }
+
+    #[test]
+    pub fn from_vertical_cross() {
+        let cube = TextureCube::from_cross(&cross(3, 4)).unwrap();
+        assert_eq!((cube.width, cube.height), (2, 2));
+        assert_eq!(
+            cube.faces[..5],
+            [cell(5), cell(3), cell(1), cell(7), cell(4)]
+        );
+        // The -Z face is rotated 180 degrees
+        assert_eq!(cube.faces[5], TextureData::RU8(vec![103, 102, 101, 100]));
+    }
+
+    #[test]
+    pub fn from_invalid_cross() {
+        for (width, height) in [(0, 0), (8, 8), (5, 3), (10, 7), (4_000_000_000, 1)] {
+            let texture = Texture2D {
+                data: TextureData::RU8(Vec::new()),
+                width,
+                height,
+                ..Default::default()
+            };
+            assert!(matches!(
+                TextureCube::from_cross(&texture),
+                Err(crate::Error::InvalidCubeCross(w, h)) if (w, h) == (width, height)
+            ));
+        }
+    }
+
+    #[test]
+    pub fn from_faces() {
+        let face = |width: u32, height: u32, value: u8| Texture2D {
+            name: "face".to_owned(),
+            data: TextureData::RU8(vec![value; (width * height) as usize]),
+            width,
+            height,
+            mag_filter: Interpolation::Nearest,
+            ..Default::default()
+        };
+        let cube = TextureCube::from_faces(std::array::from_fn(|i| face(2, 2, i as u8))).unwrap();
+        assert_eq!(cube.name, "face");
+        assert_eq!(cube.mag_filter, Interpolation::Nearest);
+        assert_eq!(cube.faces[3], TextureData::RU8(vec![3; 4]));
+
+        assert!(matches!(
+            TextureCube::from_faces(std::array::from_fn(|_| face(2, 1, 0))),
+            Err(crate::Error::NonSquareCubeFace(2, 1))
+        ));
+        assert!(matches!(
+            TextureCube::from_faces(std::array::from_fn(|i| face(
+                if i == 3 { 1 } else { 2 },
+                if i == 3 { 1 } else { 2 },
+                0
+            ))),
+            Err(crate::Error::CubeFaceMismatch(3, ..))
+        ));
+        let mut faces = std::array::from_fn(|_| face(2, 2, 0));
+        faces[4].data = TextureData::RgU8(vec![[0; 2]; 4]);
+        assert!(matches!(
+            TextureCube::from_faces(faces),
+            Err(crate::Error::CubeFaceMismatch(4, ..))
+        ));
+    }
+
+    ///
+    /// Creates an equirectangular texture where each texel stores the value of the given function of the direction through its center.
+    ///
+    fn equirectangular(
+        width: u32,
+        height: u32,
+        f: impl Fn(f32, f32, Vec3) -> [f32; 2],
+    ) -> Texture2D {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                f(u, v, equirectangular_direction(u, v))
+            })
+            .collect();
+        Texture2D {
+            data: TextureData::RgF32(data),
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    pub fn from_equirectangular_orientation() {
+        // Each texel stores its own uv coordinates, which bilinear sampling reproduces exactly away from the edges
+        let texture = equirectangular(64, 32, |u, v, _| [u, v]);
+        let uv = |cube: &TextureCube, face: usize, texel: usize| match &cube.faces[face] {
+            TextureData::RgF32(data) => data[texel],
+            _ => unreachable!(),
+        };
+        let approx =
+            |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3;
+
+        let cube = TextureCube::from_equirectangular(&texture, 1).unwrap();
+        assert!(approx(uv(&cube, 0, 0), [0.5, 0.5]));
+        assert!(approx(uv(&cube, 4, 0), [0.75, 0.5]));
+        assert!(approx(uv(&cube, 5, 0), [0.25, 0.5]));
+        // The poles are clamped to the center of the first and last row
+        assert!(approx(uv(&cube, 2, 0), [0.5, 0.5 / 32.0]));
+        assert!(approx(uv(&cube, 3, 0), [0.5, 1.0 - 0.5 / 32.0]));
+
+        // The first texel of the +X face points towards +Y and +Z, ie. the direction (1, 0.5, 0.5)
+        let cube = TextureCube::from_equirectangular(&texture, 2).unwrap();
+        let expected = [
+            0.5 + 0.5f32.atan2(1.0) / (2.0 * std::f32::consts::PI),
+            0.5 - (0.5 / 1.5f32.sqrt()).asin() / std::f32::consts::PI,
+        ];
+        assert!(approx(uv(&cube, 0, 0), expected));
+        assert!(expected[0] > 0.5 && expected[1] < 0.5);
+    }
+
+    #[test]
+    pub fn to_equirectangular_orientation() {
+        let cube = TextureCube {
+            faces: std::array::from_fn(|i| TextureData::RU8(vec![i as u8; 16])),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        let texture = cube.to_equirectangular(8, 4).unwrap();
+        assert_eq!((texture.width, texture.height), (8, 4));
+        let TextureData::RU8(data) = texture.data else {
+            unreachable!()
+        };
+        assert_eq!(&data[..8], &[2; 8]);
+        assert_eq!(&data[8..16], &[1, 5, 5, 0, 0, 4, 4, 1]);
+        assert_eq!(&data[16..24], &[1, 5, 5, 0, 0, 4, 4, 1]);
+        assert_eq!(&data[24..], &[3; 8]);
+    }
+
+    #[test]
+    pub fn equirectangular_round_trip() {
+        let texture = equirectangular(64, 32, |_, _, d| [1.0 + d.x + 0.5 * d.y, 1.0 - 0.5 * d.z]);
+        let cube = TextureCube::from_equirectangular(&texture, 32).unwrap();
+        let result = cube.to_equirectangular(64, 32).unwrap();
+        let (TextureData::RgF32(expected), TextureData::RgF32(actual)) =
+            (&texture.data, &result.data)
+        else {
+            unreachable!()
+        };
+        for (a, b) in expected.iter().zip(actual.iter()) {
+            assert!(
+                (a[0] - b[0]).abs() < 0.02 && (a[1] - b[1]).abs() < 0.02,
+                "{a:?} != {b:?}"
+            );
+        }
+    }
+}